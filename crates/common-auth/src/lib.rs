@@ -1,7 +1,12 @@
 //! Common authentication primitives shared across LokanOS services.
 
+pub mod rbac;
+pub mod token;
+
 use thiserror::Error;
 
+pub use token::{validate_token, SigningKey, TokenClaims};
+
 /// Represents a validation error for an authentication token.
 #[derive(Debug, Error)]
 pub enum AuthError {
@@ -9,12 +14,3 @@ pub enum AuthError {
     #[error("token validation failed: {0}")]
     Validation(String),
 }
-
-/// Verifies a raw authentication token.
-///
-/// This is a non-breaking stub that always accepts the token and should be
-/// replaced with real validation logic in subsequent phases.
-#[allow(unused_variables)]
-pub fn validate_token(token: &str) -> Result<(), AuthError> {
-    Ok(())
-}
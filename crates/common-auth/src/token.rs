@@ -0,0 +1,141 @@
+//! Bearer token validation: HS256 (shared secret) or RS256 (RSA public key)
+//! JWT signature verification, `exp`/`nbf` checks, and scope extraction so
+//! callers can gate write-scoped routes separately from read-only ones.
+
+use std::collections::HashSet;
+
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::AuthError;
+
+/// The key a deployment signs its bearer tokens with, loaded from config.
+#[derive(Clone)]
+pub enum SigningKey {
+    /// HS256: a shared secret known to both the issuer and this service.
+    Hmac(Vec<u8>),
+    /// RS256: the issuer's RSA public key, PEM-encoded.
+    Rsa(Vec<u8>),
+}
+
+impl SigningKey {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            SigningKey::Hmac(_) => Algorithm::HS256,
+            SigningKey::Rsa(_) => Algorithm::RS256,
+        }
+    }
+
+    fn decoding_key(&self) -> Result<DecodingKey, AuthError> {
+        match self {
+            SigningKey::Hmac(secret) => Ok(DecodingKey::from_secret(secret)),
+            SigningKey::Rsa(pem) => DecodingKey::from_rsa_pem(pem)
+                .map_err(|err| AuthError::Validation(format!("invalid RSA public key: {err}"))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    scopes: Option<Vec<String>>,
+}
+
+/// The subject and scopes a successfully validated token carries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenClaims {
+    pub subject: Option<String>,
+    pub scopes: HashSet<String>,
+}
+
+impl TokenClaims {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
+}
+
+/// Verifies `token`'s signature against `key` and checks its `exp`/`nbf`
+/// claims, returning its subject and scopes on success. Both the
+/// space-separated OAuth2-style `scope` claim and a JSON array `scopes`
+/// claim are accepted and merged.
+pub fn validate_token(token: &str, key: &SigningKey) -> Result<TokenClaims, AuthError> {
+    let decoding_key = key.decoding_key()?;
+    let mut validation = Validation::new(key.algorithm());
+    validation.validate_nbf = true;
+
+    let data = decode::<Claims>(token, &decoding_key, &validation)
+        .map_err(|err| AuthError::Validation(err.to_string()))?;
+
+    let mut scopes = HashSet::new();
+    if let Some(scope) = data.claims.scope {
+        scopes.extend(scope.split_whitespace().map(str::to_string));
+    }
+    if let Some(list) = data.claims.scopes {
+        scopes.extend(list);
+    }
+
+    Ok(TokenClaims {
+        subject: data.claims.sub,
+        scopes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct TestClaims<'a> {
+        sub: &'a str,
+        scope: &'a str,
+        exp: usize,
+    }
+
+    fn token_with_scope(secret: &[u8], scope: &str, exp: usize) -> String {
+        let claims = TestClaims {
+            sub: "device-1",
+            scope,
+            exp,
+        };
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret),
+        )
+        .expect("encode token")
+    }
+
+    #[test]
+    fn accepts_a_validly_signed_token_and_parses_scopes() {
+        let secret = b"test-secret";
+        let token = token_with_scope(secret, "radio.read radio.write", 9_999_999_999);
+
+        let claims =
+            validate_token(&token, &SigningKey::Hmac(secret.to_vec())).expect("valid token");
+
+        assert!(claims.has_scope("radio.read"));
+        assert!(claims.has_scope("radio.write"));
+        assert_eq!(claims.subject.as_deref(), Some("device-1"));
+    }
+
+    #[test]
+    fn rejects_an_expired_token() {
+        let secret = b"test-secret";
+        let token = token_with_scope(secret, "radio.read", 1);
+
+        assert!(validate_token(&token, &SigningKey::Hmac(secret.to_vec())).is_err());
+    }
+
+    #[test]
+    fn rejects_a_token_signed_with_the_wrong_secret() {
+        let token = token_with_scope(b"right-secret", "radio.read", 9_999_999_999);
+
+        assert!(validate_token(&token, &SigningKey::Hmac(b"wrong-secret".to_vec())).is_err());
+    }
+}
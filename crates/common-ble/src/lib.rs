@@ -10,9 +10,10 @@ pub struct CsrRequest {
     pub device_id: String,
     /// Binary CSR payload (DER encoded) represented as base64 in transit.
     pub csr: String,
-    /// Optional nonce to bind the CSR to a commissioning session.
-    #[serde(default)]
-    pub nonce: Option<String>,
+    /// The `session` id returned by the BLE handshake this CSR continues.
+    pub session: String,
+    /// Fresh per-request nonce, checked against the session's replay store.
+    pub nonce: String,
 }
 
 impl Default for CsrRequest {
@@ -20,7 +21,8 @@ impl Default for CsrRequest {
         Self {
             device_id: String::new(),
             csr: String::new(),
-            nonce: None,
+            session: String::new(),
+            nonce: String::new(),
         }
     }
 }
@@ -53,9 +55,10 @@ pub struct VerifyRequest {
     pub device_id: String,
     /// Signature covering the attestation challenge.
     pub signature: String,
-    /// Optional opaque session identifier.
-    #[serde(default)]
-    pub session: Option<String>,
+    /// The `session` id returned by the BLE handshake this request continues.
+    pub session: String,
+    /// Fresh per-request nonce, checked against the session's replay store.
+    pub nonce: String,
 }
 
 impl Default for VerifyRequest {
@@ -63,7 +66,8 @@ impl Default for VerifyRequest {
         Self {
             device_id: String::new(),
             signature: String::new(),
-            session: None,
+            session: String::new(),
+            nonce: String::new(),
         }
     }
 }
@@ -97,7 +101,8 @@ mod tests {
         let request = CsrRequest {
             device_id: "device-123".into(),
             csr: "YmFzZTY0IGNzciBieXRlcw==".into(),
-            nonce: Some("abc123".into()),
+            session: "session-123".into(),
+            nonce: "abc123".into(),
         };
         let json = serde_json::to_string(&request).expect("serialize");
         assert!(json.contains("deviceId"));
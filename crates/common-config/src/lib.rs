@@ -1,12 +1,17 @@
 //! Shared configuration helpers for LokanOS services.
 
 use std::env;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde_json::{Map, Value};
 use thiserror::Error;
 
+pub mod shutdown;
+pub use shutdown::{ShutdownConfig, Tripwire};
+
 /// Attempt to load variables from a local `.env` file while keeping real environment
 /// overrides intact.
 fn load_dotenv() {
@@ -25,6 +30,28 @@ pub enum ConfigError {
     /// Wrapper around deserialization failures.
     #[error("failed to deserialize configuration from environment: {0}")]
     Deserialize(#[from] envy::Error),
+    /// The config file named by `{PREFIX}CONFIG_FILE` could not be read.
+    #[error("failed to read config file {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The config file's contents didn't parse as its format.
+    #[error("failed to parse {format} config file {path:?}: {source}")]
+    Parse {
+        path: PathBuf,
+        format: &'static str,
+        source: String,
+    },
+    /// The config file's extension isn't one this loader knows how to parse.
+    #[error(
+        "unsupported config file extension for {path:?} (expected .toml, .yaml/.yml, or .dhall with the `dhall` feature)"
+    )]
+    UnsupportedFormat { path: PathBuf },
+    /// The file and environment layers, once merged, didn't deserialize
+    /// into the target type.
+    #[error("failed to deserialize merged configuration: {0}")]
+    Merge(String),
 }
 
 /// Trait implemented by strongly typed configuration structs for services.
@@ -64,6 +91,155 @@ where
     }
 }
 
+/// Load a strongly typed configuration struct the same way [`load`] does,
+/// but first layering a declarative config file underneath the
+/// environment: precedence is `struct defaults < config file < environment`.
+/// The file is located by `{PREFIX}CONFIG_FILE` (e.g. `API_GATEWAY_CONFIG_FILE`)
+/// and its format is inferred from its extension (`.toml`, `.yaml`/`.yml`,
+/// or `.dhall` behind the `dhall` feature). Services don't need to change
+/// their config struct at all — every field already derives
+/// `#[serde(default)]` for [`load`], which is what lets a partial file or
+/// a handful of env vars override just the fields they care about.
+pub fn load_layered<T>() -> Result<T, ConfigError>
+where
+    T: ServiceConfig,
+{
+    let mut config = load_layered_with_prefix::<T>(T::PREFIX)?;
+    config.apply_environment_overrides(T::PREFIX);
+    Ok(config)
+}
+
+/// Load a configuration struct using the provided environment prefix, the
+/// same layering [`load_layered`] does.
+pub fn load_layered_with_prefix<T>(prefix: &str) -> Result<T, ConfigError>
+where
+    T: DeserializeOwned + Default,
+{
+    load_dotenv();
+
+    let mut value = match config_file_path(prefix) {
+        Some(path) => read_config_file(&path)?,
+        None => Value::Object(Map::new()),
+    };
+
+    json_patch::merge(&mut value, &env_overlay_value(prefix));
+
+    serde_json::from_value(value).map_err(|source| ConfigError::Merge(source.to_string()))
+}
+
+/// Resolves `{PREFIX}CONFIG_FILE` (or bare `CONFIG_FILE` for an empty
+/// prefix) to the config file path an operator wants loaded, if any.
+fn config_file_path(prefix: &str) -> Option<PathBuf> {
+    let key = format!("{prefix}CONFIG_FILE");
+    env::var(key).ok().map(PathBuf::from)
+}
+
+/// Public entry point for [`config_file_path`], for callers that need to
+/// know which file [`load_layered`] would read without loading it — e.g. a
+/// service watching that file for hot-reload.
+pub fn layered_config_path<T: ServiceConfig>() -> Option<PathBuf> {
+    config_file_path(T::PREFIX)
+}
+
+/// Reads and parses a config file into a generic JSON value, picking the
+/// format from its extension.
+fn read_config_file(path: &Path) -> Result<Value, ConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            format: "toml",
+            source: source.to_string(),
+        }),
+        Some("yaml") | Some("yml") => {
+            serde_yaml::from_str(&contents).map_err(|source| ConfigError::Parse {
+                path: path.to_path_buf(),
+                format: "yaml",
+                source: source.to_string(),
+            })
+        }
+        Some("dhall") => read_dhall_config(&contents, path),
+        _ => Err(ConfigError::UnsupportedFormat {
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+#[cfg(feature = "dhall")]
+fn read_dhall_config(contents: &str, path: &Path) -> Result<Value, ConfigError> {
+    serde_dhall::from_str(contents)
+        .parse()
+        .map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            format: "dhall",
+            source: source.to_string(),
+        })
+}
+
+#[cfg(not(feature = "dhall"))]
+fn read_dhall_config(_contents: &str, path: &Path) -> Result<Value, ConfigError> {
+    Err(ConfigError::UnsupportedFormat {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Builds a JSON overlay from every `{PREFIX}`-prefixed environment
+/// variable, so it can be deep-merged on top of the config file layer with
+/// [`json_patch::merge`]. Nested fields use the same `__` separator envy
+/// itself recognizes (e.g. `RATE_LIMIT__BURST` -> `rate_limit.burst`), and
+/// each value is coerced to a bool/number where it parses as one so
+/// non-string fields still deserialize correctly.
+fn env_overlay_value(prefix: &str) -> Value {
+    let mut root = Map::new();
+    for (key, raw) in env::vars() {
+        let Some(stripped) = key.strip_prefix(prefix) else {
+            continue;
+        };
+        let path: Vec<String> = stripped.split("__").map(|s| s.to_lowercase()).collect();
+        insert_path(&mut root, &path, parse_env_scalar(&raw));
+    }
+    Value::Object(root)
+}
+
+fn insert_path(map: &mut Map<String, Value>, path: &[String], value: Value) {
+    match path {
+        [] => {}
+        [last] => {
+            map.insert(last.clone(), value);
+        }
+        [head, rest @ ..] => {
+            let entry = map
+                .entry(head.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(nested) = entry {
+                insert_path(nested, rest, value);
+            }
+        }
+    }
+}
+
+/// Best-effort typed coercion for a raw environment variable value, so an
+/// overlay built from flat strings can still fill in bool/number fields.
+/// Falls back to a JSON string when nothing more specific matches.
+fn parse_env_scalar(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        return Value::Bool(b);
+    }
+    if let Ok(i) = raw.parse::<i64>() {
+        return Value::Number(i.into());
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if let Some(number) = serde_json::Number::from_f64(f) {
+            return Value::Number(number);
+        }
+    }
+    Value::String(raw.to_string())
+}
+
 /// Common configuration shared across services for connecting to the message bus.
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 #[serde(default)]
@@ -186,4 +362,33 @@ mod tests {
             self.bus.apply_environment_overrides(prefix);
         }
     }
+
+    #[test]
+    fn load_layered_merges_defaults_file_and_env() {
+        use super::load_layered;
+
+        std::env::remove_var("TEST_VALUE");
+        std::env::remove_var("TEST_NUMBER");
+        std::env::remove_var("TEST_CONFIG_FILE");
+        std::env::remove_var("TEST_BUS__URL");
+        std::env::remove_var("TEST_BUS_URL");
+
+        let path = std::env::temp_dir().join("common_config_layered_test.toml");
+        std::fs::write(&path, "value = \"from_file\"\nnumber = 7\n").expect("write config file");
+        std::env::set_var("TEST_CONFIG_FILE", &path);
+
+        // Only `number` is overridden by the environment; `value` should
+        // still come from the file, and anything neither sets falls back
+        // to the struct default.
+        std::env::set_var("TEST_NUMBER", "99");
+
+        let config: TestConfig = load_layered().expect("load layered config");
+        assert_eq!(config.value, "from_file");
+        assert_eq!(config.number, 99);
+        assert_eq!(config.bus, MsgBusConfig::default());
+
+        std::fs::remove_file(&path).ok();
+        std::env::remove_var("TEST_CONFIG_FILE");
+        std::env::remove_var("TEST_NUMBER");
+    }
 }
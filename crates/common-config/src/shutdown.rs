@@ -0,0 +1,133 @@
+//! Graceful shutdown shared across services: configuration for how long a
+//! drain should take, a tripwire in-flight-aware middleware can check to
+//! stop admitting new work, and a signal-driven helper that flips it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, watch};
+
+/// How a service drains in-flight work during a rolling restart: stop
+/// accepting new connections as soon as one of `signals` fires, give
+/// existing requests up to `grace_period_ms` to finish on their own, then
+/// abort anything still running after an additional `force_period_ms`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    pub grace_period_ms: u64,
+    pub force_period_ms: u64,
+    /// Signal names that trigger shutdown, e.g. `["SIGTERM", "SIGINT"]`.
+    /// An unrecognized name is logged and skipped rather than rejected, so
+    /// a typo in a config file degrades to "one fewer trigger" instead of
+    /// a startup failure.
+    pub signals: Vec<String>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            grace_period_ms: 10_000,
+            force_period_ms: 30_000,
+            signals: vec!["SIGTERM".to_string(), "SIGINT".to_string()],
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn grace_period(&self) -> Duration {
+        Duration::from_millis(self.grace_period_ms)
+    }
+
+    pub fn force_period(&self) -> Duration {
+        Duration::from_millis(self.force_period_ms)
+    }
+}
+
+/// Shared flag a service's request-handling middleware can check to
+/// reject new work once shutdown has begun, while requests already
+/// admitted keep running until they finish or the grace period expires.
+#[derive(Debug, Clone, Default)]
+pub struct Tripwire(Arc<AtomicBool>);
+
+impl Tripwire {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True once shutdown has started.
+    pub fn is_tripped(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn trip(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Spawns the background task that waits for one of `config.signals`,
+/// then flips the returned [`Tripwire`] and notifies every clone of the
+/// returned [`watch::Receiver`]. Each of a service's listeners gets its
+/// own clone of the receiver so they all react to the same shutdown
+/// without each installing their own signal handlers.
+pub fn spawn(config: ShutdownConfig) -> (Tripwire, watch::Receiver<bool>) {
+    let tripwire = Tripwire::new();
+    let (tx, rx) = watch::channel(false);
+
+    let spawned_tripwire = tripwire.clone();
+    tokio::spawn(async move {
+        wait_for_shutdown_signal(&config).await;
+        spawned_tripwire.trip();
+        let _ = tx.send(true);
+    });
+
+    (tripwire, rx)
+}
+
+/// Waits for the first of `config.signals` to fire and returns. Signal
+/// handlers are installed one per configured name and funneled into a
+/// single channel, the same way `api-gateway`'s config-reload watcher
+/// collapses its own file-watch and `SIGHUP` triggers.
+async fn wait_for_shutdown_signal(config: &ShutdownConfig) {
+    let (tx, mut rx) = mpsc::channel::<String>(1);
+
+    for name in &config.signals {
+        let Some(kind) = parse_signal(name) else {
+            tracing::warn!(signal = %name, "unrecognized shutdown signal, ignoring");
+            continue;
+        };
+        let mut listener = match signal(kind) {
+            Ok(listener) => listener,
+            Err(err) => {
+                tracing::warn!(%err, signal = %name, "failed to install shutdown signal handler");
+                continue;
+            }
+        };
+        let tx = tx.clone();
+        let name = name.clone();
+        tokio::spawn(async move {
+            listener.recv().await;
+            let _ = tx.send(name).await;
+        });
+    }
+    drop(tx);
+
+    match rx.recv().await {
+        Some(name) => tracing::info!(signal = %name, "received shutdown signal"),
+        None => tracing::warn!(
+            "no shutdown signal handlers installed; shutdown must be triggered another way"
+        ),
+    }
+}
+
+fn parse_signal(name: &str) -> Option<SignalKind> {
+    match name.to_ascii_uppercase().as_str() {
+        "SIGTERM" => Some(SignalKind::terminate()),
+        "SIGINT" => Some(SignalKind::interrupt()),
+        "SIGHUP" => Some(SignalKind::hangup()),
+        "SIGQUIT" => Some(SignalKind::quit()),
+        _ => None,
+    }
+}
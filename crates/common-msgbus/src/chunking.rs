@@ -0,0 +1,466 @@
+//! Transparent chunking and reassembly for payloads larger than a backend
+//! can carry in one message (NATS commonly caps this around 128 KB), e.g.
+//! firmware blobs published by the `updater` service. [`ChunkingBus`] wraps
+//! any [`MessageBus`] so callers can keep publishing/requesting arbitrarily
+//! large payloads without the backend ever seeing more than `max_payload`
+//! bytes at a time.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use uuid::Uuid;
+
+use crate::{BusMessage, MessageBus, MsgBusError, Subscription};
+
+/// Payloads at or below this many bytes are left untouched, so this is also
+/// the effective size of a single published/requested message on the wire.
+pub const DEFAULT_MAX_PAYLOAD: usize = 128 * 1024;
+
+/// How long an object is kept waiting for its remaining fragments before it
+/// is dropped and a [`MsgBusError::ReassemblyFailed`] is logged for it.
+const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A generous upper bound on the framed size of a [`FragmentHeader`], used
+/// to size fragment chunks so the *framed* fragment (header + chunk) still
+/// fits under `max_payload`, not just the chunk alone.
+const FRAGMENT_FRAMING_OVERHEAD: usize = 256;
+
+/// Tags the start of a chunked fragment's payload. A payload that doesn't
+/// start with this is assumed to be an ordinary, unchunked message -- this
+/// is what lets [`ChunkingBus`] leave small payloads on the wire unmodified
+/// and interoperate with subscribers that aren't chunk-aware.
+const FRAGMENT_MAGIC: &[u8; 4] = b"CNK1";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FragmentHeader {
+    object_id: Uuid,
+    index: u32,
+    total: u32,
+    total_len: u64,
+    /// Lowercase hex BLAKE3 digest of the whole reassembled object.
+    digest: String,
+}
+
+fn encode_fragment(header: &FragmentHeader, chunk: &[u8]) -> Vec<u8> {
+    let header_json = serde_json::to_vec(header).expect("fragment header is serializable");
+    let mut framed = Vec::with_capacity(FRAGMENT_MAGIC.len() + 4 + header_json.len() + chunk.len());
+    framed.extend_from_slice(FRAGMENT_MAGIC);
+    framed.extend_from_slice(&(header_json.len() as u32).to_be_bytes());
+    framed.extend_from_slice(&header_json);
+    framed.extend_from_slice(chunk);
+    framed
+}
+
+/// Returns `None` if `payload` isn't a chunked fragment at all (i.e. it
+/// should be delivered as-is), or `Some` with the decoded header/chunk or a
+/// [`MsgBusError::ReassemblyFailed`] if it looked like a fragment but was malformed.
+fn decode_fragment(payload: &[u8]) -> Option<Result<(FragmentHeader, &[u8]), MsgBusError>> {
+    let rest = payload.strip_prefix(FRAGMENT_MAGIC.as_slice())?;
+    if rest.len() < 4 {
+        return Some(Err(MsgBusError::ReassemblyFailed(
+            "fragment payload too short for header length".to_string(),
+        )));
+    }
+    let (header_len, rest) = rest.split_at(4);
+    let header_len = u32::from_be_bytes(header_len.try_into().expect("4 bytes")) as usize;
+    if rest.len() < header_len {
+        return Some(Err(MsgBusError::ReassemblyFailed(
+            "fragment payload truncated before header end".to_string(),
+        )));
+    }
+    let (header_bytes, chunk) = rest.split_at(header_len);
+    match serde_json::from_slice(header_bytes) {
+        Ok(header) => Some(Ok((header, chunk))),
+        Err(err) => Some(Err(MsgBusError::ReassemblyFailed(format!(
+            "malformed fragment header: {err}"
+        )))),
+    }
+}
+
+fn max_chunk_len(max_payload: usize) -> usize {
+    max_payload.saturating_sub(FRAGMENT_FRAMING_OVERHEAD).max(1)
+}
+
+fn split_into_fragments(payload: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+    let object_id = Uuid::new_v4();
+    let digest = blake3::hash(payload).to_hex().to_string();
+    let total_len = payload.len() as u64;
+    let chunk_len = max_chunk_len(max_payload);
+    let chunks: Vec<&[u8]> = payload.chunks(chunk_len).collect();
+    let total = chunks.len() as u32;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let header = FragmentHeader {
+                object_id,
+                index: index as u32,
+                total,
+                total_len,
+                digest: digest.clone(),
+            };
+            encode_fragment(&header, chunk)
+        })
+        .collect()
+}
+
+/// Fragments collected so far for one in-flight object.
+struct PendingObject {
+    total: u32,
+    digest: String,
+    parts: HashMap<u32, Vec<u8>>,
+    /// The envelope (subject/reply/headers) of the final fragment, which is
+    /// the one that matters once reassembly completes.
+    carrier: Option<BusMessage>,
+    started_at: Instant,
+}
+
+impl PendingObject {
+    fn new(header: &FragmentHeader) -> Self {
+        Self {
+            total: header.total,
+            digest: header.digest.clone(),
+            parts: HashMap::new(),
+            carrier: None,
+            started_at: Instant::now(),
+        }
+    }
+
+    fn try_assemble(&self) -> Option<Vec<u8>> {
+        if self.parts.len() != self.total as usize {
+            return None;
+        }
+        let mut payload = Vec::new();
+        for index in 0..self.total {
+            payload.extend_from_slice(self.parts.get(&index)?);
+        }
+        Some(payload)
+    }
+}
+
+/// Drops objects that have been waiting longer than `timeout` for their
+/// remaining fragments, logging a [`MsgBusError::ReassemblyFailed`] for
+/// each. Called opportunistically as fragments arrive rather than on a
+/// dedicated timer, the same way the mock backend's undelivered-queue flush
+/// is piggybacked on existing activity instead of polling.
+fn evict_stale(buffers: &DashMap<Uuid, PendingObject>, timeout: Duration) {
+    let stale: Vec<Uuid> = buffers
+        .iter()
+        .filter(|entry| entry.value().started_at.elapsed() >= timeout)
+        .map(|entry| *entry.key())
+        .collect();
+    for object_id in stale {
+        if buffers.remove(&object_id).is_some() {
+            let err = MsgBusError::ReassemblyFailed(format!(
+                "object {object_id} timed out with fragments still missing"
+            ));
+            tracing::warn!(%object_id, %err, "dropping incomplete chunked object");
+        }
+    }
+}
+
+fn insert_fragment(
+    buffers: &DashMap<Uuid, PendingObject>,
+    header: FragmentHeader,
+    chunk: &[u8],
+    carrier: &BusMessage,
+) -> Result<Option<BusMessage>, MsgBusError> {
+    let object_id = header.object_id;
+    let is_last = header.index + 1 == header.total;
+    let assembled = {
+        let mut entry = buffers
+            .entry(object_id)
+            .or_insert_with(|| PendingObject::new(&header));
+        entry.parts.insert(header.index, chunk.to_vec());
+        if is_last {
+            entry.carrier = Some(carrier.clone());
+        }
+        entry.try_assemble()
+    };
+
+    let Some(payload) = assembled else {
+        return Ok(None);
+    };
+    let (_, object) = buffers
+        .remove(&object_id)
+        .expect("object was just completed above");
+
+    let actual_digest = blake3::hash(&payload).to_hex().to_string();
+    if actual_digest != object.digest {
+        return Err(MsgBusError::ReassemblyFailed(format!(
+            "digest mismatch reassembling object {object_id}"
+        )));
+    }
+
+    let mut message = object.carrier.unwrap_or_else(|| carrier.clone());
+    message.payload = payload;
+    Ok(Some(message))
+}
+
+/// Wraps `inner`'s raw message stream so that chunked fragments are buffered
+/// and reassembled before reaching the caller; unchunked messages pass
+/// through untouched.
+fn reassembling_subscription(
+    mut inner: Subscription,
+    buffers: Arc<DashMap<Uuid, PendingObject>>,
+    timeout: Duration,
+) -> Subscription {
+    let subject = inner.subject().to_string();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(message) = inner.next().await {
+            evict_stale(&buffers, timeout);
+            match decode_fragment(&message.payload) {
+                None => {
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                }
+                Some(Ok((header, chunk))) => match insert_fragment(&buffers, header, chunk, &message) {
+                    Ok(Some(reassembled)) => {
+                        if tx.send(reassembled).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        tracing::warn!(subject = %message.subject, %err, "dropping chunked object");
+                    }
+                },
+                Some(Err(err)) => {
+                    tracing::warn!(subject = %message.subject, %err, "dropping malformed chunked fragment");
+                }
+            }
+        }
+    });
+    Subscription::new(subject, UnboundedReceiverStream::new(rx))
+}
+
+/// Wraps any [`MessageBus`] to transparently chunk payloads over
+/// `max_payload` bytes into ordered, BLAKE3-verified fragments on publish,
+/// and reassemble them back into a single [`BusMessage`] on
+/// [`subscribe`](MessageBus::subscribe)/[`subscribe_queue`](MessageBus::subscribe_queue).
+/// Payloads at or under `max_payload` are left on the wire unmodified.
+///
+/// Responses sent via [`respond`](MessageBus::respond) are never chunked: a
+/// [`request`](MessageBus::request) round trip can only carry back one
+/// reply message, so there is nothing on the receiving end to reassemble
+/// multiple reply fragments into. Handlers with a large result should
+/// publish it separately and let the caller subscribe for it.
+pub struct ChunkingBus<B: ?Sized> {
+    max_payload: usize,
+    reassembly_timeout: Duration,
+    buffers: Arc<DashMap<Uuid, PendingObject>>,
+    inner: Arc<B>,
+}
+
+impl<B: ?Sized> Clone for ChunkingBus<B> {
+    fn clone(&self) -> Self {
+        Self {
+            max_payload: self.max_payload,
+            reassembly_timeout: self.reassembly_timeout,
+            buffers: self.buffers.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<B: ?Sized> ChunkingBus<B> {
+    /// Wraps `inner`, chunking payloads over `max_payload` bytes. Incomplete
+    /// objects are dropped after a default 30-second reassembly timeout;
+    /// override with [`with_reassembly_timeout`](Self::with_reassembly_timeout).
+    pub fn new(inner: Arc<B>, max_payload: usize) -> Self {
+        Self {
+            max_payload,
+            reassembly_timeout: DEFAULT_REASSEMBLY_TIMEOUT,
+            buffers: Arc::new(DashMap::new()),
+            inner,
+        }
+    }
+
+    /// Overrides how long an incomplete object is kept waiting for its
+    /// remaining fragments before being dropped.
+    pub fn with_reassembly_timeout(mut self, timeout: Duration) -> Self {
+        self.reassembly_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl<B: MessageBus + ?Sized> MessageBus for ChunkingBus<B> {
+    async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+        if payload.len() <= self.max_payload {
+            return self.inner.publish(subject, payload).await;
+        }
+        for fragment in split_into_fragments(payload, self.max_payload) {
+            self.inner.publish(subject, &fragment).await?;
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<Subscription, MsgBusError> {
+        let inner = self.inner.subscribe(subject).await?;
+        Ok(reassembling_subscription(
+            inner,
+            self.buffers.clone(),
+            self.reassembly_timeout,
+        ))
+    }
+
+    async fn subscribe_queue(
+        &self,
+        subject: &str,
+        group: &str,
+    ) -> Result<Subscription, MsgBusError> {
+        let inner = self.inner.subscribe_queue(subject, group).await?;
+        Ok(reassembling_subscription(
+            inner,
+            self.buffers.clone(),
+            self.reassembly_timeout,
+        ))
+    }
+
+    async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError> {
+        let response = if payload.len() <= self.max_payload {
+            self.inner.request(subject, payload).await?
+        } else {
+            let fragments = split_into_fragments(payload, self.max_payload);
+            let (last, head) = fragments
+                .split_last()
+                .expect("split_into_fragments always yields at least one fragment");
+            for fragment in head {
+                self.inner.publish(subject, fragment).await?;
+            }
+            self.inner.request(subject, last).await?
+        };
+
+        match decode_fragment(&response.payload) {
+            None => Ok(response),
+            Some(Err(err)) => Err(err),
+            Some(Ok((header, chunk))) => {
+                if header.total != 1 {
+                    return Err(MsgBusError::ReassemblyFailed(format!(
+                        "response on {subject} spans {} fragments; request() can only reassemble a single-fragment reply",
+                        header.total
+                    )));
+                }
+                let actual_digest = blake3::hash(chunk).to_hex().to_string();
+                if actual_digest != header.digest {
+                    return Err(MsgBusError::ReassemblyFailed(format!(
+                        "digest mismatch reassembling response on {subject}"
+                    )));
+                }
+                Ok(BusMessage {
+                    payload: chunk.to_vec(),
+                    ..response
+                })
+            }
+        }
+    }
+
+    async fn respond(&self, reply_to: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+        self.inner.respond(reply_to, payload).await
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::MockBus;
+
+    #[tokio::test]
+    async fn small_payloads_pass_through_unmodified() {
+        let bus = ChunkingBus::new(Arc::new(MockBus::new()), DEFAULT_MAX_PAYLOAD);
+        let mut subscription = bus.subscribe("updates.firmware").await.expect("subscribe");
+
+        bus.publish("updates.firmware", b"tiny").await.expect("publish");
+
+        let message = subscription.next().await.expect("message");
+        assert_eq!(message.payload, b"tiny");
+    }
+
+    // Large enough that splitting a several-hundred-byte payload yields
+    // multiple fragments without degenerating to one byte per fragment.
+    const TEST_MAX_PAYLOAD: usize = 600;
+
+    #[tokio::test]
+    async fn large_payload_is_chunked_and_reassembled() {
+        let bus = ChunkingBus::new(Arc::new(MockBus::new()), TEST_MAX_PAYLOAD);
+        let mut subscription = bus.subscribe("updates.firmware").await.expect("subscribe");
+
+        let payload: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        bus.publish("updates.firmware", &payload)
+            .await
+            .expect("publish");
+
+        let message = subscription.next().await.expect("reassembled message");
+        assert_eq!(message.payload, payload);
+    }
+
+    #[tokio::test]
+    async fn digest_mismatch_drops_the_object_instead_of_yielding_it() {
+        let bus = ChunkingBus::new(Arc::new(MockBus::new()), TEST_MAX_PAYLOAD);
+        let mut subscription = bus.subscribe("updates.firmware").await.expect("subscribe");
+
+        let payload = vec![7u8; 500];
+        let mut fragments = split_into_fragments(&payload, TEST_MAX_PAYLOAD);
+        assert!(fragments.len() > 1, "payload should span multiple fragments");
+        // Corrupt a byte in one fragment's chunk data (after the header) so
+        // the digest no longer matches once reassembled.
+        let last = fragments.last_mut().expect("at least one fragment");
+        *last.last_mut().expect("non-empty fragment") ^= 0xFF;
+        for fragment in &fragments {
+            bus.publish("updates.firmware", fragment).await.expect("publish");
+        }
+
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), subscription.next())
+                .await
+                .is_err(),
+            "a corrupted object must never be yielded to the subscriber"
+        );
+    }
+
+    #[tokio::test]
+    async fn request_rejects_responses_claiming_multiple_fragments() {
+        let bus = Arc::new(ChunkingBus::new(Arc::new(MockBus::new()), TEST_MAX_PAYLOAD));
+        let mut subscription = bus.subscribe("echo").await.expect("subscribe");
+
+        let handle = tokio::spawn({
+            let bus = bus.clone();
+            async move {
+                if let Some(message) = subscription.next().await {
+                    // A single reply message that dishonestly claims to be
+                    // fragment 0 of 2: request() has no way to fetch the
+                    // rest, so it must reject rather than return a partial
+                    // payload.
+                    let header = FragmentHeader {
+                        object_id: Uuid::new_v4(),
+                        index: 0,
+                        total: 2,
+                        total_len: 20,
+                        digest: blake3::hash(b"partial").to_hex().to_string(),
+                    };
+                    let framed = encode_fragment(&header, b"partial");
+                    message
+                        .respond(bus.inner.as_ref(), &framed)
+                        .await
+                        .expect("respond");
+                }
+            }
+        });
+
+        let result = bus.request("echo", b"ping").await;
+        assert!(matches!(result, Err(MsgBusError::ReassemblyFailed(_))));
+        handle.await.expect("responder task");
+    }
+}
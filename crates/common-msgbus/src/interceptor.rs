@@ -0,0 +1,402 @@
+//! Tower-style interceptor chain for [`MessageBus`], so cross-cutting
+//! concerns (tracing, retries, rate limiting) can be composed around any
+//! backend without touching it. Mirrors how `api_gateway` composes its
+//! `RateLimiter`, but makes the behavior reusable at the bus level for
+//! every service.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::{BusMessage, MessageBus, MsgBusError, Subscription};
+
+/// The remaining interceptors (and ultimately the inner bus) that a
+/// [`BusInterceptor`] delegates to once it has done its own work.
+pub struct Next<'a> {
+    interceptors: &'a [Arc<dyn BusInterceptor>],
+    bus: &'a dyn MessageBus,
+}
+
+impl<'a> Next<'a> {
+    /// Runs the rest of the chain's publish hooks, terminating at the inner bus.
+    pub async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+        match self.interceptors.split_first() {
+            Some((first, rest)) => {
+                let next = Next {
+                    interceptors: rest,
+                    bus: self.bus,
+                };
+                first.on_publish(subject, payload, next).await
+            }
+            None => self.bus.publish(subject, payload).await,
+        }
+    }
+
+    /// Runs the rest of the chain's request hooks, terminating at the inner bus.
+    pub async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError> {
+        match self.interceptors.split_first() {
+            Some((first, rest)) => {
+                let next = Next {
+                    interceptors: rest,
+                    bus: self.bus,
+                };
+                first.on_request(subject, payload, next).await
+            }
+            None => self.bus.request(subject, payload).await,
+        }
+    }
+}
+
+/// A single cross-cutting concern wrapped around every `publish`/`request`
+/// call made through a [`Layered`] bus. Default methods pass straight
+/// through, so an interceptor only needs to override what it cares about.
+#[async_trait]
+pub trait BusInterceptor: Send + Sync {
+    async fn on_publish<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<(), MsgBusError> {
+        next.publish(subject, payload).await
+    }
+
+    async fn on_request<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<BusMessage, MsgBusError> {
+        next.request(subject, payload).await
+    }
+}
+
+/// Wraps an inner [`MessageBus`] with a chain of [`BusInterceptor`]s that run,
+/// in registration order, around every `publish`/`request` call. `subscribe`,
+/// `subscribe_queue`, and `respond` pass straight through: interceptors only
+/// see the caller-initiated half of an exchange.
+pub struct Layered<B: ?Sized> {
+    inner: Arc<B>,
+    interceptors: Vec<Arc<dyn BusInterceptor>>,
+}
+
+impl<B: ?Sized> Clone for Layered<B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            interceptors: self.interceptors.clone(),
+        }
+    }
+}
+
+impl<B: MessageBus + ?Sized> Layered<B> {
+    /// Wraps `inner` with an initially empty interceptor chain.
+    pub fn new(inner: Arc<B>) -> Self {
+        Self {
+            inner,
+            interceptors: Vec::new(),
+        }
+    }
+
+    /// Appends an interceptor to the end of the chain. The first interceptor
+    /// added is the first to see a call and the last to see its result.
+    pub fn layer(mut self, interceptor: impl BusInterceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+}
+
+#[async_trait]
+impl<B: MessageBus + ?Sized> MessageBus for Layered<B> {
+    async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+        let next = Next {
+            interceptors: &self.interceptors,
+            bus: self.inner.as_ref(),
+        };
+        next.publish(subject, payload).await
+    }
+
+    async fn subscribe(&self, subject: &str) -> Result<Subscription, MsgBusError> {
+        self.inner.subscribe(subject).await
+    }
+
+    async fn subscribe_queue(
+        &self,
+        subject: &str,
+        group: &str,
+    ) -> Result<Subscription, MsgBusError> {
+        self.inner.subscribe_queue(subject, group).await
+    }
+
+    async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError> {
+        let next = Next {
+            interceptors: &self.interceptors,
+            bus: self.inner.as_ref(),
+        };
+        next.request(subject, payload).await
+    }
+
+    async fn respond(&self, reply_to: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+        self.inner.respond(reply_to, payload).await
+    }
+}
+
+/// Wraps each call in a `bus.publish`/`bus.request` tracing span and tags the
+/// response of a `request` with a `trace-id` header, so callers downstream in
+/// the same process can correlate the exchange without needing a dedicated
+/// propagation format.
+#[derive(Debug, Default)]
+pub struct TracingInterceptor;
+
+#[async_trait]
+impl BusInterceptor for TracingInterceptor {
+    async fn on_publish<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<(), MsgBusError> {
+        let span = tracing::info_span!("bus.publish", subject = %subject, payload_len = payload.len());
+        let _guard = span.enter();
+        next.publish(subject, payload).await
+    }
+
+    async fn on_request<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<BusMessage, MsgBusError> {
+        let span = tracing::info_span!("bus.request", subject = %subject, payload_len = payload.len());
+        let trace_id = span
+            .id()
+            .map(|id| format!("{:x}", id.into_u64()))
+            .unwrap_or_default();
+        let mut response = {
+            let _guard = span.enter();
+            next.request(subject, payload).await?
+        };
+        response.headers.entry("trace-id".to_string()).or_insert(trace_id);
+        Ok(response)
+    }
+}
+
+/// Retries `publish`/`request` calls that fail with [`MsgBusError::Publish`]
+/// or [`MsgBusError::Request`], backing off exponentially (with jitter,
+/// capped at `max_delay`) between attempts.
+pub struct RetryInterceptor {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryInterceptor {
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// Exponential backoff seeded from `base_delay`, jittered by up to 20%
+    /// and capped at `max_delay`.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.min(6);
+        let base = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        let jitter_frac = rand::thread_rng().gen_range(0.0..0.2);
+        Duration::from_secs_f64((base.as_secs_f64() * (1.0 - jitter_frac)).max(0.0))
+    }
+
+    fn retryable(err: &MsgBusError) -> bool {
+        matches!(err, MsgBusError::Publish(_) | MsgBusError::Request(_))
+    }
+}
+
+#[async_trait]
+impl BusInterceptor for RetryInterceptor {
+    async fn on_publish<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<(), MsgBusError> {
+        let mut attempt = 0;
+        loop {
+            match next.publish(subject, payload).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt + 1 < self.max_attempts && Self::retryable(&err) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn on_request<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<BusMessage, MsgBusError> {
+        let mut attempt = 0;
+        loop {
+            match next.request(subject, payload).await {
+                Ok(message) => return Ok(message),
+                Err(err) if attempt + 1 < self.max_attempts && Self::retryable(&err) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+struct TokenBucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket rate limiter enforced before every `publish`/`request` call.
+pub struct RateLimitInterceptor {
+    state: Mutex<TokenBucketState>,
+    capacity: f64,
+    rate_per_second: f64,
+}
+
+impl RateLimitInterceptor {
+    pub fn new(capacity: u32, rate_per_second: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            state: Mutex::new(TokenBucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+            capacity,
+            rate_per_second,
+        }
+    }
+
+    async fn acquire(&self, on_limited: impl FnOnce(String) -> MsgBusError) -> Result<(), MsgBusError> {
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        if elapsed > 0.0 {
+            state.tokens = (state.tokens + elapsed * self.rate_per_second).min(self.capacity);
+            state.last_refill = now;
+        }
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(on_limited("rate limit exceeded".to_string()))
+        }
+    }
+}
+
+#[async_trait]
+impl BusInterceptor for RateLimitInterceptor {
+    async fn on_publish<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<(), MsgBusError> {
+        self.acquire(MsgBusError::Publish).await?;
+        next.publish(subject, payload).await
+    }
+
+    async fn on_request<'a>(
+        &'a self,
+        subject: &'a str,
+        payload: &'a [u8],
+        next: Next<'a>,
+    ) -> Result<BusMessage, MsgBusError> {
+        self.acquire(MsgBusError::Request).await?;
+        next.request(subject, payload).await
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::MockBus;
+
+    #[tokio::test]
+    async fn retry_interceptor_recovers_from_transient_failures() {
+        struct FlakyOnceBus {
+            inner: MockBus,
+            failures_left: AtomicU32,
+        }
+
+        #[async_trait]
+        impl MessageBus for FlakyOnceBus {
+            async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+                if self.failures_left.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    (n > 0).then_some(n - 1)
+                }).is_ok() {
+                    return Err(MsgBusError::Publish("transient".to_string()));
+                }
+                self.inner.publish(subject, payload).await
+            }
+
+            async fn subscribe(&self, subject: &str) -> Result<Subscription, MsgBusError> {
+                self.inner.subscribe(subject).await
+            }
+
+            async fn subscribe_queue(
+                &self,
+                subject: &str,
+                group: &str,
+            ) -> Result<Subscription, MsgBusError> {
+                self.inner.subscribe_queue(subject, group).await
+            }
+
+            async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError> {
+                self.inner.request(subject, payload).await
+            }
+
+            async fn respond(&self, reply_to: &str, payload: &[u8]) -> Result<(), MsgBusError> {
+                self.inner.respond(reply_to, payload).await
+            }
+        }
+
+        let flaky = Arc::new(FlakyOnceBus {
+            inner: MockBus::new(),
+            failures_left: AtomicU32::new(2),
+        });
+        let bus = Layered::new(flaky).layer(RetryInterceptor::new(
+            5,
+            Duration::from_millis(1),
+            Duration::from_millis(10),
+        ));
+
+        bus.publish("retry.subject", b"payload")
+            .await
+            .expect("publish should eventually succeed");
+    }
+
+    #[tokio::test]
+    async fn rate_limit_interceptor_rejects_once_exhausted() {
+        let bus = Layered::new(Arc::new(MockBus::new()))
+            .layer(RateLimitInterceptor::new(1, 0.001));
+
+        bus.publish("limited.subject", b"first")
+            .await
+            .expect("first publish within burst");
+        let result = bus.publish("limited.subject", b"second").await;
+        assert!(matches!(result, Err(MsgBusError::Publish(_))));
+    }
+}
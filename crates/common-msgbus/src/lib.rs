@@ -1,6 +1,16 @@
 //! Message bus abstractions shared across services.
 
+mod chunking;
+mod interceptor;
+
+pub use chunking::{ChunkingBus, DEFAULT_MAX_PAYLOAD};
+pub use interceptor::{
+    BusInterceptor, Layered, Next, RateLimitInterceptor, RetryInterceptor, TracingInterceptor,
+};
+
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use async_trait::async_trait;
@@ -17,6 +27,10 @@ pub struct BusMessage {
     pub payload: Vec<u8>,
     /// Optional reply subject to send a response to.
     pub reply: Option<String>,
+    /// Transport headers, e.g. a propagated `trace-id`. Populated from the
+    /// underlying NATS headers when present; empty for backends (like
+    /// [`MockBus`]) that don't carry out-of-band metadata.
+    pub headers: HashMap<String, String>,
 }
 
 impl BusMessage {
@@ -93,6 +107,112 @@ pub enum MsgBusError {
     /// Attempted to reply to an unknown subject.
     #[error("no pending request for reply subject {0}")]
     UnknownReplySubject(String),
+    /// A durable stream could not be created or attached to.
+    #[error("stream operation failed: {0}")]
+    Stream(String),
+    /// Acknowledging, nak'ing, or terminating a delivered message failed.
+    #[error("ack failed: {0}")]
+    Ack(String),
+    /// A transactional (half-message) publish could not be resolved.
+    #[error("transaction error: {0}")]
+    Transaction(String),
+    /// A chunked payload could not be reassembled, e.g. a digest mismatch
+    /// or a fragment that never arrived before the reassembly timeout.
+    #[error("failed to reassemble chunked payload: {0}")]
+    ReassemblyFailed(String),
+}
+
+/// Configuration for a durable stream that backs at-least-once delivery via
+/// [`DurableMessageBus::pull_consumer`].
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Stream name. NATS backs this with a JetStream stream of the same name.
+    pub name: String,
+    /// Subjects captured into the stream.
+    pub subjects: Vec<String>,
+    /// Maximum number of delivery attempts. [`MockBus`] then dead-letters the
+    /// message to `<name>.DLQ`; the NATS backend hands this limit to
+    /// JetStream, which stops redelivering but publishes no `<name>.DLQ` copy.
+    pub max_deliver: u32,
+    /// How long a delivered-but-unacknowledged message stays in-flight
+    /// before it is redelivered.
+    pub ack_wait: Duration,
+}
+
+/// Acknowledgment handle for a single delivered message, abstracting over
+/// the JetStream and in-memory backends.
+#[async_trait]
+trait Ack: Send + Sync {
+    async fn ack(&self, sequence: u64) -> Result<(), MsgBusError>;
+    async fn nak(&self, sequence: u64) -> Result<(), MsgBusError>;
+    async fn term(&self, sequence: u64) -> Result<(), MsgBusError>;
+}
+
+/// A message delivered by a [`PullConsumer`], along with the handles that
+/// control its at-least-once delivery.
+///
+/// The message is considered in-flight until [`ack`](Self::ack) is called;
+/// if `ack_wait` elapses first it is redelivered with an incremented
+/// delivery count, and once `max_deliver` attempts are exhausted it is no
+/// longer redelivered (see [`StreamConfig::max_deliver`] for where it goes).
+pub struct AckableMessage {
+    /// The delivered message.
+    pub msg: BusMessage,
+    sequence: u64,
+    acker: Arc<dyn Ack>,
+}
+
+impl AckableMessage {
+    fn new(msg: BusMessage, sequence: u64, acker: Arc<dyn Ack>) -> Self {
+        Self { msg, sequence, acker }
+    }
+
+    /// Acknowledge successful processing. The message will not be redelivered.
+    pub async fn ack(&self) -> Result<(), MsgBusError> {
+        self.acker.ack(self.sequence).await
+    }
+
+    /// Negatively acknowledge, making the message immediately eligible for
+    /// redelivery without waiting for `ack_wait` to elapse.
+    pub async fn nak(&self) -> Result<(), MsgBusError> {
+        self.acker.nak(self.sequence).await
+    }
+
+    /// Terminate delivery: the message will not be redelivered, regardless
+    /// of how many attempts remain. [`MockBus`] also dead-letters it to
+    /// `<name>.DLQ`; the NATS backend does not.
+    pub async fn term(&self) -> Result<(), MsgBusError> {
+        self.acker.term(self.sequence).await
+    }
+}
+
+/// Stream of [`AckableMessage`] pulled from a durable, acknowledgment-tracked
+/// consumer.
+pub struct PullConsumer {
+    inner: Pin<Box<dyn Stream<Item = AckableMessage> + Send>>,
+}
+
+impl PullConsumer {
+    fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = AckableMessage> + Send + 'static,
+    {
+        Self {
+            inner: Box::pin(stream),
+        }
+    }
+}
+
+impl Stream for PullConsumer {
+    type Item = AckableMessage;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `inner` is pinned inside the struct and never moved after construction.
+        unsafe {
+            let inner = self.map_unchecked_mut(|me| &mut me.inner);
+            inner.poll_next(cx)
+        }
+    }
 }
 
 /// Abstraction over the platform message bus backend.
@@ -104,6 +224,18 @@ pub trait MessageBus: Send + Sync {
     /// Subscribe to the given `subject`, returning a stream of [`BusMessage`].
     async fn subscribe(&self, subject: &str) -> Result<Subscription, MsgBusError>;
 
+    /// Subscribe to `subject` as a member of the named queue `group`.
+    ///
+    /// Messages published to `subject` are load-balanced round-robin across
+    /// the live members of `group`, rather than fanned out to every
+    /// subscriber as [`subscribe`](Self::subscribe) does. This is the
+    /// mechanism services use to scale horizontally across replicas.
+    async fn subscribe_queue(
+        &self,
+        subject: &str,
+        group: &str,
+    ) -> Result<Subscription, MsgBusError>;
+
     /// Send a request and wait for the response.
     async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError>;
 
@@ -111,11 +243,133 @@ pub trait MessageBus: Send + Sync {
     async fn respond(&self, reply_to: &str, payload: &[u8]) -> Result<(), MsgBusError>;
 }
 
+/// Extension for backends that support durable, at-least-once delivery via
+/// persistent consumers, on top of the fire-and-forget [`MessageBus`].
+///
+/// This is what device-command services should use instead of `subscribe`
+/// when they need to survive a transient subscriber crash without losing
+/// in-flight work.
+#[async_trait]
+pub trait DurableMessageBus: MessageBus {
+    /// Create (or update) a durable stream capturing the configured subjects.
+    async fn create_stream(&self, config: StreamConfig) -> Result<(), MsgBusError>;
+
+    /// Attach a pull consumer to `stream` for messages on `subject`.
+    async fn pull_consumer(&self, stream: &str, subject: &str)
+        -> Result<PullConsumer, MsgBusError>;
+}
+
+/// Identifies a single in-flight half-message transaction started by
+/// [`TransactionalMessageBus::publish_transactional`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TxnId(String);
+
+impl TxnId {
+    fn new(id: String) -> Self {
+        Self(id)
+    }
+
+    fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TxnId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Outcome of polling a [`TransactionChecker`] for an in-doubt transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxnState {
+    /// The caller's local work succeeded; promote the half-message.
+    Commit,
+    /// The caller's local work failed or never happened; discard it.
+    Rollback,
+    /// Still can't tell; poll again later (up to [`TransactionChecker::max_checks`]).
+    Unknown,
+}
+
+/// Resolves the outcome of an in-doubt transaction when the publisher
+/// crashes or hangs before calling `commit`/`rollback` itself, e.g. by
+/// checking whether the corresponding local write actually landed.
+#[async_trait]
+pub trait TransactionChecker: Send + Sync {
+    /// Checks whether the local work tied to `txn_id` completed.
+    async fn check(&self, txn_id: &TxnId) -> TxnState;
+
+    /// How long to wait after publish (or the previous check) before
+    /// checking again. Defaults to 5 seconds.
+    fn check_interval(&self) -> Duration {
+        Duration::from_secs(5)
+    }
+
+    /// Maximum number of checks before giving up and rolling back.
+    /// Defaults to 3.
+    fn max_checks(&self) -> u32 {
+        3
+    }
+}
+
+/// Extension for backends that support RocketMQ-style transactional
+/// (half-message) publishing, so a service can atomically tie a bus publish
+/// to a local state change without a separate transactional-outbox table.
+#[async_trait]
+pub trait TransactionalMessageBus: MessageBus {
+    /// Publishes `payload` to `subject` in a "prepared" (half) state that
+    /// subscribers do not see. The caller must eventually call
+    /// [`commit`](Self::commit) or [`rollback`](Self::rollback); if it
+    /// doesn't, `checker` is polled to decide the outcome.
+    async fn publish_transactional(
+        &self,
+        subject: &str,
+        payload: &[u8],
+        checker: Arc<dyn TransactionChecker>,
+    ) -> Result<TxnId, MsgBusError>;
+
+    /// Promotes the half-message to a real publish on its subject.
+    async fn commit(&self, txn_id: &TxnId) -> Result<(), MsgBusError>;
+
+    /// Discards the half-message; it is never delivered.
+    async fn rollback(&self, txn_id: &TxnId) -> Result<(), MsgBusError>;
+}
+
+/// Extension for fan-out ("scatter-gather") requests where more than one
+/// service may legitimately answer the same question, e.g. "which
+/// device-registry shard holds device X" when the registry is replicated.
+/// Unlike [`MessageBus::request`], which is done the instant one reply
+/// arrives, this waits out a collection `window` so late-but-valid replies
+/// from other replicas aren't missed.
+#[async_trait]
+pub trait ScatterGatherMessageBus: MessageBus {
+    /// Publishes `payload` to `subject` under a fresh reply subject and
+    /// collects responses until either `max_responses` arrive or `window`
+    /// elapses, whichever comes first. Returns whatever arrived, including
+    /// an empty `Vec` if nothing replied in time.
+    async fn request_many(
+        &self,
+        subject: &str,
+        payload: &[u8],
+        max_responses: usize,
+        window: Duration,
+    ) -> Result<Vec<BusMessage>, MsgBusError>;
+}
+
 #[cfg(feature = "nats")]
 mod nats_impl {
-    use super::{BusMessage, MessageBus, MsgBusError, Subscription};
+    use std::sync::Arc;
+
+    use super::{
+        Ack, AckableMessage, BusMessage, DurableMessageBus, MessageBus, MsgBusError, PullConsumer,
+        ScatterGatherMessageBus, StreamConfig, Subscription, TransactionChecker,
+        TransactionalMessageBus, TxnId, TxnState,
+    };
+    use async_nats::jetstream::{self, consumer::AckPolicy, stream::Config as JsStreamConfig};
     use async_trait::async_trait;
+    use dashmap::DashMap;
     use futures::StreamExt;
+    use std::sync::atomic::{AtomicU64, Ordering};
     use tokio::time::{timeout, Duration};
 
     /// Configuration for establishing a NATS-backed message bus connection.
@@ -125,6 +379,10 @@ mod nats_impl {
         pub url: String,
         /// How long to wait for request/response exchanges.
         pub request_timeout: Duration,
+        /// Threshold above which [`ChunkingBus`](crate::ChunkingBus) splits a
+        /// payload into fragments. Not enforced by `NatsBus` itself; wrap it
+        /// in a `ChunkingBus` constructed with this value to apply it.
+        pub max_payload: usize,
     }
 
     impl Default for NatsConfig {
@@ -132,14 +390,32 @@ mod nats_impl {
             Self {
                 url: "nats://127.0.0.1:4222".to_string(),
                 request_timeout: Duration::from_secs(2),
+                max_payload: crate::DEFAULT_MAX_PAYLOAD,
+            }
+        }
+    }
+
+    /// Best-effort translation of NATS headers onto [`BusMessage::headers`].
+    fn headers_to_map(headers: Option<&async_nats::HeaderMap>) -> std::collections::HashMap<String, String> {
+        let mut map = std::collections::HashMap::new();
+        if let Some(headers) = headers {
+            for (name, value) in headers.iter() {
+                if let Some(value) = value.iter().next() {
+                    map.insert(name.to_string(), value.to_string());
+                }
             }
         }
+        map
     }
 
     /// Wrapper around an [`async_nats::Client`] implementing [`MessageBus`].
     #[derive(Clone)]
     pub struct NatsBus {
         client: async_nats::Client,
+        jetstream: jetstream::Context,
+        streams: Arc<DashMap<String, StreamConfig>>,
+        transactions: Arc<DashMap<String, Arc<Txn>>>,
+        txn_counter: Arc<AtomicU64>,
         request_timeout: Duration,
     }
 
@@ -149,13 +425,87 @@ mod nats_impl {
             let client = async_nats::connect(config.url.clone())
                 .await
                 .map_err(|err| MsgBusError::Connection(err.to_string()))?;
+            let jetstream = jetstream::new(client.clone());
             Ok(Self {
                 client,
+                jetstream,
+                streams: Arc::new(DashMap::new()),
+                transactions: Arc::new(DashMap::new()),
+                txn_counter: Arc::new(AtomicU64::new(0)),
                 request_timeout: config.request_timeout,
             })
         }
     }
 
+    /// Half-message awaiting commit/rollback or a [`TransactionChecker`] verdict.
+    struct Txn {
+        subject: String,
+        payload: Vec<u8>,
+        checker: Arc<dyn TransactionChecker>,
+    }
+
+    /// Polls `checker` on `txn.check_interval()` until it returns a definitive
+    /// verdict or `max_checks()` is exhausted (which rolls back).
+    fn spawn_txn_checker(bus: NatsBus, txn_id: TxnId) {
+        tokio::spawn(async move {
+            let max_checks = match bus.transactions.get(txn_id.as_str()) {
+                Some(entry) => entry.value().checker.max_checks(),
+                None => return,
+            };
+            for _ in 0..max_checks {
+                let interval = match bus.transactions.get(txn_id.as_str()) {
+                    Some(entry) => entry.value().checker.check_interval(),
+                    None => return,
+                };
+                tokio::time::sleep(interval).await;
+
+                let txn = match bus.transactions.get(txn_id.as_str()) {
+                    Some(entry) => entry.value().clone(),
+                    None => return,
+                };
+                match txn.checker.check(&txn_id).await {
+                    TxnState::Commit => {
+                        let _ = bus.commit(&txn_id).await;
+                        return;
+                    }
+                    TxnState::Rollback => {
+                        let _ = bus.rollback(&txn_id).await;
+                        return;
+                    }
+                    TxnState::Unknown => continue,
+                }
+            }
+            let _ = bus.rollback(&txn_id).await;
+        });
+    }
+
+    /// Acks a message delivered by a JetStream pull consumer.
+    struct JetStreamAck(jetstream::Message);
+
+    #[async_trait]
+    impl Ack for JetStreamAck {
+        async fn ack(&self, _sequence: u64) -> Result<(), MsgBusError> {
+            self.0
+                .ack()
+                .await
+                .map_err(|err| MsgBusError::Ack(err.to_string()))
+        }
+
+        async fn nak(&self, _sequence: u64) -> Result<(), MsgBusError> {
+            self.0
+                .ack_with(jetstream::AckKind::Nak(None))
+                .await
+                .map_err(|err| MsgBusError::Ack(err.to_string()))
+        }
+
+        async fn term(&self, _sequence: u64) -> Result<(), MsgBusError> {
+            self.0
+                .ack_with(jetstream::AckKind::Term)
+                .await
+                .map_err(|err| MsgBusError::Ack(err.to_string()))
+        }
+    }
+
     #[async_trait]
     impl MessageBus for NatsBus {
         async fn publish(&self, subject: &str, payload: &[u8]) -> Result<(), MsgBusError> {
@@ -177,6 +527,28 @@ mod nats_impl {
                 subject: message.subject.to_string(),
                 payload: message.payload.to_vec(),
                 reply: message.reply.map(|subject| subject.to_string()),
+                headers: headers_to_map(message.headers.as_ref()),
+            });
+            Ok(Subscription::new(subject_str, stream))
+        }
+
+        async fn subscribe_queue(
+            &self,
+            subject: &str,
+            group: &str,
+        ) -> Result<Subscription, MsgBusError> {
+            let subject_str = subject.to_string();
+            let subscriber = self
+                .client
+                .queue_subscribe(subject_str.clone(), group.to_string())
+                .await
+                .map_err(|err| MsgBusError::Subscribe(err.to_string()))?;
+
+            let stream = subscriber.map(|message| BusMessage {
+                subject: message.subject.to_string(),
+                payload: message.payload.to_vec(),
+                reply: message.reply.map(|subject| subject.to_string()),
+                headers: headers_to_map(message.headers.as_ref()),
             });
             Ok(Subscription::new(subject_str, stream))
         }
@@ -195,6 +567,7 @@ mod nats_impl {
                 subject: response.subject.to_string(),
                 payload: response.payload.to_vec(),
                 reply: response.reply.map(|subject| subject.to_string()),
+                headers: headers_to_map(response.headers.as_ref()),
             })
         }
 
@@ -206,6 +579,158 @@ mod nats_impl {
         }
     }
 
+    #[async_trait]
+    impl ScatterGatherMessageBus for NatsBus {
+        async fn request_many(
+            &self,
+            subject: &str,
+            payload: &[u8],
+            max_responses: usize,
+            window: Duration,
+        ) -> Result<Vec<BusMessage>, MsgBusError> {
+            let inbox = self.client.new_inbox();
+            let mut subscriber = self
+                .client
+                .subscribe(inbox.clone())
+                .await
+                .map_err(|err| MsgBusError::Subscribe(err.to_string()))?;
+
+            self.client
+                .publish_with_reply(subject.to_string(), inbox, payload.to_vec().into())
+                .await
+                .map_err(|err| MsgBusError::Publish(err.to_string()))?;
+
+            let mut responses = Vec::new();
+            let deadline = tokio::time::sleep(window);
+            tokio::pin!(deadline);
+            while responses.len() < max_responses {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    message = subscriber.next() => match message {
+                        Some(message) => responses.push(BusMessage {
+                            subject: message.subject.to_string(),
+                            payload: message.payload.to_vec(),
+                            reply: message.reply.map(|subject| subject.to_string()),
+                            headers: headers_to_map(message.headers.as_ref()),
+                        }),
+                        None => break,
+                    },
+                }
+            }
+            Ok(responses)
+        }
+    }
+
+    #[async_trait]
+    impl DurableMessageBus for NatsBus {
+        async fn create_stream(&self, config: StreamConfig) -> Result<(), MsgBusError> {
+            self.jetstream
+                .create_stream(JsStreamConfig {
+                    name: config.name.clone(),
+                    subjects: config.subjects.clone(),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|err| MsgBusError::Stream(err.to_string()))?;
+            self.streams.insert(config.name.clone(), config);
+            Ok(())
+        }
+
+        async fn pull_consumer(
+            &self,
+            stream: &str,
+            subject: &str,
+        ) -> Result<PullConsumer, MsgBusError> {
+            let config = self
+                .streams
+                .get(stream)
+                .map(|entry| entry.value().clone())
+                .ok_or_else(|| MsgBusError::Stream(format!("unknown stream {stream}")))?;
+
+            let js_stream = self
+                .jetstream
+                .get_stream(stream)
+                .await
+                .map_err(|err| MsgBusError::Stream(err.to_string()))?;
+
+            let consumer = js_stream
+                .create_consumer(jetstream::consumer::pull::Config {
+                    durable_name: Some(format!("{stream}-{subject}")),
+                    filter_subject: subject.to_string(),
+                    ack_policy: AckPolicy::Explicit,
+                    max_deliver: config.max_deliver as i64,
+                    ack_wait: config.ack_wait,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|err| MsgBusError::Stream(err.to_string()))?;
+
+            let messages = consumer
+                .messages()
+                .await
+                .map_err(|err| MsgBusError::Stream(err.to_string()))?;
+
+            let stream = messages.filter_map(|delivery| async move {
+                let message = delivery.ok()?;
+                let sequence = message.info().ok()?.stream_sequence;
+                let bus_message = BusMessage {
+                    subject: message.subject.to_string(),
+                    payload: message.payload.to_vec(),
+                    reply: message.reply.as_ref().map(|subject| subject.to_string()),
+                    headers: headers_to_map(message.headers.as_ref()),
+                };
+                Some(AckableMessage::new(
+                    bus_message,
+                    sequence,
+                    Arc::new(JetStreamAck(message)),
+                ))
+            });
+
+            Ok(PullConsumer::new(stream))
+        }
+    }
+
+    #[async_trait]
+    impl TransactionalMessageBus for NatsBus {
+        async fn publish_transactional(
+            &self,
+            subject: &str,
+            payload: &[u8],
+            checker: Arc<dyn TransactionChecker>,
+        ) -> Result<TxnId, MsgBusError> {
+            let txn_id = TxnId::new(format!(
+                "txn-{}",
+                self.txn_counter.fetch_add(1, Ordering::Relaxed)
+            ));
+            let txn = Arc::new(Txn {
+                subject: subject.to_string(),
+                payload: payload.to_vec(),
+                checker,
+            });
+            self.transactions.insert(txn_id.as_str().to_string(), txn);
+            spawn_txn_checker(self.clone(), txn_id.clone());
+            Ok(txn_id)
+        }
+
+        async fn commit(&self, txn_id: &TxnId) -> Result<(), MsgBusError> {
+            let (_, txn) = self.transactions.remove(txn_id.as_str()).ok_or_else(|| {
+                MsgBusError::Transaction(format!(
+                    "unknown or already-resolved transaction {txn_id}"
+                ))
+            })?;
+            self.publish(&txn.subject, &txn.payload).await
+        }
+
+        async fn rollback(&self, txn_id: &TxnId) -> Result<(), MsgBusError> {
+            self.transactions.remove(txn_id.as_str()).ok_or_else(|| {
+                MsgBusError::Transaction(format!(
+                    "unknown or already-resolved transaction {txn_id}"
+                ))
+            })?;
+            Ok(())
+        }
+    }
+
     pub use NatsBus as Client;
     pub use NatsConfig as Config;
 }
@@ -215,18 +740,23 @@ pub use nats_impl::{Client as NatsBus, Config as NatsConfig};
 
 #[cfg(feature = "mock")]
 mod mock_impl {
+    use std::collections::{HashMap, VecDeque};
     use std::sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
     };
 
     use async_trait::async_trait;
     use dashmap::DashMap;
     use futures::StreamExt;
-    use tokio::sync::{broadcast, oneshot};
-    use tokio_stream::wrappers::BroadcastStream;
+    use tokio::sync::{broadcast, mpsc, oneshot};
+    use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
-    use super::{BusMessage, MessageBus, MsgBusError, Subscription};
+    use super::{
+        Ack, AckableMessage, BusMessage, DurableMessageBus, MessageBus, MsgBusError, PullConsumer,
+        ScatterGatherMessageBus, StreamConfig, Subscription, TransactionChecker,
+        TransactionalMessageBus, TxnId, TxnState,
+    };
 
     const CHANNEL_CAPACITY: usize = 64;
 
@@ -245,11 +775,231 @@ mod mock_impl {
         }
     }
 
+    /// Round-robin membership for a single `(subject, group)` queue group.
+    #[derive(Default)]
+    struct QueueGroup {
+        members: Mutex<Vec<mpsc::UnboundedSender<BusMessage>>>,
+        next: AtomicUsize,
+    }
+
+    impl QueueGroup {
+        /// Dispatches `message` to exactly one live member, pruning any that
+        /// have since been dropped.
+        fn dispatch(&self, message: BusMessage) {
+            let mut members = self.members.lock().expect("lock poisoned");
+            members.retain(|member| !member.is_closed());
+            if members.is_empty() {
+                return;
+            }
+            let index = self.next.fetch_add(1, Ordering::Relaxed) % members.len();
+            let _ = members[index].send(message);
+        }
+    }
+
+    /// Tracks a single in-flight delivery attempt. `generation` is bumped on
+    /// every (re)delivery so a stale `ack_wait` timer can tell it already
+    /// lost the race to an ack, nak, or a later redelivery.
+    struct PendingEntry {
+        deliveries: u32,
+        generation: u64,
+    }
+
+    /// In-memory append log for a single durable stream, plus the
+    /// pending-ack bookkeeping that drives redelivery.
+    struct StreamState {
+        config: StreamConfig,
+        subjects: Arc<DashMap<String, broadcast::Sender<BusMessage>>>,
+        log: Mutex<Vec<BusMessage>>,
+        undelivered: Mutex<VecDeque<u64>>,
+        pending: DashMap<u64, PendingEntry>,
+        consumer: Mutex<Option<mpsc::UnboundedSender<AckableMessage>>>,
+    }
+
+    /// Delivers (or redelivers) the message at `sequence`, bumping its
+    /// delivery count and arming an `ack_wait` timer that redelivers or
+    /// dead-letters it if nothing acks in time.
+    fn deliver(state: Arc<StreamState>, sequence: u64) {
+        let (deliveries, generation) = {
+            let mut entry = state
+                .pending
+                .entry(sequence)
+                .or_insert(PendingEntry {
+                    deliveries: 0,
+                    generation: 0,
+                });
+            entry.deliveries += 1;
+            entry.generation += 1;
+            (entry.deliveries, entry.generation)
+        };
+
+        let message = match state.log.lock().expect("lock poisoned").get((sequence - 1) as usize) {
+            Some(message) => message.clone(),
+            None => return,
+        };
+
+        let delivered = state
+            .consumer
+            .lock()
+            .expect("lock poisoned")
+            .as_ref()
+            .map(|sender| {
+                let ackable = AckableMessage::new(
+                    message,
+                    sequence,
+                    Arc::new(MockAck {
+                        state: state.clone(),
+                    }),
+                );
+                sender.send(ackable).is_ok()
+            });
+
+        if delivered != Some(true) {
+            state.pending.remove(&sequence);
+            state.undelivered.lock().expect("lock poisoned").push_back(sequence);
+            return;
+        }
+
+        let ack_wait = state.config.ack_wait;
+        let max_deliver = state.config.max_deliver;
+        tokio::spawn(async move {
+            tokio::time::sleep(ack_wait).await;
+            let still_current = state
+                .pending
+                .get(&sequence)
+                .map(|entry| entry.generation == generation)
+                .unwrap_or(false);
+            if !still_current {
+                // Acked, termed, or nak'd (and thus already redelivered) since this timer armed.
+                return;
+            }
+            if deliveries >= max_deliver {
+                dead_letter(&state, sequence).await;
+            } else {
+                deliver(state.clone(), sequence);
+            }
+        });
+    }
+
+    /// Flushes messages appended before a consumer attached.
+    fn flush_undelivered(state: &Arc<StreamState>) {
+        let sequences: Vec<u64> = state
+            .undelivered
+            .lock()
+            .expect("lock poisoned")
+            .drain(..)
+            .collect();
+        for sequence in sequences {
+            deliver(state.clone(), sequence);
+        }
+    }
+
+    /// Dead-letters the message at `sequence` to `<stream>.DLQ`, fanning it
+    /// out like a regular publish rather than re-entering the stream log.
+    async fn dead_letter(state: &Arc<StreamState>, sequence: u64) {
+        state.pending.remove(&sequence);
+        let message = state
+            .log
+            .lock()
+            .expect("lock poisoned")
+            .get((sequence - 1) as usize)
+            .cloned();
+        if let Some(message) = message {
+            let dlq_subject = format!("{}.DLQ", state.config.name);
+            let sender = ensure_subject(&state.subjects, &dlq_subject);
+            let _ = sender.send(BusMessage {
+                subject: dlq_subject,
+                payload: message.payload,
+                reply: None,
+                headers: HashMap::new(),
+            });
+        }
+    }
+
+    /// Acks a message delivered by a [`StreamState`]'s pull consumer.
+    struct MockAck {
+        state: Arc<StreamState>,
+    }
+
+    #[async_trait]
+    impl Ack for MockAck {
+        async fn ack(&self, sequence: u64) -> Result<(), MsgBusError> {
+            self.state.pending.remove(&sequence);
+            Ok(())
+        }
+
+        async fn nak(&self, sequence: u64) -> Result<(), MsgBusError> {
+            if self.state.pending.contains_key(&sequence) {
+                deliver(self.state.clone(), sequence);
+            }
+            Ok(())
+        }
+
+        async fn term(&self, sequence: u64) -> Result<(), MsgBusError> {
+            if self.state.pending.remove(&sequence).is_some() {
+                dead_letter(&self.state, sequence).await;
+            }
+            Ok(())
+        }
+    }
+
+    /// Half-message awaiting commit/rollback or a [`TransactionChecker`] verdict.
+    struct Txn {
+        subject: String,
+        payload: Vec<u8>,
+        checker: Arc<dyn TransactionChecker>,
+    }
+
+    /// Polls `checker` on `txn.check_interval()` until it returns a definitive
+    /// verdict or `max_checks()` is exhausted (which rolls back).
+    fn spawn_txn_checker(bus: MockBus, txn_id: TxnId) {
+        tokio::spawn(async move {
+            let max_checks = match bus.transactions.get(txn_id.as_str()) {
+                Some(entry) => entry.value().checker.max_checks(),
+                None => return,
+            };
+            for _ in 0..max_checks {
+                let interval = match bus.transactions.get(txn_id.as_str()) {
+                    Some(entry) => entry.value().checker.check_interval(),
+                    None => return,
+                };
+                tokio::time::sleep(interval).await;
+
+                let txn = match bus.transactions.get(txn_id.as_str()) {
+                    Some(entry) => entry.value().clone(),
+                    None => return,
+                };
+                match txn.checker.check(&txn_id).await {
+                    TxnState::Commit => {
+                        let _ = bus.commit(&txn_id).await;
+                        return;
+                    }
+                    TxnState::Rollback => {
+                        let _ = bus.rollback(&txn_id).await;
+                        return;
+                    }
+                    TxnState::Unknown => continue,
+                }
+            }
+            let _ = bus.rollback(&txn_id).await;
+        });
+    }
+
+    /// Awaiting responder(s) for a reply subject: `request` registers a
+    /// single-use [`oneshot`], while `request_many` registers a multi-use
+    /// channel so every responder's `respond` call gets through.
+    enum PendingReply {
+        Single(oneshot::Sender<Vec<u8>>),
+        Many(mpsc::UnboundedSender<Vec<u8>>),
+    }
+
     /// In-process mock message bus used for unit/integration tests.
     #[derive(Clone, Default)]
     pub struct MockBus {
         subjects: Arc<DashMap<String, broadcast::Sender<BusMessage>>>,
-        pending: Arc<DashMap<String, oneshot::Sender<Vec<u8>>>>,
+        queue_groups: Arc<DashMap<(String, String), Arc<QueueGroup>>>,
+        streams: Arc<DashMap<String, Arc<StreamState>>>,
+        transactions: Arc<DashMap<String, Arc<Txn>>>,
+        pending: Arc<DashMap<String, PendingReply>>,
         request_counter: Arc<AtomicU64>,
     }
 
@@ -263,6 +1013,34 @@ mod mock_impl {
             let id = self.request_counter.fetch_add(1, Ordering::Relaxed);
             format!("inproc.reply.{}", id)
         }
+
+        fn dispatch_to_queue_groups(&self, subject: &str, message: &BusMessage) {
+            for entry in self.queue_groups.iter() {
+                let (group_subject, _group) = entry.key();
+                if group_subject == subject {
+                    entry.value().dispatch(message.clone());
+                }
+            }
+        }
+
+        fn dispatch_to_streams(&self, subject: &str, message: &BusMessage) {
+            for entry in self.streams.iter() {
+                let state = entry.value();
+                if !state.config.subjects.iter().any(|s| s == subject) {
+                    continue;
+                }
+                let sequence = {
+                    let mut log = state.log.lock().expect("lock poisoned");
+                    log.push(message.clone());
+                    log.len() as u64
+                };
+                if state.consumer.lock().expect("lock poisoned").is_some() {
+                    deliver(state.clone(), sequence);
+                } else {
+                    state.undelivered.lock().expect("lock poisoned").push_back(sequence);
+                }
+            }
+        }
     }
 
     #[async_trait]
@@ -273,11 +1051,14 @@ mod mock_impl {
                 subject: subject.to_string(),
                 payload: payload.to_vec(),
                 reply: None,
+                headers: HashMap::new(),
             };
-            sender
-                .send(message)
-                .map(|_| ())
-                .map_err(|err| MsgBusError::Publish(err.to_string()))
+            self.dispatch_to_queue_groups(subject, &message);
+            self.dispatch_to_streams(subject, &message);
+            // A lack of fan-out subscribers is not itself a publish failure;
+            // it just means nobody happened to be listening via `subscribe`.
+            let _ = sender.send(message);
+            Ok(())
         }
 
         async fn subscribe(&self, subject: &str) -> Result<Subscription, MsgBusError> {
@@ -292,16 +1073,37 @@ mod mock_impl {
             Ok(Subscription::new(subject_string, stream))
         }
 
+        async fn subscribe_queue(
+            &self,
+            subject: &str,
+            group: &str,
+        ) -> Result<Subscription, MsgBusError> {
+            let key = (subject.to_string(), group.to_string());
+            let queue_group = self
+                .queue_groups
+                .entry(key)
+                .or_insert_with(|| Arc::new(QueueGroup::default()))
+                .clone();
+
+            let (tx, rx) = mpsc::unbounded_channel();
+            queue_group.members.lock().expect("lock poisoned").push(tx);
+
+            let stream = UnboundedReceiverStream::new(rx);
+            Ok(Subscription::new(subject.to_string(), stream))
+        }
+
         async fn request(&self, subject: &str, payload: &[u8]) -> Result<BusMessage, MsgBusError> {
             let (tx, rx) = oneshot::channel();
             let reply_subject = self.next_reply_subject();
-            self.pending.insert(reply_subject.clone(), tx);
+            self.pending
+                .insert(reply_subject.clone(), PendingReply::Single(tx));
 
             let sender = ensure_subject(&self.subjects, subject);
             let message = BusMessage {
                 subject: subject.to_string(),
                 payload: payload.to_vec(),
                 reply: Some(reply_subject.clone()),
+                headers: HashMap::new(),
             };
             sender
                 .send(message)
@@ -315,27 +1117,166 @@ mod mock_impl {
                 subject: reply_subject,
                 payload,
                 reply: None,
+                headers: HashMap::new(),
             })
         }
 
         async fn respond(&self, reply_to: &str, payload: &[u8]) -> Result<(), MsgBusError> {
-            if let Some((_, waiter)) = self.pending.remove(reply_to) {
-                waiter
+            // `Many` waiters stay registered so later responders can still
+            // deliver; `Single` waiters are one-shot and are removed here.
+            if let Some(entry) = self.pending.get(reply_to) {
+                if let PendingReply::Many(tx) = entry.value() {
+                    return tx
+                        .send(payload.to_vec())
+                        .map_err(|_| MsgBusError::Request("pending request dropped".to_string()));
+                }
+            }
+
+            if let Some((_, PendingReply::Single(tx))) = self.pending.remove(reply_to) {
+                return tx
                     .send(payload.to_vec())
-                    .map_err(|_| MsgBusError::Request("pending request dropped".to_string()))
-            } else {
-                // fall back to publish semantics if no pending request exists
-                let sender = ensure_subject(&self.subjects, reply_to);
-                let message = BusMessage {
-                    subject: reply_to.to_string(),
-                    payload: payload.to_vec(),
-                    reply: None,
-                };
-                sender
-                    .send(message)
-                    .map(|_| ())
-                    .map_err(|_| MsgBusError::UnknownReplySubject(reply_to.to_string()))
+                    .map_err(|_| MsgBusError::Request("pending request dropped".to_string()));
+            }
+
+            // fall back to publish semantics if no pending request exists
+            let sender = ensure_subject(&self.subjects, reply_to);
+            let message = BusMessage {
+                subject: reply_to.to_string(),
+                payload: payload.to_vec(),
+                reply: None,
+                headers: HashMap::new(),
+            };
+            sender
+                .send(message)
+                .map(|_| ())
+                .map_err(|_| MsgBusError::UnknownReplySubject(reply_to.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl ScatterGatherMessageBus for MockBus {
+        async fn request_many(
+            &self,
+            subject: &str,
+            payload: &[u8],
+            max_responses: usize,
+            window: Duration,
+        ) -> Result<Vec<BusMessage>, MsgBusError> {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let reply_subject = self.next_reply_subject();
+            self.pending
+                .insert(reply_subject.clone(), PendingReply::Many(tx));
+
+            let sender = ensure_subject(&self.subjects, subject);
+            let message = BusMessage {
+                subject: subject.to_string(),
+                payload: payload.to_vec(),
+                reply: Some(reply_subject.clone()),
+                headers: HashMap::new(),
+            };
+            sender
+                .send(message)
+                .map_err(|err| MsgBusError::Request(err.to_string()))?;
+
+            let mut responses = Vec::new();
+            let deadline = tokio::time::sleep(window);
+            tokio::pin!(deadline);
+            while responses.len() < max_responses {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    payload = rx.recv() => match payload {
+                        Some(payload) => responses.push(BusMessage {
+                            subject: reply_subject.clone(),
+                            payload,
+                            reply: None,
+                            headers: HashMap::new(),
+                        }),
+                        None => break,
+                    },
+                }
+            }
+            self.pending.remove(&reply_subject);
+            Ok(responses)
+        }
+    }
+
+    #[async_trait]
+    impl DurableMessageBus for MockBus {
+        async fn create_stream(&self, config: StreamConfig) -> Result<(), MsgBusError> {
+            let state = Arc::new(StreamState {
+                subjects: self.subjects.clone(),
+                log: Mutex::new(Vec::new()),
+                undelivered: Mutex::new(VecDeque::new()),
+                pending: DashMap::new(),
+                consumer: Mutex::new(None),
+                config: config.clone(),
+            });
+            self.streams.insert(config.name, state);
+            Ok(())
+        }
+
+        async fn pull_consumer(
+            &self,
+            stream: &str,
+            subject: &str,
+        ) -> Result<PullConsumer, MsgBusError> {
+            let state = self
+                .streams
+                .get(stream)
+                .map(|entry| entry.value().clone())
+                .ok_or_else(|| MsgBusError::Stream(format!("unknown stream {stream}")))?;
+            if !state.config.subjects.iter().any(|s| s == subject) {
+                return Err(MsgBusError::Stream(format!(
+                    "stream {stream} does not capture subject {subject}"
+                )));
             }
+
+            let (tx, rx) = mpsc::unbounded_channel();
+            *state.consumer.lock().expect("lock poisoned") = Some(tx);
+            flush_undelivered(&state);
+
+            Ok(PullConsumer::new(UnboundedReceiverStream::new(rx)))
+        }
+    }
+
+    #[async_trait]
+    impl TransactionalMessageBus for MockBus {
+        async fn publish_transactional(
+            &self,
+            subject: &str,
+            payload: &[u8],
+            checker: Arc<dyn TransactionChecker>,
+        ) -> Result<TxnId, MsgBusError> {
+            let txn_id = TxnId::new(format!(
+                "txn-{}",
+                self.request_counter.fetch_add(1, Ordering::Relaxed)
+            ));
+            let txn = Arc::new(Txn {
+                subject: subject.to_string(),
+                payload: payload.to_vec(),
+                checker,
+            });
+            self.transactions.insert(txn_id.as_str().to_string(), txn);
+            spawn_txn_checker(self.clone(), txn_id.clone());
+            Ok(txn_id)
+        }
+
+        async fn commit(&self, txn_id: &TxnId) -> Result<(), MsgBusError> {
+            let (_, txn) = self.transactions.remove(txn_id.as_str()).ok_or_else(|| {
+                MsgBusError::Transaction(format!(
+                    "unknown or already-resolved transaction {txn_id}"
+                ))
+            })?;
+            self.publish(&txn.subject, &txn.payload).await
+        }
+
+        async fn rollback(&self, txn_id: &TxnId) -> Result<(), MsgBusError> {
+            self.transactions.remove(txn_id.as_str()).ok_or_else(|| {
+                MsgBusError::Transaction(format!(
+                    "unknown or already-resolved transaction {txn_id}"
+                ))
+            })?;
+            Ok(())
         }
     }
 
@@ -347,9 +1288,15 @@ pub use mock_impl::Client as MockBus;
 
 #[cfg(all(test, feature = "mock"))]
 mod tests {
+    use std::sync::Arc;
+
     use futures::StreamExt;
+    use tokio::time::Duration;
 
-    use super::{MessageBus, MockBus};
+    use super::{
+        DurableMessageBus, MessageBus, MockBus, ScatterGatherMessageBus, StreamConfig,
+        TransactionChecker, TransactionalMessageBus, TxnId, TxnState,
+    };
 
     #[tokio::test]
     async fn mock_bus_round_trip() {
@@ -372,4 +1319,233 @@ mod tests {
         assert_eq!(response.payload, b"pong");
         handle.await.expect("subscription task");
     }
+
+    #[tokio::test]
+    async fn queue_group_balances_round_robin() {
+        let bus = MockBus::new();
+        let mut first = bus
+            .subscribe_queue("work.subject", "workers")
+            .await
+            .expect("subscribe_queue");
+        let mut second = bus
+            .subscribe_queue("work.subject", "workers")
+            .await
+            .expect("subscribe_queue");
+
+        for i in 0..4 {
+            bus.publish("work.subject", format!("msg{i}").as_bytes())
+                .await
+                .expect("publish");
+        }
+
+        let first_received = vec![
+            first.next().await.expect("first message").payload,
+            first.next().await.expect("first message").payload,
+        ];
+        let second_received = vec![
+            second.next().await.expect("second message").payload,
+            second.next().await.expect("second message").payload,
+        ];
+
+        assert_eq!(first_received, vec![b"msg0".to_vec(), b"msg2".to_vec()]);
+        assert_eq!(second_received, vec![b"msg1".to_vec(), b"msg3".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn pull_consumer_redelivers_until_acked() {
+        let bus = MockBus::new();
+        bus.create_stream(StreamConfig {
+            name: "commands".to_string(),
+            subjects: vec!["commands.device".to_string()],
+            max_deliver: 3,
+            ack_wait: Duration::from_millis(20),
+        })
+        .await
+        .expect("create_stream");
+
+        let mut consumer = bus
+            .pull_consumer("commands", "commands.device")
+            .await
+            .expect("pull_consumer");
+        bus.publish("commands.device", b"reboot")
+            .await
+            .expect("publish");
+
+        let first = consumer.next().await.expect("first delivery");
+        assert_eq!(first.msg.payload, b"reboot");
+        // Let ack_wait lapse without acking so it's redelivered.
+        let second = consumer.next().await.expect("redelivery");
+        assert_eq!(second.msg.payload, b"reboot");
+        second.ack().await.expect("ack");
+    }
+
+    #[tokio::test]
+    async fn pull_consumer_dead_letters_after_max_deliver() {
+        let bus = MockBus::new();
+        bus.create_stream(StreamConfig {
+            name: "commands".to_string(),
+            subjects: vec!["commands.device".to_string()],
+            max_deliver: 2,
+            ack_wait: Duration::from_millis(20),
+        })
+        .await
+        .expect("create_stream");
+
+        let mut dlq = bus.subscribe("commands.DLQ").await.expect("subscribe dlq");
+        let mut consumer = bus
+            .pull_consumer("commands", "commands.device")
+            .await
+            .expect("pull_consumer");
+        bus.publish("commands.device", b"reboot")
+            .await
+            .expect("publish");
+
+        // Neither the initial delivery nor its one redelivery gets acked, so
+        // the second `ack_wait` timeout should dead-letter it.
+        let _ = consumer.next().await.expect("first delivery");
+        let _ = consumer.next().await.expect("redelivery");
+
+        let dead_lettered = dlq.next().await.expect("dead letter");
+        assert_eq!(dead_lettered.payload, b"reboot");
+    }
+
+    struct FixedChecker {
+        verdict: TxnState,
+        check_interval: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl TransactionChecker for FixedChecker {
+        async fn check(&self, _txn_id: &TxnId) -> TxnState {
+            self.verdict
+        }
+
+        fn check_interval(&self) -> Duration {
+            self.check_interval
+        }
+
+        fn max_checks(&self) -> u32 {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn transactional_publish_commits_on_explicit_commit() {
+        let bus = MockBus::new();
+        let mut subscription = bus.subscribe("orders.created").await.expect("subscribe");
+
+        let checker = Arc::new(FixedChecker {
+            verdict: TxnState::Unknown,
+            check_interval: Duration::from_secs(5),
+        });
+        let txn_id = bus
+            .publish_transactional("orders.created", b"order-1", checker)
+            .await
+            .expect("publish_transactional");
+
+        bus.commit(&txn_id).await.expect("commit");
+
+        let delivered = subscription.next().await.expect("delivery");
+        assert_eq!(delivered.payload, b"order-1");
+
+        // Already resolved, so a second commit must be rejected.
+        assert!(bus.commit(&txn_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn transactional_publish_rolls_back_on_explicit_rollback() {
+        let bus = MockBus::new();
+        let mut subscription = bus.subscribe("orders.created").await.expect("subscribe");
+
+        let checker = Arc::new(FixedChecker {
+            verdict: TxnState::Unknown,
+            check_interval: Duration::from_secs(5),
+        });
+        let txn_id = bus
+            .publish_transactional("orders.created", b"order-1", checker)
+            .await
+            .expect("publish_transactional");
+
+        bus.rollback(&txn_id).await.expect("rollback");
+
+        assert!(bus.commit(&txn_id).await.is_err());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), subscription.next())
+                .await
+                .is_err(),
+            "rolled-back transaction must never publish"
+        );
+    }
+
+    #[tokio::test]
+    async fn transaction_checker_commits_after_max_checks() {
+        let bus = MockBus::new();
+        let mut subscription = bus.subscribe("orders.created").await.expect("subscribe");
+
+        let checker = Arc::new(FixedChecker {
+            verdict: TxnState::Commit,
+            check_interval: Duration::from_millis(10),
+        });
+        bus.publish_transactional("orders.created", b"order-1", checker)
+            .await
+            .expect("publish_transactional");
+
+        let delivered = subscription.next().await.expect("delivery");
+        assert_eq!(delivered.payload, b"order-1");
+    }
+
+    #[tokio::test]
+    async fn request_many_collects_replies_from_every_responder() {
+        let bus = MockBus::new();
+        let mut subscription = bus
+            .subscribe("registry.locate")
+            .await
+            .expect("subscribe");
+
+        let handle = tokio::spawn({
+            let bus = bus.clone();
+            async move {
+                for _ in 0..3 {
+                    if let Some(message) = subscription.next().await {
+                        message.respond(&bus, b"shard").await.expect("respond");
+                    }
+                }
+            }
+        });
+
+        let responses = bus
+            .request_many(
+                "registry.locate",
+                b"device-42",
+                3,
+                Duration::from_millis(200),
+            )
+            .await
+            .expect("request_many");
+
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|message| message.payload == b"shard"));
+        handle.await.expect("responder task");
+    }
+
+    #[tokio::test]
+    async fn request_many_stops_at_window_when_under_max() {
+        let bus = MockBus::new();
+        let _subscription = bus
+            .subscribe("registry.locate")
+            .await
+            .expect("subscribe");
+
+        let responses = bus
+            .request_many(
+                "registry.locate",
+                b"device-42",
+                5,
+                Duration::from_millis(20),
+            )
+            .await
+            .expect("request_many");
+
+        assert!(responses.is_empty(), "no responder replied before the window elapsed");
+    }
 }
@@ -5,11 +5,19 @@ use std::{
         atomic::{AtomicU64, Ordering},
         Arc, Mutex, RwLock,
     },
+    time::{Duration, SystemTime},
 };
 
-use axum::{routing::get, Json, Router};
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    routing::get,
+    Json, Router,
+};
 use once_cell::sync::OnceCell;
+use reqwest::Client;
+use serde::Serialize;
 use serde_json::json;
+use tokio::sync::mpsc;
 use tracing::{self, field::Visit, span};
 use tracing_subscriber::{
     fmt::{self as tsfmt, format::Writer, FmtContext, FormatEvent, FormatFields, MakeWriter},
@@ -66,10 +74,78 @@ impl ObsInit {
             .event_format(ObsJsonFormat::new(service_name.clone()))
             .with_writer(writer);
 
+        // When set, spans are additionally batched and shipped to an OTLP
+        // collector; unset, behavior is unchanged from JSON-log-only.
+        let otlp_layer = std::env::var("OTLP_ENDPOINT")
+            .ok()
+            .filter(|endpoint| !endpoint.is_empty())
+            .map(|endpoint| OtlpExportLayer::new(endpoint, OTLP_DEFAULT_FLUSH_INTERVAL));
+
         Registry::default()
             .with(env_filter)
             .with(trace_layer)
             .with(fmt_layer)
+            .with(otlp_layer)
+    }
+
+    /// Like [`Self::init`], but also spawns a [`console-subscriber`] server
+    /// and layers async task instrumentation onto the subscriber, so a
+    /// `tokio-console` client can inspect task polls, stalls, and resource
+    /// waits. Requires the `tokio-console` feature and a runtime built with
+    /// `tokio_unstable`; composes with the existing JSON and trace layers
+    /// rather than replacing them.
+    #[cfg(feature = "tokio-console")]
+    pub fn with_console(service: &str) -> Result<(), ObsInitError> {
+        let subscriber =
+            Self::subscriber_with_writer(service, io::stderr).with(console_layer::build());
+        tracing::subscriber::set_global_default(subscriber).map_err(|err| {
+            if tracing::dispatcher::has_been_set() {
+                ObsInitError::AlreadyInitialized
+            } else {
+                ObsInitError::Install(err)
+            }
+        })
+    }
+}
+
+#[cfg(feature = "tokio-console")]
+mod console_layer {
+    //! Thin wrapper around `console-subscriber` so [`ObsInit::with_console`]
+    //! doesn't need to know its builder API. Kept in its own module, gated
+    //! behind the `tokio-console` feature, so non-instrumented builds don't
+    //! pull the dependency in at all.
+
+    use console_subscriber::ConsoleLayer;
+
+    /// Spawns the console-subscriber gRPC server on its default address
+    /// (overridable via the `TOKIO_CONSOLE_BIND` env var, per
+    /// `console-subscriber`'s own conventions) and returns the layer to
+    /// compose onto the rest of the subscriber stack.
+    pub(super) fn build() -> ConsoleLayer {
+        ConsoleLayer::builder().with_default_env().spawn()
+    }
+}
+
+/// Static identity attached to OTLP resource attributes, so a collector
+/// can tell which service (and which build of it) a push came from.
+/// `version`/`build_sha` can't be resolved here via `env!`/`option_env!`,
+/// since those expand against this crate's own build, not the caller's —
+/// callers build one the same way they already build their local
+/// `VERSION`/`build_sha()` (see e.g. `services/energy-svc/src/main.rs`).
+#[derive(Debug, Clone, Copy)]
+pub struct BuildInfo {
+    pub service: &'static str,
+    pub version: &'static str,
+    pub build_sha: &'static str,
+}
+
+impl BuildInfo {
+    pub fn new(service: &'static str, version: &'static str, build_sha: &'static str) -> Self {
+        Self {
+            service,
+            version,
+            build_sha,
+        }
     }
 }
 
@@ -93,6 +169,41 @@ pub fn health_router(service: &'static str) -> Router {
         .route("/v1/info", info_handler)
 }
 
+/// Build a router that serves the encoded Prometheus registry, mirroring
+/// [`health_router`]'s `/v1`-prefixed alias so a scraper can hit either path.
+pub fn metrics_router() -> Router {
+    let handler = get(|| async {
+        (
+            StatusCode::OK,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(metrics::PROMETHEUS_CONTENT_TYPE),
+            )],
+            metrics::gather(),
+        )
+    });
+
+    Router::new()
+        .route("/metrics", handler.clone())
+        .route("/v1/metrics", handler)
+}
+
+/// As [`health_router`], but also merges in [`metrics_router`] so a single
+/// `Router` exposes health, info, and metrics for scrape-based monitoring.
+pub fn health_router_with_metrics(service: &'static str) -> Router {
+    health_router(service).merge(metrics_router())
+}
+
+/// Build a router serving [`metrics::snapshot`] as JSON, for admin tooling
+/// and tests that want to assert on metric values without parsing the
+/// Prometheus exposition text.
+pub fn metrics_snapshot_router() -> Router {
+    let handler = get(|| async { Json(metrics::snapshot()) });
+    Router::new()
+        .route("/metrics/snapshot", handler.clone())
+        .route("/v1/metrics/snapshot", handler)
+}
+
 /// Helper trait for request scoped metadata.
 pub trait SpanExt {
     /// Record a request identifier on the span so that subsequent logs emit it.
@@ -100,6 +211,18 @@ pub trait SpanExt {
 
     /// Retrieve the active trace identifier for the span.
     fn trace_id(&self) -> Option<String>;
+
+    /// Seeds this span's trace context from an inbound W3C `traceparent`
+    /// header (`version-trace_id-parent_id-flags`), so a request forwarded
+    /// from another LokanOS service keeps the same trace id instead of
+    /// minting a fresh one. Malformed headers are ignored, leaving the
+    /// span's existing (root) trace context in place.
+    fn with_traceparent(&self, traceparent: &str);
+
+    /// Renders a conformant `traceparent` header for an outbound call,
+    /// minting a fresh span id for this hop while preserving the span's
+    /// trace id and flags. Returns `None` if the span has no trace context.
+    fn traceparent(&self) -> Option<String>;
 }
 
 impl SpanExt for tracing::Span {
@@ -118,6 +241,24 @@ impl SpanExt for tracing::Span {
                 .and_then(|state| state.trace_id(id.into_u64()))
         })
     }
+
+    fn with_traceparent(&self, traceparent: &str) {
+        if let Some(id) = self.id() {
+            if let Some((trace_id, _parent_id, flags)) = parse_traceparent(traceparent) {
+                if let Some(state) = TRACE_STATE.get() {
+                    state.seed(id.into_u64(), trace_id, flags);
+                }
+            }
+        }
+    }
+
+    fn traceparent(&self) -> Option<String> {
+        self.id().and_then(|id| {
+            TRACE_STATE
+                .get()
+                .and_then(|state| state.traceparent(id.into_u64()))
+        })
+    }
 }
 
 struct TraceLayer {
@@ -165,14 +306,12 @@ where
 
 #[derive(Default)]
 struct TraceState {
-    counter: AtomicU64,
     contexts: Mutex<HashMap<u64, Arc<TraceContext>>>,
 }
 
 impl TraceState {
     fn make_context(&self) -> TraceContext {
-        let id = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
-        TraceContext::new(format!("{:016x}", id))
+        TraceContext::new_root()
     }
 
     fn insert(&self, span_id: u64, ctx: Arc<TraceContext>) {
@@ -197,25 +336,65 @@ impl TraceState {
 
     fn trace_id(&self, span_id: u64) -> Option<String> {
         let map = self.contexts.lock().expect("lock poisoned");
-        map.get(&span_id).map(|ctx| ctx.trace_id().to_string())
+        map.get(&span_id).map(|ctx| ctx.trace_id_hex())
+    }
+
+    /// Overwrites the span's trace context with a trace id/flags parsed
+    /// from an inbound `traceparent` header. A no-op if the span has no
+    /// context yet (it should always have one, installed by `TraceLayer`).
+    fn seed(&self, span_id: u64, trace_id: u128, flags: u8) {
+        let ctx = {
+            let map = self.contexts.lock().expect("lock poisoned");
+            map.get(&span_id).cloned()
+        };
+        if let Some(ctx) = ctx {
+            ctx.seed(trace_id, flags);
+        }
+    }
+
+    fn traceparent(&self, span_id: u64) -> Option<String> {
+        let map = self.contexts.lock().expect("lock poisoned");
+        map.get(&span_id).map(|ctx| ctx.traceparent())
     }
 }
 
+/// Default `traceparent` flags for a freshly minted root trace: the
+/// "sampled" bit set, per the W3C Trace Context spec.
+const DEFAULT_TRACE_FLAGS: u8 = 0x01;
+
 struct TraceContext {
-    trace_id: String,
+    trace_id: RwLock<u128>,
+    flags: RwLock<u8>,
     request_id: RwLock<Option<String>>,
 }
 
 impl TraceContext {
-    fn new(trace_id: String) -> Self {
+    /// Builds a fresh root trace context with a random 128-bit trace id,
+    /// used when a span has no parent context and no inbound `traceparent`
+    /// was supplied via [`SpanExt::with_traceparent`].
+    fn new_root() -> Self {
         Self {
-            trace_id,
+            trace_id: RwLock::new(rand::random()),
+            flags: RwLock::new(DEFAULT_TRACE_FLAGS),
             request_id: RwLock::new(None),
         }
     }
 
-    fn trace_id(&self) -> &str {
-        &self.trace_id
+    fn trace_id(&self) -> u128 {
+        *self.trace_id.read().expect("lock poisoned")
+    }
+
+    fn trace_id_hex(&self) -> String {
+        format!("{:032x}", self.trace_id())
+    }
+
+    fn flags(&self) -> u8 {
+        *self.flags.read().expect("lock poisoned")
+    }
+
+    fn seed(&self, trace_id: u128, flags: u8) {
+        *self.trace_id.write().expect("lock poisoned") = trace_id;
+        *self.flags.write().expect("lock poisoned") = flags;
     }
 
     fn request_id(&self) -> Option<String> {
@@ -226,10 +405,221 @@ impl TraceContext {
         let mut guard = self.request_id.write().expect("lock poisoned");
         *guard = Some(value.to_string());
     }
+
+    /// Renders a conformant `traceparent` header, minting a fresh 64-bit
+    /// span id for this outbound hop while preserving the trace id and
+    /// flags.
+    fn traceparent(&self) -> String {
+        let span_id: u64 = rand::random();
+        format!(
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id(),
+            span_id,
+            self.flags()
+        )
+    }
+}
+
+/// Parses a W3C `traceparent` header (`version-trace_id-parent_id-flags`),
+/// returning the trace id, parent span id, and flags. Rejects malformed
+/// fields and the all-zero trace id / parent id reserved by the spec.
+fn parse_traceparent(header: &str) -> Option<(u128, u64, u8)> {
+    let mut fields = header.split('-');
+    let version = fields.next()?;
+    let trace_id = fields.next()?;
+    let parent_id = fields.next()?;
+    let flags = fields.next()?;
+    if fields.next().is_some() {
+        return None;
+    }
+    if version.len() != 2 || trace_id.len() != 32 || parent_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+
+    let trace_id = u128::from_str_radix(trace_id, 16).ok()?;
+    let parent_id = u64::from_str_radix(parent_id, 16).ok()?;
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    if trace_id == 0 || parent_id == 0 {
+        return None;
+    }
+
+    Some((trace_id, parent_id, flags))
 }
 
 static TRACE_STATE: OnceCell<Arc<TraceState>> = OnceCell::new();
 
+/// Default interval on which [`OtlpExportLayer`]'s background task ships
+/// whatever finished spans it has batched, even if the batch is small.
+const OTLP_DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Batch size at which the background task flushes early instead of
+/// waiting for [`OTLP_DEFAULT_FLUSH_INTERVAL`].
+const OTLP_MAX_BATCH: usize = 256;
+
+/// A finished span, captured by [`OtlpExportLayer`] and queued for
+/// shipping to the configured OTLP collector.
+struct ExportedSpan {
+    name: &'static str,
+    trace_id: String,
+    span_id: String,
+    start_unix_nano: u128,
+    end_unix_nano: u128,
+    attributes: Vec<(String, String)>,
+}
+
+/// Timing and attributes recorded at span creation, read back out in
+/// `on_close` to build the [`ExportedSpan`].
+struct SpanTiming {
+    start: SystemTime,
+    attributes: Vec<(String, String)>,
+}
+
+/// `Layer` sibling to [`TraceLayer`]: where `TraceLayer` seeds the
+/// `trace_id`/`request_id` fields the JSON formatter logs, this layer
+/// records each span's lifetime and ships it to a distributed tracing
+/// backend over OTLP, when `OTLP_ENDPOINT` is configured.
+struct OtlpExportLayer {
+    spans: mpsc::UnboundedSender<ExportedSpan>,
+}
+
+impl OtlpExportLayer {
+    /// Spawns the background batching/export task and returns a layer
+    /// that feeds it. Requires a Tokio runtime to already be running.
+    fn new(endpoint: String, flush_interval: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_otlp_export_loop(endpoint, flush_interval, rx));
+        Self { spans: tx }
+    }
+}
+
+impl<S> Layer<S> for OtlpExportLayer
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &span::Id,
+        ctx: Context<'_, S>,
+    ) {
+        let span = ctx.span(id).expect("span must exist");
+        let mut visitor = JsonFieldVisitor::default();
+        attrs.record(&mut visitor);
+        let attributes = visitor
+            .finish()
+            .into_iter()
+            .map(|(name, value)| (name, value.to_plain_string()))
+            .collect();
+        span.extensions_mut().insert(SpanTiming {
+            start: SystemTime::now(),
+            attributes,
+        });
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).expect("span must exist");
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else {
+            return;
+        };
+        let trace_id = extensions
+            .get::<Arc<TraceContext>>()
+            .map(|trace_ctx| trace_ctx.trace_id_hex())
+            .unwrap_or_default();
+
+        let exported = ExportedSpan {
+            name: span.name(),
+            trace_id,
+            span_id: format!("{:016x}", id.into_u64()),
+            start_unix_nano: unix_nanos(timing.start),
+            end_unix_nano: unix_nanos(SystemTime::now()),
+            attributes: timing.attributes.clone(),
+        };
+        // The background task may already be shut down (e.g. during test
+        // teardown); dropping the span export is acceptable since it is
+        // only a tracing backend, not the critical path.
+        let _ = self.spans.send(exported);
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+async fn run_otlp_export_loop(
+    endpoint: String,
+    flush_interval: Duration,
+    mut spans: mpsc::UnboundedReceiver<ExportedSpan>,
+) {
+    let client = Client::new();
+    let mut batch = Vec::new();
+    let mut interval = tokio::time::interval(flush_interval);
+
+    loop {
+        tokio::select! {
+            received = spans.recv() => {
+                match received {
+                    Some(span) => {
+                        batch.push(span);
+                        if batch.len() >= OTLP_MAX_BATCH {
+                            flush_otlp_batch(&client, &endpoint, &mut batch).await;
+                        }
+                    }
+                    None => {
+                        // Sender dropped (process shutdown): flush whatever
+                        // remains before exiting so spans aren't lost.
+                        flush_otlp_batch(&client, &endpoint, &mut batch).await;
+                        return;
+                    }
+                }
+            }
+            _ = interval.tick() => {
+                flush_otlp_batch(&client, &endpoint, &mut batch).await;
+            }
+        }
+    }
+}
+
+async fn flush_otlp_batch(client: &Client, endpoint: &str, batch: &mut Vec<ExportedSpan>) {
+    if batch.is_empty() {
+        return;
+    }
+    let payload = otlp_export_payload(batch);
+    if let Err(err) = client.post(endpoint).json(&payload).send().await {
+        tracing::warn!(error = %err, endpoint, "failed to export spans to OTLP collector");
+    }
+    batch.clear();
+}
+
+/// Renders a batch as an OTLP `ExportTraceServiceRequest`-shaped JSON
+/// payload (the HTTP/JSON encoding OTLP collectors accept alongside
+/// HTTP/protobuf).
+fn otlp_export_payload(batch: &[ExportedSpan]) -> serde_json::Value {
+    let spans: Vec<_> = batch
+        .iter()
+        .map(|span| {
+            json!({
+                "name": span.name,
+                "traceId": span.trace_id,
+                "spanId": span.span_id,
+                "startTimeUnixNano": span.start_unix_nano.to_string(),
+                "endTimeUnixNano": span.end_unix_nano.to_string(),
+                "attributes": span.attributes.iter().map(|(key, value)| json!({
+                    "key": key,
+                    "value": { "stringValue": value },
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "scopeSpans": [{ "spans": spans }],
+        }],
+    })
+}
+
 struct ObsJsonFormat {
     service: Arc<str>,
 }
@@ -261,7 +651,7 @@ where
 
         if let Some(span) = ctx.lookup_current() {
             if let Some(ctx) = find_trace_ctx(span) {
-                trace_id = Some(ctx.trace_id().to_string());
+                trace_id = Some(ctx.trace_id_hex());
                 request_id = ctx.request_id();
             }
         }
@@ -323,6 +713,17 @@ impl JsonValue {
             JsonValue::Bool(value) => writer.write_raw(if *value { "true" } else { "false" }),
         }
     }
+
+    /// Renders the value as a plain string, used when a field needs to
+    /// leave the JSON log formatter (e.g. [`OtlpExportLayer`] span
+    /// attributes).
+    fn to_plain_string(&self) -> String {
+        match self {
+            JsonValue::String(value) => value.clone(),
+            JsonValue::Number(value) => value.clone(),
+            JsonValue::Bool(value) => value.to_string(),
+        }
+    }
 }
 
 impl Visit for JsonFieldVisitor {
@@ -463,14 +864,150 @@ pub mod metrics {
 
     use once_cell::sync::Lazy;
     use std::fmt::Write as FmtWrite;
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
 
     const DEFAULT_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0];
 
     pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+    pub const OPENMETRICS_CONTENT_TYPE: &str =
+        "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+    /// Base unit of a metric's value. [`Registry::encode`] and
+    /// [`Registry::encode_openmetrics`] render it as a `# UNIT <name> <unit>`
+    /// line (right after `# TYPE`) so scrapers can auto-scale axes without
+    /// guessing whether a series is seconds, bytes, or a bare ratio.
+    /// Registering a unit also enforces the matching name suffix — see
+    /// [`assert_unit_suffix`] — codifying the convention series like
+    /// `handler_latency_seconds` and `http_requests_total` already follow.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Unit {
+        Seconds,
+        Bytes,
+        Count,
+        Ratio,
+    }
+
+    impl Unit {
+        /// The OpenMetrics unit string emitted in the `# UNIT` line.
+        fn as_str(self) -> &'static str {
+            match self {
+                Unit::Seconds => "seconds",
+                Unit::Bytes => "bytes",
+                Unit::Count => "count",
+                Unit::Ratio => "ratio",
+            }
+        }
+
+        /// The name suffix this unit's convention requires.
+        fn suffix(self) -> &'static str {
+            match self {
+                Unit::Seconds => "_seconds",
+                Unit::Bytes => "_bytes",
+                Unit::Count => "_total",
+                Unit::Ratio => "_ratio",
+            }
+        }
+    }
+
+    /// Panics if `name` doesn't carry the base-unit suffix `unit` requires.
+    fn assert_unit_suffix(name: &str, unit: Unit) {
+        assert!(
+            name.ends_with(unit.suffix()),
+            "metric {} declares unit {:?} but its name doesn't end in {}",
+            name,
+            unit,
+            unit.suffix()
+        );
+    }
+
+    /// Bitmask selecting which metric kinds an idle timeout applies to, so
+    /// [`set_idle_timeout`] can give counters a different (or disabled)
+    /// expiry than histograms.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct MetricKindMask(u8);
+
+    impl MetricKindMask {
+        pub const NONE: Self = Self(0b00);
+        pub const COUNTER: Self = Self(0b01);
+        pub const HISTOGRAM: Self = Self(0b10);
+        pub const ALL: Self = Self(0b11);
+
+        pub const fn contains(self, other: Self) -> bool {
+            self.0 & other.0 == other.0
+        }
+    }
+
+    impl std::ops::BitOr for MetricKindMask {
+        type Output = Self;
+
+        fn bitor(self, rhs: Self) -> Self {
+            Self(self.0 | rhs.0)
+        }
+    }
+
+    /// Per-kind idle timeouts. A series whose last touch is older than the
+    /// configured timeout is flagged the next time [`Registry::encode`]
+    /// runs, and dropped from its family once it's flagged on a second,
+    /// later sweep too (see [`survives_idle_sweep`]) — so a burst of
+    /// scrapes can't evict a series before its value has actually gone
+    /// stale. `None` disables eviction for that kind. Gauges have no entry
+    /// here and are never evicted: a stale gauge value is often still
+    /// meaningful.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct IdleTimeouts {
+        counters: Option<Duration>,
+        histograms: Option<Duration>,
+    }
+
+    /// Monotonic reference point `last_touch` fields are measured against,
+    /// so eviction works without depending on wall-clock time.
+    static METRICS_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+    fn now_millis() -> u64 {
+        METRICS_START.elapsed().as_millis() as u64
+    }
+
+    fn is_idle(last_touch: &AtomicU64, timeout: Duration) -> bool {
+        let elapsed = now_millis().saturating_sub(last_touch.load(Ordering::Relaxed));
+        elapsed >= timeout.as_millis() as u64
+    }
+
+    /// Decides whether a series survives one idle-eviction sweep. A series
+    /// that's been touched since last swept is never at risk. One that's
+    /// gone idle is flagged rather than dropped immediately, and only
+    /// evicted once it's been observed idle on a second, later sweep too —
+    /// so a series isn't evicted mid-scrape while its last value still
+    /// matters, only once it's been stale across two full sweeps.
+    fn survives_idle_sweep(
+        last_touch: &AtomicU64,
+        pending_eviction: &AtomicBool,
+        timeout: Duration,
+    ) -> bool {
+        if !is_idle(last_touch, timeout) {
+            pending_eviction.store(false, Ordering::Relaxed);
+            return true;
+        }
+        !pending_eviction.swap(true, Ordering::Relaxed)
+    }
+
+    /// Configures how long an idle (untouched) label series is kept before
+    /// eviction for the metric kinds selected by `mask`. Pass `None` to
+    /// disable eviction for those kinds (the default).
+    pub fn set_idle_timeout(mask: MetricKindMask, timeout: Option<Duration>) {
+        let mut guard = registry().idle_timeouts.write().expect("lock poisoned");
+        if mask.contains(MetricKindMask::COUNTER) {
+            guard.counters = timeout;
+        }
+        if mask.contains(MetricKindMask::HISTOGRAM) {
+            guard.histograms = timeout;
+        }
+    }
 
     #[derive(Default)]
     struct Registry {
         families: RwLock<Vec<MetricFamily>>,
+        idle_timeouts: RwLock<IdleTimeouts>,
     }
 
     impl Registry {
@@ -485,6 +1022,17 @@ pub mod metrics {
             guard.push(MetricFamily::Counter(counter));
         }
 
+        fn register_gauge(&self, gauge: Arc<GaugeVecInner>) {
+            let mut guard = self.families.write().expect("lock poisoned");
+            if guard.iter().any(|family| match family {
+                MetricFamily::Gauge(existing) => existing.name == gauge.name,
+                _ => false,
+            }) {
+                return;
+            }
+            guard.push(MetricFamily::Gauge(gauge));
+        }
+
         fn register_histogram(&self, histogram: Arc<HistogramVecInner>) {
             let mut guard = self.families.write().expect("lock poisoned");
             if guard.iter().any(|family| match family {
@@ -496,15 +1044,34 @@ pub mod metrics {
             guard.push(MetricFamily::Histogram(histogram));
         }
 
+        fn register_summary(&self, summary: Arc<SummaryVecInner>) {
+            let mut guard = self.families.write().expect("lock poisoned");
+            if guard.iter().any(|family| match family {
+                MetricFamily::Summary(existing) => existing.name == summary.name,
+                _ => false,
+            }) {
+                return;
+            }
+            guard.push(MetricFamily::Summary(summary));
+        }
+
         fn encode(&self) -> String {
+            let timeouts = *self.idle_timeouts.read().expect("lock poisoned");
             let mut output = String::new();
             let guard = self.families.read().expect("lock poisoned");
             for family in guard.iter() {
                 match family {
                     MetricFamily::Counter(counter) => {
+                        if let Some(timeout) = timeouts.counters {
+                            counter.evict_idle(timeout);
+                        }
                         writeln!(output, "# HELP {} {}", counter.name, counter.help)
                             .expect("write metrics");
                         writeln!(output, "# TYPE {} counter", counter.name).expect("write metrics");
+                        if let Some(unit) = counter.unit {
+                            writeln!(output, "# UNIT {} {}", counter.name, unit.as_str())
+                                .expect("write metrics");
+                        }
 
                         let mut samples = counter.collect();
                         samples.sort_by(|a, b| a.0.cmp(&b.0));
@@ -514,11 +1081,35 @@ pub mod metrics {
                             writeln!(output, " {}", value).expect("write metrics");
                         }
                     }
+                    MetricFamily::Gauge(gauge) => {
+                        writeln!(output, "# HELP {} {}", gauge.name, gauge.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} gauge", gauge.name).expect("write metrics");
+                        if let Some(unit) = gauge.unit {
+                            writeln!(output, "# UNIT {} {}", gauge.name, unit.as_str())
+                                .expect("write metrics");
+                        }
+
+                        let mut samples = gauge.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, value) in samples {
+                            write!(output, "{}", gauge.name).expect("write metrics");
+                            write_labels(&mut output, gauge.label_names, &labels);
+                            writeln!(output, " {}", format_float(value)).expect("write metrics");
+                        }
+                    }
                     MetricFamily::Histogram(histogram) => {
+                        if let Some(timeout) = timeouts.histograms {
+                            histogram.evict_idle(timeout);
+                        }
                         writeln!(output, "# HELP {} {}", histogram.name, histogram.help)
                             .expect("write metrics");
                         writeln!(output, "# TYPE {} histogram", histogram.name)
                             .expect("write metrics");
+                        if let Some(unit) = histogram.unit {
+                            writeln!(output, "# UNIT {} {}", histogram.name, unit.as_str())
+                                .expect("write metrics");
+                        }
 
                         let mut samples = histogram.collect();
                         samples.sort_by(|a, b| a.0.cmp(&b.0));
@@ -555,6 +1146,54 @@ pub mod metrics {
                             write!(output, "{}_count", histogram.name).expect("write metrics");
                             write_labels(&mut output, histogram.label_names, &labels);
                             writeln!(output, " {}", snapshot.count).expect("write metrics");
+
+                            if let (Some(quantiles), Some(sketch)) =
+                                (histogram.sketch_quantiles, &snapshot.sketch)
+                            {
+                                for quantile in quantiles {
+                                    let mut label_names = histogram.label_names.to_vec();
+                                    label_names.push("quantile");
+                                    let mut label_values = labels.clone();
+                                    label_values.push(format_float(*quantile));
+                                    write!(output, "{}", histogram.name).expect("write metrics");
+                                    write_labels(&mut output, &label_names, &label_values);
+                                    writeln!(
+                                        output,
+                                        " {}",
+                                        format_float(sketch.quantile(*quantile, snapshot.count))
+                                    )
+                                    .expect("write metrics");
+                                }
+                            }
+                        }
+                    }
+                    MetricFamily::Summary(summary) => {
+                        writeln!(output, "# HELP {} {}", summary.name, summary.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} summary", summary.name)
+                            .expect("write metrics");
+
+                        let mut samples = summary.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, snapshot) in samples {
+                            for quantile in summary.quantiles {
+                                let mut label_names = summary.label_names.to_vec();
+                                label_names.push("quantile");
+                                let mut label_values = labels.clone();
+                                label_values.push(format_float(*quantile));
+                                write!(output, "{}", summary.name).expect("write metrics");
+                                write_labels(&mut output, &label_names, &label_values);
+                                writeln!(output, " {}", format_float(snapshot.quantile(*quantile)))
+                                    .expect("write metrics");
+                            }
+
+                            write!(output, "{}_sum", summary.name).expect("write metrics");
+                            write_labels(&mut output, summary.label_names, &labels);
+                            writeln!(output, " {:.6}", snapshot.sum).expect("write metrics");
+
+                            write!(output, "{}_count", summary.name).expect("write metrics");
+                            write_labels(&mut output, summary.label_names, &labels);
+                            writeln!(output, " {}", snapshot.count).expect("write metrics");
                         }
                     }
                 }
@@ -562,37 +1201,309 @@ pub mod metrics {
 
             output
         }
-    }
-
-    fn registry() -> &'static Registry {
-        static REGISTRY: OnceCell<Registry> = OnceCell::new();
-        REGISTRY.get_or_init(Registry::default)
-    }
-
-    enum MetricFamily {
-        Counter(Arc<CounterVecInner>),
-        Histogram(Arc<HistogramVecInner>),
-    }
 
-    #[derive(Default)]
-    struct CounterValue {
-        value: AtomicU64,
-    }
+        /// As [`Registry::encode`], but in OpenMetrics text format: counter
+        /// and gauge families render the same, while histogram `_bucket`
+        /// lines gain a trailing `# {trace_id="..."} <value> <timestamp>`
+        /// exemplar when one is recorded for that bucket. Terminated with
+        /// the OpenMetrics `# EOF` marker.
+        fn encode_openmetrics(&self) -> String {
+            let timeouts = *self.idle_timeouts.read().expect("lock poisoned");
+            let mut output = String::new();
+            let guard = self.families.read().expect("lock poisoned");
+            for family in guard.iter() {
+                match family {
+                    MetricFamily::Counter(counter) => {
+                        if let Some(timeout) = timeouts.counters {
+                            counter.evict_idle(timeout);
+                        }
+                        writeln!(output, "# HELP {} {}", counter.name, counter.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} counter", counter.name).expect("write metrics");
+                        if let Some(unit) = counter.unit {
+                            writeln!(output, "# UNIT {} {}", counter.name, unit.as_str())
+                                .expect("write metrics");
+                        }
 
-    impl CounterValue {
-        fn increment(&self, amount: u64) {
-            self.value.fetch_add(amount, Ordering::Relaxed);
-        }
+                        let mut samples = counter.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, value) in samples {
+                            write!(output, "{}", counter.name).expect("write metrics");
+                            write_labels(&mut output, counter.label_names, &labels);
+                            writeln!(output, " {}", value).expect("write metrics");
+                        }
+                    }
+                    MetricFamily::Gauge(gauge) => {
+                        writeln!(output, "# HELP {} {}", gauge.name, gauge.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} gauge", gauge.name).expect("write metrics");
+                        if let Some(unit) = gauge.unit {
+                            writeln!(output, "# UNIT {} {}", gauge.name, unit.as_str())
+                                .expect("write metrics");
+                        }
 
-        fn get(&self) -> u64 {
-            self.value.load(Ordering::Relaxed)
-        }
-    }
+                        let mut samples = gauge.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, value) in samples {
+                            write!(output, "{}", gauge.name).expect("write metrics");
+                            write_labels(&mut output, gauge.label_names, &labels);
+                            writeln!(output, " {}", format_float(value)).expect("write metrics");
+                        }
+                    }
+                    MetricFamily::Histogram(histogram) => {
+                        if let Some(timeout) = timeouts.histograms {
+                            histogram.evict_idle(timeout);
+                        }
+                        writeln!(output, "# HELP {} {}", histogram.name, histogram.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} histogram", histogram.name)
+                            .expect("write metrics");
+                        if let Some(unit) = histogram.unit {
+                            writeln!(output, "# UNIT {} {}", histogram.name, unit.as_str())
+                                .expect("write metrics");
+                        }
 
-    struct CounterVecInner {
-        name: &'static str,
-        help: &'static str,
-        label_names: &'static [&'static str],
+                        let mut samples = histogram.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, snapshot) in samples {
+                            let mut cumulative = 0u64;
+                            for (idx, bound) in histogram.buckets.iter().enumerate() {
+                                cumulative += snapshot.counts[idx];
+                                let mut label_names = histogram.label_names.to_vec();
+                                label_names.push("le");
+                                let mut label_values = labels.clone();
+                                label_values.push(format_float(*bound));
+                                write!(output, "{}", histogram.name).expect("write metrics");
+                                write_labels(&mut output, &label_names, &label_values);
+                                write!(output, " {}", cumulative).expect("write metrics");
+                                write_exemplar(&mut output, snapshot.exemplars.get(idx));
+                                writeln!(output).expect("write metrics");
+                            }
+
+                            cumulative += snapshot
+                                .counts
+                                .get(histogram.buckets.len())
+                                .copied()
+                                .unwrap_or(0);
+                            let mut label_names = histogram.label_names.to_vec();
+                            label_names.push("le");
+                            let mut label_values = labels.clone();
+                            label_values.push(String::from("+Inf"));
+                            write!(output, "{}", histogram.name).expect("write metrics");
+                            write_labels(&mut output, &label_names, &label_values);
+                            write!(output, " {}", cumulative).expect("write metrics");
+                            write_exemplar(&mut output, snapshot.exemplars.get(histogram.buckets.len()));
+                            writeln!(output).expect("write metrics");
+
+                            write!(output, "{}_sum", histogram.name).expect("write metrics");
+                            write_labels(&mut output, histogram.label_names, &labels);
+                            writeln!(output, " {:.6}", snapshot.sum).expect("write metrics");
+
+                            write!(output, "{}_count", histogram.name).expect("write metrics");
+                            write_labels(&mut output, histogram.label_names, &labels);
+                            writeln!(output, " {}", snapshot.count).expect("write metrics");
+
+                            if let (Some(quantiles), Some(sketch)) =
+                                (histogram.sketch_quantiles, &snapshot.sketch)
+                            {
+                                for quantile in quantiles {
+                                    let mut label_names = histogram.label_names.to_vec();
+                                    label_names.push("quantile");
+                                    let mut label_values = labels.clone();
+                                    label_values.push(format_float(*quantile));
+                                    write!(output, "{}", histogram.name).expect("write metrics");
+                                    write_labels(&mut output, &label_names, &label_values);
+                                    writeln!(
+                                        output,
+                                        " {}",
+                                        format_float(sketch.quantile(*quantile, snapshot.count))
+                                    )
+                                    .expect("write metrics");
+                                }
+                            }
+                        }
+                    }
+                    MetricFamily::Summary(summary) => {
+                        writeln!(output, "# HELP {} {}", summary.name, summary.help)
+                            .expect("write metrics");
+                        writeln!(output, "# TYPE {} summary", summary.name)
+                            .expect("write metrics");
+
+                        let mut samples = summary.collect();
+                        samples.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (labels, snapshot) in samples {
+                            for quantile in summary.quantiles {
+                                let mut label_names = summary.label_names.to_vec();
+                                label_names.push("quantile");
+                                let mut label_values = labels.clone();
+                                label_values.push(format_float(*quantile));
+                                write!(output, "{}", summary.name).expect("write metrics");
+                                write_labels(&mut output, &label_names, &label_values);
+                                writeln!(output, " {}", format_float(snapshot.quantile(*quantile)))
+                                    .expect("write metrics");
+                            }
+
+                            write!(output, "{}_sum", summary.name).expect("write metrics");
+                            write_labels(&mut output, summary.label_names, &labels);
+                            writeln!(output, " {:.6}", snapshot.sum).expect("write metrics");
+
+                            write!(output, "{}_count", summary.name).expect("write metrics");
+                            write_labels(&mut output, summary.label_names, &labels);
+                            writeln!(output, " {}", snapshot.count).expect("write metrics");
+                        }
+                    }
+                }
+            }
+            output.push_str("# EOF\n");
+            output
+        }
+
+        fn snapshot(&self) -> MetricsSnapshot {
+            let mut snapshot = MetricsSnapshot::default();
+            let guard = self.families.read().expect("lock poisoned");
+            for family in guard.iter() {
+                match family {
+                    MetricFamily::Counter(counter) => {
+                        for (labels, value) in counter.collect() {
+                            snapshot.counters.push(CounterSample {
+                                name: counter.name,
+                                labels: zip_labels(counter.label_names, &labels),
+                                value,
+                            });
+                        }
+                    }
+                    MetricFamily::Gauge(gauge) => {
+                        for (labels, value) in gauge.collect() {
+                            snapshot.gauges.push(GaugeSample {
+                                name: gauge.name,
+                                labels: zip_labels(gauge.label_names, &labels),
+                                value,
+                            });
+                        }
+                    }
+                    MetricFamily::Histogram(histogram) => {
+                        for (labels, hist_snapshot) in histogram.collect() {
+                            let mut buckets = Vec::with_capacity(histogram.buckets.len() + 1);
+                            let mut cumulative = 0u64;
+                            for (idx, bound) in histogram.buckets.iter().enumerate() {
+                                cumulative += hist_snapshot.counts[idx];
+                                buckets.push((*bound, cumulative));
+                            }
+                            cumulative += hist_snapshot
+                                .counts
+                                .get(histogram.buckets.len())
+                                .copied()
+                                .unwrap_or(0);
+                            buckets.push((f64::INFINITY, cumulative));
+
+                            snapshot.histograms.push(HistogramSample {
+                                name: histogram.name,
+                                labels: zip_labels(histogram.label_names, &labels),
+                                buckets,
+                                sum: hist_snapshot.sum,
+                                count: hist_snapshot.count,
+                            });
+                        }
+                    }
+                    MetricFamily::Summary(_) => {}
+                }
+            }
+            snapshot
+        }
+    }
+
+    /// One label set's worth of a counter, as returned by
+    /// [`Registry::snapshot`].
+    #[derive(Debug, Clone, Serialize)]
+    pub struct CounterSample {
+        pub name: &'static str,
+        pub labels: HashMap<String, String>,
+        pub value: u64,
+    }
+
+    /// One label set's worth of a gauge, as returned by
+    /// [`Registry::snapshot`].
+    #[derive(Debug, Clone, Serialize)]
+    pub struct GaugeSample {
+        pub name: &'static str,
+        pub labels: HashMap<String, String>,
+        pub value: f64,
+    }
+
+    /// One label set's worth of a histogram, as returned by
+    /// [`Registry::snapshot`]. `buckets` pairs each bucket's upper bound
+    /// (`+Inf` serializes as `null`) with its cumulative count.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct HistogramSample {
+        pub name: &'static str,
+        pub labels: HashMap<String, String>,
+        pub buckets: Vec<(f64, u64)>,
+        pub sum: f64,
+        pub count: u64,
+    }
+
+    /// A point-in-time, serde-serializable view of every registered metric
+    /// family, for admin tooling and tests that want to assert on metric
+    /// values without parsing the Prometheus exposition text.
+    #[derive(Debug, Clone, Default, Serialize)]
+    pub struct MetricsSnapshot {
+        pub counters: Vec<CounterSample>,
+        pub gauges: Vec<GaugeSample>,
+        pub histograms: Vec<HistogramSample>,
+    }
+
+    fn zip_labels(names: &[&str], values: &[String]) -> HashMap<String, String> {
+        names
+            .iter()
+            .zip(values.iter())
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    /// Builds a [`MetricsSnapshot`] of every registered family.
+    pub fn snapshot() -> MetricsSnapshot {
+        let _ = http_requests_total();
+        let _ = handler_latency_seconds();
+        let _ = msgbus_publish_total();
+        let _ = msgbus_subscribe_total();
+        registry().snapshot()
+    }
+
+    fn registry() -> &'static Registry {
+        static REGISTRY: OnceCell<Registry> = OnceCell::new();
+        REGISTRY.get_or_init(Registry::default)
+    }
+
+    enum MetricFamily {
+        Counter(Arc<CounterVecInner>),
+        Gauge(Arc<GaugeVecInner>),
+        Histogram(Arc<HistogramVecInner>),
+        Summary(Arc<SummaryVecInner>),
+    }
+
+    #[derive(Default)]
+    struct CounterValue {
+        value: AtomicU64,
+        last_touch: AtomicU64,
+        pending_eviction: AtomicBool,
+    }
+
+    impl CounterValue {
+        fn increment(&self, amount: u64) {
+            self.value.fetch_add(amount, Ordering::Relaxed);
+            self.last_touch.store(now_millis(), Ordering::Relaxed);
+        }
+
+        fn get(&self) -> u64 {
+            self.value.load(Ordering::Relaxed)
+        }
+    }
+
+    struct CounterVecInner {
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        unit: Option<Unit>,
         values: Mutex<HashMap<Vec<String>, Arc<CounterValue>>>,
     }
 
@@ -601,11 +1512,16 @@ pub mod metrics {
             name: &'static str,
             help: &'static str,
             label_names: &'static [&'static str],
+            unit: Option<Unit>,
         ) -> Self {
+            if let Some(unit) = unit {
+                assert_unit_suffix(name, unit);
+            }
             Self {
                 name,
                 help,
                 label_names,
+                unit,
                 values: Mutex::new(HashMap::new()),
             }
         }
@@ -618,11 +1534,13 @@ pub mod metrics {
             );
             let mut guard = self.values.lock().expect("lock poisoned");
             let key: Vec<String> = label_values.iter().map(|value| value.to_string()).collect();
-            Arc::clone(
-                guard
-                    .entry(key)
-                    .or_insert_with(|| Arc::new(CounterValue::default())),
-            )
+            Arc::clone(guard.entry(key).or_insert_with(|| {
+                Arc::new(CounterValue {
+                    value: AtomicU64::new(0),
+                    last_touch: AtomicU64::new(now_millis()),
+                    pending_eviction: AtomicBool::new(false),
+                })
+            }))
         }
 
         fn collect(&self) -> Vec<(Vec<String>, u64)> {
@@ -632,6 +1550,18 @@ pub mod metrics {
                 .map(|(labels, value)| (labels.clone(), value.get()))
                 .collect()
         }
+
+        /// Drops label series whose counter hasn't been incremented for at
+        /// least `timeout`, so long-gone label combinations (an unplugged
+        /// device, a rotated request id) don't accumulate forever. A series
+        /// is only dropped once it's been idle across two consecutive
+        /// sweeps; see [`survives_idle_sweep`].
+        fn evict_idle(&self, timeout: Duration) {
+            let mut guard = self.values.lock().expect("lock poisoned");
+            guard.retain(|_, value| {
+                survives_idle_sweep(&value.last_touch, &value.pending_eviction, timeout)
+            });
+        }
     }
 
     #[derive(Clone)]
@@ -662,11 +1592,239 @@ pub mod metrics {
         }
     }
 
+    #[derive(Default)]
+    struct GaugeValue {
+        value: AtomicU64,
+    }
+
+    impl GaugeValue {
+        fn set(&self, new_value: f64) {
+            self.value.store(new_value.to_bits(), Ordering::Relaxed);
+        }
+
+        /// Atomically adds `delta` (negative to subtract) via a
+        /// compare-exchange loop, since gauges can be bumped concurrently
+        /// from multiple tasks.
+        fn add(&self, delta: f64) {
+            let mut current = self.value.load(Ordering::Relaxed);
+            loop {
+                let updated = (f64::from_bits(current) + delta).to_bits();
+                match self
+                    .value
+                    .compare_exchange_weak(current, updated, Ordering::Relaxed, Ordering::Relaxed)
+                {
+                    Ok(_) => return,
+                    Err(actual) => current = actual,
+                }
+            }
+        }
+
+        fn get(&self) -> f64 {
+            f64::from_bits(self.value.load(Ordering::Relaxed))
+        }
+    }
+
+    struct GaugeVecInner {
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        unit: Option<Unit>,
+        values: Mutex<HashMap<Vec<String>, Arc<GaugeValue>>>,
+    }
+
+    impl GaugeVecInner {
+        fn new(
+            name: &'static str,
+            help: &'static str,
+            label_names: &'static [&'static str],
+            unit: Option<Unit>,
+        ) -> Self {
+            if let Some(unit) = unit {
+                assert_unit_suffix(name, unit);
+            }
+            Self {
+                name,
+                help,
+                label_names,
+                unit,
+                values: Mutex::new(HashMap::new()),
+            }
+        }
+
+        fn get_or_create(&self, label_values: &[&str]) -> Arc<GaugeValue> {
+            assert_eq!(
+                self.label_names.len(),
+                label_values.len(),
+                "label value count mismatch"
+            );
+            let mut guard = self.values.lock().expect("lock poisoned");
+            let key: Vec<String> = label_values.iter().map(|value| value.to_string()).collect();
+            Arc::clone(
+                guard
+                    .entry(key)
+                    .or_insert_with(|| Arc::new(GaugeValue::default())),
+            )
+        }
+
+        fn collect(&self) -> Vec<(Vec<String>, f64)> {
+            let guard = self.values.lock().expect("lock poisoned");
+            guard
+                .iter()
+                .map(|(labels, value)| (labels.clone(), value.get()))
+                .collect()
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct GaugeVec {
+        inner: Arc<GaugeVecInner>,
+    }
+
+    impl GaugeVec {
+        pub fn with_label_values(&self, labels: &[&str]) -> Gauge {
+            Gauge {
+                inner: self.inner.get_or_create(labels),
+            }
+        }
+
+        pub fn set(&self, labels: &[&str], value: f64) {
+            self.with_label_values(labels).set(value);
+        }
+
+        pub fn inc(&self, labels: &[&str], amount: f64) {
+            self.with_label_values(labels).inc(amount);
+        }
+
+        pub fn dec(&self, labels: &[&str], amount: f64) {
+            self.with_label_values(labels).dec(amount);
+        }
+
+        /// Alias for [`GaugeVec::inc`].
+        pub fn add(&self, labels: &[&str], amount: f64) {
+            self.inc(labels, amount);
+        }
+
+        /// Alias for [`GaugeVec::dec`].
+        pub fn sub(&self, labels: &[&str], amount: f64) {
+            self.dec(labels, amount);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct Gauge {
+        inner: Arc<GaugeValue>,
+    }
+
+    impl Gauge {
+        pub fn set(&self, value: f64) {
+            self.inner.set(value);
+        }
+
+        pub fn inc(&self, amount: f64) {
+            self.inner.add(amount);
+        }
+
+        pub fn dec(&self, amount: f64) {
+            self.inner.add(-amount);
+        }
+
+        /// Alias for [`Gauge::inc`].
+        pub fn add(&self, amount: f64) {
+            self.inc(amount);
+        }
+
+        /// Alias for [`Gauge::dec`].
+        pub fn sub(&self, amount: f64) {
+            self.dec(amount);
+        }
+    }
+
+    /// Relative accuracy used by [`register_histogram_with_sketch_default`]
+    /// when the caller has no specific accuracy requirement; mirrors
+    /// [`DEFAULT_SUMMARY_ERROR`].
+    const DEFAULT_SKETCH_ACCURACY: f64 = 0.01;
+    /// Observations at or below this magnitude collapse into
+    /// [`DdSketch`]'s zero bucket, since a bucket index is undefined for
+    /// `x <= 0`.
+    const SKETCH_ZERO_THRESHOLD: f64 = 1e-9;
+
+    /// A mergeable DDSketch: maps each observation `x` to a bucket index
+    /// `i = ceil(ln(x) / ln(gamma))`, so that `2 * gamma^i / (gamma + 1)`
+    /// estimates any value landing in that bucket within the sketch's
+    /// relative accuracy. Memory grows with the number of distinct indices
+    /// touched, not the number of observations, and two sketches merge by
+    /// summing per-index counts.
+    #[derive(Clone)]
+    struct DdSketch {
+        gamma: f64,
+        zero_count: u64,
+        buckets: HashMap<i32, u64>,
+    }
+
+    impl DdSketch {
+        fn new(accuracy: f64) -> Self {
+            Self {
+                gamma: (1.0 + accuracy) / (1.0 - accuracy),
+                zero_count: 0,
+                buckets: HashMap::new(),
+            }
+        }
+
+        fn observe(&mut self, value: f64) {
+            if value <= SKETCH_ZERO_THRESHOLD {
+                self.zero_count += 1;
+                return;
+            }
+            let index = (value.ln() / self.gamma.ln()).ceil() as i32;
+            *self.buckets.entry(index).or_insert(0) += 1;
+        }
+
+        /// Combines `other`'s counts into this sketch by summing per-index
+        /// counts, for aggregating per-thread or per-replica sketches.
+        fn merge(&mut self, other: &DdSketch) {
+            self.zero_count += other.zero_count;
+            for (index, count) in &other.buckets {
+                *self.buckets.entry(*index).or_insert(0) += count;
+            }
+        }
+
+        /// Walks bucket indices in ascending order, accumulating counts
+        /// until the running total reaches `target rank = ceil(q * (n - 1))`,
+        /// and returns that bucket's value estimate.
+        fn quantile(&self, q: f64, total_count: u64) -> f64 {
+            if total_count == 0 {
+                return 0.0;
+            }
+            let target_rank = (q * (total_count as f64 - 1.0)).ceil().max(0.0) as u64;
+
+            let mut cumulative = self.zero_count;
+            if cumulative >= target_rank {
+                return 0.0;
+            }
+
+            let mut indices: Vec<i32> = self.buckets.keys().copied().collect();
+            indices.sort_unstable();
+            for index in indices {
+                cumulative += self.buckets[&index];
+                if cumulative >= target_rank {
+                    return 2.0 * self.gamma.powi(index) / (self.gamma + 1.0);
+                }
+            }
+            0.0
+        }
+    }
+
     struct HistogramVecInner {
         name: &'static str,
         help: &'static str,
         label_names: &'static [&'static str],
         buckets: &'static [f64],
+        /// Quantiles to report via a per-series [`DdSketch`] in
+        /// [`Registry::encode`], or `None` to stick to the linear-bucket
+        /// output every histogram already has.
+        sketch_quantiles: Option<&'static [f64]>,
+        sketch_accuracy: f64,
+        unit: Option<Unit>,
         values: Mutex<HashMap<Vec<String>, Arc<HistogramValue>>>,
     }
 
@@ -676,12 +1834,21 @@ pub mod metrics {
             help: &'static str,
             label_names: &'static [&'static str],
             buckets: &'static [f64],
+            sketch_quantiles: Option<&'static [f64]>,
+            sketch_accuracy: f64,
+            unit: Option<Unit>,
         ) -> Self {
+            if let Some(unit) = unit {
+                assert_unit_suffix(name, unit);
+            }
             Self {
                 name,
                 help,
                 label_names,
                 buckets,
+                sketch_quantiles,
+                sketch_accuracy,
+                unit,
                 values: Mutex::new(HashMap::new()),
             }
         }
@@ -694,10 +1861,11 @@ pub mod metrics {
             );
             let mut guard = self.values.lock().expect("lock poisoned");
             let key: Vec<String> = label_values.iter().map(|value| value.to_string()).collect();
+            let sketch_accuracy = self.sketch_quantiles.map(|_| self.sketch_accuracy);
             Arc::clone(
                 guard
                     .entry(key)
-                    .or_insert_with(|| HistogramValue::new(self.buckets.len())),
+                    .or_insert_with(|| HistogramValue::new(self.buckets.len(), sketch_accuracy)),
             )
         }
 
@@ -708,20 +1876,35 @@ pub mod metrics {
                 .map(|(labels, value)| (labels.clone(), value.snapshot()))
                 .collect()
         }
+
+        /// Drops label series whose histogram hasn't observed a value for
+        /// at least `timeout`. See [`CounterVecInner::evict_idle`].
+        fn evict_idle(&self, timeout: Duration) {
+            let mut guard = self.values.lock().expect("lock poisoned");
+            guard.retain(|_, value| {
+                survives_idle_sweep(&value.last_touch, &value.pending_eviction, timeout)
+            });
+        }
     }
 
     struct HistogramValue {
         state: Mutex<HistogramState>,
+        last_touch: AtomicU64,
+        pending_eviction: AtomicBool,
     }
 
     impl HistogramValue {
-        fn new(bucket_count: usize) -> Arc<Self> {
+        fn new(bucket_count: usize, sketch_accuracy: Option<f64>) -> Arc<Self> {
             Arc::new(Self {
                 state: Mutex::new(HistogramState {
                     counts: vec![0; bucket_count + 1],
+                    exemplars: vec![None; bucket_count + 1],
                     sum: 0.0,
                     count: 0,
+                    sketch: sketch_accuracy.map(DdSketch::new),
                 }),
+                last_touch: AtomicU64::new(now_millis()),
+                pending_eviction: AtomicBool::new(false),
             })
         }
 
@@ -729,6 +1912,9 @@ pub mod metrics {
             let mut state = self.state.lock().expect("lock poisoned");
             state.count += 1;
             state.sum += value;
+            if let Some(sketch) = state.sketch.as_mut() {
+                sketch.observe(value);
+            }
 
             let mut idx = buckets.len();
             for (i, bound) in buckets.iter().enumerate() {
@@ -740,22 +1926,79 @@ pub mod metrics {
             if let Some(slot) = state.counts.get_mut(idx) {
                 *slot += 1;
             }
+            // Exemplars link a bucket back to the trace that most recently
+            // landed in it; overwrite-on-observe keeps memory at one
+            // exemplar per bucket, and we simply skip it outside a span.
+            if let Some(trace_id) = tracing::Span::current().trace_id() {
+                if let Some(slot) = state.exemplars.get_mut(idx) {
+                    *slot = Some(Exemplar {
+                        trace_id,
+                        value,
+                        timestamp_seconds: unix_seconds(),
+                    });
+                }
+            }
+            drop(state);
+            self.last_touch.store(now_millis(), Ordering::Relaxed);
         }
 
         fn snapshot(&self) -> HistogramSnapshot {
             let state = self.state.lock().expect("lock poisoned");
             HistogramSnapshot {
                 counts: state.counts.clone(),
+                exemplars: state.exemplars.clone(),
                 sum: state.sum,
                 count: state.count,
+                sketch: state.sketch.clone(),
             }
         }
+
+        fn merge(&self, other: &HistogramValue) {
+            let other_state = other.state.lock().expect("lock poisoned");
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.merge(&other_state);
+        }
     }
 
     struct HistogramState {
         counts: Vec<u64>,
+        exemplars: Vec<Option<Exemplar>>,
         sum: f64,
         count: u64,
+        sketch: Option<DdSketch>,
+    }
+
+    impl HistogramState {
+        /// Sums bucket counts, `sum`/`count`, and (when both sides track
+        /// one) the quantile sketch. Exemplars aren't merged — each side
+        /// keeps its own most-recent-observation trace links.
+        fn merge(&mut self, other: &HistogramState) {
+            for (slot, other_count) in self.counts.iter_mut().zip(other.counts.iter()) {
+                *slot += other_count;
+            }
+            self.sum += other.sum;
+            self.count += other.count;
+            if let (Some(sketch), Some(other_sketch)) = (self.sketch.as_mut(), &other.sketch) {
+                sketch.merge(other_sketch);
+            }
+        }
+    }
+
+    /// Most recent observation that landed in a given histogram bucket,
+    /// rendered by [`Registry::encode_openmetrics`] as an OpenMetrics
+    /// exemplar so a latency spike can be traced back to a request.
+    #[derive(Clone)]
+    struct Exemplar {
+        trace_id: String,
+        value: f64,
+        timestamp_seconds: f64,
+    }
+
+    fn unix_seconds() -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
     }
 
     #[derive(Clone)]
@@ -786,18 +2029,286 @@ pub mod metrics {
         pub fn observe(&self, value: f64) {
             self.inner.observe(self.buckets, value);
         }
+
+        /// Merges `other`'s bucket counts, sum/count, and quantile sketch
+        /// into this histogram, so per-thread or per-replica histograms can
+        /// be combined before querying quantiles. See [`Summary::merge`].
+        pub fn merge(&self, other: &Histogram) {
+            self.inner.merge(&other.inner);
+        }
     }
 
     #[derive(Clone)]
     struct HistogramSnapshot {
         counts: Vec<u64>,
+        exemplars: Vec<Option<Exemplar>>,
         sum: f64,
         count: u64,
+        sketch: Option<DdSketch>,
     }
 
-    fn write_labels(output: &mut String, names: &[&str], values: &[String]) {
-        if names.is_empty() {
-            return;
+    /// Default relative error used by [`register_summary`] when the caller
+    /// has no specific accuracy requirement; mirrors the ~1% default most
+    /// t-digest implementations ship with.
+    const DEFAULT_SUMMARY_ERROR: f64 = 0.01;
+    const DEFAULT_SUMMARY_QUANTILES: &[f64] = &[0.5, 0.9, 0.99];
+    /// Centroid count above which [`SummaryState::observe`] triggers a
+    /// compression pass.
+    const SUMMARY_COMPRESS_THRESHOLD: usize = 300;
+
+    /// One centroid of a t-digest-style sketch: a mean and the number of
+    /// samples it represents.
+    #[derive(Clone, Copy)]
+    struct Centroid {
+        mean: f64,
+        weight: f64,
+    }
+
+    struct SummaryState {
+        centroids: Vec<Centroid>,
+        sum: f64,
+        count: u64,
+    }
+
+    impl SummaryState {
+        fn new() -> Self {
+            Self {
+                centroids: Vec::new(),
+                sum: 0.0,
+                count: 0,
+            }
+        }
+
+        fn observe(&mut self, value: f64, err: f64) {
+            self.sum += value;
+            self.count += 1;
+
+            let idx = self
+                .centroids
+                .partition_point(|centroid| centroid.mean < value);
+            self.centroids.insert(
+                idx,
+                Centroid {
+                    mean: value,
+                    weight: 1.0,
+                },
+            );
+
+            // Compress periodically rather than after every insert so the
+            // amortized cost stays low; centroid count is otherwise
+            // unbounded since every insert above is an uncompressed singleton.
+            if self.centroids.len() > SUMMARY_COMPRESS_THRESHOLD {
+                self.compress(err);
+            }
+        }
+
+        /// Merges adjacent centroids whose combined weight still fits
+        /// within the rank-dependent bound `2 * q * (1 - q) * N / err`,
+        /// keeping memory use independent of the number of samples seen.
+        fn compress(&mut self, err: f64) {
+            if self.centroids.len() < 2 {
+                return;
+            }
+            let total_weight: f64 = self.centroids.iter().map(|c| c.weight).sum();
+            let mut merged = Vec::with_capacity(self.centroids.len());
+            let mut iter = self.centroids.drain(..);
+            let mut current = iter.next().expect("checked len >= 2");
+            let mut rank_so_far = 0.0;
+            for next in iter {
+                let q = (rank_so_far + current.weight / 2.0) / total_weight;
+                let max_weight = (2.0 * q * (1.0 - q) * total_weight / err).max(1.0);
+                if current.weight + next.weight <= max_weight {
+                    let combined_weight = current.weight + next.weight;
+                    let combined_mean = (current.mean * current.weight
+                        + next.mean * next.weight)
+                        / combined_weight;
+                    current = Centroid {
+                        mean: combined_mean,
+                        weight: combined_weight,
+                    };
+                } else {
+                    rank_so_far += current.weight;
+                    merged.push(current);
+                    current = next;
+                }
+            }
+            merged.push(current);
+            self.centroids = merged;
+        }
+
+        /// Merges another sketch's centroids into this one, so per-thread or
+        /// per-replica sketches can be combined by concatenating centroids
+        /// and re-compressing.
+        fn merge(&mut self, other: &SummaryState, err: f64) {
+            self.centroids.extend_from_slice(&other.centroids);
+            self.centroids
+                .sort_by(|a, b| a.mean.partial_cmp(&b.mean).expect("centroid mean is NaN"));
+            self.sum += other.sum;
+            self.count += other.count;
+            self.compress(err);
+        }
+    }
+
+    #[derive(Clone)]
+    struct SummarySnapshot {
+        centroids: Vec<Centroid>,
+        sum: f64,
+        count: u64,
+    }
+
+    impl SummarySnapshot {
+        /// Scans centroids accumulating weight until the cumulative rank
+        /// crosses `q * N`, then linearly interpolates between the
+        /// straddling centroids.
+        fn quantile(&self, q: f64) -> f64 {
+            match self.centroids.as_slice() {
+                [] => 0.0,
+                [only] => only.mean,
+                centroids => {
+                    let total_weight: f64 = centroids.iter().map(|c| c.weight).sum();
+                    let target = q * total_weight;
+                    let mut cumulative = 0.0;
+                    for window in centroids.windows(2) {
+                        let (left, right) = (window[0], window[1]);
+                        let next_cumulative = cumulative + left.weight;
+                        if target <= next_cumulative {
+                            let fraction = if next_cumulative > cumulative {
+                                (target - cumulative) / (next_cumulative - cumulative)
+                            } else {
+                                0.0
+                            };
+                            return left.mean + fraction * (right.mean - left.mean);
+                        }
+                        cumulative = next_cumulative;
+                    }
+                    centroids.last().expect("checked non-empty").mean
+                }
+            }
+        }
+    }
+
+    struct SummaryValue {
+        state: Mutex<SummaryState>,
+        err: f64,
+    }
+
+    impl SummaryValue {
+        fn new(err: f64) -> Arc<Self> {
+            Arc::new(Self {
+                state: Mutex::new(SummaryState::new()),
+                err,
+            })
+        }
+
+        fn observe(&self, value: f64) {
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.observe(value, self.err);
+        }
+
+        fn snapshot(&self) -> SummarySnapshot {
+            let state = self.state.lock().expect("lock poisoned");
+            SummarySnapshot {
+                centroids: state.centroids.clone(),
+                sum: state.sum,
+                count: state.count,
+            }
+        }
+
+        fn merge(&self, other: &SummaryValue) {
+            let other_state = other.state.lock().expect("lock poisoned");
+            let mut state = self.state.lock().expect("lock poisoned");
+            state.merge(&other_state, self.err);
+        }
+    }
+
+    struct SummaryVecInner {
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        quantiles: &'static [f64],
+        err: f64,
+        values: Mutex<HashMap<Vec<String>, Arc<SummaryValue>>>,
+    }
+
+    impl SummaryVecInner {
+        fn new(
+            name: &'static str,
+            help: &'static str,
+            label_names: &'static [&'static str],
+            quantiles: &'static [f64],
+            err: f64,
+        ) -> Self {
+            Self {
+                name,
+                help,
+                label_names,
+                quantiles,
+                err,
+                values: Mutex::new(HashMap::new()),
+            }
+        }
+
+        fn get_or_create(&self, label_values: &[&str]) -> Arc<SummaryValue> {
+            assert_eq!(
+                self.label_names.len(),
+                label_values.len(),
+                "label value count mismatch"
+            );
+            let mut guard = self.values.lock().expect("lock poisoned");
+            let key: Vec<String> = label_values.iter().map(|value| value.to_string()).collect();
+            Arc::clone(
+                guard
+                    .entry(key)
+                    .or_insert_with(|| SummaryValue::new(self.err)),
+            )
+        }
+
+        fn collect(&self) -> Vec<(Vec<String>, SummarySnapshot)> {
+            let guard = self.values.lock().expect("lock poisoned");
+            guard
+                .iter()
+                .map(|(labels, value)| (labels.clone(), value.snapshot()))
+                .collect()
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct SummaryVec {
+        inner: Arc<SummaryVecInner>,
+    }
+
+    impl SummaryVec {
+        pub fn with_label_values(&self, labels: &[&str]) -> Summary {
+            Summary {
+                inner: self.inner.get_or_create(labels),
+            }
+        }
+
+        pub fn observe(&self, labels: &[&str], value: f64) {
+            self.with_label_values(labels).observe(value);
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct Summary {
+        inner: Arc<SummaryValue>,
+    }
+
+    impl Summary {
+        pub fn observe(&self, value: f64) {
+            self.inner.observe(value);
+        }
+
+        /// Merges `other`'s observations into this sketch, so per-thread or
+        /// per-replica summaries can be combined before querying quantiles.
+        pub fn merge(&self, other: &Summary) {
+            self.inner.merge(&other.inner);
+        }
+    }
+
+    fn write_labels(output: &mut String, names: &[&str], values: &[String]) {
+        if names.is_empty() {
+            return;
         }
 
         output.push('{');
@@ -824,6 +2335,23 @@ pub mod metrics {
         escaped
     }
 
+    /// Appends ` # {trace_id="..."} <value> <timestamp>` to `output` if
+    /// `exemplar` holds one, per the OpenMetrics exemplar syntax. A no-op
+    /// (and the usual case) when the bucket has never been observed under
+    /// an active span.
+    fn write_exemplar(output: &mut String, exemplar: Option<&Option<Exemplar>>) {
+        if let Some(exemplar) = exemplar.and_then(|slot| slot.as_ref()) {
+            write!(
+                output,
+                " # {{trace_id=\"{}\"}} {} {}",
+                escape_label_value(&exemplar.trace_id),
+                format_float(exemplar.value),
+                exemplar.timestamp_seconds
+            )
+            .expect("write metrics");
+        }
+    }
+
     fn format_float(value: f64) -> String {
         let mut formatted = format!("{value:.6}");
         while formatted.contains('.') && formatted.ends_with('0') {
@@ -847,22 +2375,183 @@ pub mod metrics {
         help: &'static str,
         label_names: &'static [&'static str],
     ) -> CounterVec {
-        let inner = Arc::new(CounterVecInner::new(name, help, label_names));
+        let inner = Arc::new(CounterVecInner::new(name, help, label_names, None));
         registry().register_counter(inner.clone());
         CounterVec { inner }
     }
 
+    /// As [`register_counter`], but also declares the series' base `unit`,
+    /// so [`Registry::encode`] emits a `# UNIT` line for it. Panics if
+    /// `name` doesn't already carry the suffix that `unit` requires.
+    pub fn register_counter_with_unit(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        unit: Unit,
+    ) -> CounterVec {
+        let inner = Arc::new(CounterVecInner::new(name, help, label_names, Some(unit)));
+        registry().register_counter(inner.clone());
+        CounterVec { inner }
+    }
+
+    pub fn register_gauge(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+    ) -> GaugeVec {
+        let inner = Arc::new(GaugeVecInner::new(name, help, label_names, None));
+        registry().register_gauge(inner.clone());
+        GaugeVec { inner }
+    }
+
+    /// As [`register_gauge`], but also declares the series' base `unit`, so
+    /// [`Registry::encode`] emits a `# UNIT` line for it. Panics if `name`
+    /// doesn't already carry the suffix that `unit` requires.
+    pub fn register_gauge_with_unit(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        unit: Unit,
+    ) -> GaugeVec {
+        let inner = Arc::new(GaugeVecInner::new(name, help, label_names, Some(unit)));
+        registry().register_gauge(inner.clone());
+        GaugeVec { inner }
+    }
+
     pub fn register_histogram(
         name: &'static str,
         help: &'static str,
         label_names: &'static [&'static str],
         buckets: &'static [f64],
     ) -> HistogramVec {
-        let inner = Arc::new(HistogramVecInner::new(name, help, label_names, buckets));
+        let inner = Arc::new(HistogramVecInner::new(
+            name,
+            help,
+            label_names,
+            buckets,
+            None,
+            0.0,
+            None,
+        ));
         registry().register_histogram(inner.clone());
         HistogramVec { inner }
     }
 
+    /// As [`register_histogram`], but also declares the series' base
+    /// `unit`, so [`Registry::encode`] emits a `# UNIT` line for it. Panics
+    /// if `name` doesn't already carry the suffix that `unit` requires.
+    pub fn register_histogram_with_unit(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+        unit: Unit,
+    ) -> HistogramVec {
+        let inner = Arc::new(HistogramVecInner::new(
+            name,
+            help,
+            label_names,
+            buckets,
+            None,
+            0.0,
+            Some(unit),
+        ));
+        registry().register_histogram(inner.clone());
+        HistogramVec { inner }
+    }
+
+    /// As [`register_histogram`], but each series also maintains a
+    /// mergeable DDSketch at relative accuracy `accuracy`, so
+    /// [`Registry::encode`] reports `quantile="Q"` samples (for each of
+    /// `quantiles`) alongside the usual linear buckets — without requiring
+    /// scrapers to guess bucket boundaries for accurate tail latencies.
+    /// Existing `register_histogram` series are unaffected: bucket output
+    /// is always emitted regardless of whether a sketch is configured.
+    pub fn register_histogram_with_sketch(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+        quantiles: &'static [f64],
+        accuracy: f64,
+    ) -> HistogramVec {
+        let inner = Arc::new(HistogramVecInner::new(
+            name,
+            help,
+            label_names,
+            buckets,
+            Some(quantiles),
+            accuracy,
+            None,
+        ));
+        registry().register_histogram(inner.clone());
+        HistogramVec { inner }
+    }
+
+    /// As [`register_histogram_with_sketch`], using
+    /// [`default_sketch_accuracy`] for the DDSketch's relative accuracy.
+    pub fn register_histogram_with_sketch_default(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        buckets: &'static [f64],
+        quantiles: &'static [f64],
+    ) -> HistogramVec {
+        register_histogram_with_sketch(
+            name,
+            help,
+            label_names,
+            buckets,
+            quantiles,
+            DEFAULT_SKETCH_ACCURACY,
+        )
+    }
+
+    pub fn default_sketch_accuracy() -> f64 {
+        DEFAULT_SKETCH_ACCURACY
+    }
+
+    /// Registers a [`SummaryVec`] tracking `quantiles` with relative error
+    /// `err` (e.g. `0.01` for ~1%). Use [`default_summary_quantiles`] and
+    /// [`default_summary_error`] for the common case.
+    pub fn register_summary(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        quantiles: &'static [f64],
+        err: f64,
+    ) -> SummaryVec {
+        let inner = Arc::new(SummaryVecInner::new(
+            name,
+            help,
+            label_names,
+            quantiles,
+            err,
+        ));
+        registry().register_summary(inner.clone());
+        SummaryVec { inner }
+    }
+
+    /// As [`register_summary`], using [`default_summary_error`] as the
+    /// sketch's relative error. Covers the common case where callers want
+    /// configurable quantiles without tuning accuracy.
+    pub fn register_summary_default(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        quantiles: &'static [f64],
+    ) -> SummaryVec {
+        register_summary(name, help, label_names, quantiles, DEFAULT_SUMMARY_ERROR)
+    }
+
+    pub fn default_summary_quantiles() -> &'static [f64] {
+        DEFAULT_SUMMARY_QUANTILES
+    }
+
+    pub fn default_summary_error() -> f64 {
+        DEFAULT_SUMMARY_ERROR
+    }
+
     pub fn encode_prometheus() -> String {
         let _ = http_requests_total();
         let _ = handler_latency_seconds();
@@ -871,6 +2560,603 @@ pub mod metrics {
         registry().encode()
     }
 
+    /// As [`encode_prometheus`], but in OpenMetrics format with histogram
+    /// exemplars linking buckets back to the trace that last landed in
+    /// them. Serve with [`OPENMETRICS_CONTENT_TYPE`].
+    pub fn encode_openmetrics() -> String {
+        let _ = http_requests_total();
+        let _ = handler_latency_seconds();
+        let _ = msgbus_publish_total();
+        let _ = msgbus_subscribe_total();
+        registry().encode_openmetrics()
+    }
+
+    /// Alias for [`encode_prometheus`] used by [`super::metrics_router`] to
+    /// scrape the registry over HTTP.
+    pub fn gather() -> String {
+        encode_prometheus()
+    }
+
+    /// Self-contained `axum` router serving `GET /metrics`, distinct from
+    /// [`super::metrics_router`] in that a scrape against it also records
+    /// its own `http_requests_total`/`handler_latency_seconds` samples, so
+    /// the exporter shows up in its own output.
+    pub fn router() -> Router {
+        let handler = get(|| async {
+            let start = std::time::Instant::now();
+            let body = encode_prometheus();
+            let elapsed = start.elapsed().as_secs_f64();
+            http_requests_total().inc(&["metrics", "/metrics", "200"], 1);
+            handler_latency_seconds().observe(&["metrics", "/metrics"], elapsed);
+            (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE))],
+                body,
+            )
+        });
+        Router::new().route("/metrics", handler)
+    }
+
+    /// Spawns a tiny server exposing [`router`] on `addr`, returning a
+    /// handle the caller can await or abort. `shutdown` resolving triggers
+    /// a graceful shutdown of the listener.
+    pub fn serve(
+        addr: std::net::SocketAddr,
+        shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let listener = match tokio::net::TcpListener::bind(addr).await {
+                Ok(listener) => listener,
+                Err(err) => {
+                    tracing::warn!(%addr, error = %err, "failed to bind metrics exporter");
+                    return;
+                }
+            };
+            if let Err(err) = axum::serve(listener, router())
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                tracing::warn!(%addr, error = %err, "metrics exporter exited with error");
+            }
+        })
+    }
+
+    const DEFAULT_RESOURCE_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+    /// Periodically samples host/process resource usage and publishes it as
+    /// gauges, so a service picks up `process_cpu_usage_ratio`,
+    /// `process_resident_memory_bytes`, `process_open_fds`, and
+    /// `system_load_average1` in [`encode_prometheus`] for free, instead of
+    /// writing its own sampling loop.
+    pub struct ResourceCollector {
+        service: &'static str,
+        interval: Duration,
+    }
+
+    impl ResourceCollector {
+        pub fn new(service: &'static str) -> Self {
+            Self {
+                service,
+                interval: DEFAULT_RESOURCE_SAMPLE_INTERVAL,
+            }
+        }
+
+        /// Overrides the default sampling interval.
+        pub fn with_interval(mut self, interval: Duration) -> Self {
+            self.interval = interval;
+            self
+        }
+
+        /// Spawns the sampling loop, returning a handle the caller can abort
+        /// on shutdown. Sampling failures are logged and skipped, not
+        /// propagated: a missed sample shouldn't take the service down.
+        pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(self.interval);
+                let mut sampler = ResourceSampler::new();
+                loop {
+                    ticker.tick().await;
+                    let sample = sampler.sample().await;
+                    PROCESS_CPU_USAGE_RATIO.set(&[self.service], sample.cpu_usage_ratio);
+                    PROCESS_RESIDENT_MEMORY_BYTES.set(&[self.service], sample.resident_memory_bytes);
+                    PROCESS_OPEN_FDS.set(&[self.service], sample.open_fds);
+                    SYSTEM_LOAD_AVERAGE1.set(&[self.service], sample.load_average_1m);
+                }
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Default)]
+    struct ResourceSample {
+        cpu_usage_ratio: f64,
+        resident_memory_bytes: f64,
+        open_fds: f64,
+        load_average_1m: f64,
+    }
+
+    /// Holds the previous CPU-time/wall-clock reading so [`Self::sample`] can
+    /// turn cumulative `/proc/self/stat` jiffies into a usage ratio over the
+    /// interval since the last sample.
+    struct ResourceSampler {
+        last_cpu_jiffies: Option<u64>,
+        last_sampled_at: Option<std::time::Instant>,
+    }
+
+    impl ResourceSampler {
+        fn new() -> Self {
+            Self {
+                last_cpu_jiffies: None,
+                last_sampled_at: None,
+            }
+        }
+
+        async fn sample(&mut self) -> ResourceSample {
+            #[cfg(target_os = "linux")]
+            {
+                self.sample_linux().await
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                self.sample_fallback().await
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        async fn sample_linux(&mut self) -> ResourceSample {
+            let mut sample = ResourceSample::default();
+
+            if let Ok(stat) = tokio::fs::read_to_string("/proc/self/stat").await {
+                if let Some((utime, stime)) = parse_proc_self_stat_times(&stat) {
+                    let jiffies = utime + stime;
+                    let now = std::time::Instant::now();
+                    if let (Some(last_jiffies), Some(last_at)) =
+                        (self.last_cpu_jiffies, self.last_sampled_at)
+                    {
+                        let elapsed = now.duration_since(last_at).as_secs_f64();
+                        if elapsed > 0.0 {
+                            let clock_ticks_per_sec = clock_ticks_per_second();
+                            let delta_seconds =
+                                jiffies.saturating_sub(last_jiffies) as f64 / clock_ticks_per_sec;
+                            sample.cpu_usage_ratio = delta_seconds / elapsed;
+                        }
+                    }
+                    self.last_cpu_jiffies = Some(jiffies);
+                    self.last_sampled_at = Some(now);
+                }
+            }
+
+            if let Ok(status) = tokio::fs::read_to_string("/proc/self/status").await {
+                if let Some(kb) = parse_proc_self_status_vmrss_kb(&status) {
+                    sample.resident_memory_bytes = kb * 1024.0;
+                }
+            }
+
+            if let Ok(mut entries) = tokio::fs::read_dir("/proc/self/fd").await {
+                let mut count = 0u64;
+                while let Ok(Some(_)) = entries.next_entry().await {
+                    count += 1;
+                }
+                sample.open_fds = count as f64;
+            }
+
+            if let Ok(loadavg) = tokio::fs::read_to_string("/proc/loadavg").await {
+                if let Some(load1) = loadavg.split_whitespace().next() {
+                    if let Ok(load1) = load1.parse::<f64>() {
+                        sample.load_average_1m = load1;
+                    }
+                }
+            }
+
+            sample
+        }
+
+        /// Non-Linux fallback: shell out to platform commands rather than
+        /// leaving the gauges unset. Best-effort only — parse failures leave
+        /// the corresponding field at its zero default.
+        #[cfg(not(target_os = "linux"))]
+        async fn sample_fallback(&mut self) -> ResourceSample {
+            let mut sample = ResourceSample::default();
+
+            if let Ok(output) = tokio::process::Command::new("ps")
+                .args(["-o", "%cpu=,rss=", "-p", &std::process::id().to_string()])
+                .output()
+                .await
+            {
+                let text = String::from_utf8_lossy(&output.stdout);
+                let mut fields = text.split_whitespace();
+                if let Some(cpu_pct) = fields.next().and_then(|f| f.parse::<f64>().ok()) {
+                    sample.cpu_usage_ratio = cpu_pct / 100.0;
+                }
+                if let Some(rss_kb) = fields.next().and_then(|f| f.parse::<f64>().ok()) {
+                    sample.resident_memory_bytes = rss_kb * 1024.0;
+                }
+            }
+
+            sample
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn clock_ticks_per_second() -> f64 {
+        // `sysconf(_SC_CLK_TCK)` is 100 on effectively every Linux platform
+        // we target; avoid a libc dependency just for this constant.
+        100.0
+    }
+
+    /// Extracts `utime`/`stime` (fields 14 and 15) from `/proc/self/stat`,
+    /// skipping past the parenthesized, possibly space-containing `comm`
+    /// field by matching on the last `)`.
+    #[cfg(target_os = "linux")]
+    fn parse_proc_self_stat_times(stat: &str) -> Option<(u64, u64)> {
+        let after_comm = stat.rsplit_once(')')?.1;
+        let mut fields = after_comm.split_whitespace();
+        let utime = fields.nth(11)?.parse().ok()?;
+        let stime = fields.next()?.parse().ok()?;
+        Some((utime, stime))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn parse_proc_self_status_vmrss_kb(status: &str) -> Option<f64> {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))
+            .and_then(|rest| rest.trim().split_whitespace().next())
+            .and_then(|kb| kb.parse().ok())
+    }
+
+    static PROCESS_CPU_USAGE_RATIO: Lazy<GaugeVec> = Lazy::new(|| {
+        register_gauge(
+            "process_cpu_usage_ratio",
+            "Process CPU usage as a fraction of one core, averaged over the sampling interval",
+            &["service"],
+        )
+    });
+
+    static PROCESS_RESIDENT_MEMORY_BYTES: Lazy<GaugeVec> = Lazy::new(|| {
+        register_gauge(
+            "process_resident_memory_bytes",
+            "Resident set size of the process in bytes",
+            &["service"],
+        )
+    });
+
+    static PROCESS_OPEN_FDS: Lazy<GaugeVec> = Lazy::new(|| {
+        register_gauge(
+            "process_open_fds",
+            "Number of file descriptors currently open by the process",
+            &["service"],
+        )
+    });
+
+    static SYSTEM_LOAD_AVERAGE1: Lazy<GaugeVec> = Lazy::new(|| {
+        register_gauge(
+            "system_load_average1",
+            "System load average over the last minute",
+            &["service"],
+        )
+    });
+
+    const DEFAULT_INFLUX_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Periodically pushes the registry to an InfluxDB write endpoint as
+    /// line protocol, for deployments that can't expose a scrape target.
+    /// Built on [`snapshot`] — the same data backing the `/metrics/snapshot`
+    /// routes — so Prometheus pull and Influx push always report identical
+    /// numbers.
+    pub struct InfluxExporter {
+        endpoint: String,
+        interval: Duration,
+    }
+
+    impl InfluxExporter {
+        pub fn new(endpoint: impl Into<String>) -> Self {
+            Self {
+                endpoint: endpoint.into(),
+                interval: DEFAULT_INFLUX_FLUSH_INTERVAL,
+            }
+        }
+
+        /// Overrides the default flush interval.
+        pub fn with_interval(mut self, interval: Duration) -> Self {
+            self.interval = interval;
+            self
+        }
+
+        /// Spawns the push loop, returning a handle the caller can abort on
+        /// shutdown. A failed push is logged and the batch dropped, not
+        /// retried: retrying would let backlog grow unbounded while the
+        /// backend is down, and the next tick's snapshot supersedes it
+        /// anyway.
+        pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+            tokio::spawn(async move {
+                let client = Client::new();
+                let mut ticker = tokio::time::interval(self.interval);
+                loop {
+                    ticker.tick().await;
+                    let body = encode_influx_line_protocol(&snapshot());
+                    if body.is_empty() {
+                        continue;
+                    }
+                    let result = client
+                        .post(&self.endpoint)
+                        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                        .body(body)
+                        .send()
+                        .await
+                        .and_then(reqwest::Response::error_for_status);
+                    if let Err(err) = result {
+                        tracing::warn!(
+                            endpoint = %self.endpoint,
+                            error = %err,
+                            "failed to push metrics to influx"
+                        );
+                    }
+                }
+            })
+        }
+    }
+
+    /// Serializes a [`MetricsSnapshot`] into InfluxDB line protocol,
+    /// mapping each sample's labels to tags and its value(s) to fields.
+    fn encode_influx_line_protocol(snapshot: &MetricsSnapshot) -> String {
+        let mut out = String::new();
+        let timestamp_nanos = unix_nanos(SystemTime::now());
+
+        for counter in &snapshot.counters {
+            write_influx_line(
+                &mut out,
+                counter.name,
+                &counter.labels,
+                &[("value", counter.value as f64)],
+                timestamp_nanos,
+            );
+        }
+        for gauge in &snapshot.gauges {
+            write_influx_line(
+                &mut out,
+                gauge.name,
+                &gauge.labels,
+                &[("value", gauge.value)],
+                timestamp_nanos,
+            );
+        }
+        for histogram in &snapshot.histograms {
+            write_influx_line(
+                &mut out,
+                histogram.name,
+                &histogram.labels,
+                &[
+                    ("sum", histogram.sum),
+                    ("count", histogram.count as f64),
+                ],
+                timestamp_nanos,
+            );
+        }
+
+        out
+    }
+
+    fn write_influx_line(
+        out: &mut String,
+        measurement: &str,
+        labels: &HashMap<String, String>,
+        fields: &[(&str, f64)],
+        timestamp_nanos: u128,
+    ) {
+        out.push_str(measurement);
+        let mut tag_keys: Vec<&String> = labels.keys().collect();
+        tag_keys.sort();
+        for key in tag_keys {
+            out.push(',');
+            out.push_str(&escape_influx_tag(key));
+            out.push('=');
+            out.push_str(&escape_influx_tag(&labels[key]));
+        }
+
+        out.push(' ');
+        for (idx, (name, value)) in fields.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            out.push_str(name);
+            out.push('=');
+            out.push_str(&format_float(*value));
+        }
+
+        out.push(' ');
+        out.push_str(&timestamp_nanos.to_string());
+        out.push('\n');
+    }
+
+    /// Escapes commas, spaces, and equals signs in an Influx tag key/value,
+    /// per the line-protocol spec.
+    fn escape_influx_tag(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace(',', "\\,")
+            .replace('=', "\\=")
+            .replace(' ', "\\ ")
+    }
+
+    const DEFAULT_OTLP_PUSH_INTERVAL: Duration = Duration::from_secs(15);
+
+    /// Periodically pushes the registry to an OTLP collector, for
+    /// deployments that can't expose a scrape target — the metrics
+    /// counterpart to [`OtlpExportLayer`]'s span export. Built on
+    /// [`snapshot`], same as [`InfluxExporter`], so every export path
+    /// reports identical numbers.
+    pub struct OtlpMetricsExporter {
+        info: BuildInfo,
+        endpoint: String,
+        interval: Duration,
+    }
+
+    impl OtlpMetricsExporter {
+        pub fn new(info: BuildInfo, endpoint: impl Into<String>) -> Self {
+            Self {
+                info,
+                endpoint: endpoint.into(),
+                interval: DEFAULT_OTLP_PUSH_INTERVAL,
+            }
+        }
+
+        /// Overrides the default push interval.
+        pub fn with_interval(mut self, interval: Duration) -> Self {
+            self.interval = interval;
+            self
+        }
+
+        /// Spawns the push loop, returning a handle the caller can abort on
+        /// shutdown. As with [`InfluxExporter::spawn`], a failed push is
+        /// logged and the batch dropped rather than retried.
+        pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+            tokio::spawn(async move {
+                let client = Client::new();
+                let mut ticker = tokio::time::interval(self.interval);
+                loop {
+                    ticker.tick().await;
+                    let payload = otlp_metrics_payload(
+                        &snapshot(),
+                        &self.info,
+                        unix_nanos(SystemTime::now()),
+                    );
+                    let result = client
+                        .post(&self.endpoint)
+                        .json(&payload)
+                        .send()
+                        .await
+                        .and_then(reqwest::Response::error_for_status);
+                    if let Err(err) = result {
+                        tracing::warn!(
+                            endpoint = %self.endpoint,
+                            error = %err,
+                            "failed to push metrics to OTLP collector"
+                        );
+                    }
+                }
+            })
+        }
+    }
+
+    /// Spawns [`OtlpMetricsExporter`] with default settings, parallel to
+    /// [`crate::ObsInit::init`] — call once at startup alongside it to turn
+    /// on metrics push for deployments that can't be scraped at `/metrics`.
+    pub fn init_otlp(
+        info: BuildInfo,
+        endpoint: impl Into<String>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        OtlpMetricsExporter::new(info, endpoint)
+            .with_interval(interval)
+            .spawn()
+    }
+
+    /// Renders a snapshot as an OTLP `ExportMetricsServiceRequest`-shaped
+    /// JSON payload (the HTTP/JSON encoding OTLP collectors accept
+    /// alongside HTTP/protobuf, same convention [`otlp_export_payload`]
+    /// uses for spans): counters become cumulative, monotonic Sums, gauges
+    /// become Gauges, and histograms become ExplicitBucketHistograms built
+    /// from the same cumulative bucket counts [`Registry::encode`] writes,
+    /// un-accumulated back into per-bucket counts as OTLP expects. `info`
+    /// is attached once, as resource attributes, rather than repeated on
+    /// every data point.
+    fn otlp_metrics_payload(
+        snapshot: &MetricsSnapshot,
+        info: &BuildInfo,
+        timestamp_nanos: u128,
+    ) -> serde_json::Value {
+        let mut metrics = Vec::new();
+
+        for counter in &snapshot.counters {
+            metrics.push(json!({
+                "name": counter.name,
+                "sum": {
+                    "dataPoints": [otlp_number_point(&counter.labels, counter.value as f64, timestamp_nanos)],
+                    "aggregationTemporality": "AGGREGATION_TEMPORALITY_CUMULATIVE",
+                    "isMonotonic": true,
+                },
+            }));
+        }
+        for gauge in &snapshot.gauges {
+            metrics.push(json!({
+                "name": gauge.name,
+                "gauge": {
+                    "dataPoints": [otlp_number_point(&gauge.labels, gauge.value, timestamp_nanos)],
+                },
+            }));
+        }
+        for histogram in &snapshot.histograms {
+            let explicit_bounds: Vec<f64> = histogram
+                .buckets
+                .iter()
+                .map(|(bound, _)| *bound)
+                .filter(|bound| bound.is_finite())
+                .collect();
+            let mut previous = 0u64;
+            let bucket_counts: Vec<u64> = histogram
+                .buckets
+                .iter()
+                .map(|(_, cumulative)| {
+                    let count = cumulative - previous;
+                    previous = *cumulative;
+                    count
+                })
+                .collect();
+
+            metrics.push(json!({
+                "name": histogram.name,
+                "histogram": {
+                    "dataPoints": [{
+                        "attributes": otlp_attributes(&histogram.labels),
+                        "timeUnixNano": timestamp_nanos.to_string(),
+                        "count": histogram.count.to_string(),
+                        "sum": histogram.sum,
+                        "bucketCounts": bucket_counts.iter().map(|count| count.to_string()).collect::<Vec<_>>(),
+                        "explicitBounds": explicit_bounds,
+                    }],
+                    "aggregationTemporality": "AGGREGATION_TEMPORALITY_CUMULATIVE",
+                },
+            }));
+        }
+
+        json!({
+            "resourceMetrics": [{
+                "resource": {
+                    "attributes": [
+                        otlp_attribute("service.name", info.service),
+                        otlp_attribute("service.version", info.version),
+                        otlp_attribute("build_sha", info.build_sha),
+                    ],
+                },
+                "scopeMetrics": [{ "metrics": metrics }],
+            }],
+        })
+    }
+
+    fn otlp_number_point(
+        labels: &HashMap<String, String>,
+        value: f64,
+        timestamp_nanos: u128,
+    ) -> serde_json::Value {
+        json!({
+            "attributes": otlp_attributes(labels),
+            "timeUnixNano": timestamp_nanos.to_string(),
+            "asDouble": value,
+        })
+    }
+
+    fn otlp_attributes(labels: &HashMap<String, String>) -> Vec<serde_json::Value> {
+        let mut keys: Vec<&String> = labels.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .map(|key| otlp_attribute(key, &labels[key]))
+            .collect()
+    }
+
+    fn otlp_attribute(key: &str, value: &str) -> serde_json::Value {
+        json!({ "key": key, "value": { "stringValue": value } })
+    }
+
     static HTTP_REQUESTS_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
         register_counter(
             "http_requests_total",
@@ -922,9 +3208,16 @@ pub mod metrics {
 }
 
 pub use metrics::{
+    default_sketch_accuracy, default_summary_error, default_summary_quantiles, encode_openmetrics,
     encode_prometheus as encode_prometheus_metrics, handler_latency_seconds, http_requests_total,
-    msgbus_publish_total, msgbus_subscribe_total, register_counter, register_histogram, Counter,
-    CounterVec, Histogram, HistogramVec, PROMETHEUS_CONTENT_TYPE,
+    init_otlp, msgbus_publish_total, msgbus_subscribe_total, register_counter,
+    register_counter_with_unit, register_gauge, register_gauge_with_unit, register_histogram,
+    register_histogram_with_sketch, register_histogram_with_sketch_default,
+    register_histogram_with_unit, register_summary, register_summary_default, set_idle_timeout,
+    snapshot as snapshot_metrics, Counter, CounterSample, CounterVec, Gauge, GaugeSample, GaugeVec,
+    Histogram, HistogramSample, HistogramVec, InfluxExporter, MetricKindMask, MetricsSnapshot,
+    OtlpMetricsExporter, ResourceCollector, Summary, SummaryVec, Unit, OPENMETRICS_CONTENT_TYPE,
+    PROMETHEUS_CONTENT_TYPE,
 };
 
 #[cfg(test)]
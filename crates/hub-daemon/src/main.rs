@@ -1,23 +1,53 @@
 use std::{
+    collections::HashMap,
+    net::SocketAddr,
     sync::{Arc, Mutex},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Result;
 use async_trait::async_trait;
-use lokan_automation::{create_echo_rule, RuleEngine};
+use axum::response::sse::{Event as SseEvent, KeepAlive};
+use axum::response::Sse;
+use axum::routing::get;
+use axum::{extract::State as AxumState, Json, Router};
+use common_config::service_port;
+use device_registry::DeviceRegistryService;
+use futures::Stream;
+use lokan_automation::{create_echo_rule, Rule, RuleEngine};
 use lokan_core::{
-    LokanConfig, Service, ServiceContext, ServiceError, ServiceManager, ServiceStatus,
+    DeviceConfig, HealthRegistry, LokanConfig, RestartPolicy, Service, ServiceContext,
+    ServiceError, ServiceHealth, ServiceManager, ServiceStatus, ServingStatus, Supervisor,
 };
+use lokan_device::modbus::{ModbusDriver, ModbusTransport, RegisterMapping};
+use lokan_device::mqtt::{MqttDeviceDriver, MqttEventBridge};
+use lokan_device::poller::{DevicePoller, PollerConfig};
 use lokan_device::{DeviceDescriptor, DeviceDriver, DeviceError, DeviceRegistry, DeviceState};
-use lokan_event::{Event, EventBus};
-use lokan_network::{ConnectionParams, ConnectivitySupervisor, MqttConnector};
+use lokan_event::{Event, EventBus, EventTransport};
+#[cfg(feature = "redis")]
+use lokan_event::{RedisEventTransport, RedisEventTransportConfig};
+use lokan_network::{ConnectionParams, ConnectivitySupervisor, LastWill, MqttConnector};
+use serde::Serialize;
 use serde_json::json;
-use tokio::{signal, sync::Mutex as AsyncMutex, task::JoinHandle, time};
+use tokio::net::TcpListener;
+use tokio::{signal, sync::Mutex as AsyncMutex};
 use tracing::{info, warn};
+use updater::{HealthClient, ServiceHealthWatch, WatchHealthClient};
 
 const EVENT_BUS_KEY: &str = "event_bus";
 const DEVICE_REGISTRY_KEY: &str = "device_registry";
+const DEVICE_DRIVER_KEY: &str = "device_driver";
+/// Extension key under which an `Arc<dyn updater::HealthClient>` backed by
+/// [`HubHealthWatch`] is registered, so an embedded `UpdaterCore` can call
+/// `commit_on_health` against the hub's own [`HealthRegistry`] instead of
+/// an HTTP health checker.
+const UPDATER_HEALTH_CLIENT_KEY: &str = "updater_health_client";
+
+const STATUS_PORT_ENV: &str = "HUB_STATUS_PORT";
+const DEFAULT_STATUS_PORT: u16 = 8090;
+/// How often the `/status` SSE stream re-polls every registered service's
+/// [`Service::health`].
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -25,19 +55,66 @@ async fn main() -> Result<()> {
 
     let config = LokanConfig::default();
     let event_bus = EventBus::new(1024);
-    let device_registry = DeviceRegistry::new();
+    let device_registry = match DeviceRegistry::open(std::path::Path::new(&config.data_dir)) {
+        Ok(registry) => registry,
+        Err(error) => {
+            warn!(%error, data_dir = %config.data_dir, "failed to open persistent device registry, falling back to in-memory");
+            DeviceRegistry::new()
+        }
+    };
+
+    if let Some(broker) = config.network.mqtt_broker.clone() {
+        let bridge = Arc::new(MqttEventBridge::connect(
+            &broker,
+            &config.network.hostname,
+            event_bus.clone(),
+        ));
+        lokan_device::mqtt::spawn_outbound_bridge(bridge.clone(), event_bus.clone());
+        lokan_device::mqtt::spawn_device_status_bridge(bridge, device_registry.subscribe());
+    }
+
+    let device_driver = Arc::new(MockTemperatureDriver::default());
 
     let manager = ServiceManager::new(config)
         .with_extension(EVENT_BUS_KEY, Arc::new(event_bus.clone()))
-        .with_extension(DEVICE_REGISTRY_KEY, Arc::new(device_registry.clone()));
+        .with_extension(DEVICE_REGISTRY_KEY, Arc::new(device_registry.clone()))
+        .with_extension(DEVICE_DRIVER_KEY, device_driver);
+
+    let health_client: Arc<dyn HealthClient> = Arc::new(WatchHealthClient::new(HubHealthWatch(
+        manager.health_registry(),
+    )));
+    let manager = manager.with_extension(UPDATER_HEALTH_CLIENT_KEY, Arc::new(health_client));
 
     let mut manager = manager;
     manager.register_service(Arc::new(AutomationService::new()));
     manager.register_service(Arc::new(DeviceMonitorService::new()));
+    manager.register_service(Arc::new(DeviceRegistryService::new()));
 
+    let manager = Arc::new(manager);
     manager.start_all().await?;
     info!("Lokan Home Hub runtime started");
 
+    let status_listener = TcpListener::bind(SocketAddr::from((
+        [0, 0, 0, 0],
+        service_port(STATUS_PORT_ENV, DEFAULT_STATUS_PORT),
+    )))
+    .await?;
+    let status_app = Router::new()
+        .route("/status", get(status_sse))
+        .route("/healthz", get(healthz))
+        .with_state(manager.clone());
+    let shutdown = manager.task_tracker().token();
+    manager
+        .task_tracker()
+        .spawn(async move {
+            let server = axum::serve(status_listener, status_app.into_make_service())
+                .with_graceful_shutdown(async move { shutdown.cancelled().await });
+            if let Err(error) = server.await {
+                warn!(%error, "status server exited with error");
+            }
+        })
+        .await;
+
     signal::ctrl_c().await?;
     info!("shutdown signal received");
 
@@ -46,6 +123,76 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Streams a combined [`ServiceHealth`] snapshot of every registered service
+/// every [`STATUS_POLL_INTERVAL`], so operators can watch per-service
+/// liveness (DB connectivity, subscriber counts, last event seq, ...) in
+/// real time instead of each service owning an isolated `/health` router.
+async fn status_sse(
+    AxumState(manager): AxumState<Arc<ServiceManager>>,
+) -> Sse<impl Stream<Item = std::result::Result<SseEvent, std::convert::Infallible>>> {
+    let stream = futures::stream::unfold(
+        (manager, tokio::time::interval(STATUS_POLL_INTERVAL)),
+        |(manager, mut ticker)| async move {
+            ticker.tick().await;
+            let snapshot: Vec<ServiceHealth> = manager.health_snapshot().await;
+            let payload = serde_json::to_string(&snapshot).unwrap_or_else(|_| "[]".to_string());
+            Some((Ok(SseEvent::default().data(payload)), (manager, ticker)))
+        },
+    );
+
+    Sse::new(stream).keep_alive(KeepAlive::new())
+}
+
+#[derive(Debug, Serialize)]
+struct HealthzResponse {
+    status: ServingStatus,
+    services: HashMap<String, ServingStatus>,
+}
+
+/// One-shot health probe (as opposed to `/status`'s SSE stream), backed by
+/// [`ServiceManager::health_registry`] instead of polling every service's
+/// [`Service::health`]. Overall `status` is only `Serving` once every
+/// registered service is.
+async fn healthz(AxumState(manager): AxumState<Arc<ServiceManager>>) -> Json<HealthzResponse> {
+    let registry = manager.health_registry();
+    Json(HealthzResponse {
+        status: registry.overall(),
+        services: registry.snapshot(),
+    })
+}
+
+/// Adapts [`HealthRegistry`] to `updater`'s [`ServiceHealthWatch`], so an
+/// embedded `UpdaterCore`'s `commit_on_health` can wait on the hub's own
+/// service watch channels instead of an external HTTP health checker.
+struct HubHealthWatch(Arc<HealthRegistry>);
+
+impl ServiceHealthWatch for HubHealthWatch {
+    fn is_serving(&self, service: &str) -> Option<bool> {
+        self.0
+            .status(service)
+            .map(|status| status == ServingStatus::Serving)
+    }
+}
+
+/// Builds the `Arc<dyn EventTransport>` backing the rule engine when
+/// `NetworkConfig::redis_event_broker` selects a Redis broker instead of the
+/// default in-process `EventBus`.
+#[cfg(feature = "redis")]
+async fn redis_event_transport(url: String) -> Result<Arc<dyn EventTransport>, ServiceError> {
+    let transport = RedisEventTransport::connect(RedisEventTransportConfig { url })
+        .await
+        .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+    Ok(Arc::new(transport))
+}
+
+#[cfg(not(feature = "redis"))]
+async fn redis_event_transport(_url: String) -> Result<Arc<dyn EventTransport>, ServiceError> {
+    Err(ServiceError::Initialization(
+        "redis_event_broker is configured but hub-daemon was built without the redis feature"
+            .into(),
+    ))
+}
+
 fn init_tracing() {
     let subscriber = tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -56,16 +203,14 @@ fn init_tracing() {
 
 struct AutomationService {
     engine: AsyncMutex<Option<Arc<RuleEngine>>>,
-    handle: AsyncMutex<Option<JoinHandle<()>>>,
-    status: Mutex<ServiceStatus>,
+    supervisor: Supervisor,
 }
 
 impl AutomationService {
     fn new() -> Self {
         Self {
             engine: AsyncMutex::new(None),
-            handle: AsyncMutex::new(None),
-            status: Mutex::new(ServiceStatus::Stopped),
+            supervisor: Supervisor::new(),
         }
     }
 }
@@ -77,78 +222,159 @@ impl Service for AutomationService {
     }
 
     async fn start(&self, ctx: ServiceContext) -> Result<(), ServiceError> {
-        {
-            let mut status = self.status.lock().unwrap();
-            *status = ServiceStatus::Starting;
-        }
+        self.supervisor.set_status(ServiceStatus::Starting);
 
         let event_bus = ctx
             .get_extension::<EventBus>(EVENT_BUS_KEY)
             .ok_or_else(|| ServiceError::Initialization("event bus not available".into()))?;
 
-        let engine = Arc::new(RuleEngine::new(event_bus.as_ref().clone()));
-        engine
-            .register_rule(create_echo_rule("sensors.temperature"))
-            .await
-            .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+        let transport = match ctx.config().network.redis_event_broker.clone() {
+            Some(url) => redis_event_transport(url).await?,
+            None => Arc::new(event_bus.as_ref().clone()) as Arc<dyn EventTransport>,
+        };
 
-        let runner = Arc::clone(&engine);
-        let handle = tokio::spawn(async move {
-            if let Err(err) = runner.run().await {
-                warn!(error = %err, "rule engine stopped");
+        let mut engine = RuleEngine::new(transport);
+        if let (Some(registry), Some(driver)) = (
+            ctx.get_extension::<DeviceRegistry>(DEVICE_REGISTRY_KEY),
+            ctx.get_extension::<MockTemperatureDriver>(DEVICE_DRIVER_KEY),
+        ) {
+            engine = engine.with_device_control(registry, driver as Arc<dyn DeviceDriver>);
+        }
+        let engine = Arc::new(engine);
+        let configured_rules = &ctx.config().automation.rules;
+        if configured_rules.is_empty() {
+            engine
+                .register_rule(create_echo_rule("sensors.temperature"))
+                .await
+                .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+        } else {
+            for rule in configured_rules {
+                let rule: Rule = serde_json::from_value(rule.clone()).map_err(|err| {
+                    ServiceError::Initialization(format!("invalid automation rule: {err}"))
+                })?;
+                engine
+                    .register_rule(rule)
+                    .await
+                    .map_err(|err| ServiceError::Initialization(err.to_string()))?;
             }
-        });
-
-        {
-            let mut engine_slot = self.engine.lock().await;
-            *engine_slot = Some(engine);
         }
 
         {
-            let mut handle_slot = self.handle.lock().await;
-            *handle_slot = Some(handle);
+            let mut engine_slot = self.engine.lock().await;
+            *engine_slot = Some(engine.clone());
         }
 
-        {
-            let mut status = self.status.lock().unwrap();
-            *status = ServiceStatus::Running;
-        }
+        // `RuleEngine::run` only returns once its event transport closes,
+        // which isn't supposed to happen while the service is up — so any
+        // exit (clean, erroring, or panicking) is unexpected and worth
+        // restarting rather than silently leaving a dead task behind.
+        self.supervisor
+            .supervise(RestartPolicy::default(), move || {
+                let engine = engine.clone();
+                async move {
+                    engine
+                        .run()
+                        .await
+                        .map_err(|err| ServiceError::Runtime(err.to_string()))
+                }
+            })
+            .await;
 
         Ok(())
     }
 
     async fn stop(&self) -> Result<(), ServiceError> {
-        {
-            let mut status = self.status.lock().unwrap();
-            *status = ServiceStatus::Stopping;
-        }
-
-        if let Some(handle) = self.handle.lock().await.take() {
-            handle.abort();
-        }
+        self.supervisor.stop().await;
 
         {
             let mut engine_slot = self.engine.lock().await;
             *engine_slot = None;
         }
 
-        {
-            let mut status = self.status.lock().unwrap();
-            *status = ServiceStatus::Stopped;
-        }
-
         Ok(())
     }
 
     fn status(&self) -> ServiceStatus {
-        *self.status.lock().unwrap()
+        self.supervisor.status()
     }
 }
 
+/// A device registered by [`DeviceMonitorService`], paired with the driver
+/// that was built for it so [`DeviceMonitorService::stop`] can unregister
+/// it again.
+struct ManagedDevice {
+    descriptor: DeviceDescriptor,
+    driver: Arc<dyn DeviceDriver>,
+}
+
+/// Maps a [`DeviceConfig`] onto a concrete [`DeviceDriver`]. Lives in
+/// hub-daemon rather than `lokan-core`, since `lokan-core` doesn't depend
+/// on the driver crates (see [`DeviceConfig::driver`]).
+fn build_driver(entry: &DeviceConfig) -> Result<Arc<dyn DeviceDriver>, ServiceError> {
+    match entry.driver.as_str() {
+        "mock" => Ok(Arc::new(MockTemperatureDriver::default())),
+        "mqtt" => {
+            #[derive(serde::Deserialize)]
+            struct MqttParams {
+                endpoint: String,
+            }
+            let params: MqttParams =
+                serde_json::from_value(entry.params.clone()).map_err(|err| {
+                    ServiceError::Initialization(format!(
+                        "invalid mqtt driver params for device {}: {err}",
+                        entry.id
+                    ))
+                })?;
+            Ok(Arc::new(MqttDeviceDriver::connect(&params.endpoint)))
+        }
+        "modbus" => {
+            #[derive(serde::Deserialize)]
+            struct ModbusParams {
+                transport: ModbusTransport,
+                registers: Vec<RegisterMapping>,
+            }
+            let params: ModbusParams =
+                serde_json::from_value(entry.params.clone()).map_err(|err| {
+                    ServiceError::Initialization(format!(
+                        "invalid modbus driver params for device {}: {err}",
+                        entry.id
+                    ))
+                })?;
+            Ok(Arc::new(ModbusDriver::new(
+                params.transport,
+                params.registers,
+            )))
+        }
+        other => Err(ServiceError::Initialization(format!(
+            "unknown driver type `{other}` for device {}",
+            entry.id
+        ))),
+    }
+}
+
+/// Builds the demo virtual temperature sensor used when
+/// [`LokanConfig::devices`] is empty, so a deployment without a config file
+/// behaves the same as before declarative device config existed.
+fn default_devices() -> Vec<DeviceConfig> {
+    vec![DeviceConfig {
+        id: "virtual.temp.sensor".into(),
+        manufacturer: "Lokan Labs".into(),
+        product: "Virtual Temperature Sensor".into(),
+        capabilities: vec!["temperature".into()],
+        driver: "mock".into(),
+        params: serde_json::Value::Null,
+    }]
+}
+
+/// Unlike [`AutomationService`], this doesn't wrap its work in a
+/// [`lokan_core::Supervisor`]: both [`DevicePoller`] and
+/// [`ConnectivitySupervisor`] already own their retry loops (each poll task
+/// backs off and keeps polling after a failed poll; the connectivity
+/// supervisor retries connecting with backoff), so there's no bare
+/// `tokio::spawn` worker here that stops at its first failure.
 struct DeviceMonitorService {
-    driver: Arc<MockTemperatureDriver>,
-    handle: AsyncMutex<Option<JoinHandle<()>>>,
-    descriptor: AsyncMutex<Option<DeviceDescriptor>>,
+    devices: AsyncMutex<Vec<ManagedDevice>>,
+    pollers: AsyncMutex<Vec<DevicePoller>>,
     registry: AsyncMutex<Option<Arc<DeviceRegistry>>>,
     supervisor: AsyncMutex<Option<ConnectivitySupervisor<MqttConnector>>>,
     status: Mutex<ServiceStatus>,
@@ -157,9 +383,8 @@ struct DeviceMonitorService {
 impl DeviceMonitorService {
     fn new() -> Self {
         Self {
-            driver: Arc::new(MockTemperatureDriver::default()),
-            handle: AsyncMutex::new(None),
-            descriptor: AsyncMutex::new(None),
+            devices: AsyncMutex::new(Vec::new()),
+            pollers: AsyncMutex::new(Vec::new()),
             registry: AsyncMutex::new(None),
             supervisor: AsyncMutex::new(None),
             status: Mutex::new(ServiceStatus::Stopped),
@@ -187,25 +412,48 @@ impl Service for DeviceMonitorService {
             .get_extension::<DeviceRegistry>(DEVICE_REGISTRY_KEY)
             .ok_or_else(|| ServiceError::Initialization("device registry not available".into()))?;
 
-        let descriptor = DeviceDescriptor {
-            id: "virtual.temp.sensor".into(),
-            manufacturer: "Lokan Labs".into(),
-            product: "Virtual Temperature Sensor".into(),
-            capabilities: vec!["temperature".into()],
+        let configured_devices = ctx.config().devices.clone();
+        let configured_devices = if configured_devices.is_empty() {
+            default_devices()
+        } else {
+            configured_devices
         };
 
-        registry
-            .register_device(descriptor.clone(), self.driver.as_ref())
-            .await
-            .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+        let mut managed_devices = Vec::with_capacity(configured_devices.len());
+        for entry in &configured_devices {
+            let driver = build_driver(entry)?;
+            let descriptor = DeviceDescriptor {
+                id: entry.id.clone(),
+                manufacturer: entry.manufacturer.clone(),
+                product: entry.product.clone(),
+                capabilities: entry.capabilities.clone(),
+            };
+            registry
+                .register_device(descriptor.clone(), driver.as_ref())
+                .await
+                .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+            managed_devices.push(ManagedDevice { descriptor, driver });
+        }
 
+        let hub_status_topic = format!("lokan/{}/status", ctx.config().network.hostname);
+        let mqtt_endpoint = ctx
+            .config()
+            .network
+            .mqtt_broker
+            .clone()
+            .unwrap_or_else(|| "mqtt://localhost:1883".into());
         let connection = ConnectivitySupervisor::new(
             MqttConnector,
             ConnectionParams {
-                endpoint: "mqtt://localhost:1883".into(),
+                endpoint: mqtt_endpoint,
                 username: None,
                 password: None,
                 keep_alive_secs: Some(30),
+                last_will: Some(LastWill {
+                    topic: hub_status_topic,
+                    payload: br#"{"status":"offline"}"#.to_vec(),
+                    retain: true,
+                }),
             },
         );
         connection.ensure_connected().await;
@@ -220,41 +468,57 @@ impl Service for DeviceMonitorService {
             *registry_slot = Some(registry.clone());
         }
 
-        {
-            let mut descriptor_slot = self.descriptor.lock().await;
-            *descriptor_slot = Some(descriptor.clone());
-        }
+        let automation = &ctx.config().automation;
+        let poller_config = PollerConfig {
+            interval: Duration::from_secs(automation.device_poll_interval_secs),
+            concurrency: automation.device_poll_concurrency,
+            max_consecutive_failures: automation.device_poll_max_failures,
+        };
 
-        let driver = self.driver.clone();
-        let registry_clone = registry.clone();
-        let descriptor_clone = descriptor.clone();
         let event_bus_clone = event_bus.as_ref().clone();
-
-        let handle = tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs(5));
-            loop {
-                interval.tick().await;
-                match driver.poll(&descriptor_clone).await {
-                    Ok(state) => {
-                        let _ = registry_clone
-                            .update_state(&descriptor_clone.id, state.clone())
-                            .await;
-                        let payload = json!({
-                            "device_id": descriptor_clone.id,
-                            "temperature_c": state.properties["temperature_c"].clone(),
-                        });
-                        event_bus_clone.publish(Event::new("sensors.temperature", payload));
-                    }
-                    Err(err) => {
-                        warn!(device_id = %descriptor_clone.id, error = %err, "failed to poll device");
-                    }
+        let on_update: Arc<dyn Fn(&DeviceDescriptor, &DeviceState) + Send + Sync> =
+            Arc::new(move |descriptor, state| {
+                if let Some(temperature_c) = state.properties.get("temperature_c") {
+                    event_bus_clone.publish(Event::new(
+                        "sensors.temperature",
+                        json!({
+                            "device_id": descriptor.id,
+                            "temperature_c": temperature_c.clone(),
+                        }),
+                    ));
                 }
-            }
-        });
+                event_bus_clone.publish(Event::new(
+                    format!("devices.{}.state", descriptor.id),
+                    json!({
+                        "device_id": descriptor.id,
+                        "properties": state.properties.clone(),
+                    }),
+                ));
+            });
+
+        let mut pollers = Vec::with_capacity(managed_devices.len());
+        for device in &managed_devices {
+            pollers.push(
+                DevicePoller::spawn_for(
+                    registry.as_ref().clone(),
+                    device.driver.clone(),
+                    event_bus.as_ref().clone(),
+                    poller_config,
+                    on_update.clone(),
+                    vec![device.descriptor.clone()],
+                )
+                .await,
+            );
+        }
+
+        {
+            let mut devices_slot = self.devices.lock().await;
+            *devices_slot = managed_devices;
+        }
 
         {
-            let mut handle_slot = self.handle.lock().await;
-            *handle_slot = Some(handle);
+            let mut pollers_slot = self.pollers.lock().await;
+            *pollers_slot = pollers;
         }
 
         {
@@ -271,8 +535,8 @@ impl Service for DeviceMonitorService {
             *status = ServiceStatus::Stopping;
         }
 
-        if let Some(handle) = self.handle.lock().await.take() {
-            handle.abort();
+        for poller in self.pollers.lock().await.drain(..) {
+            poller.shutdown().await;
         }
 
         if let Some(connection) = self.supervisor.lock().await.take() {
@@ -280,9 +544,9 @@ impl Service for DeviceMonitorService {
         }
 
         if let Some(registry) = self.registry.lock().await.take() {
-            if let Some(descriptor) = self.descriptor.lock().await.take() {
+            for device in self.devices.lock().await.drain(..) {
                 registry
-                    .unregister_device(&descriptor.id, self.driver.as_ref())
+                    .unregister_device(&device.descriptor.id, device.driver.as_ref())
                     .await
                     .map_err(|err| ServiceError::Shutdown(err.to_string()))?;
             }
@@ -339,4 +603,16 @@ impl DeviceDriver for MockTemperatureDriver {
         info!(device_id = %descriptor.id, "mock driver shutdown");
         Ok(())
     }
+
+    async fn send_command(
+        &self,
+        descriptor: &DeviceDescriptor,
+        command: serde_json::Value,
+    ) -> Result<(), DeviceError> {
+        if let Some(target) = command.get("set_temperature_c").and_then(|v| v.as_f64()) {
+            *self.temperature.lock().await = target;
+        }
+        info!(device_id = %descriptor.id, %command, "mock driver received command");
+        Ok(())
+    }
 }
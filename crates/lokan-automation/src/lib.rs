@@ -1,11 +1,13 @@
 use std::{collections::HashMap, sync::Arc};
 
-use lokan_event::{Event, EventBus};
+use futures::StreamExt;
+use lokan_device::{DeviceDriver, DeviceRegistry};
+use lokan_event::{Event, EventTransport};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MatchOperator {
@@ -29,6 +31,21 @@ pub enum ActionKind {
         topic: String,
         payload_template: serde_json::Value,
     },
+    /// Fires an HTTP request. `url`, `headers`, and `body_template` are all
+    /// rendered through [`render_template`] before the request is sent.
+    HttpWebhook {
+        url: String,
+        method: String,
+        headers: HashMap<String, String>,
+        body_template: serde_json::Value,
+    },
+    /// Resolves `device_id` via the `DeviceRegistry` configured on
+    /// [`RuleEngine::with_device_control`] and invokes its `DeviceDriver`
+    /// with the rendered `command_template`.
+    CallDevice {
+        device_id: String,
+        command_template: serde_json::Value,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,17 +123,32 @@ pub enum RuleError {
 #[derive(Clone)]
 pub struct RuleEngine {
     rules: Arc<RwLock<HashMap<String, Rule>>>,
-    event_bus: EventBus,
+    event_transport: Arc<dyn EventTransport>,
+    http_client: reqwest::Client,
+    device_control: Option<(Arc<DeviceRegistry>, Arc<dyn DeviceDriver>)>,
 }
 
 impl RuleEngine {
-    pub fn new(event_bus: EventBus) -> Self {
+    pub fn new(event_transport: Arc<dyn EventTransport>) -> Self {
         Self {
             rules: Arc::new(RwLock::new(HashMap::new())),
-            event_bus,
+            event_transport,
+            http_client: reqwest::Client::new(),
+            device_control: None,
         }
     }
 
+    /// Attaches the registry/driver pair `CallDevice` actions dispatch
+    /// through. Without this, `CallDevice` actions are logged and skipped.
+    pub fn with_device_control(
+        mut self,
+        registry: Arc<DeviceRegistry>,
+        driver: Arc<dyn DeviceDriver>,
+    ) -> Self {
+        self.device_control = Some((registry, driver));
+        self
+    }
+
     pub async fn register_rule(&self, rule: Rule) -> Result<(), RuleError> {
         let mut rules = self.rules.write().await;
         if rules.contains_key(&rule.id) {
@@ -154,20 +186,100 @@ impl RuleEngine {
                     payload_template,
                 } => {
                     let payload = render_template(payload_template.clone(), event);
-                    self.event_bus.publish(Event::new(topic.clone(), payload));
+                    if let Err(error) = self
+                        .event_transport
+                        .publish(Event::new(topic.clone(), payload))
+                        .await
+                    {
+                        warn!(%error, rule_id = %rule.id, topic, "failed to publish rule action event");
+                    }
+                }
+                ActionKind::HttpWebhook {
+                    url,
+                    method,
+                    headers,
+                    body_template,
+                } => {
+                    let rendered_url = render_string_template(url, event);
+                    let body = render_template(body_template.clone(), event);
+                    let method = method
+                        .parse::<reqwest::Method>()
+                        .unwrap_or(reqwest::Method::POST);
+
+                    let mut request = self.http_client.request(method, &rendered_url).json(&body);
+                    for (name, value) in headers {
+                        request = request.header(name, render_string_template(value, event));
+                    }
+
+                    if let Err(error) = request.send().await {
+                        warn!(%error, rule_id = %rule.id, url = %rendered_url, "failed to call webhook action");
+                        self.publish_action_failure(rule, "http_webhook", &error.to_string())
+                            .await;
+                    }
+                }
+                ActionKind::CallDevice {
+                    device_id,
+                    command_template,
+                } => {
+                    let command = render_template(command_template.clone(), event);
+                    match &self.device_control {
+                        Some((registry, driver)) => {
+                            let descriptor = registry
+                                .list_devices()
+                                .await
+                                .into_iter()
+                                .find(|descriptor| &descriptor.id == device_id);
+                            match descriptor {
+                                Some(descriptor) => {
+                                    if let Err(error) =
+                                        driver.send_command(&descriptor, command).await
+                                    {
+                                        warn!(%error, rule_id = %rule.id, device_id, "failed to dispatch device command action");
+                                        self.publish_action_failure(
+                                            rule,
+                                            "call_device",
+                                            &error.to_string(),
+                                        )
+                                        .await;
+                                    }
+                                }
+                                None => {
+                                    warn!(rule_id = %rule.id, device_id, "call_device action references an unknown device");
+                                }
+                            }
+                        }
+                        None => {
+                            warn!(rule_id = %rule.id, device_id, "call_device action configured but no device registry/driver is attached to this rule engine");
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// Publishes a `rule.action.failed` event so a failing webhook or
+    /// device call is observable without reading logs.
+    async fn publish_action_failure(&self, rule: &Rule, action_kind: &str, error: &str) {
+        let payload = json!({
+            "rule_id": rule.id,
+            "action": action_kind,
+            "error": error,
+        });
+        if let Err(error) = self
+            .event_transport
+            .publish(Event::new("rule.action.failed", payload))
+            .await
+        {
+            warn!(%error, rule_id = %rule.id, "failed to publish rule action failure event");
+        }
+    }
+
     pub async fn run(self: Arc<Self>) -> Result<(), RuleError> {
-        let mut rx = self.event_bus.subscribe();
-        loop {
-            match rx.recv().await {
-                Ok(event) => self.process_event(&event).await,
-                Err(_) => return Err(RuleError::BusClosed),
-            }
+        let mut events = self.event_transport.subscribe().await;
+        while let Some(event) = events.next().await {
+            self.process_event(&event).await;
         }
+        Err(RuleError::BusClosed)
     }
 
     pub async fn list_rules(&self) -> Vec<Rule> {
@@ -179,10 +291,7 @@ impl RuleEngine {
 fn render_template(mut template: serde_json::Value, event: &Event) -> serde_json::Value {
     match &mut template {
         serde_json::Value::String(value) => {
-            let rendered = value.replace("{{event.topic}}", &event.topic);
-            let payload_str = event.payload.to_string();
-            let rendered = rendered.replace("{{event.payload}}", &payload_str);
-            serde_json::Value::String(rendered)
+            serde_json::Value::String(render_string_template(value, event))
         }
         serde_json::Value::Object(map) => {
             for value in map.values_mut() {
@@ -202,6 +311,58 @@ fn render_template(mut template: serde_json::Value, event: &Event) -> serde_json
     }
 }
 
+/// Renders every `{{token}}` placeholder in `value`, leaving unrecognized
+/// tokens untouched. Supports `event.topic`, `event.payload`, and
+/// `event.payload.<json-pointer>` (e.g. `{{event.payload./temperature_c}}`).
+fn render_string_template(value: &str, event: &Event) -> String {
+    let mut rendered = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        match after_open.find("}}") {
+            Some(end) => {
+                let token = after_open[..end].trim();
+                match resolve_token(token, event) {
+                    Some(resolved) => rendered.push_str(&resolved),
+                    None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+                break;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Resolves a single `{{...}}` token body against `event`. Returns `None`
+/// for unknown tokens so the caller can leave them in place verbatim.
+fn resolve_token(token: &str, event: &Event) -> Option<String> {
+    if token == "event.topic" {
+        return Some(event.topic.clone());
+    }
+    if token == "event.payload" {
+        return Some(event.payload.to_string());
+    }
+    if let Some(pointer) = token.strip_prefix("event.payload.") {
+        let pointer = if pointer.starts_with('/') {
+            pointer.to_string()
+        } else {
+            format!("/{pointer}")
+        };
+        return event.payload.pointer(&pointer).map(|value| match value {
+            serde_json::Value::String(string) => string.clone(),
+            other => other.to_string(),
+        });
+    }
+    None
+}
+
 /// Helper to construct a simple rule that echoes events.
 pub fn create_echo_rule(topic: &str) -> Rule {
     Rule {
@@ -14,6 +14,11 @@ pub struct LokanConfig {
     pub automation: AutomationConfig,
     /// Telemetry and tracing configuration.
     pub telemetry: TelemetryConfig,
+    /// Devices to provision at startup. Empty by default, in which case
+    /// `hub-daemon` falls back to its built-in demo sensor rather than
+    /// starting with nothing registered.
+    #[serde(default)]
+    pub devices: Vec<DeviceConfig>,
 }
 
 impl Default for LokanConfig {
@@ -23,10 +28,31 @@ impl Default for LokanConfig {
             network: NetworkConfig::default(),
             automation: AutomationConfig::default(),
             telemetry: TelemetryConfig::default(),
+            devices: Vec::new(),
         }
     }
 }
 
+/// One device to provision at hub startup, as declared in a hub config
+/// file. `hub-daemon` maps `driver` and `params` onto a concrete
+/// [`lokan_device`]-style driver, since `lokan-core` doesn't depend on the
+/// driver implementations — this is just the declarative shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    pub id: String,
+    pub manufacturer: String,
+    pub product: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Which driver backs this device, e.g. `"mock"`, `"mqtt"`, `"modbus"`.
+    pub driver: String,
+    /// Driver-specific parameters, interpreted by whichever driver factory
+    /// `driver` selects (e.g. an MQTT endpoint, or a Modbus transport and
+    /// register map).
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
 /// Error type for configuration related failures.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -63,6 +89,14 @@ pub struct NetworkConfig {
     pub enable_matter: bool,
     /// Whether the built-in Zigbee stack should be enabled.
     pub enable_zigbee: bool,
+    /// Optional Redis URL selecting a `RedisEventTransport` for rule-engine
+    /// event distribution, so rules fan out across every hub sharing the
+    /// broker. When unset, the in-process `EventBus` transport is used.
+    pub redis_event_broker: Option<String>,
+    /// Port the device-registry HTTP server binds to when run as a
+    /// [`crate::Service`] under a [`crate::ServiceManager`]. Falls back to
+    /// the service's own env-var/default resolution when unset.
+    pub device_registry_port: Option<u16>,
 }
 
 impl Default for NetworkConfig {
@@ -72,6 +106,8 @@ impl Default for NetworkConfig {
             mqtt_broker: None,
             enable_matter: true,
             enable_zigbee: false,
+            redis_event_broker: None,
+            device_registry_port: None,
         }
     }
 }
@@ -83,6 +119,20 @@ pub struct AutomationConfig {
     pub max_rules: usize,
     /// Whether rules are enabled globally.
     pub enabled: bool,
+    /// How often, in seconds, each registered device is polled for fresh
+    /// state by the background device poller.
+    pub device_poll_interval_secs: u64,
+    /// Maximum number of devices polled concurrently.
+    pub device_poll_concurrency: usize,
+    /// Consecutive poll failures before a device is marked offline.
+    pub device_poll_max_failures: u32,
+    /// Automation rules to register at startup, each a serialized
+    /// `lokan_automation::Rule`. Kept untyped here for the same reason as
+    /// [`DeviceConfig::params`] — `lokan-core` doesn't depend on
+    /// `lokan-automation`. Empty by default, in which case `hub-daemon`
+    /// falls back to its built-in demo rule rather than registering none.
+    #[serde(default)]
+    pub rules: Vec<serde_json::Value>,
 }
 
 impl Default for AutomationConfig {
@@ -90,6 +140,10 @@ impl Default for AutomationConfig {
         Self {
             max_rules: 1024,
             enabled: true,
+            device_poll_interval_secs: 30,
+            device_poll_concurrency: 8,
+            device_poll_max_failures: 3,
+            rules: Vec::new(),
         }
     }
 }
@@ -0,0 +1,129 @@
+//! Push-based service health, mirroring gRPC's three-level serving status
+//! instead of the point-in-time, pull-only snapshot [`Service::status`]
+//! gives callers. [`ServiceManager`](crate::ServiceManager) keeps one
+//! [`tokio::sync::watch`] channel per registered service here, updated at
+//! the same transitions it already announces over the message bus, so
+//! anything that needs to *wait* for a service to come back up (an health
+//! probe, `updater`'s `commit_on_health`) can do so without polling.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::service::ServiceStatus;
+
+/// Three-level serving status for a single service, or the hub overall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServingStatus {
+    /// No transition has been observed yet (e.g. before `start_all` runs).
+    Unknown,
+    /// Known, but not ready to take traffic.
+    NotServing,
+    /// Up and ready to take traffic.
+    Serving,
+}
+
+impl From<ServiceStatus> for ServingStatus {
+    /// Only [`ServiceStatus::Running`] counts as serving; every other
+    /// status (including `Starting`/`Stopping`, which are mid-transition)
+    /// is treated as not serving.
+    fn from(status: ServiceStatus) -> Self {
+        match status {
+            ServiceStatus::Running => ServingStatus::Serving,
+            _ => ServingStatus::NotServing,
+        }
+    }
+}
+
+/// Owns a [`watch::Sender<ServingStatus>`] per service name, created lazily
+/// on first use.
+#[derive(Default)]
+pub struct HealthRegistry {
+    channels: RwLock<HashMap<String, watch::Sender<ServingStatus>>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates `service`'s status, creating its channel first if this is
+    /// the first time it's been seen.
+    pub fn set(&self, service: &str, status: ServingStatus) {
+        if let Some(tx) = self.channels.read().expect("lock poisoned").get(service) {
+            let _ = tx.send(status);
+            return;
+        }
+
+        let mut channels = self.channels.write().expect("lock poisoned");
+        match channels.get(service) {
+            Some(tx) => {
+                let _ = tx.send(status);
+            }
+            None => {
+                let (tx, _rx) = watch::channel(status);
+                channels.insert(service.to_string(), tx);
+            }
+        }
+    }
+
+    /// Subscribes to `service`'s status, creating its channel (seeded at
+    /// [`ServingStatus::Unknown`]) first if this is the first time it's
+    /// been seen.
+    pub fn watch(&self, service: &str) -> watch::Receiver<ServingStatus> {
+        if let Some(tx) = self.channels.read().expect("lock poisoned").get(service) {
+            return tx.subscribe();
+        }
+
+        let mut channels = self.channels.write().expect("lock poisoned");
+        channels
+            .entry(service.to_string())
+            .or_insert_with(|| watch::channel(ServingStatus::Unknown).0)
+            .subscribe()
+    }
+
+    /// `service`'s last known status, or `None` if it's never been seen.
+    pub fn status(&self, service: &str) -> Option<ServingStatus> {
+        self.channels
+            .read()
+            .expect("lock poisoned")
+            .get(service)
+            .map(|tx| *tx.borrow())
+    }
+
+    /// Every tracked service's last known status.
+    pub fn snapshot(&self) -> HashMap<String, ServingStatus> {
+        self.channels
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .map(|(name, tx)| (name.clone(), *tx.borrow()))
+            .collect()
+    }
+
+    /// Aggregate hub status: [`ServingStatus::NotServing`] if any tracked
+    /// service is, [`ServingStatus::Serving`] if every tracked service is,
+    /// [`ServingStatus::Unknown`] otherwise (including when nothing is
+    /// tracked yet).
+    pub fn overall(&self) -> ServingStatus {
+        let snapshot = self.snapshot();
+        if snapshot.is_empty() {
+            return ServingStatus::Unknown;
+        }
+        if snapshot
+            .values()
+            .any(|status| *status == ServingStatus::NotServing)
+        {
+            ServingStatus::NotServing
+        } else if snapshot
+            .values()
+            .all(|status| *status == ServingStatus::Serving)
+        {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::Unknown
+        }
+    }
+}
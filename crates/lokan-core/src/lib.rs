@@ -1,7 +1,17 @@
 pub mod config;
+pub mod health;
 pub mod runtime;
 pub mod service;
+pub mod supervisor;
+pub mod task_tracker;
 
-pub use config::LokanConfig;
+pub use config::{DeviceConfig, LokanConfig};
+pub use health::{HealthRegistry, ServingStatus};
 pub use runtime::ServiceManager;
-pub use service::{Service, ServiceContext, ServiceError, ServiceStatus};
+pub use service::{
+    publish_status_event, service_status_subject, subscribe_status_events, Service, ServiceContext,
+    ServiceError, ServiceHealth, ServiceStatus, ServiceStatusEvent, MESSAGE_BUS_EXTENSION_KEY,
+    TASK_TRACKER_EXTENSION_KEY,
+};
+pub use supervisor::{RestartPolicy, Supervisor};
+pub use task_tracker::TaskTracker;
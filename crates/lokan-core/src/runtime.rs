@@ -1,9 +1,21 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration, time::SystemTime};
 
+use common_msgbus::MessageBus;
+use futures::{Stream, StreamExt};
 use tokio::sync::RwLock;
+use tokio_stream::wrappers::IntervalStream;
 use tracing::{info, warn};
 
-use crate::{service::ServiceContext, LokanConfig, Service, ServiceError};
+use crate::{
+    health::{HealthRegistry, ServingStatus},
+    service::{publish_status_event, ServiceContext, ServiceStatusEvent},
+    LokanConfig, Service, ServiceError, ServiceHealth, ServiceStatus, TaskTracker,
+    MESSAGE_BUS_EXTENSION_KEY, TASK_TRACKER_EXTENSION_KEY,
+};
+
+/// How long [`ServiceManager::stop_all`] waits for tasks spawned through its
+/// shared [`TaskTracker`] to finish on their own before aborting the rest.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Central orchestrator for services that make up the Lokan Home Hub runtime.
 pub struct ServiceManager {
@@ -11,6 +23,9 @@ pub struct ServiceManager {
     extensions: HashMap<String, Arc<dyn std::any::Any + Send + Sync>>,
     services: Vec<Arc<dyn Service>>,
     started: Arc<RwLock<bool>>,
+    task_tracker: Arc<TaskTracker>,
+    shutdown_timeout: Duration,
+    health: Arc<HealthRegistry>,
 }
 
 impl ServiceManager {
@@ -20,9 +35,36 @@ impl ServiceManager {
             extensions: HashMap::new(),
             services: Vec::new(),
             started: Arc::new(RwLock::new(false)),
+            task_tracker: Arc::new(TaskTracker::new()),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            health: Arc::new(HealthRegistry::new()),
         }
     }
 
+    /// Overrides how long [`Self::stop_all`] waits for tracked tasks to
+    /// finish cooperatively before aborting them. Defaults to
+    /// [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// The shared [`TaskTracker`] every [`ServiceContext`] built by
+    /// [`Self::start_all`] exposes under [`TASK_TRACKER_EXTENSION_KEY`].
+    /// Exposed directly too, for tasks spawned from outside a [`Service`]
+    /// impl (e.g. `main`'s own top-level workers).
+    pub fn task_tracker(&self) -> Arc<TaskTracker> {
+        self.task_tracker.clone()
+    }
+
+    /// The shared [`HealthRegistry`] every registered service's
+    /// [`ServiceStatus`] transitions are mirrored into, as a three-level
+    /// [`ServingStatus`] an HTTP probe or `updater`'s `commit_on_health` can
+    /// wait on instead of polling [`Self::health_snapshot`].
+    pub fn health_registry(&self) -> Arc<HealthRegistry> {
+        self.health.clone()
+    }
+
     /// Registers an extension that should be visible to all services.
     pub fn with_extension(
         mut self,
@@ -33,11 +75,53 @@ impl ServiceManager {
         self
     }
 
+    /// Registers a shared message bus under [`MESSAGE_BUS_EXTENSION_KEY`] so
+    /// services can reach it via [`ServiceContext::message_bus`], and so the
+    /// runtime itself can publish [`ServiceStatusEvent`]s during start/stop.
+    pub fn with_message_bus(self, bus: Arc<dyn MessageBus>) -> Self {
+        self.with_extension(MESSAGE_BUS_EXTENSION_KEY, Arc::new(bus))
+    }
+
     /// Register a service instance with the runtime.
     pub fn register_service(&mut self, service: Arc<dyn Service>) {
+        self.health.set(service.name(), ServingStatus::Unknown);
         self.services.push(service);
     }
 
+    fn message_bus(&self) -> Option<Arc<dyn MessageBus>> {
+        self.extensions
+            .get(MESSAGE_BUS_EXTENSION_KEY)
+            .and_then(|value| value.clone().downcast::<Arc<dyn MessageBus>>().ok())
+            .map(|bus| (*bus).clone())
+    }
+
+    /// Publishes a [`ServiceStatusEvent`] if a message bus extension is
+    /// configured. Publish failures are logged, not propagated: status
+    /// events are observability, not part of the start/stop critical path.
+    async fn announce_transition(
+        &self,
+        service: &str,
+        previous: ServiceStatus,
+        current: ServiceStatus,
+        error: Option<String>,
+    ) {
+        self.health.set(service, ServingStatus::from(current));
+
+        let Some(bus) = self.message_bus() else {
+            return;
+        };
+        let event = ServiceStatusEvent {
+            service: service.to_string(),
+            previous,
+            current,
+            timestamp: SystemTime::now(),
+            error,
+        };
+        if let Err(err) = publish_status_event(bus.as_ref(), &event).await {
+            warn!(service, error = %err, "failed to publish service status event");
+        }
+    }
+
     /// Start all registered services sequentially.
     pub async fn start_all(&self) -> Result<(), ServiceError> {
         {
@@ -52,27 +136,87 @@ impl ServiceManager {
         for (key, value) in &self.extensions {
             ctx = ctx.with_extension(key.clone(), value.clone());
         }
+        ctx = ctx.with_extension(TASK_TRACKER_EXTENSION_KEY, self.task_tracker.clone());
 
         for service in &self.services {
             info!(service = service.name(), "starting service");
-            if let Err(err) = service.start(ctx.clone()).await {
-                warn!(service = service.name(), error = %err, "service failed to start");
-                return Err(err);
+            self.announce_transition(
+                service.name(),
+                ServiceStatus::Stopped,
+                ServiceStatus::Starting,
+                None,
+            )
+            .await;
+
+            match service.start(ctx.clone()).await {
+                Ok(()) => {
+                    self.announce_transition(
+                        service.name(),
+                        ServiceStatus::Starting,
+                        service.status(),
+                        None,
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    warn!(service = service.name(), error = %err, "service failed to start");
+                    self.announce_transition(
+                        service.name(),
+                        ServiceStatus::Starting,
+                        ServiceStatus::Failed,
+                        Some(err.to_string()),
+                    )
+                    .await;
+                    return Err(err);
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Stop all services in reverse order.
+    /// Stop all services in reverse order, then give tasks spawned through
+    /// the shared [`TaskTracker`] up to `shutdown_timeout` (see
+    /// [`Self::with_shutdown_timeout`]) to finish cooperatively before
+    /// aborting whatever's left.
     pub async fn stop_all(&self) {
+        self.task_tracker.cancel();
+
         for service in self.services.iter().rev() {
             info!(service = service.name(), "stopping service");
-            if let Err(err) = service.stop().await {
-                warn!(service = service.name(), error = %err, "service failed to stop cleanly");
+            self.announce_transition(
+                service.name(),
+                ServiceStatus::Running,
+                ServiceStatus::Stopping,
+                None,
+            )
+            .await;
+
+            match service.stop().await {
+                Ok(()) => {
+                    self.announce_transition(
+                        service.name(),
+                        ServiceStatus::Stopping,
+                        service.status(),
+                        None,
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    warn!(service = service.name(), error = %err, "service failed to stop cleanly");
+                    self.announce_transition(
+                        service.name(),
+                        ServiceStatus::Stopping,
+                        ServiceStatus::Failed,
+                        Some(err.to_string()),
+                    )
+                    .await;
+                }
             }
         }
 
+        self.task_tracker.join(self.shutdown_timeout).await;
+
         let mut started = self.started.write().await;
         *started = false;
     }
@@ -80,4 +224,21 @@ impl ServiceManager {
     pub fn config(&self) -> Arc<LokanConfig> {
         self.config.clone()
     }
+
+    /// Collects a [`ServiceHealth`] snapshot from every registered service,
+    /// in registration order.
+    pub async fn health_snapshot(&self) -> Vec<ServiceHealth> {
+        let mut snapshot = Vec::with_capacity(self.services.len());
+        for service in &self.services {
+            snapshot.push(service.health().await);
+        }
+        snapshot
+    }
+
+    /// Polls [`Self::health_snapshot`] on `interval` and yields the combined
+    /// result each tick, so an HTTP layer can expose it as a `/status` SSE
+    /// stream without each service owning its own isolated health endpoint.
+    pub fn health_stream(&self, interval: Duration) -> impl Stream<Item = Vec<ServiceHealth>> + '_ {
+        IntervalStream::new(tokio::time::interval(interval)).then(move |_| self.health_snapshot())
+    }
 }
@@ -1,9 +1,19 @@
-use std::{any::Any, collections::HashMap, sync::Arc};
+use std::{any::Any, collections::HashMap, sync::Arc, time::SystemTime};
 
 use async_trait::async_trait;
+use common_msgbus::{MessageBus, MsgBusError};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::LokanConfig;
+use crate::{LokanConfig, TaskTracker};
+
+/// Well-known [`ServiceContext`] extension key under which the runtime's
+/// shared [`MessageBus`] is registered, when one is configured.
+pub const MESSAGE_BUS_EXTENSION_KEY: &str = "message_bus";
+
+/// Well-known [`ServiceContext`] extension key under which
+/// [`crate::ServiceManager`]'s shared [`TaskTracker`] is registered.
+pub const TASK_TRACKER_EXTENSION_KEY: &str = "task_tracker";
 
 /// Immutable metadata shared with services when they are started.
 #[derive(Clone, Default)]
@@ -49,10 +59,26 @@ impl ServiceContext {
     ) -> Self {
         self.with_extension(key, value)
     }
+
+    /// Retrieve the shared message bus, if the runtime was configured with
+    /// one under [`MESSAGE_BUS_EXTENSION_KEY`].
+    pub fn message_bus(&self) -> Option<Arc<dyn MessageBus>> {
+        self.get_extension::<Arc<dyn MessageBus>>(MESSAGE_BUS_EXTENSION_KEY)
+            .map(|bus| (*bus).clone())
+    }
+
+    /// Retrieve the runtime's shared [`TaskTracker`], registered under
+    /// [`TASK_TRACKER_EXTENSION_KEY`] by every [`crate::ServiceManager`].
+    /// Services should spawn their background workers through this instead
+    /// of bare `tokio::spawn`, so [`crate::ServiceManager::stop_all`] can
+    /// shut them down cooperatively.
+    pub fn task_tracker(&self) -> Option<Arc<TaskTracker>> {
+        self.get_extension::<TaskTracker>(TASK_TRACKER_EXTENSION_KEY)
+    }
 }
 
 /// Runtime state of an individual service.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ServiceStatus {
     Stopped,
     Starting,
@@ -61,6 +87,94 @@ pub enum ServiceStatus {
     Failed,
 }
 
+impl ServiceStatus {
+    /// Encodes the status into a single byte, for
+    /// [`crate::supervisor::Supervisor`]'s `Arc<AtomicU8>` status cell.
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            ServiceStatus::Stopped => 0,
+            ServiceStatus::Starting => 1,
+            ServiceStatus::Running => 2,
+            ServiceStatus::Stopping => 3,
+            ServiceStatus::Failed => 4,
+        }
+    }
+
+    /// Inverse of [`Self::to_u8`]. Any unrecognized byte decodes to
+    /// `Failed`: only [`Self::to_u8`] ever writes the cell, so such a byte
+    /// indicates a bug rather than a valid status.
+    pub(crate) fn from_u8(value: u8) -> Self {
+        match value {
+            0 => ServiceStatus::Stopped,
+            1 => ServiceStatus::Starting,
+            2 => ServiceStatus::Running,
+            3 => ServiceStatus::Stopping,
+            _ => ServiceStatus::Failed,
+        }
+    }
+}
+
+/// Structured notification for a [`ServiceStatus`] transition, published to
+/// [`service_status_subject`] so a supervisor service can react to failures
+/// (restart, alert, ...) without polling [`Service::status`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatusEvent {
+    /// Name of the service that transitioned, per [`Service::name`].
+    pub service: String,
+    /// Status the service was in before this transition.
+    pub previous: ServiceStatus,
+    /// Status the service is in after this transition.
+    pub current: ServiceStatus,
+    /// When the transition occurred.
+    pub timestamp: SystemTime,
+    /// Failure detail, populated when `current` is [`ServiceStatus::Failed`].
+    pub error: Option<String>,
+}
+
+/// Subject a service's status transitions are published to:
+/// `lokan.service.<name>.status`.
+pub fn service_status_subject(service: &str) -> String {
+    format!("lokan.service.{service}.status")
+}
+
+/// Serializes `event` and publishes it to [`service_status_subject`] for its
+/// service.
+pub async fn publish_status_event(
+    bus: &dyn MessageBus,
+    event: &ServiceStatusEvent,
+) -> Result<(), MsgBusError> {
+    let subject = service_status_subject(&event.service);
+    let payload = serde_json::to_vec(event).map_err(|err| MsgBusError::Publish(err.to_string()))?;
+    bus.publish(&subject, &payload).await
+}
+
+/// Subscribes to `service`'s status transitions. Callers decode each
+/// [`BusMessage`](common_msgbus::BusMessage) payload with
+/// [`serde_json::from_slice`] into a [`ServiceStatusEvent`].
+pub async fn subscribe_status_events(
+    bus: &dyn MessageBus,
+    service: &str,
+) -> Result<common_msgbus::Subscription, MsgBusError> {
+    bus.subscribe(&service_status_subject(service)).await
+}
+
+/// Point-in-time health snapshot for a single service, as returned by
+/// [`Service::health`] and aggregated by [`crate::runtime::ServiceManager`]
+/// into a combined status stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    /// Name of the service, per [`Service::name`].
+    pub service: String,
+    /// Coarse lifecycle status, per [`Service::status`].
+    pub status: ServiceStatus,
+    /// Free-form, service-specific detail (e.g. DB connectivity, subscriber
+    /// counts, last event sequence number). Empty object when a service
+    /// hasn't overridden the default [`Service::health`] implementation.
+    pub detail: serde_json::Value,
+    /// When this snapshot was taken.
+    pub observed_at: SystemTime,
+}
+
 /// Error type returned by services at runtime.
 #[derive(Debug, Error)]
 pub enum ServiceError {
@@ -86,4 +200,18 @@ pub trait Service: Send + Sync {
 
     /// Current status of the service.
     fn status(&self) -> ServiceStatus;
+
+    /// Point-in-time health snapshot, aggregated by [`crate::runtime::ServiceManager`]
+    /// into its combined status stream. Defaults to [`Self::status`] with an
+    /// empty detail payload; services with something more specific to report
+    /// (DB connectivity, subscriber counts, last event seq, ...) should
+    /// override this.
+    async fn health(&self) -> ServiceHealth {
+        ServiceHealth {
+            service: self.name().to_string(),
+            status: self.status(),
+            detail: serde_json::Value::Object(serde_json::Map::new()),
+            observed_at: SystemTime::now(),
+        }
+    }
 }
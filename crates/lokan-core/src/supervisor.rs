@@ -0,0 +1,165 @@
+//! Restart supervision for long-running [`Service`] worker tasks.
+//!
+//! [`Service::start`] implementations that spawn a single worker with bare
+//! `tokio::spawn` and log a warning if it ever returns leave the service
+//! stuck in [`ServiceStatus::Running`] with a dead task underneath it.
+//! [`Supervisor`] wraps that spawn: it re-invokes the worker factory after
+//! an exponential backoff whenever the worker exits (cleanly, with an
+//! error, or by panicking), and gives up — marking the service
+//! [`ServiceStatus::Failed`] — once restarts happen too often within a
+//! sliding window.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::{AbortHandle, JoinHandle};
+use tracing::warn;
+
+use crate::{ServiceError, ServiceStatus};
+
+/// Restart backoff/budget knobs for [`Supervisor::supervise`].
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Delay before the first restart attempt.
+    pub restart_period: Duration,
+    /// Upper bound the doubling backoff is capped at.
+    pub max_backoff: Duration,
+    /// How many restarts are tolerated inside `window` before the
+    /// supervisor gives up and marks the service [`ServiceStatus::Failed`].
+    pub max_retries_within_window: u32,
+    /// Sliding window restarts are counted against.
+    pub window: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            restart_period: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            max_retries_within_window: 5,
+            window: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Owns the restart loop for a single supervised worker, plus the
+/// [`ServiceStatus`] cell a [`Service::status`] implementation can read
+/// directly instead of tracking its own.
+pub struct Supervisor {
+    status: Arc<AtomicU8>,
+    supervisor_handle: AsyncMutex<Option<JoinHandle<()>>>,
+    worker_abort: Arc<StdMutex<Option<AbortHandle>>>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(AtomicU8::new(ServiceStatus::Stopped.to_u8())),
+            supervisor_handle: AsyncMutex::new(None),
+            worker_abort: Arc::new(StdMutex::new(None)),
+        }
+    }
+
+    /// Current status. [`Self::supervise`] sets `Running`, the restart loop
+    /// sets `Failed` once it gives up, and [`Self::stop`] sets `Stopped`;
+    /// `Starting`/`Stopping` are only visible if callers set them through
+    /// [`Self::set_status`].
+    pub fn status(&self) -> ServiceStatus {
+        ServiceStatus::from_u8(self.status.load(Ordering::SeqCst))
+    }
+
+    /// Manually sets the status, e.g. to `Starting` while a caller does
+    /// setup before calling [`Self::supervise`], which otherwise only ever
+    /// transitions between `Running` and `Failed` on its own.
+    pub fn set_status(&self, status: ServiceStatus) {
+        self.status.store(status.to_u8(), Ordering::SeqCst);
+    }
+
+    /// Spawns `factory` as a supervised worker under `policy`: each time the
+    /// produced future exits — `Ok`, `Err`, or panic — it's re-spawned after
+    /// an exponential backoff (starting at `policy.restart_period`, capped
+    /// at `policy.max_backoff`), unless more than
+    /// `policy.max_retries_within_window` restarts have happened within
+    /// `policy.window`, in which case the status is set to
+    /// [`ServiceStatus::Failed`] and the supervisor stops retrying.
+    pub async fn supervise<F, Fut>(&self, policy: RestartPolicy, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), ServiceError>> + Send + 'static,
+    {
+        self.status
+            .store(ServiceStatus::Running.to_u8(), Ordering::SeqCst);
+        let status = self.status.clone();
+        let worker_abort = self.worker_abort.clone();
+
+        let supervisor_task = tokio::spawn(async move {
+            let mut restarts: VecDeque<Instant> = VecDeque::new();
+            let mut backoff = policy.restart_period;
+
+            loop {
+                let handle = tokio::spawn(factory());
+                *worker_abort.lock().unwrap() = Some(handle.abort_handle());
+
+                match handle.await {
+                    Ok(Ok(())) => {
+                        warn!("supervised worker exited unexpectedly");
+                    }
+                    Ok(Err(err)) => {
+                        warn!(error = %err, "supervised worker returned an error");
+                    }
+                    Err(join_err) => {
+                        if join_err.is_cancelled() {
+                            // Stopped intentionally via Supervisor::stop.
+                            return;
+                        }
+                        warn!(error = %join_err, "supervised worker panicked");
+                    }
+                }
+
+                let now = Instant::now();
+                restarts.push_back(now);
+                while restarts
+                    .front()
+                    .is_some_and(|oldest| now.duration_since(*oldest) > policy.window)
+                {
+                    restarts.pop_front();
+                }
+                if restarts.len() as u32 > policy.max_retries_within_window {
+                    warn!(
+                        retries = restarts.len(),
+                        "supervised worker exceeded its restart budget; giving up"
+                    );
+                    status.store(ServiceStatus::Failed.to_u8(), Ordering::SeqCst);
+                    return;
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(policy.max_backoff);
+            }
+        });
+
+        *self.supervisor_handle.lock().await = Some(supervisor_task);
+    }
+
+    /// Cancels both the current worker and the restart loop itself. Idempotent.
+    pub async fn stop(&self) {
+        if let Some(abort) = self.worker_abort.lock().unwrap().take() {
+            abort.abort();
+        }
+        if let Some(handle) = self.supervisor_handle.lock().await.take() {
+            handle.abort();
+        }
+        self.status
+            .store(ServiceStatus::Stopped.to_u8(), Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,89 @@
+//! Centralized background-task registry with graceful, deadline-bounded
+//! shutdown.
+//!
+//! Stashing a worker's `JoinHandle` in an `AsyncMutex<Option<...>>` and
+//! calling `handle.abort()` on shutdown (the pattern [`crate::Supervisor`]
+//! uses for restart-on-crash) hard-kills whatever the task happened to be
+//! doing mid-tick. [`TaskTracker`] instead hands every tracked task a
+//! [`CancellationToken`] to `select!` on, so well-behaved workers stop
+//! between ticks on their own; only tasks still running past the shutdown
+//! deadline get aborted.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// Owns every task spawned through it, plus the [`CancellationToken`] they
+/// should cooperatively shut down on.
+pub struct TaskTracker {
+    token: CancellationToken,
+    tasks: AsyncMutex<JoinSet<()>>,
+}
+
+impl Default for TaskTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskTracker {
+    pub fn new() -> Self {
+        Self {
+            token: CancellationToken::new(),
+            tasks: AsyncMutex::new(JoinSet::new()),
+        }
+    }
+
+    /// Cancellation signal tracked workers should `select!` on, e.g.
+    /// `tokio::select! { _ = token.cancelled() => break, _ = tokio::time::sleep(interval) => {} }`.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Spawns `future` onto the tracker instead of a bare `tokio::spawn`, so
+    /// [`Self::shutdown`] can wait for it to finish on its own before
+    /// giving up and aborting it.
+    pub async fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.lock().await.spawn(future);
+    }
+
+    /// Signals [`Self::token`] without waiting for anything to react to it;
+    /// callers that need to run other shutdown work (e.g. each
+    /// [`crate::Service::stop`]) concurrently with tracked tasks winding
+    /// down should call this first and [`Self::join`] afterward.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Waits up to `timeout` for every tracked task to finish on its own;
+    /// anything still running past the deadline is aborted. Does not itself
+    /// cancel [`Self::token`] — call [`Self::cancel`] first.
+    pub async fn join(&self, timeout: Duration) {
+        let mut tasks = self.tasks.lock().await;
+
+        let drain = async { while tasks.join_next().await.is_some() {} };
+        if tokio::time::timeout(timeout, drain).await.is_err() {
+            warn!(
+                remaining = tasks.len(),
+                "tasks did not shut down within the deadline, aborting the rest"
+            );
+            tasks.abort_all();
+            while tasks.join_next().await.is_some() {}
+        }
+    }
+
+    /// Convenience for [`Self::cancel`] immediately followed by
+    /// [`Self::join`], for callers with no other shutdown work to overlap
+    /// it with.
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.cancel();
+        self.join(timeout).await;
+    }
+}
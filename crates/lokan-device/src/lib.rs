@@ -1,10 +1,15 @@
-use std::{collections::HashMap, sync::Arc};
+pub mod modbus;
+pub mod mqtt;
+pub mod poller;
+mod storage;
+
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
 
 /// High level device descriptor stored in the registry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,12 +50,47 @@ pub trait DeviceDriver: Send + Sync {
 
     /// Called before a device is unregistered.
     async fn shutdown(&self, descriptor: &DeviceDescriptor) -> Result<(), DeviceError>;
+
+    /// Sends an arbitrary command to the device, e.g. dispatched by a rule
+    /// engine's `CallDevice` action.
+    async fn send_command(
+        &self,
+        descriptor: &DeviceDescriptor,
+        command: serde_json::Value,
+    ) -> Result<(), DeviceError>;
+}
+
+/// A device's state changed, emitted on [`DeviceRegistry::subscribe`] by
+/// [`DeviceRegistry::update_state`], [`DeviceRegistry::mark_online`], and
+/// [`DeviceRegistry::register_device`]/[`DeviceRegistry::unregister_device`]
+/// (as online/offline transitions respectively) so callers can build a live
+/// view instead of polling [`DeviceRegistry::get_state`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceStateChange {
+    pub device_id: String,
+    pub state: DeviceState,
 }
 
-/// Thread safe in-memory registry for devices managed by the hub.
-#[derive(Default, Clone)]
+/// Thread safe registry for devices managed by the hub. Falls back to a
+/// purely in-memory `HashMap` when constructed with [`DeviceRegistry::new`];
+/// [`DeviceRegistry::open`] additionally persists every record to sled so
+/// the registry survives a restart.
+#[derive(Clone)]
 pub struct DeviceRegistry {
     devices: Arc<RwLock<HashMap<String, (DeviceDescriptor, DeviceState)>>>,
+    tree: Option<sled::Tree>,
+    changes: broadcast::Sender<DeviceStateChange>,
+}
+
+impl Default for DeviceRegistry {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(256);
+        Self {
+            devices: Arc::new(RwLock::new(HashMap::new())),
+            tree: None,
+            changes,
+        }
+    }
 }
 
 impl DeviceRegistry {
@@ -58,6 +98,42 @@ impl DeviceRegistry {
         Self::default()
     }
 
+    /// Opens (or creates) a sled-backed registry under `data_dir`, hydrating
+    /// the in-memory cache from whatever was persisted on a previous run.
+    pub fn open(data_dir: &Path) -> Result<Self, DeviceError> {
+        let tree = storage::open_tree(data_dir)?;
+        let devices = storage::load_all(&tree)?.into_iter().collect();
+        let (changes, _) = broadcast::channel(256);
+        Ok(Self {
+            devices: Arc::new(RwLock::new(devices)),
+            tree: Some(tree),
+            changes,
+        })
+    }
+
+    /// Subscribes to every [`DeviceStateChange`] from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<DeviceStateChange> {
+        self.changes.subscribe()
+    }
+
+    fn persist(&self, device_id: &str, record: &(DeviceDescriptor, DeviceState)) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        if let Err(error) = storage::persist(tree, device_id, record) {
+            warn!(device_id, %error, "failed to persist device record");
+        }
+    }
+
+    fn remove_persisted(&self, device_id: &str) {
+        let Some(tree) = &self.tree else {
+            return;
+        };
+        if let Err(error) = storage::remove(tree, device_id) {
+            warn!(device_id, %error, "failed to remove persisted device record");
+        }
+    }
+
     pub async fn register_device(
         &self,
         descriptor: DeviceDescriptor,
@@ -68,7 +144,17 @@ impl DeviceRegistry {
             return Err(DeviceError::AlreadyExists(descriptor.id));
         }
         driver.initialize(&descriptor).await?;
-        devices.insert(descriptor.id.clone(), (descriptor, DeviceState::default()));
+        let state = DeviceState {
+            online: true,
+            ..DeviceState::default()
+        };
+        let record = (descriptor.clone(), state);
+        self.persist(&descriptor.id, &record);
+        let _ = self.changes.send(DeviceStateChange {
+            device_id: descriptor.id.clone(),
+            state: record.1.clone(),
+        });
+        devices.insert(descriptor.id, record);
         Ok(())
     }
 
@@ -78,10 +164,16 @@ impl DeviceRegistry {
         driver: &dyn DeviceDriver,
     ) -> Result<(), DeviceError> {
         let mut devices = self.devices.write().await;
-        let (descriptor, _) = devices
+        let (descriptor, mut state) = devices
             .remove(device_id)
             .ok_or_else(|| DeviceError::NotFound(device_id.into()))?;
         driver.shutdown(&descriptor).await?;
+        self.remove_persisted(device_id);
+        state.online = false;
+        let _ = self.changes.send(DeviceStateChange {
+            device_id: device_id.to_string(),
+            state,
+        });
         Ok(())
     }
 
@@ -95,6 +187,11 @@ impl DeviceRegistry {
             .get_mut(device_id)
             .ok_or_else(|| DeviceError::NotFound(device_id.into()))?;
         entry.1 = state;
+        self.persist(device_id, entry);
+        let _ = self.changes.send(DeviceStateChange {
+            device_id: device_id.to_string(),
+            state: entry.1.clone(),
+        });
         Ok(())
     }
 
@@ -131,6 +228,10 @@ impl DeviceRegistry {
         if let Some((_descriptor, state)) = devices.get_mut(device_id) {
             state.online = online;
             debug!(device_id, online, "device status changed");
+            let _ = self.changes.send(DeviceStateChange {
+                device_id: device_id.to_string(),
+                state: state.clone(),
+            });
         }
     }
 
@@ -0,0 +1,370 @@
+//! MQTT-backed [`DeviceDriver`] and `EventBus` bridge, wired from
+//! `NetworkConfig::mqtt_broker`.
+//!
+//! Both [`MqttDeviceDriver`] and [`MqttEventBridge`] derive the topic prefix
+//! they operate under from the path component of the broker URL, the way
+//! modbus-style bridges take their topic prefix from the connection URL
+//! (e.g. `mqtt://broker.local:1883/lokan` scopes everything under `lokan/`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use lokan_event::{Event, EventBus};
+use rumqttc::{AsyncClient, Event as MqttEvent, LastWill, MqttOptions, Packet, QoS};
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::{DeviceDescriptor, DeviceDriver, DeviceError, DeviceState, DeviceStateChange};
+
+const DEFAULT_MQTT_PORT: u16 = 1883;
+const DEFAULT_TOPIC_PREFIX: &str = "lokan";
+
+/// Errors raised by [`MqttEventBridge`]. Driver failures are reported as
+/// [`DeviceError`] instead, since [`MqttDeviceDriver`] must speak that trait.
+#[derive(Debug, Error)]
+pub enum MqttBridgeError {
+    #[error("mqtt client error: {0}")]
+    Client(String),
+    #[error("failed to encode event payload: {0}")]
+    Encode(String),
+}
+
+/// Splits a broker URL into `(host, port, topic_prefix)`, e.g.
+/// `mqtt://broker.local:1883/lokan` -> `("broker.local", 1883, "lokan")`.
+/// Falls back to [`DEFAULT_MQTT_PORT`]/[`DEFAULT_TOPIC_PREFIX`] for any
+/// component the URL omits.
+fn parse_broker_url(endpoint: &str) -> (String, u16, String) {
+    let without_scheme = endpoint
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(endpoint);
+    let (authority, path) = without_scheme
+        .split_once('/')
+        .unwrap_or((without_scheme, ""));
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) => (host, port.parse().unwrap_or(DEFAULT_MQTT_PORT)),
+        None => (authority, DEFAULT_MQTT_PORT),
+    };
+    let prefix = if path.is_empty() {
+        DEFAULT_TOPIC_PREFIX.to_string()
+    } else {
+        path.trim_end_matches('/').to_string()
+    };
+    (host.to_string(), port, prefix)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// [`DeviceDriver`] backed by an MQTT broker: `poll` reads the latest
+/// retained state published on the device's state topic, while
+/// `initialize`/`shutdown` announce the device's online/offline command.
+pub struct MqttDeviceDriver {
+    client: AsyncClient,
+    prefix: String,
+    retained: Arc<Mutex<HashMap<String, serde_json::Value>>>,
+}
+
+impl MqttDeviceDriver {
+    /// Connects to the broker at `endpoint` and spawns the background task
+    /// driving the client's event loop.
+    pub fn connect(endpoint: &str) -> Self {
+        let (host, port, prefix) = parse_broker_url(endpoint);
+        let mut options = MqttOptions::new("lokan-device-driver", host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+        let retained = Arc::new(Mutex::new(HashMap::new()));
+        let retained_clone = retained.clone();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                        match serde_json::from_slice::<serde_json::Value>(&publish.payload) {
+                            Ok(value) => {
+                                retained_clone.lock().await.insert(publish.topic, value);
+                            }
+                            Err(error) => {
+                                warn!(%error, topic = %publish.topic, "failed to decode mqtt device state");
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        warn!(%error, "mqtt device driver event loop error");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Self {
+            client,
+            prefix,
+            retained,
+        }
+    }
+
+    fn state_topic(&self, device_id: &str) -> String {
+        format!("{}/{}/state", self.prefix, device_id)
+    }
+
+    fn command_topic(&self, device_id: &str) -> String {
+        format!("{}/{}/command", self.prefix, device_id)
+    }
+}
+
+#[async_trait]
+impl DeviceDriver for MqttDeviceDriver {
+    async fn initialize(&self, descriptor: &DeviceDescriptor) -> Result<(), DeviceError> {
+        self.client
+            .subscribe(self.state_topic(&descriptor.id), QoS::AtLeastOnce)
+            .await
+            .map_err(|err| DeviceError::Driver(err.to_string()))?;
+        self.client
+            .publish(
+                self.command_topic(&descriptor.id),
+                QoS::AtLeastOnce,
+                false,
+                b"online".to_vec(),
+            )
+            .await
+            .map_err(|err| DeviceError::Driver(err.to_string()))
+    }
+
+    async fn poll(&self, descriptor: &DeviceDescriptor) -> Result<DeviceState, DeviceError> {
+        let properties = self
+            .retained
+            .lock()
+            .await
+            .get(&self.state_topic(&descriptor.id))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let online = !properties.is_null();
+
+        Ok(DeviceState {
+            online,
+            last_seen_epoch_ms: now_millis(),
+            properties,
+        })
+    }
+
+    async fn shutdown(&self, descriptor: &DeviceDescriptor) -> Result<(), DeviceError> {
+        self.client
+            .publish(
+                self.command_topic(&descriptor.id),
+                QoS::AtLeastOnce,
+                false,
+                b"offline".to_vec(),
+            )
+            .await
+            .map_err(|err| DeviceError::Driver(err.to_string()))?;
+        self.client
+            .unsubscribe(self.state_topic(&descriptor.id))
+            .await
+            .map_err(|err| DeviceError::Driver(err.to_string()))
+    }
+
+    async fn send_command(
+        &self,
+        descriptor: &DeviceDescriptor,
+        command: serde_json::Value,
+    ) -> Result<(), DeviceError> {
+        let payload =
+            serde_json::to_vec(&command).map_err(|err| DeviceError::Driver(err.to_string()))?;
+        self.client
+            .publish(
+                self.command_topic(&descriptor.id),
+                QoS::AtLeastOnce,
+                false,
+                payload,
+            )
+            .await
+            .map_err(|err| DeviceError::Driver(err.to_string()))
+    }
+}
+
+/// Retained payload published on [`hub_status_topic`] while the hub is
+/// connected; the broker substitutes [`OFFLINE_PAYLOAD`] via the Last Will
+/// once the connection drops without a clean disconnect.
+const ONLINE_PAYLOAD: &[u8] = br#"{"status":"online"}"#;
+const OFFLINE_PAYLOAD: &[u8] = br#"{"status":"offline"}"#;
+
+/// Retained topic the hub itself advertises connectivity on:
+/// `<prefix>/<hub_id>/status`. A disconnected hub is detected by subscribers
+/// through the broker publishing [`OFFLINE_PAYLOAD`] there via the client's
+/// Last Will, the same way `ConnectivitySupervisor<MqttConnector>`'s
+/// simulated last will works for the device-monitor's connectivity probe.
+fn hub_status_topic(prefix: &str, hub_id: &str) -> String {
+    format!("{prefix}/{hub_id}/status")
+}
+
+/// Retained topic a single device's online/offline transitions are
+/// published on: `<prefix>/<device_id>/status`.
+fn device_status_topic(prefix: &str, device_id: &str) -> String {
+    format!("{prefix}/{device_id}/status")
+}
+
+/// Bridges `lokan_event::Event`s with an MQTT broker: [`MqttEventBridge::publish`]
+/// republishes an event onto `<prefix>/<topic>`, and the background task
+/// spawned by [`MqttEventBridge::connect`] ingests inbound messages under
+/// `<prefix>/#` back onto the `EventBus` supplied at connect time, so the
+/// `RuleEngine` can trigger on external MQTT traffic.
+///
+/// [`MqttEventBridge::connect`] also registers a Last Will on
+/// [`hub_status_topic`] and publishes a retained online message once
+/// connected, so downstream subscribers can tell a crashed hub apart from
+/// one that was never connected.
+pub struct MqttEventBridge {
+    client: AsyncClient,
+    prefix: String,
+}
+
+impl MqttEventBridge {
+    /// Connects to `endpoint`, registers a Last Will on [`hub_status_topic`]
+    /// for `hub_id`, subscribes to every topic under the endpoint's prefix,
+    /// and spawns the inbound half of the bridge.
+    pub fn connect(endpoint: &str, hub_id: &str, event_bus: EventBus) -> Self {
+        let (host, port, prefix) = parse_broker_url(endpoint);
+        let mut options = MqttOptions::new("lokan-event-bridge", host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            hub_status_topic(&prefix, hub_id),
+            OFFLINE_PAYLOAD,
+            QoS::AtLeastOnce,
+            true,
+        ));
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+        let announce_client = client.clone();
+        let online_topic = hub_status_topic(&prefix, hub_id);
+        let wildcard = format!("{prefix}/#");
+        tokio::spawn(async move {
+            if let Err(error) = announce_client.subscribe(wildcard, QoS::AtLeastOnce).await {
+                warn!(%error, "failed to subscribe to mqtt event bridge topics");
+            }
+            if let Err(error) = announce_client
+                .publish(online_topic, QoS::AtLeastOnce, true, ONLINE_PAYLOAD)
+                .await
+            {
+                warn!(%error, "failed to publish retained online status");
+            }
+        });
+
+        let inbound_prefix = format!("{prefix}/");
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(MqttEvent::Incoming(Packet::Publish(publish))) => {
+                        let Some(topic) = publish.topic.strip_prefix(&inbound_prefix) else {
+                            continue;
+                        };
+                        match serde_json::from_slice::<serde_json::Value>(&publish.payload) {
+                            Ok(payload) => {
+                                event_bus.publish(Event::new(topic.to_string(), payload))
+                            }
+                            Err(error) => {
+                                warn!(%error, topic, "failed to decode mqtt payload as event")
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        warn!(%error, "mqtt event bridge event loop error");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Self { client, prefix }
+    }
+
+    /// Republishes `event` onto `<prefix>/<event.topic>`.
+    pub async fn publish(&self, event: &Event) -> Result<(), MqttBridgeError> {
+        let payload = serde_json::to_vec(&event.payload)
+            .map_err(|err| MqttBridgeError::Encode(err.to_string()))?;
+        self.publish_retained(&format!("{}/{}", self.prefix, event.topic), payload, false)
+            .await
+    }
+
+    /// Publishes `payload` verbatim to `topic`, optionally retained. Used
+    /// for device/hub status topics, which aren't `lokan_event::Event`s and
+    /// so skip [`Self::publish`]'s JSON-encoding step.
+    pub async fn publish_retained(
+        &self,
+        topic: &str,
+        payload: impl Into<Vec<u8>>,
+        retain: bool,
+    ) -> Result<(), MqttBridgeError> {
+        self.client
+            .publish(topic, QoS::AtLeastOnce, retain, payload)
+            .await
+            .map_err(|err| MqttBridgeError::Client(err.to_string()))
+    }
+
+    fn device_status_topic(&self, device_id: &str) -> String {
+        device_status_topic(&self.prefix, device_id)
+    }
+}
+
+/// Spawns the outbound half of the bridge: subscribes to `event_bus` and
+/// republishes every event it sees through `bridge`.
+pub fn spawn_outbound_bridge(bridge: Arc<MqttEventBridge>, event_bus: EventBus) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut rx = event_bus.subscribe();
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Err(error) = bridge.publish(&event).await {
+                        warn!(%error, topic = %event.topic, "failed to republish event to mqtt");
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Spawns the device-status half of the bridge: subscribes to `changes` and
+/// publishes a retained `{"status": "online"|"offline"}` message on each
+/// device's [`device_status_topic`] as it transitions, so MQTT subscribers
+/// can track per-device connectivity the same way [`hub_status_topic`] lets
+/// them track the hub's.
+pub fn spawn_device_status_bridge(
+    bridge: Arc<MqttEventBridge>,
+    mut changes: broadcast::Receiver<DeviceStateChange>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match changes.recv().await {
+                Ok(change) => {
+                    let topic = bridge.device_status_topic(&change.device_id);
+                    let payload: &[u8] = if change.state.online {
+                        ONLINE_PAYLOAD
+                    } else {
+                        OFFLINE_PAYLOAD
+                    };
+                    if let Err(error) = bridge.publish_retained(&topic, payload, true).await {
+                        warn!(%error, device_id = %change.device_id, "failed to publish device status to mqtt");
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        skipped,
+                        "device status bridge lagged behind registry changes"
+                    );
+                }
+            }
+        }
+    })
+}
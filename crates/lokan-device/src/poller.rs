@@ -0,0 +1,166 @@
+//! Background polling scheduler for [`DeviceRegistry`].
+//!
+//! [`DeviceRegistry::refresh_all`] only keeps state fresh when something
+//! calls it. [`DevicePoller`] instead owns one long-lived task per
+//! registered device plus a broadcast shutdown signal, so callers get a
+//! supervised subsystem instead of bare `tokio::spawn` calls they have to
+//! track themselves.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use lokan_event::{Event, EventBus};
+use serde_json::json;
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::{DeviceDriver, DeviceRegistry};
+
+/// Poll scheduling knobs, mirroring `AutomationConfig`'s `device_poll_*`
+/// fields so callers can build one directly from `LokanConfig`.
+#[derive(Debug, Clone, Copy)]
+pub struct PollerConfig {
+    /// Base interval between successful polls of a device.
+    pub interval: Duration,
+    /// Maximum number of devices polled at the same time.
+    pub concurrency: usize,
+    /// Consecutive failures before a device is marked offline.
+    pub max_consecutive_failures: u32,
+}
+
+/// Owns the background tasks that keep a [`DeviceRegistry`] fresh. Spawns
+/// one polling task per device registered at spawn time, each backing off
+/// exponentially on failure, and joins every task cleanly on
+/// [`DevicePoller::shutdown`] instead of leaving them detached.
+pub struct DevicePoller {
+    shutdown: broadcast::Sender<()>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl DevicePoller {
+    /// Spawns a polling task for every device currently in `registry`.
+    /// `on_update` runs after every successful poll, before the
+    /// online/offline transition check, so callers can publish their own
+    /// domain events (e.g. a sensor reading) without a second poll loop.
+    pub async fn spawn(
+        registry: DeviceRegistry,
+        driver: Arc<dyn DeviceDriver>,
+        event_bus: EventBus,
+        config: PollerConfig,
+        on_update: Arc<dyn Fn(&crate::DeviceDescriptor, &crate::DeviceState) + Send + Sync>,
+    ) -> Self {
+        let descriptors = registry.list_devices().await;
+        Self::spawn_for(registry, driver, event_bus, config, on_update, descriptors).await
+    }
+
+    /// Like [`Self::spawn`], but polls only `descriptors` instead of every
+    /// device in `registry`. Lets a caller managing devices across several
+    /// drivers spawn one [`DevicePoller`] per driver, each scoped to the
+    /// devices that driver actually owns.
+    pub async fn spawn_for(
+        registry: DeviceRegistry,
+        driver: Arc<dyn DeviceDriver>,
+        event_bus: EventBus,
+        config: PollerConfig,
+        on_update: Arc<dyn Fn(&crate::DeviceDescriptor, &crate::DeviceState) + Send + Sync>,
+        descriptors: Vec<crate::DeviceDescriptor>,
+    ) -> Self {
+        let (shutdown, _) = broadcast::channel(1);
+        let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+
+        let handles = descriptors
+            .into_iter()
+            .map(|descriptor| {
+                tokio::spawn(poll_device(
+                    registry.clone(),
+                    driver.clone(),
+                    event_bus.clone(),
+                    descriptor,
+                    config,
+                    semaphore.clone(),
+                    shutdown.subscribe(),
+                    on_update.clone(),
+                ))
+            })
+            .collect();
+
+        Self { shutdown, handles }
+    }
+
+    /// Signals every poll task to stop and waits for them to finish.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        for handle in self.handles {
+            let _ = handle.await;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn poll_device(
+    registry: DeviceRegistry,
+    driver: Arc<dyn DeviceDriver>,
+    event_bus: EventBus,
+    descriptor: crate::DeviceDescriptor,
+    config: PollerConfig,
+    semaphore: Arc<Semaphore>,
+    mut shutdown: broadcast::Receiver<()>,
+    on_update: Arc<dyn Fn(&crate::DeviceDescriptor, &crate::DeviceState) + Send + Sync>,
+) {
+    let mut consecutive_failures: u32 = 0;
+    let mut backoff = config.interval;
+    let mut online = registry
+        .get_state(&descriptor.id)
+        .await
+        .map(|state| state.online)
+        .unwrap_or(false);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown.recv() => break,
+        }
+
+        let Ok(_permit) = semaphore.acquire().await else {
+            break;
+        };
+
+        match driver.poll(&descriptor).await {
+            Ok(state) => {
+                consecutive_failures = 0;
+                backoff = config.interval;
+                on_update(&descriptor, &state);
+                let _ = registry.update_state(&descriptor.id, state).await;
+
+                if !online {
+                    online = true;
+                    registry.mark_online(&descriptor.id, true).await;
+                    event_bus.publish(Event::new(
+                        "device.online",
+                        json!({ "device_id": descriptor.id }),
+                    ));
+                }
+            }
+            Err(error) => {
+                consecutive_failures += 1;
+                backoff = (backoff * 2).min(Duration::from_secs(300));
+                warn!(
+                    device_id = %descriptor.id,
+                    %error,
+                    consecutive_failures,
+                    "device poll failed"
+                );
+
+                if online && consecutive_failures >= config.max_consecutive_failures {
+                    online = false;
+                    registry.mark_online(&descriptor.id, false).await;
+                    event_bus.publish(Event::new(
+                        "device.offline",
+                        json!({ "device_id": descriptor.id }),
+                    ));
+                }
+            }
+        }
+    }
+}
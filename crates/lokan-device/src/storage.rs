@@ -0,0 +1,60 @@
+//! sled-backed persistence for [`crate::DeviceRegistry`], so device
+//! descriptors and their latest state survive a restart instead of living
+//! only in the registry's in-memory `HashMap`.
+
+use std::path::Path;
+
+use once_cell::sync::OnceCell;
+
+use crate::{DeviceDescriptor, DeviceError, DeviceState};
+
+/// Process-wide handle to the opened device database, cached behind a
+/// `OnceCell` the way `common_obs::metrics` caches its process-wide
+/// `Registry` instead of reopening it per call site.
+static DEVICE_DB: OnceCell<sled::Db> = OnceCell::new();
+
+/// A single persisted device record: its descriptor plus latest state.
+pub(crate) type DeviceRecord = (DeviceDescriptor, DeviceState);
+
+/// Opens (or reuses the process-wide) sled database under `data_dir` and
+/// returns its `devices` tree.
+pub(crate) fn open_tree(data_dir: &Path) -> Result<sled::Tree, DeviceError> {
+    let db_path = data_dir.join("devices.sled");
+    let db = DEVICE_DB
+        .get_or_try_init(|| sled::open(&db_path))
+        .map_err(|err| DeviceError::Driver(err.to_string()))?;
+    db.open_tree("devices")
+        .map_err(|err| DeviceError::Driver(err.to_string()))
+}
+
+/// Reads every persisted device record out of `tree`.
+pub(crate) fn load_all(tree: &sled::Tree) -> Result<Vec<(String, DeviceRecord)>, DeviceError> {
+    tree.iter()
+        .map(|entry| {
+            let (key, value) = entry.map_err(|err| DeviceError::Driver(err.to_string()))?;
+            let device_id = String::from_utf8_lossy(&key).into_owned();
+            let record: DeviceRecord =
+                bincode::deserialize(&value).map_err(|err| DeviceError::Driver(err.to_string()))?;
+            Ok((device_id, record))
+        })
+        .collect()
+}
+
+/// Persists (or overwrites) a single device record.
+pub(crate) fn persist(
+    tree: &sled::Tree,
+    device_id: &str,
+    record: &DeviceRecord,
+) -> Result<(), DeviceError> {
+    let bytes = bincode::serialize(record).map_err(|err| DeviceError::Driver(err.to_string()))?;
+    tree.insert(device_id.as_bytes(), bytes)
+        .map_err(|err| DeviceError::Driver(err.to_string()))?;
+    Ok(())
+}
+
+/// Removes a device record.
+pub(crate) fn remove(tree: &sled::Tree, device_id: &str) -> Result<(), DeviceError> {
+    tree.remove(device_id.as_bytes())
+        .map_err(|err| DeviceError::Driver(err.to_string()))?;
+    Ok(())
+}
@@ -1,7 +1,13 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::{Duration, SystemTime};
 
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
 use tracing::warn;
 
 /// Structured event emitted by services and the runtime.
@@ -54,3 +60,181 @@ impl EventBus {
         }
     }
 }
+
+/// Stream of [`Event`]s produced by an [`EventTransport`] subscription.
+pub struct EventStream {
+    inner: Pin<Box<dyn Stream<Item = Event> + Send>>,
+}
+
+impl EventStream {
+    fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Event> + Send + 'static,
+    {
+        Self {
+            inner: Box::pin(stream),
+        }
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: `inner` is pinned inside the struct and never moved after construction.
+        unsafe {
+            let inner = self.map_unchecked_mut(|me| &mut me.inner);
+            inner.poll_next(cx)
+        }
+    }
+}
+
+/// Errors surfaced by an [`EventTransport`] implementation.
+#[derive(Debug, Error)]
+pub enum EventTransportError {
+    /// The transport could not deliver the event to its backing broker.
+    #[error("failed to publish event: {0}")]
+    Publish(String),
+}
+
+/// Pluggable backend for distributing [`Event`]s. [`lokan_automation::RuleEngine`]
+/// depends only on this trait, so it can run against the in-process
+/// [`EventBus`] or a networked transport like a Redis-backed implementation
+/// without any change to its own rule-matching logic.
+#[async_trait]
+pub trait EventTransport: Send + Sync {
+    /// Publishes `event` to every subscriber reachable through this transport.
+    async fn publish(&self, event: Event) -> Result<(), EventTransportError>;
+
+    /// Subscribes to every event flowing through this transport.
+    async fn subscribe(&self) -> EventStream;
+}
+
+#[async_trait]
+impl EventTransport for EventBus {
+    async fn publish(&self, event: Event) -> Result<(), EventTransportError> {
+        self.publish(event);
+        Ok(())
+    }
+
+    async fn subscribe(&self) -> EventStream {
+        let stream = BroadcastStream::new(self.subscribe()).filter_map(|item| match item {
+            Ok(event) => Some(event),
+            Err(_) => None,
+        });
+        EventStream::new(stream)
+    }
+}
+
+/// Redis-backed [`EventTransport`] so multiple LokanOS hubs sharing a broker
+/// see one another's events and rule-triggered actions fan out cluster-wide.
+#[cfg(feature = "redis")]
+mod redis_impl {
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use redis::AsyncCommands;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+    use tracing::warn;
+
+    use super::{Event, EventStream, EventTransport, EventTransportError};
+
+    /// Prefix applied to the Redis pub/sub channel derived from an event's topic.
+    const CHANNEL_PREFIX: &str = "lokan.events.";
+
+    fn channel_for(topic: &str) -> String {
+        format!("{CHANNEL_PREFIX}{topic}")
+    }
+
+    /// Connection settings for [`RedisEventTransport`].
+    #[derive(Debug, Clone)]
+    pub struct RedisEventTransportConfig {
+        /// URL pointing to the Redis server instance.
+        pub url: String,
+    }
+
+    impl Default for RedisEventTransportConfig {
+        fn default() -> Self {
+            Self {
+                url: "redis://127.0.0.1:6379".to_string(),
+            }
+        }
+    }
+
+    /// Publishes each [`Event`] (topic + JSON payload) to a Redis pub/sub
+    /// channel named after its topic, and re-emits whatever other hubs
+    /// publish back into subscribers as regular [`Event`]s.
+    #[derive(Clone)]
+    pub struct RedisEventTransport {
+        client: redis::Client,
+    }
+
+    impl RedisEventTransport {
+        /// Establish a new connection to the configured Redis endpoint.
+        pub async fn connect(
+            config: RedisEventTransportConfig,
+        ) -> Result<Self, EventTransportError> {
+            let client = redis::Client::open(config.url)
+                .map_err(|err| EventTransportError::Publish(err.to_string()))?;
+            Ok(Self { client })
+        }
+    }
+
+    #[async_trait]
+    impl EventTransport for RedisEventTransport {
+        async fn publish(&self, event: Event) -> Result<(), EventTransportError> {
+            let payload = serde_json::to_vec(&event)
+                .map_err(|err| EventTransportError::Publish(err.to_string()))?;
+            let mut connection = self
+                .client
+                .get_multiplexed_async_connection()
+                .await
+                .map_err(|err| EventTransportError::Publish(err.to_string()))?;
+            connection
+                .publish::<_, _, ()>(channel_for(&event.topic), payload)
+                .await
+                .map_err(|err| EventTransportError::Publish(err.to_string()))
+        }
+
+        async fn subscribe(&self) -> EventStream {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let mut pubsub = match client.get_async_pubsub().await {
+                    Ok(pubsub) => pubsub,
+                    Err(error) => {
+                        warn!(%error, "failed to open redis pubsub connection");
+                        return;
+                    }
+                };
+                if let Err(error) = pubsub.psubscribe(format!("{CHANNEL_PREFIX}*")).await {
+                    warn!(%error, "failed to subscribe to redis event channels");
+                    return;
+                }
+
+                let mut messages = pubsub.on_message();
+                while let Some(message) = messages.next().await {
+                    let payload: Vec<u8> = match message.get_payload() {
+                        Ok(payload) => payload,
+                        Err(error) => {
+                            warn!(%error, "failed to read redis pubsub payload");
+                            continue;
+                        }
+                    };
+                    match serde_json::from_slice::<Event>(&payload) {
+                        Ok(event) => {
+                            if tx.send(event).is_err() {
+                                break;
+                            }
+                        }
+                        Err(error) => warn!(%error, "failed to decode event received from redis"),
+                    }
+                }
+            });
+            EventStream::new(UnboundedReceiverStream::new(rx))
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_impl::{RedisEventTransport, RedisEventTransportConfig};
@@ -1,52 +1,361 @@
-use serde::Serialize;
-use std::time::SystemTime;
+use std::collections::VecDeque;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
+use common_obs::CounterVec;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::audit_sink::AuditSink;
+use crate::config::{AuditBackpressure, AuditConfig};
+
+static AUDIT_EVENTS_DELIVERED: Lazy<CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "api_gateway_audit_events_delivered_total",
+        "Audit events successfully delivered, by sink",
+        &["sink"],
+    )
+});
+
+static AUDIT_EVENTS_DROPPED: Lazy<CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "api_gateway_audit_events_dropped_total",
+        "Audit events lost without ever being delivered, by sink and reason",
+        &["sink", "reason"],
+    )
+});
+
+static AUDIT_BATCHES_RETRIED: Lazy<CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "api_gateway_audit_batches_retried_total",
+        "Audit batch delivery attempts that failed and were retried, by sink",
+        &["sink"],
+    )
+});
+
+/// Delivers [`AuditEvent`]s to every configured [`AuditSink`] without making
+/// callers wait on the network: `record` only has to push onto an in-memory
+/// queue, and a background worker (spawned in `new`) drains it in batches,
+/// fanning each batch out to all sinks concurrently and retrying each
+/// sink's failed deliveries independently instead of dropping them on the
+/// first error. Cloning an `AuditClient` is cheap — every clone shares the
+/// same queue and worker.
 #[derive(Clone)]
 pub struct AuditClient {
-    endpoint: Option<String>,
-    client: reqwest::Client,
-    enabled: bool,
+    worker: Option<Arc<Worker>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+struct Worker {
+    sinks: Vec<Box<dyn AuditSink>>,
+    queue: Mutex<VecDeque<AuditEvent>>,
+    capacity: usize,
+    backpressure: AuditBackpressure,
+    batch_max_events: usize,
+    batch_max_interval: Duration,
+    max_delivery_attempts: u32,
+    spill_path: Option<PathBuf>,
+    enqueued: Notify,
+    drained: Notify,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEvent {
     pub actor: String,
     pub role: String,
     pub action: String,
     pub resource: String,
     pub outcome: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     pub detail: Option<serde_json::Value>,
     pub timestamp: SystemTime,
 }
 
 impl AuditClient {
-    pub fn new(endpoint: String, enabled: bool) -> Self {
+    /// Builds a client from `config` and `sinks`. If `sinks` is non-empty,
+    /// spawns the delivery worker — replaying any batch left over from a
+    /// prior run's spill file first. An empty `sinks` list (e.g. every
+    /// sink disabled in config) skips the worker entirely; `record` then
+    /// just logs events locally.
+    ///
+    /// Most callers should build `sinks` with
+    /// [`crate::audit_sink::sinks_from_config`] rather than constructing
+    /// them by hand.
+    pub fn new(config: &AuditConfig, sinks: Vec<Box<dyn AuditSink>>) -> Self {
+        if sinks.is_empty() {
+            return Self { worker: None };
+        }
+
+        let worker = Arc::new(Worker {
+            sinks,
+            queue: Mutex::new(VecDeque::new()),
+            capacity: config.queue_capacity.max(1),
+            backpressure: config.backpressure,
+            batch_max_events: config.batch_max_events.max(1),
+            batch_max_interval: config.batch_max_interval(),
+            max_delivery_attempts: config.max_delivery_attempts.max(1),
+            spill_path: config.spill_path.clone(),
+            enqueued: Notify::new(),
+            drained: Notify::new(),
+        });
+
+        worker.replay_spilled();
+        tokio::spawn(Worker::run(worker.clone()));
+
         Self {
-            endpoint: if enabled && !endpoint.is_empty() {
-                Some(endpoint)
-            } else {
-                None
-            },
-            client: reqwest::Client::new(),
-            enabled,
+            worker: Some(worker),
         }
     }
 
+    /// Enqueues `event` for delivery. Never blocks on the network; with
+    /// [`AuditBackpressure::Block`] it can await until the queue has room,
+    /// but with the default [`AuditBackpressure::DropOldest`] it always
+    /// returns immediately.
     pub async fn record(&self, event: AuditEvent) {
-        if !self.enabled {
-            tracing::trace!(action = %event.action, "audit disabled; dropping event");
+        match &self.worker {
+            Some(worker) => worker.enqueue(event).await,
+            None => {
+                tracing::info!(action = %event.action, outcome = %event.outcome, resource = %event.resource, actor = %event.actor, "audit disabled; logging event instead of delivering it");
+            }
+        }
+    }
+}
+
+impl Worker {
+    fn replay_spilled(&self) {
+        let Some(path) = &self.spill_path else {
             return;
+        };
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+            Err(error) => {
+                tracing::warn!(%error, path = %path.display(), "failed to read audit spill file; starting with an empty queue");
+                return;
+            }
+        };
+
+        let mut replayed = 0;
+        {
+            let mut queue = self.queue.lock().expect("audit queue poisoned");
+            for line in contents.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<AuditEvent>(line) {
+                    Ok(event) => {
+                        queue.push_back(event);
+                        replayed += 1;
+                    }
+                    Err(error) => {
+                        tracing::warn!(%error, "dropping unreadable spilled audit event");
+                    }
+                }
+            }
+        }
+
+        if replayed > 0 {
+            tracing::info!(replayed, path = %path.display(), "replaying spilled audit events from a prior run");
         }
+        if let Err(error) = std::fs::remove_file(path) {
+            tracing::warn!(%error, path = %path.display(), "failed to remove audit spill file after replay");
+        }
+    }
 
-        if let Some(endpoint) = &self.endpoint {
-            let request = self.client.post(endpoint).json(&event).send().await;
-            if let Err(error) = request {
-                tracing::warn!(%error, endpoint, "failed to deliver audit event");
+    async fn enqueue(&self, event: AuditEvent) {
+        loop {
+            let mut queue = self.queue.lock().expect("audit queue poisoned");
+            if queue.len() < self.capacity {
+                queue.push_back(event);
+                drop(queue);
+                self.enqueued.notify_one();
+                return;
             }
-        } else {
-            tracing::info!(action = %event.action, outcome = %event.outcome, resource = %event.resource, actor = %event.actor, "audit endpoint not configured; logging event");
+
+            match self.backpressure {
+                AuditBackpressure::DropOldest => {
+                    queue.pop_front();
+                    queue.push_back(event);
+                    drop(queue);
+                    AUDIT_EVENTS_DROPPED.inc(&["queue", "queue_full"], 1);
+                    self.enqueued.notify_one();
+                    return;
+                }
+                AuditBackpressure::Block => {
+                    drop(queue);
+                    self.drained.notified().await;
+                }
+            }
+        }
+    }
+
+    async fn run(self: Arc<Self>) {
+        loop {
+            let batch = self.next_batch().await;
+            if !batch.is_empty() {
+                Arc::clone(&self).deliver_with_retry(batch).await;
+            }
+        }
+    }
+
+    /// Waits until either `batch_max_events` events are queued or
+    /// `batch_max_interval` has elapsed since this call began waiting (the
+    /// timer restarts whenever it fires on an empty queue), whichever comes
+    /// first, then drains and returns that batch.
+    async fn next_batch(&self) -> Vec<AuditEvent> {
+        let deadline = tokio::time::sleep(self.batch_max_interval);
+        tokio::pin!(deadline);
+
+        loop {
+            if let Some(batch) = self.drain_if_ready() {
+                return batch;
+            }
+
+            tokio::select! {
+                _ = self.enqueued.notified() => {}
+                () = &mut deadline => {
+                    if let Some(batch) = self.drain_all_if_any() {
+                        return batch;
+                    }
+                    deadline
+                        .as_mut()
+                        .reset(tokio::time::Instant::now() + self.batch_max_interval);
+                }
+            }
+        }
+    }
+
+    fn drain_if_ready(&self) -> Option<Vec<AuditEvent>> {
+        let mut queue = self.queue.lock().expect("audit queue poisoned");
+        if queue.len() < self.batch_max_events {
+            return None;
+        }
+        let batch = queue.drain(..self.batch_max_events).collect();
+        drop(queue);
+        self.drained.notify_waiters();
+        Some(batch)
+    }
+
+    fn drain_all_if_any(&self) -> Option<Vec<AuditEvent>> {
+        let mut queue = self.queue.lock().expect("audit queue poisoned");
+        if queue.is_empty() {
+            return None;
+        }
+        let batch = queue.drain(..).collect();
+        drop(queue);
+        self.drained.notify_waiters();
+        Some(batch)
+    }
+
+    /// Fans `batch` out to every sink concurrently. Each sink retries its
+    /// own delivery independently, so one sink being down doesn't delay or
+    /// drop the batch for any other sink.
+    async fn deliver_with_retry(self: Arc<Self>, batch: Vec<AuditEvent>) {
+        let batch = Arc::new(batch);
+        let mut tasks = Vec::with_capacity(self.sinks.len());
+        for index in 0..self.sinks.len() {
+            let worker = Arc::clone(&self);
+            let batch = Arc::clone(&batch);
+            tasks.push(tokio::spawn(async move {
+                worker.deliver_to_sink(index, &batch).await;
+            }));
+        }
+
+        for task in tasks {
+            if let Err(error) = task.await {
+                tracing::error!(%error, "audit sink delivery task panicked");
+            }
+        }
+    }
+
+    async fn deliver_to_sink(&self, sink_index: usize, batch: &[AuditEvent]) {
+        let sink = &self.sinks[sink_index];
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match sink.emit(batch).await {
+                Ok(()) => {
+                    AUDIT_EVENTS_DELIVERED.inc(&[sink.name()], batch.len() as u64);
+                    return;
+                }
+                Err(error) => {
+                    tracing::warn!(sink = sink.name(), %error, events = batch.len(), "audit sink failed to deliver batch");
+                }
+            }
+
+            if attempt >= self.max_delivery_attempts {
+                self.spill_or_drop(sink.name(), batch);
+                return;
+            }
+
+            AUDIT_BATCHES_RETRIED.inc(&[sink.name()], 1);
+            tokio::time::sleep(Self::backoff_for(attempt)).await;
+        }
+    }
+
+    /// `min(200ms * 2^attempt, 30s)`, jittered down by up to 20% so a burst
+    /// of simultaneous failures doesn't retry in lockstep.
+    fn backoff_for(attempt: u32) -> Duration {
+        let base = Duration::from_millis(200);
+        let max = Duration::from_secs(30);
+        let exponent = attempt.min(16);
+        let scaled = base.saturating_mul(1u32.checked_shl(exponent).unwrap_or(u32::MAX));
+        let capped = scaled.min(max);
+
+        let jitter_frac = rand::thread_rng().gen_range(0.0..0.2);
+        Duration::from_secs_f64((capped.as_secs_f64() * (1.0 - jitter_frac)).max(0.0))
+    }
+
+    /// Spills (or, with spilling disabled, drops) a batch one sink failed
+    /// to deliver after exhausting retries. Since sinks can fail and spill
+    /// concurrently, the whole batch is serialized into one buffer and
+    /// written with a single `write_all` call, so appends from different
+    /// sinks can't interleave mid-line.
+    fn spill_or_drop(&self, sink_name: &'static str, batch: &[AuditEvent]) {
+        let Some(path) = &self.spill_path else {
+            AUDIT_EVENTS_DROPPED.inc(&[sink_name, "delivery_failed"], batch.len() as u64);
+            tracing::error!(
+                sink = sink_name,
+                dropped = batch.len(),
+                "giving up on audit batch delivery; events are lost"
+            );
+            return;
+        };
+
+        let mut buffer = String::new();
+        for event in batch {
+            match serde_json::to_string(event) {
+                Ok(line) => {
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                }
+                Err(error) => {
+                    tracing::error!(%error, "failed to encode audit event for spilling");
+                }
+            }
+        }
+
+        let mut file = match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+        {
+            Ok(file) => file,
+            Err(error) => {
+                AUDIT_EVENTS_DROPPED.inc(&[sink_name, "spill_failed"], batch.len() as u64);
+                tracing::error!(%error, path = %path.display(), dropped = batch.len(), "failed to open audit spill file; events are lost");
+                return;
+            }
+        };
+
+        if let Err(error) = file.write_all(buffer.as_bytes()) {
+            tracing::error!(%error, path = %path.display(), "failed to spill audit batch");
+            return;
         }
+        tracing::warn!(sink = sink_name, spilled = batch.len(), path = %path.display(), "spilled undeliverable audit batch to disk for replay on next startup");
     }
 }
 
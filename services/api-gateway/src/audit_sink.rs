@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::audit::AuditEvent;
+use crate::config::AuditConfig;
+
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("sink rejected the batch with status {status}")]
+    Rejected { status: u16 },
+    #[error("transport error: {0}")]
+    Transport(String),
+    #[error("io error: {0}")]
+    Io(String),
+}
+
+/// One delivery target for a batch of [`AuditEvent`]s. `Worker` fans a
+/// batch out to every configured sink concurrently and retries each one
+/// independently, so a sink that's down (or slow) never blocks or drops
+/// events bound for any other sink.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// A short, stable label identifying this sink in logs and metrics,
+    /// e.g. `"http"` or `"object_storage"`.
+    fn name(&self) -> &'static str;
+
+    async fn emit(&self, events: &[AuditEvent]) -> Result<(), SinkError>;
+}
+
+/// Posts a batch as a JSON array to an HTTP endpoint — the gateway's
+/// original delivery mechanism, and still the default one.
+pub struct HttpSink {
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl HttpSink {
+    pub fn new(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for HttpSink {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn emit(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(events)
+            .send()
+            .await
+            .map_err(|error| SinkError::Transport(error.to_string()))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(SinkError::Rejected {
+                status: response.status().as_u16(),
+            })
+        }
+    }
+}
+
+/// Writes a batch as newline-delimited JSON to stdout, for air-gapped
+/// deployments with no reachable audit endpoint — an operator (or a log
+/// shipper tailing the process's stdout) is the only consumer.
+pub struct StdoutSink;
+
+#[async_trait]
+impl AuditSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn emit(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        use std::io::Write as _;
+
+        let mut buffer = String::new();
+        for event in events {
+            let line =
+                serde_json::to_string(event).map_err(|error| SinkError::Io(error.to_string()))?;
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        handle
+            .write_all(buffer.as_bytes())
+            .and_then(|()| handle.flush())
+            .map_err(|error| SinkError::Io(error.to_string()))
+    }
+}
+
+/// Uploads a batch as a single newline-delimited-JSON object to an
+/// S3-compatible bucket, keyed by a random, time-prefixed name so
+/// concurrent uploads never collide. Performs a plain authenticated `PUT`
+/// rather than full AWS SigV4 request signing — enough for a
+/// self-hosted, trusted-network object store (e.g. MinIO/Ceph RGW), with
+/// no AWS SDK dependency required.
+pub struct ObjectStorageSink {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    client: reqwest::Client,
+}
+
+impl ObjectStorageSink {
+    pub fn new(
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    ) -> Self {
+        Self {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn object_url(&self) -> String {
+        let key = format!(
+            "audit/{}/{}-{}.jsonl",
+            self.region,
+            humantime_epoch_secs(),
+            uuid::Uuid::new_v4()
+        );
+        format!(
+            "{}/{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            self.bucket,
+            key
+        )
+    }
+}
+
+fn humantime_epoch_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl AuditSink for ObjectStorageSink {
+    fn name(&self) -> &'static str {
+        "object_storage"
+    }
+
+    async fn emit(&self, events: &[AuditEvent]) -> Result<(), SinkError> {
+        let mut body = String::new();
+        for event in events {
+            let line =
+                serde_json::to_string(event).map_err(|error| SinkError::Io(error.to_string()))?;
+            body.push_str(&line);
+            body.push('\n');
+        }
+
+        let response = self
+            .client
+            .put(self.object_url())
+            .basic_auth(&self.access_key, Some(&self.secret_key))
+            .body(body)
+            .send()
+            .await
+            .map_err(|error| SinkError::Transport(error.to_string()))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(SinkError::Rejected {
+                status: response.status().as_u16(),
+            })
+        }
+    }
+}
+
+/// Builds the sink list a deployment's [`AuditConfig`] asks for: the HTTP
+/// sink when `enabled` and `endpoint` is set, `stdout_sink` when set, and
+/// `object_storage` when configured. An empty result means auditing is
+/// fully disabled.
+pub fn sinks_from_config(config: &AuditConfig) -> Vec<Box<dyn AuditSink>> {
+    let mut sinks: Vec<Box<dyn AuditSink>> = Vec::new();
+
+    if config.enabled && !config.endpoint.is_empty() {
+        sinks.push(Box::new(HttpSink::new(config.endpoint.clone())));
+    }
+    if config.stdout_sink {
+        sinks.push(Box::new(StdoutSink));
+    }
+    if let Some(object_storage) = &config.object_storage {
+        sinks.push(Box::new(ObjectStorageSink::new(
+            object_storage.endpoint.clone(),
+            object_storage.bucket.clone(),
+            object_storage.region.clone(),
+            object_storage.access_key.clone(),
+            object_storage.secret_key.clone(),
+        )));
+    }
+
+    sinks
+}
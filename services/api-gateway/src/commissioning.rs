@@ -6,20 +6,35 @@ use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use common_ble::{CsrRequest, CsrResponse, VerifyRequest, VerifyResponse};
 use common_obs::msgbus_publish_total;
-use rand::RngCore;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::Sha256;
 use tracing::warn;
 use uuid::Uuid;
+use x25519_dalek::{EphemeralSecret, PublicKey};
 
+use crate::session::SessionError;
 use crate::{ApiError, AppState};
 
+/// Domain separation label for the HKDF step deriving the session key from
+/// the raw X25519 shared secret.
+const SESSION_KEY_INFO: &[u8] = b"lokan-commissioning-session-v1";
+/// Domain separation label for the key-confirmation MAC proving both sides
+/// derived the same session key, without revealing it.
+const KEY_CONFIRMATION_INFO: &[u8] = b"lokan-commissioning-confirm-v1";
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BleHandshakeRequest {
     pub qr_payload: String,
     pub device_id: String,
     pub nonce: String,
+    /// Base64-encoded X25519 public key generated by the client for this
+    /// handshake.
+    pub client_public_key: String,
     #[serde(default)]
     pub metadata: Option<serde_json::Value>,
 }
@@ -28,7 +43,12 @@ pub struct BleHandshakeRequest {
 #[serde(rename_all = "camelCase")]
 pub struct BleHandshakeResponse {
     pub session: String,
-    pub shared_key: String,
+    /// Base64-encoded X25519 public key generated by the server for this
+    /// handshake.
+    pub server_public_key: String,
+    /// Base64-encoded MAC proving the server derived the same session key as
+    /// the client, without exposing the key itself.
+    pub key_confirmation: String,
 }
 
 pub async fn ble_handshake(
@@ -38,9 +58,20 @@ pub async fn ble_handshake(
     validate_qr(&request.qr_payload)?;
     validate_device_id(&request.device_id)?;
     validate_nonce(&request.nonce)?;
+    let client_public_key = decode_public_key(&request.client_public_key)?;
+
+    let server_secret = EphemeralSecret::random_from_rng(OsRng);
+    let server_public_key = PublicKey::from(&server_secret);
+    let shared_secret = server_secret.diffie_hellman(&client_public_key);
 
     let session = Uuid::new_v4().to_string();
-    let shared_key = generate_shared_secret();
+    let session_key = derive_session_key(shared_secret.as_bytes(), &request.nonce);
+    let key_confirmation = compute_key_confirmation(&session_key, &session);
+
+    state
+        .session_store
+        .create(session.clone(), &request.nonce, session_key)
+        .await;
 
     let event = json!({
         "type": "commissioning.handshake",
@@ -54,7 +85,8 @@ pub async fn ble_handshake(
 
     Ok(Json(BleHandshakeResponse {
         session,
-        shared_key,
+        server_public_key: BASE64.encode(server_public_key.as_bytes()),
+        key_confirmation: BASE64.encode(key_confirmation),
     }))
 }
 
@@ -74,9 +106,9 @@ pub async fn submit_csr(
         });
     }
 
-    if let Some(nonce) = &request.nonce {
-        validate_nonce(nonce)?;
-    }
+    validate_nonce(&request.session)?;
+    validate_nonce(&request.nonce)?;
+    consume_session_nonce(&state, &request.session, &request.nonce).await?;
 
     let mut certificate_bytes = Vec::new();
     certificate_bytes.extend_from_slice(b"lokan-dev-cert:");
@@ -93,7 +125,7 @@ pub async fn submit_csr(
     let event = json!({
         "type": "commissioning.csr",
         "deviceId": request.device_id,
-        "nonce": request.nonce,
+        "session": request.session,
         "csrLength": csr_bytes.len(),
     });
     publish_event(&state, "radio.commissioning.csr", &event).await;
@@ -118,9 +150,9 @@ pub async fn verify_credentials(
         });
     }
 
-    if let Some(session) = &request.session {
-        validate_nonce(session)?;
-    }
+    validate_nonce(&request.session)?;
+    validate_nonce(&request.nonce)?;
+    consume_session_nonce(&state, &request.session, &request.nonce).await?;
 
     let event = json!({
         "type": "commissioning.verify",
@@ -136,6 +168,26 @@ pub async fn verify_credentials(
     }))
 }
 
+async fn consume_session_nonce(
+    state: &AppState,
+    session: &str,
+    nonce: &str,
+) -> Result<(), ApiError> {
+    state
+        .session_store
+        .consume(session, nonce)
+        .await
+        .map(|_| ())
+        .map_err(|error| ApiError::Validation {
+            message: match error {
+                SessionError::Unknown => "session is unknown or has expired".to_string(),
+                SessionError::NonceReused => {
+                    "nonce has already been used for this session".to_string()
+                }
+            },
+        })
+}
+
 fn validate_qr(qr: &str) -> Result<(), ApiError> {
     if qr.is_empty() {
         return Err(ApiError::Validation {
@@ -184,10 +236,34 @@ fn validate_nonce(nonce: &str) -> Result<(), ApiError> {
     Ok(())
 }
 
-fn generate_shared_secret() -> String {
-    let mut bytes = [0u8; 32];
-    rand::thread_rng().fill_bytes(&mut bytes);
-    BASE64.encode(bytes)
+fn decode_public_key(encoded: &str) -> Result<PublicKey, ApiError> {
+    let bytes = BASE64
+        .decode(encoded.as_bytes())
+        .map_err(|_| ApiError::Validation {
+            message: "clientPublicKey must be valid base64 data".to_string(),
+        })?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| ApiError::Validation {
+        message: "clientPublicKey must be a 32-byte X25519 public key".to_string(),
+    })?;
+    Ok(PublicKey::from(bytes))
+}
+
+/// Runs the raw ECDH output through HKDF keyed by the handshake nonce so the
+/// session key never leaves the process in its raw Diffie-Hellman form.
+fn derive_session_key(shared_secret: &[u8], nonce: &str) -> [u8; 32] {
+    let hkdf = Hkdf::<Sha256>::new(Some(nonce.as_bytes()), shared_secret);
+    let mut session_key = [0u8; 32];
+    hkdf.expand(SESSION_KEY_INFO, &mut session_key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    session_key
+}
+
+/// Proves both sides derived the same session key without transmitting it.
+fn compute_key_confirmation(session_key: &[u8; 32], session: &str) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(session_key).expect("HMAC accepts any key length");
+    mac.update(KEY_CONFIRMATION_INFO);
+    mac.update(session.as_bytes());
+    mac.finalize().into_bytes().into()
 }
 
 async fn publish_event(state: &AppState, subject: &str, payload: &serde_json::Value) {
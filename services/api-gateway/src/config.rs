@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
-use common_config::{MsgBusConfig, ServiceConfig};
+use arc_swap::ArcSwap;
+use common_config::{MsgBusConfig, ServiceConfig, ShutdownConfig};
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -18,6 +22,13 @@ pub struct ApiGatewayConfig {
     pub audit: AuditConfig,
     pub device_registry_url: String,
     pub rate_limit: RateLimitSettings,
+    pub proxy: ProxyConfig,
+    pub listener: ListenerConfig,
+    pub credentials: CredentialsConfig,
+    pub token_issuer: TokenIssuerConfig,
+    pub deadline: DeadlineConfig,
+    pub http3: Http3Config,
+    pub shutdown: ShutdownConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -33,6 +44,83 @@ pub struct TlsConfig {
 pub struct AuditConfig {
     pub endpoint: String,
     pub enabled: bool,
+    /// Maximum number of events batched into a single delivery POST.
+    pub batch_max_events: usize,
+    /// Upper bound, in milliseconds, on how long an event waits in the
+    /// queue before its batch is flushed even if `batch_max_events` hasn't
+    /// been reached yet.
+    pub batch_max_interval_ms: u64,
+    /// Capacity of the in-memory queue feeding the delivery worker.
+    pub queue_capacity: usize,
+    /// What happens to a new event when the queue is already full.
+    pub backpressure: AuditBackpressure,
+    /// A batch is retried (with exponential backoff and jitter) up to this
+    /// many times before it is spilled to `spill_path` (or dropped, if
+    /// spilling is disabled).
+    pub max_delivery_attempts: u32,
+    /// Append-only file that undelivered batches are spilled to, so they
+    /// survive a restart or a prolonged endpoint outage and get replayed
+    /// on the next startup. `None` disables spilling.
+    pub spill_path: Option<PathBuf>,
+    /// Also write every batch as newline-delimited JSON to stdout. Meant
+    /// for air-gapped deployments with no reachable audit endpoint, where
+    /// an operator (or a log shipper watching the process's stdout) is the
+    /// only consumer.
+    pub stdout_sink: bool,
+    /// Also upload every batch to an S3-compatible object store. `None`
+    /// leaves this sink disabled.
+    pub object_storage: Option<ObjectStorageSinkConfig>,
+}
+
+impl AuditConfig {
+    pub fn batch_max_interval(&self) -> Duration {
+        Duration::from_millis(self.batch_max_interval_ms)
+    }
+}
+
+/// What an [`AuditConfig`]-backed client does with a new event when its
+/// delivery queue is already at `queue_capacity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditBackpressure {
+    /// Evict the oldest queued event to make room — favors delivering
+    /// recent events over old ones when the endpoint can't keep up.
+    DropOldest,
+    /// Make the caller wait until space frees up — favors never losing an
+    /// event over request latency.
+    Block,
+}
+
+impl Default for AuditBackpressure {
+    fn default() -> Self {
+        Self::DropOldest
+    }
+}
+
+/// Where `audit_sink::ObjectStorageSink` uploads batches. Talks to any endpoint
+/// that accepts a plain authenticated `PUT` (e.g. a self-hosted
+/// MinIO/Ceph RGW deployment) rather than a specific cloud provider's API,
+/// so no client SDK is required.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ObjectStorageSinkConfig {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl Default for ObjectStorageSinkConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            bucket: String::new(),
+            region: "us-east-1".to_string(),
+            access_key: String::new(),
+            secret_key: String::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -40,6 +128,169 @@ pub struct AuditConfig {
 pub struct RateLimitSettings {
     pub requests_per_minute: u32,
     pub burst: u32,
+    /// Per-role capacity/refill overrides, keyed by lowercase role name
+    /// (`owner`, `admin`, `member`, `guest`). A role with no entry here
+    /// uses `requests_per_minute`/`burst` above as its tier. An unknown key
+    /// is ignored with a warning rather than rejected, so a typo doesn't
+    /// take the gateway down.
+    pub per_role: BTreeMap<String, RateLimitTier>,
+    /// Per-route capacity/refill overrides, keyed by exact request path
+    /// (e.g. `/v1/commissioning/csr`). Checked in addition to the
+    /// caller's global (per-role) bucket, not instead of it, so a route
+    /// listed here gets its own stricter ceiling on top of the overall
+    /// per-actor limit.
+    pub per_route: BTreeMap<String, RateLimitTier>,
+    /// Capacity/refill for the bucket keyed on raw client identity alone
+    /// (ignoring role), checked in addition to the caller's own
+    /// identity-and-role bucket above, so a client's requests count
+    /// against both limits whatever role it presents.
+    pub per_client_requests_per_minute: u32,
+    pub per_client_burst: u32,
+    /// How long an idle bucket (sitting at full capacity, untouched) is
+    /// kept before the background sweep evicts it, bounding memory for a
+    /// gateway that sees a long tail of one-off client identities.
+    pub per_client_idle_ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitTier {
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
+
+/// Maps the first `/v1/<prefix>` path segment to the base URL of the
+/// internal service it should be reverse-proxied to, e.g.
+/// `{"mqtt-bridge": "http://127.0.0.1:8005"}`. A prefix with no entry here
+/// and no healthy self-registered upstream (see `crate::registry`) gets the
+/// proxy fallback's 404.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub upstreams: BTreeMap<String, String>,
+}
+
+/// Where the gateway binds its listener: `tcp://host:port` for a TCP+TLS
+/// socket, or `unix:/path/to/socket` for a Unix-domain-socket listener
+/// (e.g. a sidecar/loopback deployment with no TCP port exposed). Empty
+/// falls back to `bind_address`/`port`, so existing TCP deployments don't
+/// need to set this.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListenerConfig {
+    pub address: String,
+    /// Whether the listener terminates TLS. Always `true` for a TCP
+    /// listener regardless of this setting; a Unix listener honors it, so
+    /// a socket that's already local-only (or sitting behind another
+    /// proxy) can skip in-process TLS.
+    pub tls_enabled: bool,
+    /// Permission bits applied to a freshly bound Unix-domain-socket path.
+    /// Ignored for a TCP listener.
+    pub unix_socket_mode: u32,
+}
+
+/// Optional HTTP/3-over-QUIC listener, built and bound behind the
+/// `http3` feature alongside the gateway's normal TCP+TLS listener (never
+/// instead of it — client HTTP/3 support isn't universal, so TCP stays
+/// the fallback). Reuses the same `TlsConfig` cert/key for the QUIC
+/// handshake; see `crate::http3`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Http3Config {
+    pub enabled: bool,
+    /// UDP port the QUIC endpoint binds to, on the same host as the TCP
+    /// listener. Also the value advertised in the `Alt-Svc` header on TCP
+    /// responses, so a client knows where to open its QUIC connection.
+    pub port: u16,
+}
+
+impl Default for Http3Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8443,
+        }
+    }
+}
+
+/// Controls verification of the `Authorization: Bearer` tokens that carry a
+/// caller's subject, role, and route scopes. See `crate::credentials`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CredentialsConfig {
+    pub public_key_path: PathBuf,
+    /// Falls back to trusting the plaintext `x-lokan-role`/`x-lokan-subject`
+    /// headers when no bearer token is presented. Only meant for local/dev
+    /// deployments that sit behind a trusted proxy — defaults to `false`.
+    pub insecure_header_auth: bool,
+}
+
+/// Configures minting of the bearer tokens `credentials::CredentialVerifier`
+/// verifies. When `enabled`, the gateway binary reads the matching Ed25519
+/// signing key from `private_key_path` at startup to serve
+/// `/v1/auth/refresh`. `enabled: false` keeps a deployment
+/// verification-only, with tokens minted out-of-band elsewhere.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TokenIssuerConfig {
+    pub enabled: bool,
+    pub private_key_path: PathBuf,
+    /// How long a freshly issued or refreshed token stays valid.
+    pub ttl_secs: u64,
+}
+
+impl TokenIssuerConfig {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs.max(1))
+    }
+}
+
+impl Default for TokenIssuerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            private_key_path: PathBuf::from(
+                "security/pki/dev/out/services/api-gateway/token-signing-key.pem",
+            ),
+            ttl_secs: 900,
+        }
+    }
+}
+
+/// Bounds how long a protected request may run before the deadline
+/// middleware aborts it with [`crate::error::ApiError::Timeout`]. Keyed by
+/// exact request path, same convention as `RateLimitSettings::per_route`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DeadlineConfig {
+    pub default_ms: u64,
+    pub per_route: BTreeMap<String, u64>,
+    /// Whether a caller's `X-Request-Deadline` header (milliseconds) may
+    /// shorten the deadline for its own request. It can never extend one
+    /// past `max_ms`.
+    pub allow_client_override: bool,
+    pub max_ms: u64,
+}
+
+impl DeadlineConfig {
+    pub fn default_duration(&self) -> Duration {
+        Duration::from_millis(self.default_ms.max(1))
+    }
+
+    pub fn max_duration(&self) -> Duration {
+        Duration::from_millis(self.max_ms.max(1))
+    }
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            default_ms: 10_000,
+            per_route: BTreeMap::new(),
+            allow_client_override: true,
+            max_ms: 30_000,
+        }
+    }
 }
 
 impl Default for ApiGatewayConfig {
@@ -55,6 +306,13 @@ impl Default for ApiGatewayConfig {
             audit: AuditConfig::default(),
             device_registry_url: "http://127.0.0.1:8001".to_string(),
             rate_limit: RateLimitSettings::default(),
+            proxy: ProxyConfig::default(),
+            listener: ListenerConfig::default(),
+            credentials: CredentialsConfig::default(),
+            token_issuer: TokenIssuerConfig::default(),
+            deadline: DeadlineConfig::default(),
+            http3: Http3Config::default(),
+            shutdown: ShutdownConfig::default(),
         }
     }
 }
@@ -78,6 +336,14 @@ impl Default for AuditConfig {
         Self {
             endpoint: "http://127.0.0.1:8008/v1/events".to_string(),
             enabled: true,
+            batch_max_events: 50,
+            batch_max_interval_ms: 1_000,
+            queue_capacity: 2_048,
+            backpressure: AuditBackpressure::DropOldest,
+            max_delivery_attempts: 5,
+            spill_path: None,
+            stdout_sink: false,
+            object_storage: None,
         }
     }
 }
@@ -87,6 +353,49 @@ impl Default for RateLimitSettings {
         Self {
             requests_per_minute: 120,
             burst: 40,
+            per_role: BTreeMap::new(),
+            per_route: BTreeMap::new(),
+            per_client_requests_per_minute: 60,
+            per_client_burst: 20,
+            per_client_idle_ttl_secs: 600,
+        }
+    }
+}
+
+impl Default for RateLimitTier {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 120,
+            burst: 40,
+        }
+    }
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            upstreams: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for ListenerConfig {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            tls_enabled: true,
+            unix_socket_mode: 0o660,
+        }
+    }
+}
+
+impl Default for CredentialsConfig {
+    fn default() -> Self {
+        Self {
+            public_key_path: PathBuf::from(
+                "security/pki/dev/out/services/api-gateway/token-verifying-key.pem",
+            ),
+            insecure_header_auth: false,
         }
     }
 }
@@ -95,6 +404,50 @@ impl ApiGatewayConfig {
     pub fn socket_addr(&self) -> Result<SocketAddr, std::net::AddrParseError> {
         format!("{}:{}", self.bind_address, self.port).parse()
     }
+
+    /// Every address/protocol the gateway listens on: its primary
+    /// TCP-or-unix listener (per `ListenerConfig::address`), plus a QUIC
+    /// endpoint when `http3.enabled`. Generalizes the single address
+    /// `socket_addr()` returns, so a caller that needs to enumerate every
+    /// endpoint (mDNS announcement, startup logging) doesn't separately
+    /// need to know about `http3`.
+    pub fn endpoints(&self) -> Result<Vec<GatewayEndpoint>, crate::listener::ListenerError> {
+        let fallback = self
+            .socket_addr()
+            .map_err(crate::listener::ListenerError::InvalidSocketAddr)?;
+        let primary = match crate::listener::Listener::parse(&self.listener.address, fallback)? {
+            crate::listener::Listener::Tcp(addr) => GatewayEndpoint::Tcp(addr),
+            crate::listener::Listener::Unix(path) => GatewayEndpoint::Unix(path),
+        };
+
+        let mut endpoints = vec![primary];
+        if self.http3.enabled {
+            endpoints.push(GatewayEndpoint::Quic(SocketAddr::new(
+                fallback.ip(),
+                self.http3.port,
+            )));
+        }
+        Ok(endpoints)
+    }
+}
+
+/// One address/protocol combination the gateway listens on, as enumerated
+/// by [`ApiGatewayConfig::endpoints`].
+#[derive(Debug, Clone)]
+pub enum GatewayEndpoint {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+    Quic(SocketAddr),
+}
+
+impl std::fmt::Display for GatewayEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GatewayEndpoint::Tcp(addr) => write!(f, "tcp://{addr}"),
+            GatewayEndpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+            GatewayEndpoint::Quic(addr) => write!(f, "quic://{addr}"),
+        }
+    }
 }
 
 impl ServiceConfig for ApiGatewayConfig {
@@ -104,3 +457,34 @@ impl ServiceConfig for ApiGatewayConfig {
         self.bus.apply_environment_overrides(prefix);
     }
 }
+
+/// A hot-reloadable handle around a value `T` that's derived from config —
+/// an [`RbacPolicy`](crate::rbac::RbacPolicy), a `RateLimiter`, and so on.
+/// Readers call [`ReloadableConfig::current`] for a cheap `Arc` snapshot
+/// that stays valid even if a reload replaces it mid-request; the
+/// `reload` module calls [`ReloadableConfig::store`] to atomically publish
+/// a freshly validated version. Backed by [`ArcSwap`] rather than a
+/// `parking_lot::RwLock` so a read never blocks on a concurrent reload and
+/// never has to be held across an `.await`.
+pub struct ReloadableConfig<T> {
+    current: ArcSwap<T>,
+}
+
+impl<T> ReloadableConfig<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(value),
+        }
+    }
+
+    /// A snapshot of the value as of this call. Later reloads don't
+    /// mutate it; the caller sees whatever was live when it asked.
+    pub fn current(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    /// Atomically publishes `value` as the new current snapshot.
+    pub fn store(&self, value: T) {
+        self.current.store(Arc::new(value));
+    }
+}
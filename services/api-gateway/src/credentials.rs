@@ -0,0 +1,130 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::pkcs8::DecodePublicKey;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::rbac::Role;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Tolerance applied to `not_before`/`not_after` so small clock drift
+/// between the token issuer and this gateway doesn't spuriously reject an
+/// otherwise-valid token.
+const CLOCK_SKEW_TOLERANCE_SECS: i64 = 30;
+
+/// The verified claims carried by a bearer token: who it's for, what role
+/// they hold, which routes it's scoped to, and the window it's valid in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub subject: String,
+    pub role: Role,
+    /// Path prefixes this token may be used against, e.g. `/v1/devices`.
+    /// `"*"` grants every route this token's role is otherwise permitted.
+    /// Empty denies everything — a token must declare scope explicitly.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl TokenClaims {
+    /// Whether `path` falls under one of this token's scopes.
+    pub fn allows_path(&self, path: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|scope| scope == "*" || path.starts_with(scope.as_str()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenError {
+    #[error("missing bearer token")]
+    Missing,
+    #[error("malformed bearer token")]
+    Malformed,
+    #[error("invalid token signature")]
+    InvalidSignature,
+    #[error("token is not yet valid")]
+    NotYetValid,
+    #[error("token has expired")]
+    Expired,
+}
+
+impl TokenError {
+    /// Audit action recorded for this failure, matching the two the RBAC
+    /// middleware is expected to distinguish: time-window problems vs.
+    /// everything else (missing, malformed, or a signature that doesn't
+    /// verify).
+    pub fn audit_action(&self) -> &'static str {
+        match self {
+            TokenError::NotYetValid | TokenError::Expired => "auth.token.expired",
+            TokenError::Missing | TokenError::Malformed | TokenError::InvalidSignature => {
+                "auth.token.invalid"
+            }
+        }
+    }
+}
+
+/// Verifies `Authorization: Bearer <token>` headers against a configured
+/// Ed25519 public key. A token is `<base64 claims json>.<base64 signature>`
+/// — a detached signature over the claims bytes, following the same
+/// Ed25519 scheme `services/updater` uses to sign update bundles, rather
+/// than pulling in a general-purpose JWT library.
+#[derive(Clone)]
+pub struct CredentialVerifier {
+    verifying_key: VerifyingKey,
+}
+
+impl CredentialVerifier {
+    pub fn from_public_key_pem(pem: &str) -> Result<Self, TokenError> {
+        let verifying_key =
+            VerifyingKey::from_public_key_pem(pem).map_err(|_| TokenError::Malformed)?;
+        Ok(Self { verifying_key })
+    }
+
+    /// Verifies the raw `Authorization` header value (including the
+    /// `Bearer ` prefix) and returns its claims if the signature checks out
+    /// and `now` falls within `not_before`/`not_after` (plus clock-skew
+    /// tolerance).
+    pub fn verify(
+        &self,
+        header_value: &str,
+        now: DateTime<Utc>,
+    ) -> Result<TokenClaims, TokenError> {
+        let token = header_value
+            .strip_prefix(BEARER_PREFIX)
+            .ok_or(TokenError::Malformed)?;
+        let (claims_part, signature_part) = token.split_once('.').ok_or(TokenError::Malformed)?;
+
+        let claims_bytes = BASE64
+            .decode(claims_part)
+            .map_err(|_| TokenError::Malformed)?;
+        let signature_bytes = BASE64
+            .decode(signature_part)
+            .map_err(|_| TokenError::Malformed)?;
+        let signature_array: [u8; 64] = signature_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| TokenError::Malformed)?;
+        let signature = Signature::from_bytes(&signature_array);
+
+        self.verifying_key
+            .verify(&claims_bytes, &signature)
+            .map_err(|_| TokenError::InvalidSignature)?;
+
+        let claims: TokenClaims =
+            serde_json::from_slice(&claims_bytes).map_err(|_| TokenError::Malformed)?;
+
+        let tolerance = chrono::Duration::seconds(CLOCK_SKEW_TOLERANCE_SECS);
+        if now + tolerance < claims.not_before {
+            return Err(TokenError::NotYetValid);
+        }
+        if now - tolerance > claims.not_after {
+            return Err(TokenError::Expired);
+        }
+
+        Ok(claims)
+    }
+}
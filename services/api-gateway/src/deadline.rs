@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::config::DeadlineConfig;
+
+/// Header a caller may set to shorten (never extend) the deadline the
+/// gateway otherwise applies to its request, in milliseconds.
+pub const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+
+/// Resolves how long a request is allowed to run before the deadline
+/// middleware aborts it: a per-route override if one is configured for the
+/// exact request path, else the service-wide default, optionally shortened
+/// further by the caller's `X-Request-Deadline` header (in milliseconds) —
+/// but never past `max`.
+#[derive(Debug, Clone)]
+pub struct DeadlinePolicy {
+    default: Duration,
+    per_route: BTreeMap<String, Duration>,
+    allow_client_override: bool,
+    max: Duration,
+}
+
+impl DeadlinePolicy {
+    pub fn new(config: &DeadlineConfig) -> Self {
+        Self {
+            default: config.default_duration(),
+            per_route: config
+                .per_route
+                .iter()
+                .map(|(route, ms)| (route.clone(), Duration::from_millis((*ms).max(1))))
+                .collect(),
+            allow_client_override: config.allow_client_override,
+            max: config.max_duration(),
+        }
+    }
+
+    /// `path` is matched against `per_route` verbatim (the exact request
+    /// path, same convention as `RateLimitSettings::per_route`), and
+    /// `requested_ms` is the caller's parsed `X-Request-Deadline` value, if
+    /// any and if one was successfully parsed.
+    pub fn resolve(&self, path: &str, requested_ms: Option<u64>) -> Duration {
+        let base = self
+            .per_route
+            .get(path)
+            .copied()
+            .unwrap_or(self.default)
+            .min(self.max);
+
+        if !self.allow_client_override {
+            return base;
+        }
+
+        match requested_ms.map(Duration::from_millis) {
+            Some(requested) => requested.min(base).min(self.max),
+            None => base,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(default_ms: u64, max_ms: u64) -> DeadlineConfig {
+        DeadlineConfig {
+            default_ms,
+            per_route: BTreeMap::new(),
+            allow_client_override: true,
+            max_ms,
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_default_with_no_override() {
+        let policy = DeadlinePolicy::new(&config(5_000, 30_000));
+        assert_eq!(
+            policy.resolve("/v1/anything", None),
+            Duration::from_millis(5_000)
+        );
+    }
+
+    #[test]
+    fn per_route_override_takes_precedence_over_the_default() {
+        let mut config = config(5_000, 30_000);
+        config
+            .per_route
+            .insert("/v1/slow-report".to_string(), 20_000);
+        let policy = DeadlinePolicy::new(&config);
+
+        assert_eq!(
+            policy.resolve("/v1/slow-report", None),
+            Duration::from_millis(20_000)
+        );
+        assert_eq!(
+            policy.resolve("/v1/anything", None),
+            Duration::from_millis(5_000)
+        );
+    }
+
+    #[test]
+    fn client_override_can_shorten_but_not_lengthen_the_deadline() {
+        let policy = DeadlinePolicy::new(&config(5_000, 30_000));
+
+        assert_eq!(
+            policy.resolve("/v1/anything", Some(1_000)),
+            Duration::from_millis(1_000)
+        );
+        assert_eq!(
+            policy.resolve("/v1/anything", Some(60_000)),
+            Duration::from_millis(5_000)
+        );
+    }
+
+    #[test]
+    fn client_override_is_ignored_when_disabled() {
+        let mut config = config(5_000, 30_000);
+        config.allow_client_override = false;
+        let policy = DeadlinePolicy::new(&config);
+
+        assert_eq!(
+            policy.resolve("/v1/anything", Some(1_000)),
+            Duration::from_millis(5_000)
+        );
+    }
+}
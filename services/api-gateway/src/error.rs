@@ -14,13 +14,22 @@ pub enum ApiError {
     #[error("forbidden")]
     Forbidden { reason: String },
     #[error("rate limited")]
-    RateLimited { retry_after: Duration },
+    RateLimited {
+        retry_after: Duration,
+        remaining: u32,
+    },
     #[error("upstream call failed: {0}")]
     Upstream(String),
+    #[error("bad gateway: {0}")]
+    BadGateway(String),
     #[error("internal server error")]
     Internal,
     #[error("invalid request: {message}")]
     Validation { message: String },
+    #[error("request exceeded its deadline")]
+    Timeout { elapsed: Duration },
+    #[error("service unavailable")]
+    Unavailable { reason: String },
 }
 
 #[derive(Debug, Serialize)]
@@ -36,39 +45,75 @@ struct ErrorDetails<'a> {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, code, message, retry_after) = match &self {
+        let (status, code, message, retry_after, remaining) = match &self {
             ApiError::Unauthorized => (
                 StatusCode::UNAUTHORIZED,
                 "unauthorized",
                 self.to_string(),
                 None,
+                None,
             ),
-            ApiError::Forbidden { reason } => {
-                (StatusCode::FORBIDDEN, "forbidden", reason.clone(), None)
-            }
-            ApiError::RateLimited { retry_after } => (
+            ApiError::Forbidden { reason } => (
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                reason.clone(),
+                None,
+                None,
+            ),
+            ApiError::RateLimited {
+                retry_after,
+                remaining,
+            } => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "rate_limited",
                 self.to_string(),
                 Some(*retry_after),
+                Some(*remaining),
             ),
             ApiError::Upstream(message) => (
                 StatusCode::BAD_GATEWAY,
                 "upstream_error",
                 message.clone(),
                 None,
+                None,
+            ),
+            ApiError::BadGateway(message) => (
+                StatusCode::BAD_GATEWAY,
+                "bad_gateway",
+                message.clone(),
+                None,
+                None,
             ),
             ApiError::Internal => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "internal_error",
                 self.to_string(),
                 None,
+                None,
             ),
             ApiError::Validation { message } => (
                 StatusCode::BAD_REQUEST,
                 "invalid_request",
                 message.clone(),
                 None,
+                None,
+            ),
+            ApiError::Timeout { elapsed } => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+                format!(
+                    "request exceeded its deadline after {}ms",
+                    elapsed.as_millis()
+                ),
+                None,
+                None,
+            ),
+            ApiError::Unavailable { reason } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "unavailable",
+                reason.clone(),
+                None,
+                None,
             ),
         };
 
@@ -86,6 +131,14 @@ impl IntoResponse for ApiError {
             }
         }
 
+        if let Some(remaining) = remaining {
+            if let Ok(header_value) = axum::http::HeaderValue::from_str(&remaining.to_string()) {
+                response
+                    .headers_mut()
+                    .insert(crate::rate_limit::RATE_LIMIT_REMAINING_HEADER, header_value);
+            }
+        }
+
         response
     }
 }
@@ -102,6 +155,8 @@ impl From<reqwest::Error> for ApiError {
     fn from(error: reqwest::Error) -> Self {
         if error.is_timeout() {
             ApiError::Upstream("request to upstream service timed out".to_string())
+        } else if error.is_connect() {
+            ApiError::BadGateway(error.to_string())
         } else if error.status().is_some() {
             ApiError::Upstream(error.to_string())
         } else {
@@ -0,0 +1,131 @@
+//! Optional HTTP/3-over-QUIC listener, compiled in behind the `http3`
+//! feature and started only when [`crate::config::Http3Config::enabled`]
+//! is set. Runs alongside the gateway's normal TCP+TLS listener rather
+//! than replacing it; see `crate::config::ApiGatewayConfig::endpoints`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::Router;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use tower::Service;
+
+use crate::build_rustls_server_config;
+use crate::config::TlsConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Http3Error {
+    #[error("failed to build tls config for quic: {0}")]
+    Tls(#[source] Box<dyn std::error::Error>),
+    #[error("failed to build quic server config: {0}")]
+    QuicConfig(#[from] quinn::crypto::rustls::NoInitialCipherSuite),
+    #[error("failed to bind quic endpoint on {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Serves `router` over HTTP/3 on `addr` until the process exits, reusing
+/// `tls`'s certificate and key so a client trusts the same identity over
+/// QUIC that it does over the TCP listener.
+pub async fn serve(router: Router, addr: SocketAddr, tls: &TlsConfig) -> Result<(), Http3Error> {
+    let mut server_config = build_rustls_server_config(tls)
+        .await
+        .map_err(Http3Error::Tls)?;
+    server_config.alpn_protocols = vec![b"h3".to_vec()];
+
+    let quic_server_config =
+        quinn::crypto::rustls::QuicServerConfig::try_from(server_config)?;
+    let endpoint = quinn::Endpoint::server(
+        quinn::ServerConfig::with_crypto(Arc::new(quic_server_config)),
+        addr,
+    )
+    .map_err(|source| Http3Error::Bind { addr, source })?;
+
+    tracing::info!(%addr, "listening on quic (http/3)");
+
+    while let Some(incoming) = endpoint.accept().await {
+        let router = router.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(incoming, router).await {
+                tracing::warn!(%err, "http/3 connection failed");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_connection(
+    incoming: quinn::Incoming,
+    router: Router,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let connection = incoming.await?;
+    let mut h3_conn =
+        h3::server::Connection::new(h3_quinn::Connection::new(connection)).await?;
+
+    loop {
+        match h3_conn.accept().await {
+            Ok(Some((request, stream))) => {
+                let router = router.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_request(request, stream, router).await {
+                        tracing::warn!(%err, "http/3 request failed");
+                    }
+                });
+            }
+            Ok(None) => break,
+            Err(err) => return Err(Box::new(err)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Bridges one HTTP/3 request/response pair onto the same [`Router`] the
+/// TCP listener serves. Bodies are buffered whole rather than streamed —
+/// every gateway route is a small JSON request/response, so there's no
+/// benefit to the extra complexity of streaming here.
+async fn handle_request<S>(
+    request: http::Request<()>,
+    mut stream: h3::server::RequestStream<S, Bytes>,
+    router: Router,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    S: h3::quic::BidiStream<Bytes>,
+{
+    let (parts, ()) = request.into_parts();
+    let mut request_body = bytes::BytesMut::new();
+    while let Some(chunk) = stream.recv_data().await? {
+        bytes::BufMut::put(&mut request_body, chunk);
+    }
+    let axum_request =
+        axum::http::Request::from_parts(parts, Body::from(request_body.freeze()));
+
+    let mut make_service = router.into_make_service();
+    let mut tower_service = make_service
+        .call(())
+        .await
+        .expect("IntoMakeService's error type is Infallible");
+    let response = tower_service
+        .call(axum_request)
+        .await
+        .expect("Router's error type is Infallible");
+
+    let (parts, body) = response.into_parts();
+    stream
+        .send_response(http::Response::from_parts(parts, ()))
+        .await?;
+
+    let body = body.collect().await?.to_bytes();
+    if !body.is_empty() {
+        stream.send_data(body).await?;
+    }
+    stream.finish().await?;
+
+    Ok(())
+}
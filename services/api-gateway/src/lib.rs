@@ -1,10 +1,17 @@
 pub mod audit;
+pub mod audit_sink;
 pub mod commissioning;
 pub mod config;
+pub mod credentials;
+pub mod deadline;
 pub mod device_registry;
 pub mod error;
+pub mod proxy;
 pub mod rate_limit;
 pub mod rbac;
+pub mod registry;
+pub mod session;
+pub mod token_issuer;
 
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
@@ -18,18 +25,24 @@ use axum::middleware::{from_fn, from_fn_with_state, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use chrono::{DateTime, Utc};
 use commissioning::{ble_handshake, submit_csr, verify_credentials};
 use common_msgbus::MessageBus;
 use common_obs::{
-    encode_prometheus_metrics, handler_latency_seconds, http_requests_total, SpanExt,
-    PROMETHEUS_CONTENT_TYPE,
+    encode_prometheus_metrics, handler_latency_seconds, http_requests_total, snapshot_metrics,
+    SpanExt, PROMETHEUS_CONTENT_TYPE,
 };
+use credentials::{CredentialVerifier, TokenClaims, TokenError};
+use deadline::{DeadlinePolicy, REQUEST_DEADLINE_HEADER};
 use device_registry::DeviceRegistryClient;
 use error::ApiError;
-use rate_limit::RateLimiter;
+use proxy::ProxyClient;
+use rate_limit::{RateKey, RateLimiter, RATE_LIMIT_REMAINING_HEADER};
 use rbac::{PolicyError, RbacPolicy, Role};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use session::SessionStore;
+use token_issuer::TokenIssuer;
 use tokio::net::{lookup_host, TcpStream};
 use tokio::time::timeout;
 use tracing::info_span;
@@ -47,6 +60,19 @@ pub struct AppState {
     pub rate_limiter: RateLimiter,
     pub device_client: DeviceRegistryClient,
     pub bus: Arc<dyn MessageBus>,
+    pub session_store: SessionStore,
+    pub proxy: ProxyClient,
+    pub credential_verifier: Option<CredentialVerifier>,
+    /// Lets `rbac_guard` fall back to trusting the plaintext
+    /// `x-lokan-role`/`x-lokan-subject` headers when no bearer token is
+    /// presented (or no verifier is configured). Mirrors
+    /// `config::CredentialsConfig::insecure_header_auth` — only meant for
+    /// local/dev deployments.
+    pub insecure_header_auth: bool,
+    /// Mints the tokens `credential_verifier` checks. `None` disables
+    /// `/v1/auth/refresh`, mirroring `config::TokenIssuerConfig::enabled`.
+    pub token_issuer: Option<TokenIssuer>,
+    pub deadline: DeadlinePolicy,
 }
 
 #[derive(Clone, Debug)]
@@ -68,39 +94,139 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/v1/commissioning/ble/handshake", post(ble_handshake))
         .route("/v1/commissioning/csr", post(submit_csr))
         .route("/v1/commissioning/verify", post(verify_credentials))
+        .route("/v1/registry/register", post(register_upstream))
+        .route("/v1/auth/refresh", post(refresh_token))
+        .fallback(proxy_fallback)
+        .layer(from_fn_with_state(state.clone(), deadline_guard))
         .layer(from_fn_with_state(state.clone(), rate_limit_guard))
         .layer(from_fn_with_state(state.clone(), rbac_guard))
         .layer(Extension(state.clone()));
 
     Router::new()
         .route("/metrics", get(metrics))
+        .route("/stats", get(stats))
         .merge(protected_routes)
         .layer(from_fn(request_context))
         .with_state(state)
 }
 
+/// Computes the [`RateKey`] a request should be throttled under: the
+/// subject `rbac_guard` resolved (from a bearer token or trusted headers)
+/// when one is known, or the caller's remote IP when anonymous, so one
+/// noisy anonymous client can't drain every other anonymous caller's
+/// bucket.
+fn rate_key(req: &Request<Body>) -> RateKey {
+    let (subject, role) = match req.extensions().get::<UserContext>() {
+        Some(user) => (user.subject.clone(), user.role),
+        None => (extract_subject(req.headers()), extract_role(req.headers())),
+    };
+    let identity = if subject == "anonymous" {
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| format!("ip:{}", info.0.ip()))
+            .unwrap_or_else(|| "ip:unknown".to_string())
+    } else {
+        subject
+    };
+    RateKey::new(identity, role)
+}
+
 pub async fn rate_limit_guard(
     State(state): State<Arc<AppState>>,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    if let Err(err) = state.rate_limiter.check().await {
-        let role = extract_role(req.headers());
-        let subject = extract_subject(req.headers());
-        let path = req.uri().path().to_string();
+    let key = rate_key(&req);
+    let path = req.uri().path().to_string();
+    let outcome = state.rate_limiter.check(&key, &path);
+
+    if !outcome.allowed {
         let event = AuditEvent::new(
-            subject,
-            role.as_str().to_string(),
+            key.identity.clone(),
+            key.role.as_str().to_string(),
             "rate_limit.check".to_string(),
             path,
             "throttle".to_string(),
         )
-        .with_detail(json!({ "reason": "rate limit exceeded" }));
+        .with_detail(json!({
+            "reason": "rate limit exceeded",
+            "key": key.to_string(),
+            "remaining": outcome.remaining,
+        }));
         state.audit.record(event).await;
-        return Err(err);
+        return Err(rate_limit::rejection(outcome));
+    }
+
+    let mut response = next.run(req).await;
+    if let Ok(header_value) = HeaderValue::from_str(&outcome.remaining.to_string()) {
+        response
+            .headers_mut()
+            .insert(RATE_LIMIT_REMAINING_HEADER, header_value);
+    }
+    Ok(response)
+}
+
+/// Races the rest of the chain against the deadline `state.deadline`
+/// resolves for this request, aborting it with [`ApiError::Timeout`] if it
+/// fires first. Applied as the innermost layer on protected routes so it
+/// only bounds handler work, not the auth/rate-limit checks in front of it.
+pub async fn deadline_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let path = req.uri().path().to_string();
+    let requested_ms = req
+        .headers()
+        .get(REQUEST_DEADLINE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    let deadline = state.deadline.resolve(&path, requested_ms);
+    let started = Instant::now();
+
+    timeout(deadline, next.run(req))
+        .await
+        .map_err(|_| ApiError::Timeout {
+            elapsed: started.elapsed(),
+        })
+}
+
+/// Resolves the caller's identity for a request: verifies a bearer token
+/// against `state.credential_verifier` when one is presented, and only
+/// falls back to trusting the `x-lokan-role`/`x-lokan-subject` headers when
+/// `state.insecure_header_auth` is set (no token, or no verifier
+/// configured). Returns the token's claims alongside the identity so
+/// `rbac_guard` can additionally check the token's route scopes.
+fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(UserContext, Option<TokenClaims>), TokenError> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if let (Some(value), Some(verifier)) = (bearer, state.credential_verifier.as_ref()) {
+        let claims = verifier.verify(value, Utc::now())?;
+        return Ok((
+            UserContext {
+                subject: claims.subject.clone(),
+                role: claims.role,
+            },
+            Some(claims),
+        ));
     }
 
-    Ok(next.run(req).await)
+    if state.insecure_header_auth {
+        return Ok((
+            UserContext {
+                subject: extract_subject(headers),
+                role: extract_role(headers),
+            },
+            None,
+        ));
+    }
+
+    Err(TokenError::Missing)
 }
 
 pub async fn rbac_guard(
@@ -108,36 +234,55 @@ pub async fn rbac_guard(
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    let role = extract_role(req.headers());
-    let subject = extract_subject(req.headers());
     let method = req.method().clone();
     let path = req.uri().path().to_string();
 
-    let decision = state.policy.authorize(role, &method, &path);
+    let (user, claims) = match authenticate(&state, req.headers()) {
+        Ok(authenticated) => authenticated,
+        Err(err) => {
+            let event = AuditEvent::new(
+                "unknown".to_string(),
+                Role::Guest.as_str().to_string(),
+                err.audit_action().to_string(),
+                path.clone(),
+                "deny".to_string(),
+            )
+            .with_detail(json!({ "method": method.as_str(), "reason": err.to_string() }));
+            state.audit.record(event).await;
+            return Err(ApiError::Unauthorized);
+        }
+    };
+
+    let decision = state.policy.authorize(user.role, &method, &path);
+    let scope_allowed = claims
+        .as_ref()
+        .map(|claims| claims.allows_path(&path))
+        .unwrap_or(true);
     let action = decision
         .audit_action
         .clone()
         .unwrap_or_else(|| format!("{} {}", method, path));
     let mut event = AuditEvent::new(
-        subject.clone(),
-        role.as_str().to_string(),
+        user.subject.clone(),
+        user.role.as_str().to_string(),
         action,
         path.clone(),
         "deny".to_string(),
     )
     .with_detail(json!({ "method": method.as_str() }));
 
-    if !decision.allowed {
+    if !decision.allowed || !scope_allowed {
         state.audit.record(event.clone()).await;
         return Err(ApiError::Forbidden {
-            reason: format!("role {} is not permitted to access {}", role.as_str(), path),
+            reason: format!(
+                "role {} is not permitted to access {}",
+                user.role.as_str(),
+                path
+            ),
         });
     }
 
-    req.extensions_mut().insert(UserContext {
-        subject: subject.clone(),
-        role,
-    });
+    req.extensions_mut().insert(user);
 
     let response = next.run(req).await;
 
@@ -323,10 +468,59 @@ async fn tcp_probe(address: String) -> std::io::Result<(Vec<SocketAddr>, bool)>
     Ok((addrs, false))
 }
 
+/// Probes a registered upstream's `base_url` with the same
+/// `tcp_probe`/`normalize_target` machinery `diag_ping` uses interactively.
+async fn probe_upstream(base_url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(base_url) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    let address = normalize_target(&format!("{host}:{port}"), None);
+    matches!(tcp_probe(address).await, Ok((_, true)))
+}
+
+/// Probes every entry in `state.proxy`'s [`registry::UpstreamRegistry`] on
+/// `interval`, flipping each entry's `healthy` flag and evicting anything
+/// past its TTL. Runs for the lifetime of the service; intended to be
+/// spawned once at startup via [`spawn_health_checker`].
+pub async fn run_health_checks(state: Arc<AppState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let registry = state.proxy.registry();
+        registry.evict_expired();
+        for (name, entry) in registry.snapshot() {
+            let healthy = probe_upstream(&entry.base_url).await;
+            registry.set_healthy(&name, healthy);
+        }
+    }
+}
+
+/// Spawns [`run_health_checks`] as a background task, returning its handle.
+pub fn spawn_health_checker(
+    state: Arc<AppState>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run_health_checks(state, interval))
+}
+
 #[derive(Debug, Serialize)]
 struct RoutesResponse {
     guarded: Vec<GuardedRoute>,
     public: Vec<PublicRoute>,
+    upstreams: Vec<UpstreamStatus>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpstreamStatus {
+    name: String,
+    base_url: String,
+    healthy: bool,
+    expires_in_secs: u64,
 }
 
 #[derive(Debug, Serialize)]
@@ -358,12 +552,40 @@ async fn diag_routes(State(state): State<Arc<AppState>>) -> Json<RoutesResponse>
 
     guarded.sort_by(|a, b| a.pattern.cmp(&b.pattern));
 
-    let public = vec![PublicRoute {
-        path: "/metrics",
-        methods: &["GET"],
-    }];
+    let public = vec![
+        PublicRoute {
+            path: "/metrics",
+            methods: &["GET"],
+        },
+        PublicRoute {
+            path: "/stats",
+            methods: &["GET"],
+        },
+    ];
+
+    let mut upstreams: Vec<UpstreamStatus> = state
+        .proxy
+        .registry()
+        .snapshot()
+        .into_iter()
+        .map(|(name, entry)| UpstreamStatus {
+            name,
+            base_url: entry.base_url,
+            healthy: entry.healthy,
+            expires_in_secs: entry
+                .ttl
+                .checked_sub(entry.registered_at.elapsed())
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+        })
+        .collect();
+    upstreams.sort_by(|a, b| a.name.cmp(&b.name));
 
-    Json(RoutesResponse { guarded, public })
+    Json(RoutesResponse {
+        guarded,
+        public,
+        upstreams,
+    })
 }
 
 async fn metrics() -> impl IntoResponse {
@@ -377,6 +599,13 @@ async fn metrics() -> impl IntoResponse {
     )
 }
 
+/// JSON counterpart to `/metrics`'s Prometheus text, for dashboards and
+/// scripting that would rather deserialize structured data than parse the
+/// exposition format.
+async fn stats() -> Json<common_obs::MetricsSnapshot> {
+    Json(snapshot_metrics())
+}
+
 async fn health() -> Json<serde_json::Value> {
     Json(json!({ "status": "ok", "service": SERVICE_NAME }))
 }
@@ -415,6 +644,154 @@ async fn devices_not_implemented() -> impl IntoResponse {
     )
 }
 
+const DEFAULT_REGISTRATION_TTL_SECS: u64 = 30;
+
+fn default_ttl_secs() -> u64 {
+    DEFAULT_REGISTRATION_TTL_SECS
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterUpstreamRequest {
+    name: String,
+    base_url: String,
+    #[serde(default = "default_ttl_secs")]
+    ttl_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterUpstreamResponse {
+    name: String,
+    ttl_secs: u64,
+}
+
+/// Lets a backend service register itself as the upstream for
+/// `/v1/<name>/...`, refreshing its entry's TTL on every call. Newly
+/// registered entries start unhealthy; [`run_health_checks`] must confirm
+/// one before the proxy fallback will route to it.
+async fn register_upstream(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RegisterUpstreamRequest>,
+) -> Result<Json<RegisterUpstreamResponse>, ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::Validation {
+            message: "name is required".to_string(),
+        });
+    }
+    if reqwest::Url::parse(&request.base_url).is_err() {
+        return Err(ApiError::Validation {
+            message: "base_url must be a valid URL".to_string(),
+        });
+    }
+    if request.ttl_secs == 0 {
+        return Err(ApiError::Validation {
+            message: "ttl_secs must be greater than zero".to_string(),
+        });
+    }
+
+    state.proxy.registry().register(
+        name.to_string(),
+        request.base_url,
+        Duration::from_secs(request.ttl_secs),
+    );
+
+    Ok(Json(RegisterUpstreamResponse {
+        name: name.to_string(),
+        ttl_secs: request.ttl_secs,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Mints a fresh bearer token for the caller presenting a currently-valid
+/// one, so a client can renew its session before the old token's
+/// `not_after` without re-authenticating from scratch. Re-verifies the
+/// `Authorization` header itself rather than trusting the `UserContext`
+/// `rbac_guard` already inserted, since it needs the original token's
+/// `scopes`, which `UserContext` doesn't carry.
+async fn refresh_token(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<RefreshTokenResponse>, ApiError> {
+    let issuer = state.token_issuer.as_ref().ok_or(ApiError::Internal)?;
+    let verifier = state
+        .credential_verifier
+        .as_ref()
+        .ok_or(ApiError::Internal)?;
+
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(ApiError::Unauthorized)?;
+    let claims = verifier
+        .verify(bearer, Utc::now())
+        .map_err(|_| ApiError::Unauthorized)?;
+
+    let (token, expires_at) = issuer
+        .issue(claims.subject, claims.role, claims.scopes, Utc::now())
+        .map_err(|_| ApiError::Internal)?;
+
+    Ok(Json(RefreshTokenResponse { token, expires_at }))
+}
+
+/// Catches any `/v1/*` request that didn't match a fixed route and, if its
+/// leading path segment has a configured upstream, reverse-proxies it
+/// there. Runs behind the same `rate_limit_guard`/`rbac_guard` layers as
+/// the fixed routes, so a proxied request is authorized exactly like one
+/// the gateway handles itself. Falls through to a plain 404 if no upstream
+/// is registered for the segment.
+async fn proxy_fallback(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    req: Request<Body>,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let Some(rest) = path.strip_prefix("/v1/") else {
+        return not_found_response(&path);
+    };
+    let (prefix, remainder) = rest.split_once('/').unwrap_or((rest, ""));
+
+    if !state.proxy.handles(prefix) {
+        return not_found_response(&path);
+    }
+
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    match state
+        .proxy
+        .forward(prefix, remainder, req, &user, &request_id)
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => err.into_response(),
+    }
+}
+
+fn not_found_response(path: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "error": {
+                "code": "not_found",
+                "message": format!("no route or upstream registered for {path}"),
+            }
+        })),
+    )
+        .into_response()
+}
+
 pub fn load_policy(config: &config::ApiGatewayConfig) -> Result<RbacPolicy, PolicyError> {
     RbacPolicy::from_path(&config.rbac_policy_path)
 }
@@ -0,0 +1,261 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use hyper_util::rt::TokioIo;
+use hyper_util::server::conn::auto::Builder as HyperBuilder;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::UnixListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tower::Service;
+
+/// A graceful-shutdown trigger shared by every listener path: a `watch`
+/// receiver that fires once when `common_config::shutdown::spawn` trips,
+/// plus how long a listener lets its in-flight connections drain before
+/// giving up on them.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    pub rx: watch::Receiver<bool>,
+    pub grace_period: Duration,
+}
+
+impl ShutdownSignal {
+    pub fn new(rx: watch::Receiver<bool>, grace_period: Duration) -> Self {
+        Self { rx, grace_period }
+    }
+
+    /// Resolves once shutdown has been triggered.
+    async fn tripped(&self) {
+        let mut rx = self.rx.clone();
+        if *rx.borrow() {
+            return;
+        }
+        let _ = rx.changed().await;
+    }
+}
+
+/// Where to bind the gateway's [`Router`]. Parsed from a
+/// `ListenerConfig::address` of the form `tcp://host:port` or
+/// `unix:/path/to/socket`, following the same split the Rocket listener
+/// rework settled on for TCP vs. Unix-domain-socket listeners.
+///
+/// A TCP listener always terminates TLS. A Unix listener terminates TLS
+/// only when configured to — local-only deployments (a sidecar sharing a
+/// loopback socket, or a host socket sitting behind another proxy) can
+/// skip in-process TLS entirely.
+pub enum Listener {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListenerError {
+    #[error("invalid listener address {0:?}: expected tcp://host:port or unix:/path")]
+    InvalidAddress(String),
+    #[error("invalid listener socket address: {0}")]
+    InvalidSocketAddr(#[from] std::net::AddrParseError),
+    #[error("failed to prepare unix socket at {path}: {source}")]
+    UnixSocket {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("listener failed: {0}")]
+    Serve(#[source] std::io::Error),
+}
+
+impl Listener {
+    /// Parses `address`, falling back to `fallback_tcp` (the legacy
+    /// `bind_address`/`port` pair) when it's empty, so existing TCP
+    /// deployments don't need to set anything new.
+    pub fn parse(address: &str, fallback_tcp: SocketAddr) -> Result<Self, ListenerError> {
+        if address.is_empty() {
+            return Ok(Listener::Tcp(fallback_tcp));
+        }
+        if let Some(rest) = address.strip_prefix("tcp://") {
+            return Ok(Listener::Tcp(rest.parse()?));
+        }
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(Listener::Unix(PathBuf::from(path)));
+        }
+        Err(ListenerError::InvalidAddress(address.to_string()))
+    }
+
+    /// Serves `router` on this listener until it fails, or until `shutdown`
+    /// trips and in-flight connections have drained. `tls`
+    /// is required for [`Listener::Tcp`] and optional for
+    /// [`Listener::Unix`]; `unix_socket_mode` is ignored for
+    /// [`Listener::Tcp`].
+    pub async fn serve(
+        self,
+        router: Router,
+        tls: Option<RustlsConfig>,
+        unix_socket_mode: u32,
+        shutdown: ShutdownSignal,
+    ) -> Result<(), ListenerError> {
+        match self {
+            Listener::Tcp(addr) => {
+                let tls = tls.expect("a TCP listener requires TLS configuration");
+                tracing::info!(%addr, "listening on tcp");
+
+                let handle = axum_server::Handle::new();
+                let shutdown_handle = handle.clone();
+                let grace_period = shutdown.grace_period;
+                tokio::spawn(async move {
+                    shutdown.tripped().await;
+                    shutdown_handle.graceful_shutdown(Some(grace_period));
+                });
+
+                axum_server::bind_rustls(addr, tls)
+                    .handle(handle)
+                    .serve(router.into_make_service())
+                    .await
+                    .map_err(ListenerError::Serve)
+            }
+            Listener::Unix(path) => {
+                replace_unix_socket(&path).await?;
+                let listener =
+                    UnixListener::bind(&path).map_err(|source| ListenerError::UnixSocket {
+                        path: path.clone(),
+                        source,
+                    })?;
+                set_unix_socket_mode(&path, unix_socket_mode).await?;
+                tracing::info!(path = %path.display(), tls = tls.is_some(), "listening on unix socket");
+
+                match tls {
+                    Some(tls) => serve_unix_tls(listener, router, tls, shutdown).await,
+                    None => serve_unix_plain(listener, router, shutdown).await,
+                }
+            }
+        }
+    }
+}
+
+/// Removes a stale socket file left behind by a previous run (a fresh
+/// bind to an existing path otherwise fails with `AddrInUse`), and makes
+/// sure the parent directory exists.
+async fn replace_unix_socket(path: &Path) -> Result<(), ListenerError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|source| ListenerError::UnixSocket {
+                path: path.to_path_buf(),
+                source,
+            })?;
+    }
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(source) => Err(ListenerError::UnixSocket {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+#[cfg(unix)]
+async fn set_unix_socket_mode(path: &Path, mode: u32) -> Result<(), ListenerError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let permissions = std::fs::Permissions::from_mode(mode);
+    tokio::fs::set_permissions(path, permissions)
+        .await
+        .map_err(|source| ListenerError::UnixSocket {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+#[cfg(not(unix))]
+async fn set_unix_socket_mode(_path: &Path, _mode: u32) -> Result<(), ListenerError> {
+    Ok(())
+}
+
+async fn serve_unix_plain(
+    listener: UnixListener,
+    router: Router,
+    shutdown: ShutdownSignal,
+) -> Result<(), ListenerError> {
+    axum::serve(listener, router.into_make_service())
+        .with_graceful_shutdown(async move { shutdown.tripped().await })
+        .await
+        .map_err(ListenerError::Serve)
+}
+
+/// Terminates TLS manually over the unix socket: `axum_server` only knows
+/// how to bind TCP sockets, so a TLS-over-unix listener accepts
+/// connections itself, wraps each in the same `rustls` server config used
+/// for TCP, and hands the decrypted stream to the router via hyper's
+/// auto (HTTP/1.1 or HTTP/2) connection builder.
+async fn serve_unix_tls(
+    listener: UnixListener,
+    router: Router,
+    tls: RustlsConfig,
+    shutdown: ShutdownSignal,
+) -> Result<(), ListenerError> {
+    let grace_period = shutdown.grace_period;
+    let mut shutdown_rx = shutdown.rx;
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _addr) = accepted.map_err(ListenerError::Serve)?;
+                // Rebuilt per connection (instead of once outside the loop) so a
+                // hot-reloaded TLS config via `RustlsConfig::reload_from_config`
+                // takes effect on the next connection, same as the TCP listener.
+                let acceptor = tokio_rustls::TlsAcceptor::from(tls.get_inner().await);
+                let router = router.clone();
+
+                connections.spawn(async move {
+                    let tls_stream = match acceptor.accept(stream).await {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            tracing::warn!(%err, "tls handshake over unix socket failed");
+                            return;
+                        }
+                    };
+
+                    let mut make_service = router.into_make_service();
+                    let tower_service = make_service
+                        .call(())
+                        .await
+                        .expect("IntoMakeService's error type is Infallible");
+                    let service = TowerToHyperService::new(tower_service);
+
+                    if let Err(err) = HyperBuilder::new(hyper_util::rt::TokioExecutor::new())
+                        .serve_connection(TokioIo::new(tls_stream), service)
+                        .await
+                    {
+                        tracing::warn!(%err, "connection over unix socket failed");
+                    }
+                });
+            }
+            _ = shutdown_rx.changed() => {
+                tracing::info!("shutdown triggered, draining unix tls connections");
+                break;
+            }
+            // Reaps finished connection tasks as they complete so `connections`
+            // doesn't grow without bound for the life of the listener; the
+            // `if` guard keeps this branch from busy-looping once the set is
+            // empty, since `join_next` resolves to `None` immediately then.
+            Some(result) = connections.join_next(), if !connections.is_empty() => {
+                if let Err(err) = result {
+                    tracing::warn!(%err, "unix tls connection task panicked");
+                }
+            }
+        }
+    }
+
+    let drain = async {
+        while connections.join_next().await.is_some() {}
+    };
+    if tokio::time::timeout(grace_period, drain).await.is_err() {
+        tracing::warn!("grace period elapsed with connections still open; aborting them");
+        connections.shutdown().await;
+    }
+    Ok(())
+}
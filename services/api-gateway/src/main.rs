@@ -1,46 +1,92 @@
 mod audit;
+mod audit_sink;
+mod commissioning;
 mod config;
+mod credentials;
+mod deadline;
 mod device_registry;
 mod error;
+#[cfg(feature = "http3")]
+mod http3;
+mod listener;
+mod proxy;
 mod rate_limit;
 mod rbac;
+mod registry;
+mod reload;
+mod session;
+mod token_issuer;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use audit::{AuditClient, AuditEvent};
+use audit_sink::sinks_from_config;
 use axum::body::Body;
 use axum::extract::{Extension, State};
-use axum::http::{Request, StatusCode};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
 use axum::middleware::{from_fn_with_state, Next};
-use axum::response::Response;
-use axum::routing::get;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
 use axum::{Json, Router};
 use axum_server::tls_rustls::RustlsConfig;
-use common_config::load;
+use chrono::{DateTime, Utc};
+use commissioning::{ble_handshake, submit_csr, verify_credentials};
+use common_config::{layered_config_path, load_layered, Tripwire};
 use common_mdns::announce;
-use common_msgbus::{NatsBus, NatsConfig};
-use config::{ApiGatewayConfig, TlsConfig};
+use common_msgbus::{MessageBus, NatsBus, NatsConfig, DEFAULT_MAX_PAYLOAD};
+use config::{ApiGatewayConfig, CredentialsConfig, ReloadableConfig, TlsConfig, TokenIssuerConfig};
+use credentials::{CredentialVerifier, TokenClaims, TokenError};
+use deadline::{DeadlinePolicy, REQUEST_DEADLINE_HEADER};
 use device_registry::DeviceRegistryClient;
 use error::ApiError;
-use rate_limit::RateLimiter;
+use listener::{Listener, ShutdownSignal};
+use proxy::ProxyClient;
+use rate_limit::{RateKey, RateLimiter, RATE_LIMIT_REMAINING_HEADER};
 use rbac::{PolicyError, RbacPolicy, Role};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
 use rustls::server::WebPkiClientVerifier;
 use rustls::{RootCertStore, ServerConfig as RustlsServerConfig};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use session::SessionStore;
+use token_issuer::TokenIssuer;
 use tokio::fs;
 use tracing_subscriber::EnvFilter;
 
 const SERVICE_NAME: &str = "api-gateway";
 const ROLE_HEADER: &str = "x-lokan-role";
 const SUBJECT_HEADER: &str = "x-lokan-subject";
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
 #[derive(Clone)]
 struct AppState {
-    policy: Arc<RbacPolicy>,
+    policy: Arc<ReloadableConfig<RbacPolicy>>,
     audit: AuditClient,
-    rate_limiter: RateLimiter,
+    rate_limiter: Arc<ReloadableConfig<RateLimiter>>,
     device_client: DeviceRegistryClient,
+    deadline: DeadlinePolicy,
+    /// Pre-rendered `Alt-Svc` header advertising the QUIC endpoint, set
+    /// when `Http3Config::enabled`; `None` leaves TCP responses
+    /// unannounced, e.g. because HTTP/3 is disabled or this binary wasn't
+    /// built with the `http3` feature.
+    alt_svc: Option<HeaderValue>,
+    /// Tripped once a shutdown signal is received, so `shutdown_guard` can
+    /// start rejecting new requests while in-flight ones keep running
+    /// until the listener's own grace period finishes draining them.
+    shutdown: Tripwire,
+    /// Verifies `Authorization: Bearer` tokens. `None` when
+    /// `credentials.public_key_path` couldn't be loaded, in which case
+    /// `rbac_guard` falls back to `insecure_header_auth` (or denies
+    /// everything, if that's also unset).
+    credential_verifier: Option<CredentialVerifier>,
+    insecure_header_auth: bool,
+    /// Mints the tokens `credential_verifier` checks. `None` disables
+    /// `/v1/auth/refresh`, mirroring `config::TokenIssuerConfig::enabled`.
+    token_issuer: Option<TokenIssuer>,
+    proxy: ProxyClient,
+    bus: Arc<dyn MessageBus>,
+    session_store: SessionStore,
 }
 
 #[derive(Clone, Debug)]
@@ -53,15 +99,18 @@ struct UserContext {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     init_tracing();
 
-    let config = load::<ApiGatewayConfig>()?;
+    let config = load_layered::<ApiGatewayConfig>()?;
     let addr = config.socket_addr()?;
-    tracing::info!(%addr, service = SERVICE_NAME, "starting service");
+    for endpoint in config.endpoints()? {
+        tracing::info!(%endpoint, service = SERVICE_NAME, "starting service");
+    }
 
     let bus_config = NatsConfig {
         url: config.bus.url.clone(),
         request_timeout: config.bus.request_timeout(),
+        max_payload: DEFAULT_MAX_PAYLOAD,
     };
-    let _bus = NatsBus::connect(bus_config).await?;
+    let bus: Arc<dyn MessageBus> = Arc::new(NatsBus::connect(bus_config).await?);
 
     let _mdns = if config.announce_mdns {
         Some(announce(&config.mdns_service, config.port).await?)
@@ -70,29 +119,159 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
-    let policy = Arc::new(load_policy(&config)?);
-    let audit = AuditClient::new(config.audit.endpoint.clone(), config.audit.enabled);
-    let rate_limiter = RateLimiter::new(&config.rate_limit);
+    // HTTP/3 gets its own announcement (same service label, its own UDP
+    // port) so a client whose resolver exposes both records can prefer
+    // QUIC without first connecting over TCP to discover it.
+    let _mdns_quic = if config.announce_mdns && config.http3.enabled {
+        Some(announce(&config.mdns_service, config.http3.port).await?)
+    } else {
+        None
+    };
+
+    let policy = Arc::new(ReloadableConfig::new(load_policy(&config)?));
+    let audit = AuditClient::new(&config.audit, sinks_from_config(&config.audit));
+    let rate_limiter = Arc::new(ReloadableConfig::new(RateLimiter::new(&config.rate_limit)));
+    spawn_idle_sweeper(rate_limiter.clone());
     let device_client = DeviceRegistryClient::new(config.device_registry_url.clone())
         .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+    let deadline = DeadlinePolicy::new(&config.deadline);
+    let alt_svc = alt_svc_header(&config.http3);
+    let (shutdown_tripwire, shutdown_rx) = common_config::shutdown::spawn(config.shutdown.clone());
+    spawn_shutdown_watchdog(
+        shutdown_rx.clone(),
+        config.shutdown.grace_period(),
+        config.shutdown.force_period(),
+    );
+    let credential_verifier = load_credential_verifier(&config.credentials).await;
+    let token_issuer = load_token_issuer(&config.token_issuer).await;
+    let proxy = ProxyClient::new(&config.proxy.upstreams)
+        .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+    let session_store = SessionStore::new();
 
     let state = Arc::new(AppState {
         policy,
         audit,
         rate_limiter,
         device_client,
+        deadline,
+        alt_svc,
+        shutdown: shutdown_tripwire,
+        credential_verifier,
+        insecure_header_auth: config.credentials.insecure_header_auth,
+        token_issuer,
+        proxy,
+        bus,
+        session_store,
     });
 
     let router = build_router(state.clone());
-    let rustls_config = build_rustls_config(&config.tls).await?;
 
-    axum_server::bind_rustls(addr, rustls_config)
-        .serve(router.into_make_service())
+    let listener = Listener::parse(&config.listener.address, addr)?;
+    let tls_required = !matches!(listener, Listener::Unix(_)) || config.listener.tls_enabled;
+    let rustls_config = if tls_required {
+        Some(build_rustls_config(&config.tls).await?)
+    } else {
+        tracing::warn!(
+            service = SERVICE_NAME,
+            "serving unix socket without TLS; the socket itself must be the trust boundary"
+        );
+        None
+    };
+
+    reload::spawn(
+        state.clone(),
+        rustls_config.clone(),
+        layered_config_path::<ApiGatewayConfig>(),
+        config.rbac_policy_path.clone(),
+    );
+
+    spawn_http3_listener(&config, router.clone(), addr);
+
+    let shutdown_signal = ShutdownSignal::new(shutdown_rx, config.shutdown.grace_period());
+    listener
+        .serve(
+            router,
+            rustls_config,
+            config.listener.unix_socket_mode,
+            shutdown_signal,
+        )
         .await?;
 
     Ok(())
 }
 
+/// Last-resort watchdog for a shutdown that doesn't drain cleanly: once
+/// triggered, the listener itself already gives in-flight connections up
+/// to `grace_period` to drain, so if the process is still alive
+/// `force_period` after that it's stuck on something the graceful path
+/// can't resolve (a leaked connection, a hung upstream call) and exiting
+/// is safer than hanging a rolling restart forever.
+fn spawn_shutdown_watchdog(
+    mut shutdown_rx: tokio::sync::watch::Receiver<bool>,
+    grace_period: Duration,
+    force_period: Duration,
+) {
+    tokio::spawn(async move {
+        if shutdown_rx.changed().await.is_err() {
+            return;
+        }
+        tokio::time::sleep(grace_period + force_period).await;
+        tracing::error!("graceful shutdown exceeded force period; exiting now");
+        std::process::exit(1);
+    });
+}
+
+/// Builds the `Alt-Svc` header value advertising the QUIC endpoint, when
+/// HTTP/3 is enabled in config and this binary was built with the `http3`
+/// feature. `ma=86400` lets a client cache the advertisement for 24h.
+fn alt_svc_header(http3: &config::Http3Config) -> Option<HeaderValue> {
+    if !http3.enabled || !cfg!(feature = "http3") {
+        return None;
+    }
+    HeaderValue::from_str(&format!("h3=\":{}\"; ma=86400", http3.port)).ok()
+}
+
+#[cfg(feature = "http3")]
+fn spawn_http3_listener(config: &ApiGatewayConfig, router: Router, addr: std::net::SocketAddr) {
+    if !config.http3.enabled {
+        return;
+    }
+    let http3_addr = std::net::SocketAddr::new(addr.ip(), config.http3.port);
+    let tls = config.tls.clone();
+    tokio::spawn(async move {
+        if let Err(err) = http3::serve(router, http3_addr, &tls).await {
+            tracing::error!(%err, "http/3 listener failed");
+        }
+    });
+}
+
+#[cfg(not(feature = "http3"))]
+fn spawn_http3_listener(config: &ApiGatewayConfig, _router: Router, _addr: std::net::SocketAddr) {
+    if config.http3.enabled {
+        tracing::warn!(
+            "http3.enabled is set but this binary was built without the `http3` feature; \
+             no quic listener was started"
+        );
+    }
+}
+
+/// Spawns the background sweep for `rate_limiter`'s idle buckets. Reads the
+/// TTL and the limiter itself through the `ReloadableConfig` handle on
+/// every tick rather than capturing either once, so a reload that swaps
+/// in a new `RateLimiter` (with a possibly different
+/// `per_client_idle_ttl_secs`) is picked up without restarting the
+/// sweeper.
+fn spawn_idle_sweeper(rate_limiter: Arc<ReloadableConfig<RateLimiter>>) {
+    tokio::spawn(async move {
+        loop {
+            let ttl = rate_limiter.current().idle_ttl();
+            let period = (ttl / 2).max(Duration::from_secs(1));
+            tokio::time::sleep(period).await;
+            rate_limiter.current().sweep_idle(ttl);
+        }
+    });
+}
+
 fn init_tracing() {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
     let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
@@ -106,33 +285,115 @@ fn build_router(state: Arc<AppState>) -> Router {
             "/v1/devices",
             get(list_devices).post(devices_not_implemented),
         )
+        .route("/v1/commissioning/ble/handshake", post(ble_handshake))
+        .route("/v1/commissioning/csr", post(submit_csr))
+        .route("/v1/commissioning/verify", post(verify_credentials))
+        .route("/v1/registry/register", post(register_upstream))
+        .route("/v1/auth/refresh", post(refresh_token))
+        .fallback(proxy_fallback)
+        .layer(from_fn_with_state(state.clone(), deadline_guard))
         .layer(from_fn_with_state(state.clone(), rate_limit_guard))
         .layer(from_fn_with_state(state.clone(), rbac_guard))
+        .layer(from_fn_with_state(state.clone(), alt_svc_guard))
+        .layer(from_fn_with_state(state.clone(), shutdown_guard))
         .layer(Extension(state))
 }
 
+/// Rejects new requests once shutdown has begun, so a rolling restart
+/// stops admitting work the listener is already trying to drain.
+/// Requests already past this middleware when the tripwire flips keep
+/// running to completion.
+async fn shutdown_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if state.shutdown.is_tripped() {
+        return Err(ApiError::Unavailable {
+            reason: "service is shutting down".to_string(),
+        });
+    }
+    Ok(next.run(req).await)
+}
+
+/// Advertises the QUIC endpoint to clients speaking HTTP/1.1 or HTTP/2
+/// over TCP, so they can opt into HTTP/3 on their next request. A no-op
+/// when `Http3Config::enabled` is unset.
+async fn alt_svc_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    if let Some(header_value) = &state.alt_svc {
+        response
+            .headers_mut()
+            .insert(axum::http::header::ALT_SVC, header_value.clone());
+    }
+    response
+}
+
+async fn deadline_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let path = req.uri().path().to_string();
+    let requested_ms = req
+        .headers()
+        .get(REQUEST_DEADLINE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    let deadline = state.deadline.resolve(&path, requested_ms);
+    let started = std::time::Instant::now();
+
+    tokio::time::timeout(deadline, next.run(req))
+        .await
+        .map_err(|_| ApiError::Timeout {
+            elapsed: started.elapsed(),
+        })
+}
+
 async fn rate_limit_guard(
     State(state): State<Arc<AppState>>,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    if let Err(err) = state.rate_limiter.check().await {
-        let role = extract_role(req.headers());
-        let subject = extract_subject(req.headers());
-        let path = req.uri().path().to_string();
+    let (subject, role) = match req.extensions().get::<UserContext>() {
+        Some(user) => (user.subject.clone(), user.role),
+        None => (extract_subject(req.headers()), extract_role(req.headers())),
+    };
+    let path = req.uri().path().to_string();
+    // Every anonymous caller shares the `anonymous` identity's buckets here:
+    // this binary's listeners don't attach `ConnectInfo`, so there is no
+    // remote-IP fallback like the one `rate_key` in lib.rs applies.
+    let key = RateKey::new(subject, role);
+    let outcome = state.rate_limiter.current().check(&key, &path);
+
+    if !outcome.allowed {
         let event = AuditEvent::new(
-            subject,
-            role.as_str().to_string(),
+            key.identity.clone(),
+            key.role.as_str().to_string(),
             "rate_limit.check".to_string(),
             path,
             "throttle".to_string(),
         )
-        .with_detail(json!({ "reason": "rate limit exceeded" }));
+        .with_detail(json!({
+            "reason": "rate limit exceeded",
+            "key": key.to_string(),
+            "remaining": outcome.remaining,
+        }));
         state.audit.record(event).await;
-        return Err(err);
+        return Err(rate_limit::rejection(outcome));
     }
 
-    Ok(next.run(req).await)
+    let mut response = next.run(req).await;
+    if let Ok(header_value) = HeaderValue::from_str(&outcome.remaining.to_string()) {
+        response
+            .headers_mut()
+            .insert(RATE_LIMIT_REMAINING_HEADER, header_value);
+    }
+    Ok(response)
 }
 
 async fn rbac_guard(
@@ -140,36 +401,55 @@ async fn rbac_guard(
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    let role = extract_role(req.headers());
-    let subject = extract_subject(req.headers());
     let method = req.method().clone();
     let path = req.uri().path().to_string();
 
-    let decision = state.policy.authorize(role, &method, &path);
+    let (user, claims) = match authenticate(&state, req.headers()) {
+        Ok(authenticated) => authenticated,
+        Err(err) => {
+            let event = AuditEvent::new(
+                "unknown".to_string(),
+                Role::Guest.as_str().to_string(),
+                err.audit_action().to_string(),
+                path.clone(),
+                "deny".to_string(),
+            )
+            .with_detail(json!({ "method": method.as_str(), "reason": err.to_string() }));
+            state.audit.record(event).await;
+            return Err(ApiError::Unauthorized);
+        }
+    };
+
+    let decision = state.policy.current().authorize(user.role, &method, &path);
+    let scope_allowed = claims
+        .as_ref()
+        .map(|claims| claims.allows_path(&path))
+        .unwrap_or(true);
     let action = decision
         .audit_action
         .clone()
         .unwrap_or_else(|| format!("{} {}", method, path));
     let mut event = AuditEvent::new(
-        subject.clone(),
-        role.as_str().to_string(),
+        user.subject.clone(),
+        user.role.as_str().to_string(),
         action,
         path.clone(),
         "deny".to_string(),
     )
     .with_detail(json!({ "method": method.as_str() }));
 
-    if !decision.allowed {
+    if !decision.allowed || !scope_allowed {
         state.audit.record(event.clone()).await;
         return Err(ApiError::Forbidden {
-            reason: format!("role {} is not permitted to access {}", role.as_str(), path),
+            reason: format!(
+                "role {} is not permitted to access {}",
+                user.role.as_str(),
+                path
+            ),
         });
     }
 
-    req.extensions_mut().insert(UserContext {
-        subject: subject.clone(),
-        role,
-    });
+    req.extensions_mut().insert(user);
 
     let response = next.run(req).await;
 
@@ -217,13 +497,269 @@ async fn devices_not_implemented() -> impl axum::response::IntoResponse {
     )
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RefreshTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Mints a fresh bearer token for the caller presenting a currently-valid
+/// one, so a client can renew its session before the old token's
+/// `not_after` without re-authenticating from scratch. Re-verifies the
+/// `Authorization` header itself rather than trusting the `UserContext`
+/// `rbac_guard` already inserted, since it needs the original token's
+/// `scopes`, which `UserContext` doesn't carry.
+async fn refresh_token(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<RefreshTokenResponse>, ApiError> {
+    let issuer = state.token_issuer.as_ref().ok_or(ApiError::Internal)?;
+    let verifier = state
+        .credential_verifier
+        .as_ref()
+        .ok_or(ApiError::Internal)?;
+
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(ApiError::Unauthorized)?;
+    let claims = verifier
+        .verify(bearer, Utc::now())
+        .map_err(|_| ApiError::Unauthorized)?;
+
+    let (token, expires_at) = issuer
+        .issue(claims.subject, claims.role, claims.scopes, Utc::now())
+        .map_err(|_| ApiError::Internal)?;
+
+    Ok(Json(RefreshTokenResponse { token, expires_at }))
+}
+
+const DEFAULT_REGISTRATION_TTL_SECS: u64 = 30;
+
+fn default_ttl_secs() -> u64 {
+    DEFAULT_REGISTRATION_TTL_SECS
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterUpstreamRequest {
+    name: String,
+    base_url: String,
+    #[serde(default = "default_ttl_secs")]
+    ttl_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisterUpstreamResponse {
+    name: String,
+    ttl_secs: u64,
+}
+
+/// Lets a backend service register itself as the upstream for
+/// `/v1/<name>/...`, refreshing its entry's TTL on every call. Newly
+/// registered entries start unhealthy; nothing in main.rs runs
+/// `run_health_checks` yet, so a self-registered upstream won't actually
+/// be routed to until something flips it healthy.
+async fn register_upstream(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RegisterUpstreamRequest>,
+) -> Result<Json<RegisterUpstreamResponse>, ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::Validation {
+            message: "name is required".to_string(),
+        });
+    }
+    if reqwest::Url::parse(&request.base_url).is_err() {
+        return Err(ApiError::Validation {
+            message: "base_url must be a valid URL".to_string(),
+        });
+    }
+    if request.ttl_secs == 0 {
+        return Err(ApiError::Validation {
+            message: "ttl_secs must be greater than zero".to_string(),
+        });
+    }
+
+    state.proxy.registry().register(
+        name.to_string(),
+        request.base_url,
+        Duration::from_secs(request.ttl_secs),
+    );
+
+    Ok(Json(RegisterUpstreamResponse {
+        name: name.to_string(),
+        ttl_secs: request.ttl_secs,
+    }))
+}
+
+/// Catches any request that didn't match a fixed route and, if its leading
+/// `/v1/<prefix>` path segment has a configured or self-registered
+/// upstream, reverse-proxies it there. Runs behind the same
+/// `rate_limit_guard`/`rbac_guard` layers as the fixed routes, so a
+/// proxied request is authorized exactly like one the gateway handles
+/// itself. Falls through to a plain 404 if no upstream is registered for
+/// the segment.
+async fn proxy_fallback(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserContext>,
+    req: Request<Body>,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let Some(rest) = path.strip_prefix("/v1/") else {
+        return not_found_response(&path);
+    };
+    let (prefix, remainder) = rest.split_once('/').unwrap_or((rest, ""));
+
+    if !state.proxy.handles(prefix) {
+        return not_found_response(&path);
+    }
+
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    match state
+        .proxy
+        .forward(prefix, remainder, req, &user, &request_id)
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => err.into_response(),
+    }
+}
+
+fn not_found_response(path: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "error": {
+                "code": "not_found",
+                "message": format!("no route or upstream registered for {path}"),
+            }
+        })),
+    )
+        .into_response()
+}
+
 fn load_policy(config: &ApiGatewayConfig) -> Result<RbacPolicy, PolicyError> {
     RbacPolicy::from_path(&config.rbac_policy_path)
 }
 
+/// Loads the Ed25519 public key `rbac_guard` verifies bearer tokens
+/// against. Missing or unparsable key material disables bearer-token
+/// verification rather than failing startup, since a deployment may
+/// intentionally run with `insecure_header_auth` (or, if neither is usable,
+/// `rbac_guard` fails closed and denies every request).
+async fn load_credential_verifier(config: &CredentialsConfig) -> Option<CredentialVerifier> {
+    let pem = match fs::read_to_string(&config.public_key_path).await {
+        Ok(pem) => pem,
+        Err(err) => {
+            tracing::warn!(
+                %err,
+                path = %config.public_key_path.display(),
+                "credentials public key not found; bearer-token verification disabled"
+            );
+            return None;
+        }
+    };
+    match CredentialVerifier::from_public_key_pem(&pem) {
+        Ok(verifier) => Some(verifier),
+        Err(err) => {
+            tracing::warn!(%err, "failed to parse credentials public key; bearer-token verification disabled");
+            None
+        }
+    }
+}
+
+/// Loads the signing key that mints the tokens `load_credential_verifier`'s
+/// key verifies, when `config.enabled`. Unlike `load_credential_verifier`,
+/// a configured-but-unreadable key is logged at error level rather than as
+/// a warning, since `enabled: true` is an explicit opt-in to running
+/// `/v1/auth/refresh`; startup still continues, with that endpoint
+/// unavailable.
+async fn load_token_issuer(config: &TokenIssuerConfig) -> Option<TokenIssuer> {
+    if !config.enabled {
+        return None;
+    }
+    let pem = match fs::read_to_string(&config.private_key_path).await {
+        Ok(pem) => pem,
+        Err(err) => {
+            tracing::error!(
+                %err,
+                path = %config.private_key_path.display(),
+                "token_issuer.enabled is set but its private key could not be read; \
+                 /v1/auth/refresh will be unavailable"
+            );
+            return None;
+        }
+    };
+    match TokenIssuer::from_private_key_pem(&pem, config.ttl()) {
+        Ok(issuer) => Some(issuer),
+        Err(err) => {
+            tracing::error!(%err, "failed to parse token_issuer private key; /v1/auth/refresh will be unavailable");
+            None
+        }
+    }
+}
+
+/// Resolves the caller's identity for a request: verifies a bearer token
+/// against `state.credential_verifier` when one is presented, and only
+/// falls back to trusting the `x-lokan-role`/`x-lokan-subject` headers when
+/// `state.insecure_header_auth` is set (no token, or no verifier
+/// configured). Returns the token's claims alongside the identity so
+/// `rbac_guard` can additionally check the token's route scopes.
+fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(UserContext, Option<TokenClaims>), TokenError> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if let (Some(value), Some(verifier)) = (bearer, state.credential_verifier.as_ref()) {
+        let claims = verifier.verify(value, Utc::now())?;
+        return Ok((
+            UserContext {
+                subject: claims.subject.clone(),
+                role: claims.role,
+            },
+            Some(claims),
+        ));
+    }
+
+    if state.insecure_header_auth {
+        return Ok((
+            UserContext {
+                subject: extract_subject(headers),
+                role: extract_role(headers),
+            },
+            None,
+        ));
+    }
+
+    Err(TokenError::Missing)
+}
+
 async fn build_rustls_config(
     config: &TlsConfig,
 ) -> Result<RustlsConfig, Box<dyn std::error::Error>> {
+    let server_config = build_rustls_server_config(config).await?;
+    Ok(RustlsConfig::from_config(Arc::new(server_config)))
+}
+
+/// The raw `rustls::ServerConfig` half of [`build_rustls_config`], split
+/// out so `reload::reload` can rebuild just the `Arc` a live
+/// [`RustlsConfig`] reloads from, without tearing down and replacing the
+/// handle itself.
+pub(crate) async fn build_rustls_server_config(
+    config: &TlsConfig,
+) -> Result<RustlsServerConfig, Box<dyn std::error::Error>> {
     let certs = load_certs(&config.cert_path).await?;
     let key = load_private_key(&config.key_path).await?;
     let client_store = load_client_ca(&config.client_ca_path).await?;
@@ -235,7 +771,7 @@ async fn build_rustls_config(
         .with_client_cert_verifier(client_verifier)
         .with_single_cert(certs, key)?;
 
-    Ok(RustlsConfig::from_config(Arc::new(server_config)))
+    Ok(server_config)
 }
 
 async fn load_certs(
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderValue, Request};
+use axum::response::Response;
+use http_body_util::BodyExt;
+use reqwest::Url;
+
+use crate::error::ApiError;
+use crate::registry::UpstreamRegistry;
+use crate::{UserContext, REQUEST_ID_HEADER, ROLE_HEADER, SUBJECT_HEADER};
+
+/// Headers that must not be forwarded verbatim between the gateway and an
+/// upstream: the hop-by-hop set from RFC 7230 §6.1, plus `host` and
+/// `content-length`, which describe the connection to the gateway itself
+/// rather than the one the gateway is about to open to the upstream.
+const STRIPPED_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "content-length",
+];
+
+/// Reverse-proxies `/v1/<prefix>/...` requests to whichever internal
+/// service `prefix` is configured for, streaming both the request and
+/// response bodies rather than buffering them. Resolves `prefix` against
+/// the statically configured upstream map first, falling back to the
+/// dynamic, health-gated [`UpstreamRegistry`] for self-registered services.
+#[derive(Clone)]
+pub struct ProxyClient {
+    client: reqwest::Client,
+    upstreams: BTreeMap<String, Url>,
+    registry: UpstreamRegistry,
+}
+
+impl ProxyClient {
+    pub fn new(upstreams: &BTreeMap<String, String>) -> Result<Self, ApiError> {
+        let mut parsed = BTreeMap::new();
+        for (prefix, base_url) in upstreams {
+            let url = Url::parse(base_url).map_err(|_| ApiError::Internal)?;
+            parsed.insert(prefix.clone(), url);
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            upstreams: parsed,
+            registry: UpstreamRegistry::new(),
+        })
+    }
+
+    /// The dynamic, self-registered upstream table backing this client.
+    pub fn registry(&self) -> &UpstreamRegistry {
+        &self.registry
+    }
+
+    /// Whether `prefix` (the first path segment after `/v1/`) currently
+    /// resolves to an upstream, either statically configured or a healthy
+    /// registry entry.
+    pub fn handles(&self, prefix: &str) -> bool {
+        self.upstreams.contains_key(prefix) || self.registry.healthy_upstream(prefix).is_some()
+    }
+
+    /// Forwards `req` to the upstream registered for `prefix`, rewriting
+    /// the path to `rest` (everything after `/v1/<prefix>`) and the query
+    /// string from `req`'s original URI. Propagates `x-request-id` and the
+    /// resolved [`UserContext`] (subject/role) as headers so the upstream
+    /// can trust an identity the gateway already authorized, rather than
+    /// re-deriving it from whatever the client sent.
+    pub async fn forward(
+        &self,
+        prefix: &str,
+        rest: &str,
+        req: Request<Body>,
+        user: &UserContext,
+        request_id: &str,
+    ) -> Result<Response, ApiError> {
+        let base = match self.upstreams.get(prefix) {
+            Some(configured) => configured.clone(),
+            None => {
+                let dynamic = self
+                    .registry
+                    .healthy_upstream(prefix)
+                    .ok_or(ApiError::Internal)?;
+                Url::parse(&dynamic).map_err(|_| ApiError::Internal)?
+            }
+        };
+
+        let mut url = base
+            .join(rest.trim_start_matches('/'))
+            .map_err(|_| ApiError::Internal)?;
+        url.set_query(req.uri().query());
+
+        let method = req.method().clone();
+        let mut headers = req.headers().clone();
+        strip_forwarded_headers(&mut headers);
+        headers.insert(
+            ROLE_HEADER,
+            HeaderValue::from_str(user.role.as_str()).map_err(|_| ApiError::Internal)?,
+        );
+        headers.insert(
+            SUBJECT_HEADER,
+            HeaderValue::from_str(&user.subject).map_err(|_| ApiError::Internal)?,
+        );
+        headers.insert(
+            REQUEST_ID_HEADER,
+            HeaderValue::from_str(request_id).map_err(|_| ApiError::Internal)?,
+        );
+
+        let body_stream = req.into_body().into_data_stream();
+        let upstream_response = self
+            .client
+            .request(method, url)
+            .headers(headers)
+            .body(reqwest::Body::wrap_stream(body_stream))
+            .send()
+            .await?;
+
+        let status = upstream_response.status();
+        let mut response_headers = upstream_response.headers().clone();
+        strip_forwarded_headers(&mut response_headers);
+        let body = Body::from_stream(upstream_response.bytes_stream());
+
+        let mut response = Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = response_headers;
+        Ok(response)
+    }
+}
+
+fn strip_forwarded_headers(headers: &mut HeaderMap) {
+    for name in STRIPPED_HEADERS {
+        headers.remove(*name);
+    }
+}
@@ -1,101 +1,518 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use tokio::sync::Mutex;
+use common_obs::GaugeVec;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 
-use crate::config::RateLimitSettings;
+use crate::config::{RateLimitSettings, RateLimitTier};
 use crate::error::ApiError;
+use crate::rbac::Role;
 
-#[derive(Clone)]
-pub struct RateLimiter {
-    inner: Arc<Inner>,
+/// Tracks how many distinct buckets (per-client, per-role, and per-route)
+/// the limiter is currently holding, for capacity planning and leak
+/// detection.
+static RATE_LIMITER_ACTIVE_BUCKETS: Lazy<GaugeVec> = Lazy::new(|| {
+    common_obs::register_gauge(
+        "api_gateway_rate_limiter_active_buckets",
+        "Number of distinct rate-limit buckets currently tracked",
+        &[],
+    )
+});
+
+/// Response header reporting the remaining token-bucket capacity for the
+/// caller that just completed this request.
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+
+/// Identifies whose bucket a request should draw from: the caller's
+/// subject, or `ip:<addr>` when anonymous (no bearer token or trusted
+/// header identified them). Role is part of the key too, since each role
+/// can have its own capacity/refill tier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RateKey {
+    pub identity: String,
+    pub role: Role,
 }
 
-struct Inner {
-    state: Mutex<State>,
+impl RateKey {
+    pub fn new(identity: String, role: Role) -> Self {
+        Self { identity, role }
+    }
+}
+
+impl fmt::Display for RateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.role.as_str(), self.identity)
+    }
+}
+
+/// Result of a bucket check: whether the request may proceed, the tokens
+/// left afterward, and how long a rejected caller should wait before
+/// retrying.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitOutcome {
+    pub allowed: bool,
+    pub remaining: u32,
+    pub retry_after: Duration,
+}
+
+struct Tier {
     capacity: f64,
     rate_per_second: f64,
 }
 
-struct State {
+impl Tier {
+    fn from_settings(requests_per_minute: u32, burst: u32) -> Self {
+        Self {
+            capacity: burst.max(1) as f64,
+            rate_per_second: (requests_per_minute.max(1) as f64) / 60.0,
+        }
+    }
+}
+
+struct Bucket {
     tokens: f64,
+    capacity: f64,
+    rate_per_second: f64,
     last_refill: Instant,
 }
 
+impl Bucket {
+    /// Whether this bucket is idle as of `now`: it would have refilled to
+    /// full capacity by now (so evicting it loses no in-progress burst)
+    /// and its last draw was longer than `ttl` ago. Projects the refill
+    /// forward rather than trusting the stored `tokens`, since a bucket
+    /// only actually refills on its next draw.
+    fn is_idle(&self, now: Instant, ttl: Duration) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        if elapsed < ttl {
+            return false;
+        }
+        let projected =
+            (self.tokens + elapsed.as_secs_f64() * self.rate_per_second).min(self.capacity);
+        projected >= self.capacity
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    default_tier: Tier,
+    role_tiers: HashMap<Role, Tier>,
+    route_tiers: HashMap<String, Tier>,
+    per_client_tier: Tier,
+    idle_ttl: Duration,
+    buckets: DashMap<RateKey, Bucket>,
+    route_buckets: DashMap<(String, RateKey), Bucket>,
+    client_buckets: DashMap<String, Bucket>,
+}
+
 impl RateLimiter {
     pub fn new(settings: &RateLimitSettings) -> Self {
-        let capacity = settings.burst.max(1) as f64;
-        let rate_per_second = (settings.requests_per_minute.max(1) as f64) / 60.0;
+        let default_tier = Tier::from_settings(settings.requests_per_minute, settings.burst);
+
+        let mut role_tiers = HashMap::new();
+        for (name, tier) in &settings.per_role {
+            match name.parse::<Role>() {
+                Ok(role) => {
+                    role_tiers.insert(
+                        role,
+                        Tier::from_settings(tier.requests_per_minute, tier.burst),
+                    );
+                }
+                Err(_) => {
+                    tracing::warn!(role = %name, "ignoring rate limit tier for unknown role");
+                }
+            }
+        }
+
+        let mut route_tiers = HashMap::new();
+        for (route, tier) in &settings.per_route {
+            route_tiers.insert(
+                route.clone(),
+                Tier::from_settings(tier.requests_per_minute, tier.burst),
+            );
+        }
+
+        let per_client_tier = Tier::from_settings(
+            settings.per_client_requests_per_minute,
+            settings.per_client_burst,
+        );
+
         Self {
             inner: Arc::new(Inner {
-                state: Mutex::new(State {
-                    tokens: capacity,
-                    last_refill: Instant::now(),
-                }),
-                capacity,
-                rate_per_second,
+                default_tier,
+                role_tiers,
+                route_tiers,
+                per_client_tier,
+                idle_ttl: Duration::from_secs(settings.per_client_idle_ttl_secs.max(1)),
+                buckets: DashMap::new(),
+                route_buckets: DashMap::new(),
+                client_buckets: DashMap::new(),
             }),
         }
     }
 
-    pub async fn check(&self) -> Result<(), ApiError> {
-        let mut state = self.inner.state.lock().await;
+    /// How long an idle bucket is kept before [`RateLimiter::sweep_idle`]
+    /// evicts it, per [`RateLimitSettings::per_client_idle_ttl_secs`] — so
+    /// a caller spawning the background sweep doesn't need to thread the
+    /// settings through separately.
+    pub fn idle_ttl(&self) -> Duration {
+        self.inner.idle_ttl
+    }
+
+    /// Draws one token from `key`'s identity-only bucket, its role bucket,
+    /// and (if `route` has a configured tier) its per-route bucket —
+    /// rejecting if any of them is exhausted. Both the identity-only bucket
+    /// and the role bucket belong to this caller alone: the first applies
+    /// the per-client tier whatever the role, the second the caller's role
+    /// tier. A bucket seen for the first time starts at its tier's full
+    /// capacity.
+    pub fn check(&self, key: &RateKey, route: &str) -> RateLimitOutcome {
+        let client = Self::draw(
+            &self.inner.client_buckets,
+            key.identity.clone(),
+            &self.inner.per_client_tier,
+        );
+
+        let role_tier = self
+            .inner
+            .role_tiers
+            .get(&key.role)
+            .unwrap_or(&self.inner.default_tier);
+        let global = if client.allowed {
+            Self::draw(&self.inner.buckets, key.clone(), role_tier)
+        } else {
+            client
+        };
+
+        let outcome = match self.inner.route_tiers.get(route) {
+            Some(route_tier) if global.allowed => {
+                let route_outcome = Self::draw(
+                    &self.inner.route_buckets,
+                    (route.to_string(), key.clone()),
+                    route_tier,
+                );
+                RateLimitOutcome {
+                    allowed: route_outcome.allowed,
+                    remaining: global.remaining.min(route_outcome.remaining),
+                    retry_after: route_outcome.retry_after,
+                }
+            }
+            _ => global,
+        };
+
+        RATE_LIMITER_ACTIVE_BUCKETS.set(&[], self.bucket_count() as f64);
+
+        outcome
+    }
+
+    /// Convenience for callers holding an owned [`RateKey`] rather than a
+    /// borrowed one, otherwise identical to [`RateLimiter::check`].
+    pub fn check_for(&self, key: RateKey, route: &str) -> RateLimitOutcome {
+        self.check(&key, route)
+    }
+
+    /// The number of distinct buckets currently tracked (per-identity,
+    /// per-role, and per-route), for callers that want the raw count
+    /// rather than going through the gauge.
+    pub fn bucket_count(&self) -> usize {
+        self.inner.buckets.len() + self.inner.route_buckets.len() + self.inner.client_buckets.len()
+    }
+
+    /// Evicts every bucket across all three maps that's sat at full
+    /// capacity, untouched, for longer than `ttl` — bounding memory for a
+    /// gateway that sees a long tail of one-off client identities.
+    pub fn sweep_idle(&self, ttl: Duration) {
         let now = Instant::now();
-        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        self.inner
+            .client_buckets
+            .retain(|_, bucket| !bucket.is_idle(now, ttl));
+        self.inner
+            .buckets
+            .retain(|_, bucket| !bucket.is_idle(now, ttl));
+        self.inner
+            .route_buckets
+            .retain(|_, bucket| !bucket.is_idle(now, ttl));
+        RATE_LIMITER_ACTIVE_BUCKETS.set(&[], self.bucket_count() as f64);
+    }
+
+    fn draw<K: std::hash::Hash + Eq>(
+        buckets: &DashMap<K, Bucket>,
+        key: K,
+        tier: &Tier,
+    ) -> RateLimitOutcome {
+        let mut bucket = buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: tier.capacity,
+            capacity: tier.capacity,
+            rate_per_second: tier.rate_per_second,
+            last_refill: Instant::now(),
+        });
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
         if elapsed > 0.0 {
-            let replenished = elapsed * self.inner.rate_per_second;
-            state.tokens = (state.tokens + replenished).min(self.inner.capacity);
-            state.last_refill = now;
+            let replenished = elapsed * tier.rate_per_second;
+            bucket.tokens = (bucket.tokens + replenished).min(tier.capacity);
+            bucket.last_refill = now;
         }
 
-        if state.tokens >= 1.0 {
-            state.tokens -= 1.0;
-            Ok(())
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            RateLimitOutcome {
+                allowed: true,
+                remaining: bucket.tokens as u32,
+                retry_after: Duration::ZERO,
+            }
         } else {
-            Err(ApiError::RateLimited {
-                retry_after: Duration::from_secs(1),
-            })
+            let deficit = 1.0 - bucket.tokens;
+            RateLimitOutcome {
+                allowed: false,
+                remaining: 0,
+                retry_after: wait_for_deficit(deficit, tier.rate_per_second),
+            }
         }
     }
 }
 
+/// Exact wait until `deficit` tokens have refilled at `rate_per_second`,
+/// so a caller's `Retry-After` reflects its actual tier instead of an
+/// assumed one-token-per-second rate. Guards a pathological zero rate
+/// (not reachable via [`Tier::from_settings`], which floors at one
+/// request per minute, but cheap insurance against a future caller
+/// constructing a [`Tier`] directly).
+fn wait_for_deficit(deficit: f64, rate_per_second: f64) -> Duration {
+    if rate_per_second <= 0.0 {
+        return Duration::from_secs(1);
+    }
+    Duration::from_secs_f64(deficit / rate_per_second)
+}
+
+/// Builds an [`ApiError::RateLimited`] from a rejected [`RateLimitOutcome`].
+pub fn rejection(outcome: RateLimitOutcome) -> ApiError {
+    ApiError::RateLimited {
+        retry_after: outcome.retry_after,
+        remaining: outcome.remaining,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn allows_within_burst() {
+    const ROUTE: &str = "/v1/test";
+
+    fn settings(requests_per_minute: u32, burst: u32) -> RateLimitSettings {
+        RateLimitSettings {
+            requests_per_minute,
+            burst,
+            per_role: BTreeMap::new(),
+            per_route: BTreeMap::new(),
+            // Generous enough to stay out of the way of tests that are
+            // exercising the role/route buckets, not the per-client one.
+            per_client_requests_per_minute: 100_000,
+            per_client_burst: 100_000,
+            per_client_idle_ttl_secs: 600,
+        }
+    }
+
+    #[test]
+    fn allows_within_burst() {
+        let limiter = RateLimiter::new(&settings(120, 2));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+
+        assert!(limiter.check(&key, ROUTE).allowed);
+        assert!(limiter.check(&key, ROUTE).allowed);
+    }
+
+    #[test]
+    fn rejects_when_exhausted() {
+        let limiter = RateLimiter::new(&settings(2, 1));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+
+        assert!(limiter.check(&key, ROUTE).allowed);
+        assert!(!limiter.check(&key, ROUTE).allowed);
+    }
+
+    #[test]
+    fn tracks_separate_buckets_per_key() {
+        let limiter = RateLimiter::new(&settings(2, 1));
+        let alice = RateKey::new("alice".to_string(), Role::Member);
+        let bob = RateKey::new("bob".to_string(), Role::Member);
+
+        assert!(limiter.check(&alice, ROUTE).allowed);
+        assert!(!limiter.check(&alice, ROUTE).allowed);
+        assert!(limiter.check(&bob, ROUTE).allowed);
+    }
+
+    #[test]
+    fn role_tier_overrides_default_capacity() {
+        let mut per_role = BTreeMap::new();
+        per_role.insert(
+            "owner".to_string(),
+            RateLimitTier {
+                requests_per_minute: 600,
+                burst: 5,
+            },
+        );
         let limiter = RateLimiter::new(&RateLimitSettings {
-            requests_per_minute: 120,
-            burst: 2,
+            requests_per_minute: 60,
+            burst: 1,
+            per_role,
+            per_route: BTreeMap::new(),
+            per_client_requests_per_minute: 100_000,
+            per_client_burst: 100_000,
+            per_client_idle_ttl_secs: 600,
         });
 
-        assert!(limiter.check().await.is_ok());
-        assert!(limiter.check().await.is_ok());
+        let owner = RateKey::new("root".to_string(), Role::Owner);
+        for _ in 0..5 {
+            assert!(limiter.check(&owner, ROUTE).allowed);
+        }
+        assert!(!limiter.check(&owner, ROUTE).allowed);
     }
 
-    #[tokio::test]
-    async fn rejects_when_exhausted() {
+    #[test]
+    fn route_tier_adds_a_stricter_ceiling_on_top_of_the_global_bucket() {
+        let mut per_route = BTreeMap::new();
+        per_route.insert(
+            "/v1/commissioning/csr".to_string(),
+            RateLimitTier {
+                requests_per_minute: 60,
+                burst: 1,
+            },
+        );
         let limiter = RateLimiter::new(&RateLimitSettings {
-            requests_per_minute: 2,
-            burst: 1,
+            requests_per_minute: 600,
+            burst: 10,
+            per_role: BTreeMap::new(),
+            per_route,
+            per_client_requests_per_minute: 100_000,
+            per_client_burst: 100_000,
+            per_client_idle_ttl_secs: 600,
         });
 
-        assert!(limiter.check().await.is_ok());
-        let result = limiter.check().await;
-        assert!(matches!(result, Err(ApiError::RateLimited { .. })));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+        assert!(limiter.check(&key, "/v1/commissioning/csr").allowed);
+        assert!(!limiter.check(&key, "/v1/commissioning/csr").allowed);
+        // The global (per-actor) bucket still has plenty of capacity left;
+        // only the route-specific bucket for this path is exhausted.
+        assert!(limiter.check(&key, "/v1/other").allowed);
     }
 
-    #[tokio::test]
-    async fn refills_over_time() {
+    #[test]
+    fn bucket_count_tracks_distinct_client_role_and_route_buckets() {
+        let mut per_route = BTreeMap::new();
+        per_route.insert(
+            "/v1/commissioning/csr".to_string(),
+            RateLimitTier {
+                requests_per_minute: 60,
+                burst: 1,
+            },
+        );
         let limiter = RateLimiter::new(&RateLimitSettings {
-            requests_per_minute: 60,
-            burst: 1,
+            requests_per_minute: 600,
+            burst: 10,
+            per_role: BTreeMap::new(),
+            per_route,
+            per_client_requests_per_minute: 100_000,
+            per_client_burst: 100_000,
+            per_client_idle_ttl_secs: 600,
         });
 
-        limiter.check().await.unwrap();
-        assert!(limiter.check().await.is_err());
+        let key = RateKey::new("alice".to_string(), Role::Member);
+        assert_eq!(limiter.bucket_count(), 0);
+        limiter.check(&key, "/v1/commissioning/csr");
+        assert_eq!(limiter.bucket_count(), 3);
+    }
+
+    #[test]
+    fn per_client_bucket_is_independent_of_role_bucket() {
+        let mut per_role = BTreeMap::new();
+        per_role.insert(
+            "owner".to_string(),
+            RateLimitTier {
+                requests_per_minute: 6000,
+                burst: 1000,
+            },
+        );
+        let limiter = RateLimiter::new(&RateLimitSettings {
+            requests_per_minute: 6000,
+            burst: 1000,
+            per_role,
+            per_route: BTreeMap::new(),
+            per_client_requests_per_minute: 2,
+            per_client_burst: 1,
+            per_client_idle_ttl_secs: 600,
+        });
+
+        let key = RateKey::new("alice".to_string(), Role::Owner);
+        assert!(limiter.check(&key, ROUTE).allowed);
+        // The role bucket has plenty of headroom left; it's the
+        // identity-only bucket that's now exhausted.
+        assert!(!limiter.check(&key, ROUTE).allowed);
+    }
+
+    #[test]
+    fn check_for_behaves_like_check_with_an_owned_key() {
+        let limiter = RateLimiter::new(&settings(2, 1));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+
+        assert!(limiter.check_for(key.clone(), ROUTE).allowed);
+        assert!(!limiter.check_for(key, ROUTE).allowed);
+    }
+
+    #[test]
+    fn sweep_idle_evicts_untouched_buckets_but_keeps_active_ones() {
+        // A fast refill rate so a short sleep is enough for `idle`'s
+        // bucket to project back to full capacity.
+        let limiter = RateLimiter::new(&settings(6000, 1));
+        let idle = RateKey::new("idle-client".to_string(), Role::Member);
+        let active = RateKey::new("active-client".to_string(), Role::Member);
+
+        limiter.check(&idle, ROUTE);
+        std::thread::sleep(Duration::from_millis(20));
+        limiter.check(&active, ROUTE);
+
+        limiter.sweep_idle(Duration::from_millis(10));
+
+        // `idle`'s identity and role buckets have sat refilled and
+        // untouched longer than the TTL and are evicted; `active`'s were
+        // just drawn from and survive.
+        assert_eq!(limiter.bucket_count(), 2);
+    }
+
+    #[test]
+    fn retry_after_reflects_the_bucket_s_own_refill_rate() {
+        // 30 requests/minute = 0.5 tokens/sec, so a caller starting from
+        // an empty bucket should be told to wait ~2s for the next token,
+        // not the old hardcoded 1s.
+        let limiter = RateLimiter::new(&settings(30, 1));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+
+        assert!(limiter.check(&key, ROUTE).allowed);
+        let outcome = limiter.check(&key, ROUTE);
+        assert!(!outcome.allowed);
+        assert!(
+            (outcome.retry_after.as_secs_f64() - 2.0).abs() < 0.05,
+            "expected ~2s, got {:?}",
+            outcome.retry_after
+        );
+    }
+
+    #[tokio::test]
+    async fn refills_over_time() {
+        let limiter = RateLimiter::new(&settings(60, 1));
+        let key = RateKey::new("alice".to_string(), Role::Member);
+
+        assert!(limiter.check(&key, ROUTE).allowed);
+        assert!(!limiter.check(&key, ROUTE).allowed);
         tokio::time::sleep(Duration::from_millis(1100)).await;
-        assert!(limiter.check().await.is_ok());
+        assert!(limiter.check(&key, ROUTE).allowed);
     }
 }
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+
+/// A backend service's self-registered proxy target. Expires if not
+/// refreshed within `ttl` of `registered_at`, and is only eligible for
+/// [`crate::proxy::ProxyClient::forward`] while `healthy` — flipped by a
+/// background checker, not by the registering service itself.
+#[derive(Debug, Clone)]
+pub struct UpstreamEntry {
+    pub base_url: String,
+    pub registered_at: Instant,
+    pub ttl: Duration,
+    pub healthy: bool,
+}
+
+impl UpstreamEntry {
+    fn is_expired(&self) -> bool {
+        self.registered_at.elapsed() > self.ttl
+    }
+}
+
+/// Runtime table of self-registered upstreams, keyed by the `/v1/<name>`
+/// path prefix they proxy for. Modeled on the same "connected backend"
+/// bookkeeping a relay would keep, but scoped to what the gateway's proxy
+/// fallback needs: an address to forward to, and whether it's currently
+/// healthy.
+#[derive(Clone, Default)]
+pub struct UpstreamRegistry {
+    entries: Arc<DashMap<String, UpstreamEntry>>,
+}
+
+impl UpstreamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers (or refreshes) `name` with a fresh TTL. New and refreshed
+    /// entries start unhealthy until the background checker confirms them,
+    /// so a just-registered service doesn't receive traffic before it has
+    /// been probed even once.
+    pub fn register(&self, name: String, base_url: String, ttl: Duration) {
+        self.entries.insert(
+            name,
+            UpstreamEntry {
+                base_url,
+                registered_at: Instant::now(),
+                ttl,
+                healthy: false,
+            },
+        );
+    }
+
+    /// The base URL for `name`, if it has a non-expired entry currently
+    /// marked healthy.
+    pub fn healthy_upstream(&self, name: &str) -> Option<String> {
+        let entry = self.entries.get(name)?;
+        if entry.is_expired() || !entry.healthy {
+            return None;
+        }
+        Some(entry.base_url.clone())
+    }
+
+    pub fn set_healthy(&self, name: &str, healthy: bool) {
+        if let Some(mut entry) = self.entries.get_mut(name) {
+            entry.healthy = healthy;
+        }
+    }
+
+    /// Drops entries past their TTL. Expected to be called once per
+    /// background health-check tick rather than on every lookup.
+    pub fn evict_expired(&self) {
+        self.entries.retain(|_, entry| !entry.is_expired());
+    }
+
+    /// A point-in-time copy of the table, for the health checker to probe
+    /// and for `diag_routes` to report.
+    pub fn snapshot(&self) -> Vec<(String, UpstreamEntry)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+}
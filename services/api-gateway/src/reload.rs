@@ -0,0 +1,160 @@
+//! Hot-reload of the gateway's config file and RBAC policy, so an operator
+//! can roll out a new rate limit, RBAC rule, or TLS cert/key without a
+//! restart. Two triggers feed the same validate-then-swap path: a
+//! debounced filesystem watch on the config file and `rbac_policy_path`,
+//! and a `SIGHUP` for an operator who wants an immediate reload (or whose
+//! deployment doesn't deliver filesystem events, e.g. a networked mount).
+//!
+//! A reload that fails to parse or validate is logged and discarded —
+//! whatever was live before stays live — and in-flight requests are
+//! unaffected, since [`crate::config::ReloadableConfig`] only ever swaps
+//! in a fully-built replacement.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum_server::tls_rustls::RustlsConfig;
+use common_config::load_layered;
+use notify::{RecursiveMode, Watcher};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+
+use crate::config::ApiGatewayConfig;
+use crate::rate_limit::RateLimiter;
+use crate::rbac::RbacPolicy;
+use crate::{build_rustls_server_config, AppState};
+
+/// How long to wait after the last filesystem event before reloading. A
+/// single `save` in most editors fires several events in quick succession
+/// (truncate, write, rename-from-swapfile, ...); waiting this long after
+/// the last one collapses them into a single reload instead of several.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Starts the background tasks that keep `state` (and, when TLS is
+/// enabled, `rustls_config`) in sync with `config_path`/`rbac_policy_path`
+/// on disk. Runs for the life of the process; there's no handle to stop
+/// it because the gateway never needs to.
+pub fn spawn(
+    state: Arc<AppState>,
+    rustls_config: Option<RustlsConfig>,
+    config_path: Option<PathBuf>,
+    rbac_policy_path: PathBuf,
+) {
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+
+    if let Err(err) = spawn_watcher(tx.clone(), config_path, rbac_policy_path.clone()) {
+        tracing::warn!(
+            %err,
+            "failed to start config file watcher; reload is still available via SIGHUP"
+        );
+    }
+
+    tokio::spawn(async move {
+        match signal(SignalKind::hangup()) {
+            Ok(mut sighup) => loop {
+                sighup.recv().await;
+                let _ = tx.send(()).await;
+            },
+            Err(err) => {
+                tracing::warn!(
+                    %err,
+                    "failed to install SIGHUP handler; reload is still available via file watch"
+                );
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            // Collapse any further triggers that arrived during the
+            // debounce window into this same reload.
+            while rx.try_recv().is_ok() {}
+            reload(&state, rustls_config.as_ref(), &rbac_policy_path).await;
+        }
+    });
+}
+
+/// Watches `config_path` (if the gateway was started with one) and
+/// `rbac_policy_path`, sending on `tx` whenever either changes.
+fn spawn_watcher(
+    tx: mpsc::Sender<()>,
+    config_path: Option<PathBuf>,
+    rbac_policy_path: PathBuf,
+) -> notify::Result<()> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok() {
+            let _ = tx.blocking_send(());
+        }
+    })?;
+
+    if let Some(path) = &config_path {
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+    }
+    watcher.watch(&rbac_policy_path, RecursiveMode::NonRecursive)?;
+
+    // Keeps the watcher (and the inotify/kqueue/... handle it owns) alive
+    // for the rest of the process instead of dropping it at the end of
+    // this function.
+    std::mem::forget(watcher);
+    Ok(())
+}
+
+/// Reloads config and RBAC policy from disk and swaps in whichever of
+/// them parsed cleanly; a failure on one side doesn't block the other.
+/// Also rebuilds the TLS config from the (possibly changed) cert/key
+/// paths when the gateway is serving TLS.
+///
+/// Note for unix-domain-socket deployments: the TCP listener's acceptor
+/// (`axum_server::bind_rustls`) reads the live `RustlsConfig` on every
+/// accepted connection, so a TLS reload applies there immediately. The
+/// unix-socket-with-TLS path rebuilds its `tokio_rustls::TlsAcceptor` once
+/// per accepted connection too (see `listener::serve_unix_tls`), so both
+/// paths pick up a reloaded cert/key on their next connection.
+async fn reload(
+    state: &AppState,
+    rustls_config: Option<&RustlsConfig>,
+    rbac_policy_path: &std::path::Path,
+) {
+    let config = match load_layered::<ApiGatewayConfig>() {
+        Ok(config) => Some(config),
+        Err(err) => {
+            tracing::warn!(%err, "config reload failed; keeping previous configuration");
+            None
+        }
+    };
+
+    match RbacPolicy::from_path(rbac_policy_path) {
+        Ok(policy) => {
+            state.policy.store(policy);
+            tracing::info!(path = %rbac_policy_path.display(), "reloaded rbac policy");
+        }
+        Err(err) => {
+            tracing::warn!(
+                %err,
+                path = %rbac_policy_path.display(),
+                "rbac policy reload failed; keeping previous policy"
+            );
+        }
+    }
+
+    let Some(config) = config else {
+        return;
+    };
+
+    state.rate_limiter.store(RateLimiter::new(&config.rate_limit));
+    tracing::info!("reloaded rate limit settings");
+
+    if let Some(rustls_config) = rustls_config {
+        match build_rustls_server_config(&config.tls).await {
+            Ok(server_config) => {
+                rustls_config.reload_from_config(Arc::new(server_config)).await;
+                tracing::info!("reloaded tls certificate and key");
+            }
+            Err(err) => {
+                tracing::warn!(%err, "tls reload failed; keeping previous certificate and key");
+            }
+        }
+    }
+}
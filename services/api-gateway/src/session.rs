@@ -0,0 +1,116 @@
+//! Short-lived store for commissioning session keys derived during the BLE
+//! handshake. Sessions expire after [`SESSION_TTL`] and each nonce presented
+//! against a session may only be consumed once, so a captured `submit_csr`
+//! or `verify_credentials` request cannot be replayed.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+/// How long a handshake's derived key remains usable before the device must
+/// re-run the handshake.
+const SESSION_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// The session id is not known, or was known but has since expired.
+    Unknown,
+    /// The nonce was already consumed against this session.
+    NonceReused,
+}
+
+struct SessionRecord {
+    key: [u8; 32],
+    consumed_nonces: HashSet<String>,
+    expires_at: Instant,
+}
+
+/// Keyed by the handshake `session` id, recording the HKDF-derived key and
+/// the set of nonces already consumed against it.
+#[derive(Clone)]
+pub struct SessionStore {
+    records: Arc<Mutex<HashMap<String, SessionRecord>>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self {
+            records: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Registers a freshly negotiated session, marking the handshake nonce
+    /// itself as consumed so it cannot be replayed against `submit_csr` or
+    /// `verify_credentials`.
+    pub async fn create(&self, session: String, handshake_nonce: &str, key: [u8; 32]) {
+        let mut consumed_nonces = HashSet::new();
+        consumed_nonces.insert(handshake_nonce.to_string());
+
+        let now = Instant::now();
+        let mut records = self.records.lock().await;
+        records.retain(|_, record| record.expires_at > now);
+        records.insert(
+            session,
+            SessionRecord {
+                key,
+                consumed_nonces,
+                expires_at: now + SESSION_TTL,
+            },
+        );
+    }
+
+    /// Validates that `session`/`nonce` refer to a live handshake whose nonce
+    /// has not already been used, then marks the nonce consumed. Returns the
+    /// session's derived key on success.
+    pub async fn consume(&self, session: &str, nonce: &str) -> Result<[u8; 32], SessionError> {
+        let now = Instant::now();
+        let mut records = self.records.lock().await;
+        records.retain(|_, record| record.expires_at > now);
+
+        let record = records.get_mut(session).ok_or(SessionError::Unknown)?;
+        if !record.consumed_nonces.insert(nonce.to_string()) {
+            return Err(SessionError::NonceReused);
+        }
+        Ok(record.key)
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn consumes_each_nonce_once() {
+        let store = SessionStore::new();
+        store
+            .create("session-1".to_string(), "handshake-nonce", [7u8; 32])
+            .await;
+
+        assert_eq!(store.consume("session-1", "csr-nonce").await, Ok([7u8; 32]));
+        assert_eq!(
+            store.consume("session-1", "csr-nonce").await,
+            Err(SessionError::NonceReused)
+        );
+        assert_eq!(
+            store.consume("session-1", "handshake-nonce").await,
+            Err(SessionError::NonceReused)
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_session() {
+        let store = SessionStore::new();
+        assert_eq!(
+            store.consume("missing", "some-nonce").await,
+            Err(SessionError::Unknown)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::pkcs8::DecodePrivateKey;
+use ed25519_dalek::{Signer, SigningKey};
+
+use crate::credentials::TokenClaims;
+use crate::rbac::Role;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IssuerError {
+    #[error("failed to parse signing key")]
+    InvalidKey,
+    #[error("failed to encode token claims")]
+    Encode,
+}
+
+/// Mints the bearer tokens `credentials::CredentialVerifier` verifies: an
+/// Ed25519 detached signature over the claims JSON, base64-joined as
+/// `<claims>.<signature>`. This is the signing half of the same scheme
+/// `CredentialVerifier` checks — following the same Ed25519 approach
+/// `services/updater` uses to sign update bundles, rather than a
+/// general-purpose JWT library.
+#[derive(Clone)]
+pub struct TokenIssuer {
+    signing_key: SigningKey,
+    ttl: Duration,
+}
+
+impl TokenIssuer {
+    pub fn from_private_key_pem(pem: &str, ttl: Duration) -> Result<Self, IssuerError> {
+        let signing_key = SigningKey::from_pkcs8_pem(pem).map_err(|_| IssuerError::InvalidKey)?;
+        Ok(Self { signing_key, ttl })
+    }
+
+    /// Mints a token for `subject`/`role`, scoped to `scopes`, valid from
+    /// `now` for this issuer's configured TTL. Returns the wire-format
+    /// token alongside its expiry so a caller (e.g. the refresh endpoint)
+    /// can report it without recomputing the TTL math.
+    pub fn issue(
+        &self,
+        subject: String,
+        role: Role,
+        scopes: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> Result<(String, DateTime<Utc>), IssuerError> {
+        let ttl =
+            chrono::Duration::from_std(self.ttl).unwrap_or_else(|_| chrono::Duration::seconds(1));
+        let not_after = now + ttl;
+
+        let claims = TokenClaims {
+            subject,
+            role,
+            scopes,
+            not_before: now,
+            not_after,
+        };
+        let claims_bytes = serde_json::to_vec(&claims).map_err(|_| IssuerError::Encode)?;
+        let signature = self.signing_key.sign(&claims_bytes);
+
+        let token = format!(
+            "{}.{}",
+            BASE64.encode(&claims_bytes),
+            BASE64.encode(signature.to_bytes())
+        );
+        Ok((token, not_after))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{CredentialVerifier, TokenError};
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+
+    fn issuer_and_verifier(ttl: Duration) -> (TokenIssuer, CredentialVerifier) {
+        let signing_key = SigningKey::generate(&mut rand_core::OsRng);
+        let pem = signing_key
+            .to_pkcs8_pem(Default::default())
+            .expect("encode private key");
+        let issuer = TokenIssuer::from_private_key_pem(&pem, ttl).expect("issuer");
+
+        let verifying_key_pem = signing_key
+            .verifying_key()
+            .to_public_key_pem(Default::default())
+            .expect("encode public key");
+        let verifier =
+            CredentialVerifier::from_public_key_pem(&verifying_key_pem).expect("verifier");
+
+        (issuer, verifier)
+    }
+
+    #[test]
+    fn minted_token_verifies_with_the_matching_public_key() {
+        let (issuer, verifier) = issuer_and_verifier(Duration::from_secs(60));
+        let now = Utc::now();
+
+        let (token, expires_at) = issuer
+            .issue("alice".to_string(), Role::Admin, vec!["*".to_string()], now)
+            .expect("issue");
+
+        let claims = verifier
+            .verify(&format!("Bearer {token}"), now)
+            .expect("verify");
+        assert_eq!(claims.subject, "alice");
+        assert_eq!(claims.role, Role::Admin);
+        assert_eq!(claims.not_after, expires_at);
+    }
+
+    #[test]
+    fn minted_token_expires_after_its_ttl() {
+        let (issuer, verifier) = issuer_and_verifier(Duration::from_secs(60));
+        let now = Utc::now();
+
+        let (token, _) = issuer
+            .issue("alice".to_string(), Role::Guest, vec!["*".to_string()], now)
+            .expect("issue");
+
+        let later = now + chrono::Duration::minutes(10);
+        let result = verifier.verify(&format!("Bearer {token}"), later);
+        assert!(matches!(result, Err(TokenError::Expired)));
+    }
+}
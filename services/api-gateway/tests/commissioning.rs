@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
 use api_gateway::audit::AuditClient;
+use api_gateway::audit_sink::sinks_from_config;
 use api_gateway::config::RateLimitSettings;
+use api_gateway::deadline::DeadlinePolicy;
 use api_gateway::device_registry::DeviceRegistryClient;
 use api_gateway::rate_limit::RateLimiter;
 use api_gateway::rbac::RbacPolicy;
@@ -13,9 +15,11 @@ use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use common_msgbus::{BusMessage, MessageBus, MsgBusError, Subscription};
 use http_body_util::BodyExt;
+use rand_core::OsRng;
 use serde_json::json;
 use tokio::sync::Mutex;
 use tower::ServiceExt;
+use x25519_dalek::{EphemeralSecret, PublicKey};
 
 #[derive(Clone, Default)]
 struct MockBus {
@@ -36,6 +40,14 @@ impl MessageBus for MockBus {
         Err(MsgBusError::Subscribe("not implemented".into()))
     }
 
+    async fn subscribe_queue(
+        &self,
+        _subject: &str,
+        _group: &str,
+    ) -> Result<Subscription, MsgBusError> {
+        Err(MsgBusError::Subscribe("not implemented".into()))
+    }
+
     async fn request(&self, _subject: &str, _payload: &[u8]) -> Result<BusMessage, MsgBusError> {
         Err(MsgBusError::Request("not implemented".into()))
     }
@@ -51,10 +63,13 @@ async fn commissioning_flow_emits_bus_events() {
         std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/rbac.yaml");
     let policy = Arc::new(RbacPolicy::from_path(&policy_path).expect("policy"));
 
-    let audit = AuditClient::new(String::new(), false);
+    let audit = AuditClient::new(&Default::default(), sinks_from_config(&Default::default()));
     let rate_limiter = RateLimiter::new(&RateLimitSettings {
         requests_per_minute: 500,
         burst: 100,
+        per_role: Default::default(),
+        per_route: Default::default(),
+        ..Default::default()
     });
     let device_client =
         DeviceRegistryClient::new("http://127.0.0.1:8001".to_string()).expect("device client");
@@ -69,14 +84,24 @@ async fn commissioning_flow_emits_bus_events() {
         rate_limiter,
         device_client,
         bus,
+        session_store: api_gateway::session::SessionStore::new(),
+        proxy: api_gateway::proxy::ProxyClient::new(&Default::default()).expect("proxy client"),
+        credential_verifier: None,
+        insecure_header_auth: true,
+        token_issuer: None,
+        deadline: DeadlinePolicy::new(&Default::default()),
     });
 
     let router = build_router(state);
 
+    let client_secret = EphemeralSecret::random_from_rng(OsRng);
+    let client_public_key = PublicKey::from(&client_secret);
+
     let handshake_payload = json!({
         "qrPayload": "LOKAN:thread-demo",
         "deviceId": "device-001",
         "nonce": "abc123",
+        "clientPublicKey": BASE64.encode(client_public_key.as_bytes()),
     });
 
     let handshake_response = router
@@ -107,13 +132,16 @@ async fn commissioning_flow_emits_bus_events() {
         .and_then(|value| value.as_str())
         .expect("session")
         .to_string();
-    assert!(handshake_json.get("sharedKey").is_some());
+    assert!(handshake_json.get("serverPublicKey").is_some());
+    assert!(handshake_json.get("keyConfirmation").is_some());
+    assert!(handshake_json.get("sharedKey").is_none());
 
     let csr_payload = BASE64.encode(b"fake-csr-payload");
     let csr_request = json!({
         "deviceId": "device-001",
         "csr": csr_payload,
-        "nonce": session,
+        "session": session,
+        "nonce": "csr-nonce-1",
     });
 
     let csr_response = router
@@ -140,6 +168,7 @@ async fn commissioning_flow_emits_bus_events() {
         "deviceId": "device-001",
         "signature": BASE64.encode(vec![0x42; 32]),
         "session": session,
+        "nonce": "verify-nonce-1",
     });
 
     let verify_response = router
@@ -173,3 +202,116 @@ async fn commissioning_flow_emits_bus_events() {
     assert_eq!(recorded[2].0, "radio.commissioning.verify");
     assert_eq!(verify_event["signatureLength"].as_u64().unwrap(), 32);
 }
+
+#[tokio::test]
+async fn submit_csr_rejects_replayed_nonce() {
+    let policy_path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/rbac.yaml");
+    let policy = Arc::new(RbacPolicy::from_path(&policy_path).expect("policy"));
+
+    let audit = AuditClient::new(&Default::default(), sinks_from_config(&Default::default()));
+    let rate_limiter = RateLimiter::new(&RateLimitSettings {
+        requests_per_minute: 500,
+        burst: 100,
+        per_role: Default::default(),
+        per_route: Default::default(),
+        ..Default::default()
+    });
+    let device_client =
+        DeviceRegistryClient::new("http://127.0.0.1:8001".to_string()).expect("device client");
+    let bus: Arc<dyn MessageBus> = Arc::new(MockBus::default());
+
+    let state = Arc::new(AppState {
+        policy,
+        audit,
+        rate_limiter,
+        device_client,
+        bus,
+        session_store: api_gateway::session::SessionStore::new(),
+        proxy: api_gateway::proxy::ProxyClient::new(&Default::default()).expect("proxy client"),
+        credential_verifier: None,
+        insecure_header_auth: true,
+        token_issuer: None,
+        deadline: DeadlinePolicy::new(&Default::default()),
+    });
+
+    let router = build_router(state);
+
+    let client_secret = EphemeralSecret::random_from_rng(OsRng);
+    let client_public_key = PublicKey::from(&client_secret);
+
+    let handshake_payload = json!({
+        "qrPayload": "LOKAN:thread-demo",
+        "deviceId": "device-001",
+        "nonce": "abc123",
+        "clientPublicKey": BASE64.encode(client_public_key.as_bytes()),
+    });
+
+    let handshake_response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/commissioning/ble/handshake")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("x-lokan-role", "guest")
+                .header("x-lokan-subject", "commissioner")
+                .body(Body::from(handshake_payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .expect("handshake response");
+
+    let handshake_body = handshake_response
+        .into_body()
+        .collect()
+        .await
+        .unwrap()
+        .to_bytes();
+    let handshake_json: serde_json::Value = serde_json::from_slice(&handshake_body).unwrap();
+    let session = handshake_json
+        .get("session")
+        .and_then(|value| value.as_str())
+        .expect("session")
+        .to_string();
+
+    let csr_payload = BASE64.encode(b"fake-csr-payload");
+    let csr_request = json!({
+        "deviceId": "device-001",
+        "csr": csr_payload,
+        "session": session,
+        "nonce": "csr-nonce-1",
+    });
+
+    let first_attempt = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/commissioning/csr")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("x-lokan-role", "guest")
+                .header("x-lokan-subject", "commissioner")
+                .body(Body::from(csr_request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .expect("first csr response");
+    assert_eq!(first_attempt.status(), StatusCode::OK);
+
+    let replayed_attempt = router
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/commissioning/csr")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("x-lokan-role", "guest")
+                .header("x-lokan-subject", "commissioner")
+                .body(Body::from(csr_request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .expect("replayed csr response");
+
+    assert_eq!(replayed_attempt.status(), StatusCode::BAD_REQUEST);
+}
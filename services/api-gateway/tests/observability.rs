@@ -2,7 +2,9 @@ use std::path::Path;
 use std::sync::Arc;
 
 use api_gateway::audit::AuditClient;
+use api_gateway::audit_sink::sinks_from_config;
 use api_gateway::config::RateLimitSettings;
+use api_gateway::deadline::DeadlinePolicy;
 use api_gateway::device_registry::DeviceRegistryClient;
 use api_gateway::rate_limit::RateLimiter;
 use api_gateway::rbac::RbacPolicy;
@@ -27,6 +29,14 @@ impl MessageBus for NullBus {
         Err(MsgBusError::Subscribe("not implemented".into()))
     }
 
+    async fn subscribe_queue(
+        &self,
+        _subject: &str,
+        _group: &str,
+    ) -> Result<Subscription, MsgBusError> {
+        Err(MsgBusError::Subscribe("not implemented".into()))
+    }
+
     async fn request(&self, _subject: &str, _payload: &[u8]) -> Result<BusMessage, MsgBusError> {
         Err(MsgBusError::Request("not implemented".into()))
     }
@@ -41,10 +51,13 @@ async fn metrics_endpoint_returns_uptime() {
     let policy_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../configs/rbac.yaml");
     let policy = Arc::new(RbacPolicy::from_path(&policy_path).expect("policy"));
 
-    let audit = AuditClient::new(String::new(), false);
+    let audit = AuditClient::new(&Default::default(), sinks_from_config(&Default::default()));
     let rate_limiter = RateLimiter::new(&RateLimitSettings {
         requests_per_minute: 500,
         burst: 100,
+        per_role: Default::default(),
+        per_route: Default::default(),
+        ..Default::default()
     });
     let device_client =
         DeviceRegistryClient::new("http://127.0.0.1:8001".to_string()).expect("device client");
@@ -57,6 +70,12 @@ async fn metrics_endpoint_returns_uptime() {
         rate_limiter,
         device_client,
         bus,
+        session_store: api_gateway::session::SessionStore::new(),
+        proxy: api_gateway::proxy::ProxyClient::new(&Default::default()).expect("proxy client"),
+        credential_verifier: None,
+        insecure_header_auth: true,
+        token_issuer: None,
+        deadline: DeadlinePolicy::new(&Default::default()),
     });
 
     let router = build_router(state);
@@ -2,20 +2,21 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use axum::body::Body;
-use axum::extract::{MatchedPath, State};
+use axum::body::{Body, Bytes};
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{MatchedPath, Path, Query, State, WebSocketUpgrade};
 use axum::http::{header, HeaderValue, Request, StatusCode};
 use axum::middleware::{from_fn, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Utc};
+use futures_util::{stream, SinkExt, StreamExt};
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use tokio::fs::{self, OpenOptions};
-use tokio::io::AsyncWriteExt;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
 use common_config::service_port;
 use common_obs::{
@@ -30,7 +31,16 @@ use std::time::Instant;
 const SERVICE_NAME: &str = "audit-log";
 const PORT_ENV: &str = "AUDIT_LOG_PORT";
 const DEFAULT_PORT: u16 = 8008;
-const DEFAULT_PATH: &str = "audit.log";
+/// Directory for the sled database backing the audit log.
+const DEFAULT_PATH: &str = "audit.sled";
+/// Number of records per Merkle checkpoint batch. A proof for any record is
+/// then the sibling hashes along its batch's `log2(MERKLE_BATCH_SIZE)` path.
+const MERKLE_BATCH_SIZE: u64 = 128;
+
+/// Process-wide handle to the opened audit database, cached behind a
+/// `OnceCell` the way `common_obs::metrics` caches its process-wide
+/// `Registry` instead of reopening it per call site.
+static AUDIT_DB: OnceCell<sled::Db> = OnceCell::new();
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -45,10 +55,12 @@ fn build_time() -> &'static str {
 #[derive(Clone)]
 struct AppState {
     writer: Arc<Mutex<AuditWriter>>,
+    events: broadcast::Sender<AuditRecord>,
 }
 
 struct AuditWriter {
-    path: PathBuf,
+    tree: sled::Tree,
+    merkle_roots: sled::Tree,
     prev_hash: Vec<u8>,
 }
 
@@ -77,6 +89,8 @@ enum AuditError {
     Io(String),
     #[error("malformed log entry")]
     Malformed,
+    #[error("no such record or checkpoint")]
+    NotFound,
 }
 
 impl From<std::io::Error> for AuditError {
@@ -90,6 +104,7 @@ impl IntoResponse for AuditError {
         let status = match self {
             AuditError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AuditError::Malformed => StatusCode::BAD_REQUEST,
+            AuditError::NotFound => StatusCode::NOT_FOUND,
         };
         (
             status,
@@ -108,8 +123,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let log_path = std::env::var("AUDIT_LOG_PATH").unwrap_or_else(|_| DEFAULT_PATH.to_string());
 
     let writer = AuditWriter::new(PathBuf::from(&log_path)).await?;
+    let (events, _) = broadcast::channel(256);
     let state = AppState {
         writer: Arc::new(Mutex::new(writer)),
+        events,
     };
 
     tracing::info!(
@@ -126,6 +143,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = Router::new()
         .route("/v1/events", post(record_event))
         .route("/v1/events/export", get(export_events))
+        .route("/v1/events/verify", get(verify_chain))
+        .route("/v1/events/:seq/proof", get(event_proof))
+        .route("/v1/stream", get(stream_events))
         .route("/metrics", get(metrics))
         .with_state(state)
         .merge(health_router(SERVICE_NAME))
@@ -142,16 +162,331 @@ async fn record_event(
     Json(event): Json<IncomingEvent>,
 ) -> Result<StatusCode, AuditError> {
     let mut writer = state.writer.lock().await;
-    writer.append(event).await?;
+    let record = writer.append(event).await?;
+    drop(writer);
+    let _ = state.events.send(record);
     Ok(StatusCode::ACCEPTED)
 }
 
+/// Filters for `GET /v1/events/export` and the `subscribe` message on
+/// `/v1/stream`. Every field is optional and narrows the records further
+/// when present.
+#[derive(Debug, Default, Deserialize)]
+struct ExportQuery {
+    actor: Option<String>,
+    action: Option<String>,
+    outcome: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl ExportQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        self.actor
+            .as_deref()
+            .map_or(true, |actor| record.event.actor == actor)
+            && self
+                .action
+                .as_deref()
+                .map_or(true, |action| record.event.action == action)
+            && self
+                .outcome
+                .as_deref()
+                .map_or(true, |outcome| record.event.outcome == outcome)
+            && self.since.map_or(true, |since| record.timestamp >= since)
+            && self.until.map_or(true, |until| record.timestamp <= until)
+    }
+}
+
+/// Streams the audit log as newline-delimited JSON instead of buffering
+/// every record into a `Vec` first, so exporting a long-lived log doesn't
+/// hold the whole thing in memory at once.
 async fn export_events(
     State(state): State<AppState>,
-) -> Result<Json<Vec<AuditRecord>>, AuditError> {
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AuditError> {
+    let writer = state.writer.lock().await;
+    let tree = writer.tree.clone();
+    drop(writer);
+
+    let lines = tree.iter().filter_map(move |entry| {
+        let record = match entry {
+            Ok((_, value)) => match bincode::deserialize::<AuditRecord>(&value) {
+                Ok(record) => record,
+                Err(_) => return Some(Err(AuditError::Malformed)),
+            },
+            Err(err) => return Some(Err(AuditError::Io(err.to_string()))),
+        };
+        if !query.matches(&record) {
+            return None;
+        }
+        let mut line = match serde_json::to_vec(&record) {
+            Ok(line) => line,
+            Err(_) => return Some(Err(AuditError::Malformed)),
+        };
+        line.push(b'\n');
+        Some(Ok(Bytes::from(line)))
+    });
+
+    let body = Body::from_stream(stream::iter(lines));
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-ndjson"),
+        )],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Serialize)]
+struct VerifyResponse {
+    intact: bool,
+    first_break: Option<u64>,
+    head_hash: Option<String>,
+}
+
+/// Re-walks the hash chain from the start, recomputing
+/// `hash = SHA256(prev_hash || event)` for every record, and reports the
+/// sequence of the first record whose stored hash doesn't match.
+async fn verify_chain(State(state): State<AppState>) -> Result<Json<VerifyResponse>, AuditError> {
     let writer = state.writer.lock().await;
-    let entries = writer.read_all().await?;
-    Ok(Json(entries))
+    let tree = writer.tree.clone();
+    drop(writer);
+
+    let mut expected_prev = vec![0u8; 32];
+    for entry in tree.iter() {
+        let (key, value) = entry.map_err(|err| AuditError::Io(err.to_string()))?;
+        let record: AuditRecord =
+            bincode::deserialize(&value).map_err(|_| AuditError::Malformed)?;
+        let sequence_bytes: [u8; 8] = key.as_ref().try_into().map_err(|_| AuditError::Malformed)?;
+        let sequence = u64::from_be_bytes(sequence_bytes);
+
+        let stored_prev = STANDARD
+            .decode(&record.prev_hash)
+            .map_err(|_| AuditError::Malformed)?;
+        let stored_hash = STANDARD
+            .decode(&record.hash)
+            .map_err(|_| AuditError::Malformed)?;
+        let computed_hash = hash_record(&expected_prev, &record.event)?;
+
+        if stored_prev != expected_prev || stored_hash != computed_hash {
+            return Ok(Json(VerifyResponse {
+                intact: false,
+                first_break: Some(sequence),
+                head_hash: None,
+            }));
+        }
+
+        expected_prev = computed_hash;
+    }
+
+    Ok(Json(VerifyResponse {
+        intact: true,
+        first_break: None,
+        head_hash: Some(STANDARD.encode(&expected_prev)),
+    }))
+}
+
+fn hash_record(prev_hash: &[u8], event: &IncomingEvent) -> Result<Vec<u8>, AuditError> {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash);
+    hasher.update(serde_json::to_vec(event).map_err(|_| AuditError::Malformed)?);
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Leaf hash for a record going into a Merkle checkpoint:
+/// `SHA256(0x00 || record_hash)`.
+fn merkle_leaf_hash(record_hash: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(record_hash);
+    hasher.finalize().to_vec()
+}
+
+/// Internal node hash for a Merkle checkpoint:
+/// `SHA256(0x01 || left || right)`.
+fn merkle_parent_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// Folds `leaves` up to a single Merkle root, duplicating the last node of
+/// any level with an odd count.
+fn merkle_root(mut level: Vec<Vec<u8>>) -> Vec<u8> {
+    if level.is_empty() {
+        return vec![0u8; 32];
+    }
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(level.last().expect("checked non-empty above").clone());
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| merkle_parent_hash(&pair[0], &pair[1]))
+            .collect();
+    }
+    level.into_iter().next().unwrap_or_default()
+}
+
+/// One step of a Merkle audit path: the sibling hash and whether it sits to
+/// the left of the node being folded.
+struct MerkleProofStep {
+    sibling: Vec<u8>,
+    sibling_is_left: bool,
+}
+
+/// Builds the root and the sibling audit path from `leaves[index]` to the
+/// root, applying the same odd-level duplication as [`merkle_root`].
+fn merkle_proof(mut level: Vec<Vec<u8>>, mut index: usize) -> (Vec<u8>, Vec<MerkleProofStep>) {
+    let mut steps = Vec::new();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(level.last().expect("checked non-empty above").clone());
+        }
+        let sibling_index = index ^ 1;
+        steps.push(MerkleProofStep {
+            sibling: level[sibling_index].clone(),
+            sibling_is_left: sibling_index < index,
+        });
+        level = level
+            .chunks(2)
+            .map(|pair| merkle_parent_hash(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+    (level.into_iter().next().unwrap_or_default(), steps)
+}
+
+/// Reads the stored `hash` of every record in `[batch_start, batch_start +
+/// MERKLE_BATCH_SIZE)` and turns each into its Merkle leaf hash.
+fn batch_leaf_hashes(tree: &sled::Tree, batch_start: u64) -> Result<Vec<Vec<u8>>, AuditError> {
+    let start = batch_start.to_be_bytes();
+    let end = (batch_start + MERKLE_BATCH_SIZE).to_be_bytes();
+    tree.range(start..end)
+        .map(|entry| {
+            let (_, value) = entry.map_err(|err| AuditError::Io(err.to_string()))?;
+            let record: AuditRecord =
+                bincode::deserialize(&value).map_err(|_| AuditError::Malformed)?;
+            let record_hash = STANDARD
+                .decode(&record.hash)
+                .map_err(|_| AuditError::Malformed)?;
+            Ok(merkle_leaf_hash(&record_hash))
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+struct MerkleProofStepDto {
+    hash: String,
+    is_left: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct MerkleProofResponse {
+    seq: u64,
+    batch_index: u64,
+    leaf_hash: String,
+    root: String,
+    siblings: Vec<MerkleProofStepDto>,
+}
+
+/// Returns the sibling hashes a caller needs to recompute the Merkle root of
+/// `seq`'s checkpoint batch from that one record alone. Only available once
+/// the batch containing `seq` has been fully checkpointed.
+async fn event_proof(
+    State(state): State<AppState>,
+    Path(seq): Path<u64>,
+) -> Result<Json<MerkleProofResponse>, AuditError> {
+    let writer = state.writer.lock().await;
+    let tree = writer.tree.clone();
+    let merkle_roots = writer.merkle_roots.clone();
+    drop(writer);
+
+    let batch_index = seq / MERKLE_BATCH_SIZE;
+    let batch_start = batch_index * MERKLE_BATCH_SIZE;
+
+    let stored_root = merkle_roots
+        .get(batch_index.to_be_bytes())
+        .map_err(|err| AuditError::Io(err.to_string()))?
+        .ok_or(AuditError::NotFound)?;
+
+    let leaves = batch_leaf_hashes(&tree, batch_start)?;
+    let index = (seq - batch_start) as usize;
+    let leaf_hash = leaves.get(index).cloned().ok_or(AuditError::NotFound)?;
+    let (root, steps) = merkle_proof(leaves, index);
+
+    if root != stored_root.as_ref() {
+        return Err(AuditError::Malformed);
+    }
+
+    Ok(Json(MerkleProofResponse {
+        seq,
+        batch_index,
+        leaf_hash: STANDARD.encode(&leaf_hash),
+        root: STANDARD.encode(&root),
+        siblings: steps
+            .into_iter()
+            .map(|step| MerkleProofStepDto {
+                hash: STANDARD.encode(&step.sibling),
+                is_left: step.sibling_is_left,
+            })
+            .collect(),
+    }))
+}
+
+/// Upgrades to a WebSocket that pushes every newly appended [`AuditRecord`]
+/// as a JSON frame. The client may send a single `ExportQuery`-shaped JSON
+/// message within a second of connecting to narrow the stream to, e.g.,
+/// one actor's activity; omitting it (or sending something unparseable)
+/// streams everything.
+async fn stream_events(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_stream_socket(socket, state))
+}
+
+async fn handle_stream_socket(socket: WebSocket, state: AppState) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut events = state.events.subscribe();
+
+    let mut filter = ExportQuery::default();
+    if let Ok(Some(Ok(Message::Text(text)))) =
+        tokio::time::timeout(std::time::Duration::from_secs(1), receiver.next()).await
+    {
+        if let Ok(parsed) = serde_json::from_str::<ExportQuery>(&text) {
+            filter = parsed;
+        }
+    }
+
+    let forward = tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(record) => {
+                    if !filter.matches(&record) {
+                        continue;
+                    }
+                    let Ok(payload) = serde_json::to_string(&record) else {
+                        continue;
+                    };
+                    if sender.send(Message::Text(payload)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // drain incoming messages to keep the connection alive until the client closes it
+    while let Some(Ok(msg)) = receiver.next().await {
+        if matches!(msg, Message::Close(_)) {
+            break;
+        }
+    }
+    forward.abort();
 }
 
 async fn metrics() -> impl IntoResponse {
@@ -186,80 +521,79 @@ async fn track_http_metrics(req: Request<Body>, next: Next) -> Response {
 
 impl AuditWriter {
     async fn new(path: PathBuf) -> Result<Self, AuditError> {
-        if let Some(parent) = path.parent() {
-            if !parent.as_os_str().is_empty() {
-                fs::create_dir_all(parent).await?;
-            }
-        }
+        let tree = Self::open_tree(&path, "records")?;
+        let merkle_roots = Self::open_tree(&path, "merkle_roots")?;
+        let prev_hash = Self::hydrate_prev_hash(&tree)?;
+        Ok(Self {
+            tree,
+            merkle_roots,
+            prev_hash,
+        })
+    }
 
-        let prev_hash = Self::hydrate_prev_hash(&path).await?;
-        Ok(Self { path, prev_hash })
+    /// Opens (or reuses the process-wide) sled database backing the audit
+    /// log and returns the named tree within it.
+    fn open_tree(path: &std::path::Path, name: &str) -> Result<sled::Tree, AuditError> {
+        let db = AUDIT_DB
+            .get_or_try_init(|| sled::open(path))
+            .map_err(|err| AuditError::Io(err.to_string()))?;
+        db.open_tree(name)
+            .map_err(|err| AuditError::Io(err.to_string()))
     }
 
-    async fn hydrate_prev_hash(path: &PathBuf) -> Result<Vec<u8>, AuditError> {
-        if !path.exists() {
-            return Ok(vec![0u8; 32]);
-        }
-        let contents = fs::read(path).await?;
-        if contents.is_empty() {
-            return Ok(vec![0u8; 32]);
-        }
-        let mut prev = vec![0u8; 32];
-        for line in contents
-            .split(|b| *b == b'\n')
-            .filter(|line| !line.is_empty())
-        {
-            let record: AuditRecord =
-                serde_json::from_slice(line).map_err(|_| AuditError::Malformed)?;
-            prev = STANDARD
-                .decode(record.hash)
-                .map_err(|_| AuditError::Malformed)?;
+    /// O(1): seeks straight to the highest-sequence record instead of
+    /// replaying the whole log to find the last hash.
+    fn hydrate_prev_hash(tree: &sled::Tree) -> Result<Vec<u8>, AuditError> {
+        match tree.last().map_err(|err| AuditError::Io(err.to_string()))? {
+            Some((_, value)) => {
+                let record: AuditRecord =
+                    bincode::deserialize(&value).map_err(|_| AuditError::Malformed)?;
+                STANDARD
+                    .decode(record.hash)
+                    .map_err(|_| AuditError::Malformed)
+            }
+            None => Ok(vec![0u8; 32]),
         }
-        Ok(prev)
     }
 
-    async fn append(&mut self, event: IncomingEvent) -> Result<(), AuditError> {
+    async fn append(&mut self, event: IncomingEvent) -> Result<AuditRecord, AuditError> {
         let timestamp = Utc::now();
-        let mut hasher = Sha256::new();
-        hasher.update(&self.prev_hash);
-        hasher.update(serde_json::to_vec(&event).map_err(|_| AuditError::Malformed)?);
-        let hash = hasher.finalize();
+        let hash = hash_record(&self.prev_hash, &event)?;
         let record = AuditRecord {
             timestamp,
             prev_hash: STANDARD.encode(&self.prev_hash),
             hash: STANDARD.encode(&hash),
             event,
         };
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.path)
-            .await?;
-        file.write_all(
-            serde_json::to_vec(&record)
-                .map_err(|_| AuditError::Malformed)?
-                .as_slice(),
-        )
-        .await?;
-        file.write_all(b"\n").await?;
-        self.prev_hash = hash.to_vec();
-        Ok(())
+
+        let sequence = self
+            .tree
+            .generate_id()
+            .map_err(|err| AuditError::Io(err.to_string()))?;
+        let bytes = bincode::serialize(&record).map_err(|_| AuditError::Malformed)?;
+        self.tree
+            .insert(sequence.to_be_bytes(), bytes)
+            .map_err(|err| AuditError::Io(err.to_string()))?;
+
+        self.prev_hash = hash;
+        self.checkpoint_batch_if_complete(sequence)?;
+        Ok(record)
     }
 
-    async fn read_all(&self) -> Result<Vec<AuditRecord>, AuditError> {
-        if !self.path.exists() {
-            return Ok(Vec::new());
-        }
-        let contents = fs::read(&self.path).await?;
-        let mut records = Vec::new();
-        for line in contents
-            .split(|b| *b == b'\n')
-            .filter(|line| !line.is_empty())
-        {
-            let record: AuditRecord =
-                serde_json::from_slice(line).map_err(|_| AuditError::Malformed)?;
-            records.push(record);
+    /// If `sequence` completes a `MERKLE_BATCH_SIZE`-record batch, builds
+    /// that batch's Merkle tree and persists its root.
+    fn checkpoint_batch_if_complete(&self, sequence: u64) -> Result<(), AuditError> {
+        let batch_index = sequence / MERKLE_BATCH_SIZE;
+        let batch_start = batch_index * MERKLE_BATCH_SIZE;
+        if sequence + 1 - batch_start != MERKLE_BATCH_SIZE {
+            return Ok(());
         }
-        Ok(records)
+
+        let leaves = batch_leaf_hashes(&self.tree, batch_start)?;
+        let root = merkle_root(leaves);
+        self.merkle_roots
+            .insert(batch_index.to_be_bytes(), root)
+            .map_err(|err| AuditError::Io(err.to_string()))?;
+        Ok(())
     }
 }
@@ -0,0 +1,1142 @@
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::{ws::Message, MatchedPath, Path, Query, State, WebSocketUpgrade};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::middleware::{from_fn, Next};
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
+use axum::routing::{delete, get, put};
+use axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use futures_core::Stream;
+use futures_util::stream::{self, FuturesUnordered};
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, Semaphore};
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+
+use sqlx::Row;
+
+use common_config::service_port;
+use common_obs::{
+    encode_prometheus_metrics, handler_latency_seconds, health_router, http_requests_total,
+    ObsInit, PROMETHEUS_CONTENT_TYPE,
+};
+use lokan_core::{
+    LokanConfig, Service, ServiceContext, ServiceError, ServiceHealth, ServiceManager,
+    ServiceStatus,
+};
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod store;
+
+#[cfg(feature = "sled")]
+use store::SledStore;
+use store::{RegistryStore, RegistryStoreError, SqlxStore};
+
+/// Tick interval for the outbox delivery worker spawned in `DeviceRegistryService::start`.
+const OUTBOX_TICK_INTERVAL: Duration = Duration::from_secs(1);
+/// Upper bound on rows pulled per tick, so one slow tick can't starve the
+/// next.
+const OUTBOX_BATCH_SIZE: i64 = 64;
+/// Upper bound on in-flight webhook deliveries per tick.
+const OUTBOX_MAX_CONCURRENT_DELIVERIES: usize = 16;
+/// A row is moved to `dead` once it has failed more than this many times.
+const OUTBOX_MAX_ATTEMPTS: i64 = 8;
+const OUTBOX_BASE_BACKOFF_MS: i64 = 1_000;
+const OUTBOX_MAX_BACKOFF_MS: i64 = 300_000;
+const OUTBOX_DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many recent events [`EventLog`] keeps around for reconnecting
+/// SSE/WS clients to replay. Older events are only reachable through the
+/// durable outbox, not through resumable streams.
+const EVENT_LOG_CAPACITY: usize = 256;
+
+#[cfg(all(feature = "sqlite", feature = "postgres"))]
+compile_error!("enable only one backend feature at a time");
+#[cfg(all(feature = "sled", feature = "postgres"))]
+compile_error!("enable only one backend feature at a time");
+#[cfg(all(feature = "sled", feature = "sqlite"))]
+compile_error!("enable only one backend feature at a time");
+
+type DbPool = sqlx::AnyPool;
+
+const SERVICE_NAME: &str = "device-registry";
+const PORT_ENV: &str = "DEVICE_REGISTRY_PORT";
+const DEFAULT_PORT: u16 = 8001;
+#[cfg(feature = "postgres")]
+const DEFAULT_DB_URL: &str = "postgres://localhost/device_registry";
+#[cfg(not(feature = "postgres"))]
+const DEFAULT_DB_URL: &str = "sqlite://device-registry.db";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn build_sha() -> &'static str {
+    option_env!("BUILD_SHA").unwrap_or("unknown")
+}
+
+fn build_time() -> &'static str {
+    option_env!("BUILD_TIME").unwrap_or("unknown")
+}
+
+#[derive(Clone)]
+struct AppState {
+    /// Still used directly for the outbox/subscription tables, which stay
+    /// sqlx-only regardless of which [`RegistryStore`] backs `store` — see
+    /// [`enqueue_and_publish`].
+    pool: DbPool,
+    /// Device/room/capability storage. Backed by [`SqlxStore`] (the `pool`
+    /// above) by default, or by [`SledStore`] when the `sled` feature is
+    /// enabled.
+    store: Arc<dyn RegistryStore>,
+    event_log: Arc<EventLog>,
+    http_client: Client,
+}
+
+/// Bounded buffer of the most recent [`DeviceEvent`]s alongside the
+/// monotonic sequence number each was assigned, so a client reconnecting to
+/// `/v1/events/sse` (via `Last-Event-ID`) or `/v1/events/ws` (via `?since=`)
+/// can replay what it missed instead of silently skipping the gap. This is
+/// a best-effort, in-memory complement to the durable outbox, not a
+/// replacement for it — events older than [`EVENT_LOG_CAPACITY`] are only
+/// reachable through the outbox.
+struct EventLog {
+    sender: broadcast::Sender<(u64, DeviceEvent)>,
+    buffer: RwLock<VecDeque<(u64, DeviceEvent)>>,
+    next_seq: AtomicU64,
+}
+
+impl EventLog {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_LOG_CAPACITY);
+        Self {
+            sender,
+            buffer: RwLock::new(VecDeque::with_capacity(EVENT_LOG_CAPACITY)),
+            next_seq: AtomicU64::new(1),
+        }
+    }
+
+    /// Assigns the next sequence number to `event`, records it in the
+    /// replay buffer, and fans it out to any live subscriber.
+    fn publish(&self, event: DeviceEvent) {
+        let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut buffer = self.buffer.write().expect("event log buffer poisoned");
+            buffer.push_back((seq, event.clone()));
+            while buffer.len() > EVENT_LOG_CAPACITY {
+                buffer.pop_front();
+            }
+        }
+        let _ = self.sender.send((seq, event));
+    }
+
+    /// Number of live SSE/WS subscribers, for [`DeviceRegistryService::health`].
+    fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Sequence number of the most recently published event, or `0` if none
+    /// has been published yet.
+    fn last_seq(&self) -> u64 {
+        self.next_seq.load(Ordering::SeqCst).saturating_sub(1)
+    }
+
+    /// Replays every buffered event with `seq > since` (or the whole
+    /// buffer, if `since` is `None`), then switches to the live stream,
+    /// skipping any live event already covered by the replay.
+    fn stream_since(&self, since: Option<u64>) -> impl Stream<Item = (u64, DeviceEvent)> {
+        let since = since.unwrap_or(0);
+        let backlog: Vec<_> = {
+            let buffer = self.buffer.read().expect("event log buffer poisoned");
+            buffer
+                .iter()
+                .filter(|(seq, _)| *seq > since)
+                .cloned()
+                .collect()
+        };
+        let last_replayed = backlog.last().map(|(seq, _)| *seq).unwrap_or(since);
+
+        let live = BroadcastStream::new(self.sender.subscribe())
+            .filter_map(|event| async move { event.ok() })
+            .filter(move |(seq, _)| std::future::ready(*seq > last_replayed));
+
+        stream::iter(backlog).chain(live)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DeviceEvent {
+    kind: EventKind,
+    device_id: String,
+    payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Updated => "updated",
+            EventKind::Deleted => "deleted",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "created" => Some(EventKind::Created),
+            "updated" => Some(EventKind::Updated),
+            "deleted" => Some(EventKind::Deleted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Subscription {
+    id: String,
+    url: String,
+    kind: Option<EventKind>,
+    device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NewSubscription {
+    url: String,
+    kind: Option<EventKind>,
+    device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Room {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Device {
+    id: String,
+    room_id: Option<String>,
+    name: String,
+    kind: String,
+    status: String,
+    state: serde_json::Value,
+    version: i64,
+    /// Base64-encoded Ed25519 public key, if the device authenticates its
+    /// own state pushes. See [`DeviceStateUpdate`].
+    public_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Capability {
+    id: i64,
+    device_id: String,
+    capability: String,
+    properties: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NewRoom {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NewDevice {
+    room_id: Option<String>,
+    name: String,
+    kind: String,
+    #[serde(default = "default_status")]
+    status: String,
+    #[serde(default)]
+    state: serde_json::Value,
+    /// Base64-encoded Ed25519 public key. When set, `update_device_state`
+    /// requires every state push for this device to carry a matching
+    /// detached signature.
+    #[serde(default)]
+    public_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DeviceStateUpdate {
+    state: serde_json::Value,
+    /// Base64-encoded detached Ed25519 signature over the canonical
+    /// (sorted-key) JSON encoding of `state`. Required whenever the target
+    /// device has a `public_key` registered.
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct UpdateDevice {
+    room_id: Option<String>,
+    name: Option<String>,
+    kind: Option<String>,
+    status: Option<String>,
+    state: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CapabilityPayload {
+    capability: String,
+    #[serde(default)]
+    properties: serde_json::Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum RegistryError {
+    #[error("record not found")]
+    NotFound,
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("device was modified by another writer; refetch and retry with the current version")]
+    Conflict,
+    #[error("state update signature is missing or invalid")]
+    InvalidSignature,
+    #[error("storage error: {0}")]
+    Store(#[from] RegistryStoreError),
+}
+
+impl IntoResponse for RegistryError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match &self {
+            RegistryError::NotFound => StatusCode::NOT_FOUND,
+            RegistryError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            RegistryError::Conflict => StatusCode::PRECONDITION_FAILED,
+            RegistryError::InvalidSignature => StatusCode::BAD_REQUEST,
+            RegistryError::Store(err) => match err {
+                RegistryStoreError::NotFound => StatusCode::NOT_FOUND,
+                RegistryStoreError::Conflict => StatusCode::PRECONDITION_FAILED,
+                RegistryStoreError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+        };
+        let msg = self.to_string();
+        (status, Json(serde_json::json!({ "error": msg }))).into_response()
+    }
+}
+
+/// Builds the full axum router for the service, given an already-initialized
+/// [`AppState`]. Shared by the standalone binary and [`DeviceRegistryService`].
+fn build_router(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/rooms", get(list_rooms).post(create_room))
+        .route("/v1/devices", get(list_devices).post(create_device))
+        .route(
+            "/v1/devices/:id",
+            get(fetch_device).put(update_device).delete(delete_device),
+        )
+        .route("/v1/devices/:id/state", put(update_device_state))
+        .route(
+            "/v1/devices/:id/capabilities",
+            get(list_capabilities).post(add_capability),
+        )
+        .route("/v1/events/sse", get(events_sse))
+        .route("/v1/events/ws", get(events_ws))
+        .route(
+            "/v1/subscriptions",
+            get(list_subscriptions).post(create_subscription),
+        )
+        .route("/v1/subscriptions/:id", delete(delete_subscription))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+        .merge(health_router(SERVICE_NAME))
+        .layer(from_fn(track_http_metrics))
+}
+
+/// [`ServiceContext`] extension key under which a caller (e.g. a hub runtime
+/// sharing one database across services) may provide an already-open
+/// [`DbPool`] for [`DeviceRegistryService`] to reuse instead of opening its
+/// own from `DEVICE_REGISTRY_DATABASE_URL`.
+pub const DB_POOL_EXTENSION_KEY: &str = "device_registry_db_pool";
+
+struct RunningDeviceRegistry {
+    state: AppState,
+    server_handle: tokio::task::JoinHandle<()>,
+    outbox_handle: tokio::task::JoinHandle<()>,
+}
+
+/// Runs the device-registry HTTP server as a [`lokan_core::Service`], so a
+/// hub runtime can start, stop, and monitor it alongside its other services
+/// instead of it living in its own standalone process. [`run_standalone`]
+/// registers this same type on a single-service [`ServiceManager`], so the
+/// standalone binary is supervised the same way rather than duplicating the
+/// startup logic.
+pub struct DeviceRegistryService {
+    running: tokio::sync::Mutex<Option<RunningDeviceRegistry>>,
+    status: std::sync::Mutex<ServiceStatus>,
+}
+
+impl Default for DeviceRegistryService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceRegistryService {
+    pub fn new() -> Self {
+        Self {
+            running: tokio::sync::Mutex::new(None),
+            status: std::sync::Mutex::new(ServiceStatus::Stopped),
+        }
+    }
+
+    fn set_status(&self, status: ServiceStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+}
+
+#[async_trait]
+impl Service for DeviceRegistryService {
+    fn name(&self) -> &'static str {
+        SERVICE_NAME
+    }
+
+    async fn start(&self, ctx: ServiceContext) -> Result<(), ServiceError> {
+        self.set_status(ServiceStatus::Starting);
+
+        let port = ctx
+            .config()
+            .network
+            .device_registry_port
+            .unwrap_or_else(|| service_port(PORT_ENV, DEFAULT_PORT));
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+        let pool = match ctx.get_extension::<DbPool>(DB_POOL_EXTENSION_KEY) {
+            Some(pool) => (*pool).clone(),
+            None => {
+                let database_url = std::env::var("DEVICE_REGISTRY_DATABASE_URL")
+                    .unwrap_or_else(|_| DEFAULT_DB_URL.to_string());
+                init_pool(&database_url)
+                    .await
+                    .map_err(|err| ServiceError::Initialization(err.to_string()))?
+            }
+        };
+        init_schema(&pool)
+            .await
+            .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+
+        #[cfg(feature = "sled")]
+        let store: Arc<dyn RegistryStore> = {
+            let sled_path = std::env::var("DEVICE_REGISTRY_SLED_PATH")
+                .unwrap_or_else(|_| "device-registry.sled".to_string());
+            Arc::new(
+                SledStore::open(&sled_path)
+                    .map_err(|err| ServiceError::Initialization(err.to_string()))?,
+            )
+        };
+        #[cfg(not(feature = "sled"))]
+        let store: Arc<dyn RegistryStore> = Arc::new(SqlxStore::new(pool.clone()));
+
+        let state = AppState {
+            pool,
+            store,
+            event_log: Arc::new(EventLog::new()),
+            http_client: Client::new(),
+        };
+
+        let outbox_handle = tokio::spawn(run_outbox_worker(
+            state.pool.clone(),
+            state.http_client.clone(),
+        ));
+
+        tracing::info!(
+            event = "service_start",
+            service = SERVICE_NAME,
+            version = VERSION,
+            build_sha = build_sha(),
+            build_time = build_time(),
+            listen_addr = %addr,
+            "starting service"
+        );
+
+        let app = build_router(state.clone());
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|err| ServiceError::Initialization(err.to_string()))?;
+        let server_handle = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app.into_make_service()).await {
+                tracing::warn!(%err, "device-registry http server exited with error");
+            }
+        });
+
+        *self.running.lock().await = Some(RunningDeviceRegistry {
+            state,
+            server_handle,
+            outbox_handle,
+        });
+        self.set_status(ServiceStatus::Running);
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), ServiceError> {
+        self.set_status(ServiceStatus::Stopping);
+        if let Some(running) = self.running.lock().await.take() {
+            running.server_handle.abort();
+            running.outbox_handle.abort();
+        }
+        self.set_status(ServiceStatus::Stopped);
+        Ok(())
+    }
+
+    fn status(&self) -> ServiceStatus {
+        *self.status.lock().unwrap()
+    }
+
+    async fn health(&self) -> ServiceHealth {
+        let detail = match self.running.lock().await.as_ref() {
+            Some(running) => {
+                let db_connected = sqlx::query("SELECT 1")
+                    .execute(&running.state.pool)
+                    .await
+                    .is_ok();
+                serde_json::json!({
+                    "db_connected": db_connected,
+                    "event_subscribers": running.state.event_log.subscriber_count(),
+                    "last_event_seq": running.state.event_log.last_seq(),
+                })
+            }
+            None => serde_json::Value::Null,
+        };
+        ServiceHealth {
+            service: self.name().to_string(),
+            status: self.status(),
+            detail,
+            observed_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Runs the device-registry as a standalone process, supervised by its own
+/// single-service [`ServiceManager`]. This is the entry point the binary
+/// target uses; composing it into a larger hub runtime instead means
+/// registering [`DeviceRegistryService`] on that runtime's own manager.
+pub async fn run_standalone() -> Result<(), Box<dyn std::error::Error>> {
+    ObsInit::init(SERVICE_NAME).map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+
+    let mut manager = ServiceManager::new(LokanConfig::default());
+    manager.register_service(Arc::new(DeviceRegistryService::new()));
+    manager.start_all().await?;
+
+    tokio::signal::ctrl_c().await?;
+    tracing::info!(service = SERVICE_NAME, "shutdown signal received");
+    manager.stop_all().await;
+
+    Ok(())
+}
+
+async fn init_pool(url: &str) -> Result<DbPool, sqlx::Error> {
+    sqlx::AnyPool::connect(url).await
+}
+
+async fn init_schema(pool: &DbPool) -> Result<(), sqlx::Error> {
+    // Device/room/capability tables are only needed when `SqlxStore` is the
+    // active `RegistryStore`; with the `sled` feature enabled that data
+    // lives in `SledStore`'s embedded database instead. The outbox and
+    // subscription tables below stay sqlx-only either way.
+    #[cfg(not(feature = "sled"))]
+    {
+        #[cfg(feature = "postgres")]
+        let create_capabilities = "CREATE TABLE IF NOT EXISTS capabilities (
+        id SERIAL PRIMARY KEY,
+        device_id TEXT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+        capability TEXT NOT NULL,
+        properties TEXT NOT NULL
+    )";
+
+        #[cfg(not(feature = "postgres"))]
+        let create_capabilities = "CREATE TABLE IF NOT EXISTS capabilities (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        device_id TEXT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+        capability TEXT NOT NULL,
+        properties TEXT NOT NULL
+    )";
+
+        let create_rooms =
+            "CREATE TABLE IF NOT EXISTS rooms (id TEXT PRIMARY KEY, name TEXT NOT NULL)";
+        let create_devices = "CREATE TABLE IF NOT EXISTS devices (
+        id TEXT PRIMARY KEY,
+        room_id TEXT REFERENCES rooms(id) ON DELETE SET NULL,
+        name TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        status TEXT NOT NULL,
+        state TEXT NOT NULL,
+        version INTEGER NOT NULL DEFAULT 0,
+        public_key TEXT
+    )";
+        sqlx::query(create_rooms).execute(pool).await?;
+        sqlx::query(create_devices).execute(pool).await?;
+        sqlx::query(create_capabilities).execute(pool).await?;
+    }
+
+    #[cfg(feature = "postgres")]
+    let create_outbox = "CREATE TABLE IF NOT EXISTS outbox (
+        id SERIAL PRIMARY KEY,
+        subscription_id TEXT NOT NULL REFERENCES subscriptions(id) ON DELETE CASCADE,
+        event_json TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts BIGINT NOT NULL DEFAULT 0,
+        next_attempt_at BIGINT NOT NULL
+    )";
+
+    #[cfg(not(feature = "postgres"))]
+    let create_outbox = "CREATE TABLE IF NOT EXISTS outbox (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        subscription_id TEXT NOT NULL REFERENCES subscriptions(id) ON DELETE CASCADE,
+        event_json TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts BIGINT NOT NULL DEFAULT 0,
+        next_attempt_at BIGINT NOT NULL
+    )";
+
+    let create_subscriptions = "CREATE TABLE IF NOT EXISTS subscriptions (
+        id TEXT PRIMARY KEY,
+        url TEXT NOT NULL,
+        kind TEXT,
+        device_id TEXT
+    )";
+    sqlx::query(create_subscriptions).execute(pool).await?;
+    sqlx::query(create_outbox).execute(pool).await?;
+    Ok(())
+}
+
+/// Enqueues one durable outbox row per subscription matching `event`'s kind
+/// and device, within the caller's transaction `tx` (see
+/// [`enqueue_and_publish`]). Matching happens here (rather than at delivery time) so a
+/// subscription added after the event fired never sees it, and one added
+/// before it always does.
+async fn enqueue_outbox(
+    tx: &mut sqlx::Transaction<'_, sqlx::Any>,
+    event: &DeviceEvent,
+) -> Result<(), sqlx::Error> {
+    let event_json = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    let subscriptions = sqlx::query("SELECT id FROM subscriptions WHERE (kind IS NULL OR kind = ?) AND (device_id IS NULL OR device_id = ?)")
+        .bind(event.kind.as_str())
+        .bind(&event.device_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    for subscription in subscriptions {
+        let subscription_id: String = subscription.get("id");
+        sqlx::query(
+            "INSERT INTO outbox (subscription_id, event_json, next_attempt_at) VALUES (?, ?, ?)",
+        )
+        .bind(subscription_id)
+        .bind(&event_json)
+        .bind(now_millis())
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Enqueues outbox rows for `event` and fans it out to any live SSE/WS
+/// subscriber, once the store mutation that produced `event` has already
+/// succeeded. Device mutations go through `AppState::store` now (which may
+/// be `SledStore`, not `pool`), so this can no longer share a single
+/// transaction with that mutation the way it could when everything lived in
+/// one sqlx transaction; it runs as a best-effort follow-up instead.
+async fn enqueue_and_publish(state: &AppState, event: DeviceEvent) -> Result<(), RegistryError> {
+    let mut tx = state.pool.begin().await?;
+    enqueue_outbox(&mut tx, &event).await?;
+    tx.commit().await?;
+    state.event_log.publish(event);
+    Ok(())
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+        )],
+        encode_prometheus_metrics(),
+    )
+}
+
+async fn track_http_metrics(req: Request<Body>, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    let start = Instant::now();
+    let response = next.run(req).await;
+    let latency = start.elapsed().as_secs_f64();
+    let status = response.status().as_u16().to_string();
+
+    http_requests_total().inc(&[SERVICE_NAME, route.as_str(), status.as_str()], 1);
+    handler_latency_seconds().observe(&[SERVICE_NAME, route.as_str()], latency);
+
+    response
+}
+
+async fn list_rooms(State(state): State<AppState>) -> Result<Json<Vec<Room>>, RegistryError> {
+    Ok(Json(state.store.list_rooms().await?))
+}
+
+async fn create_room(
+    State(state): State<AppState>,
+    Json(payload): Json<NewRoom>,
+) -> Result<Json<Room>, RegistryError> {
+    let room = Room {
+        id: Uuid::new_v4().to_string(),
+        name: payload.name,
+    };
+    state.store.create_room(room.clone()).await?;
+    Ok(Json(room))
+}
+
+async fn list_devices(State(state): State<AppState>) -> Result<Json<Vec<Device>>, RegistryError> {
+    Ok(Json(state.store.list_devices().await?))
+}
+
+async fn create_device(
+    State(state): State<AppState>,
+    Json(payload): Json<NewDevice>,
+) -> Result<Json<Device>, RegistryError> {
+    let device = Device {
+        id: Uuid::new_v4().to_string(),
+        room_id: payload.room_id,
+        name: payload.name,
+        kind: payload.kind,
+        status: payload.status,
+        state: payload.state,
+        version: 0,
+        public_key: payload.public_key,
+    };
+    state.store.create_device(device.clone()).await?;
+
+    let event = DeviceEvent {
+        kind: EventKind::Created,
+        device_id: device.id.clone(),
+        payload: serde_json::to_value(&device).unwrap_or_default(),
+    };
+    enqueue_and_publish(&state, event).await?;
+    Ok(Json(device))
+}
+
+async fn fetch_device(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, RegistryError> {
+    let device = state.store.fetch_device(&id).await?;
+    let etag = HeaderValue::from_str(&device.version.to_string())
+        .unwrap_or_else(|_| HeaderValue::from_static("0"));
+    Ok((StatusCode::OK, [(header::ETAG, etag)], Json(device)))
+}
+
+async fn update_device(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateDevice>,
+) -> Result<Json<Device>, RegistryError> {
+    let existing = state.store.fetch_device(&id).await?;
+    let if_match = if_match_version(&headers).ok_or(RegistryError::Conflict)?;
+    if if_match != existing.version {
+        return Err(RegistryError::Conflict);
+    }
+
+    let device = Device {
+        id: id.clone(),
+        room_id: payload.room_id.or(existing.room_id.clone()),
+        name: payload.name.unwrap_or(existing.name.clone()),
+        kind: payload.kind.unwrap_or(existing.kind.clone()),
+        status: payload.status.unwrap_or(existing.status.clone()),
+        state: payload.state.unwrap_or(existing.state.clone()),
+        version: existing.version + 1,
+        public_key: existing.public_key.clone(),
+    };
+    state
+        .store
+        .update_device(device.clone(), existing.version)
+        .await?;
+
+    let event = DeviceEvent {
+        kind: EventKind::Updated,
+        device_id: id,
+        payload: serde_json::to_value(&device).unwrap_or_default(),
+    };
+    enqueue_and_publish(&state, event).await?;
+    Ok(Json(device))
+}
+
+async fn update_device_state(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<DeviceStateUpdate>,
+) -> Result<Json<Device>, RegistryError> {
+    let existing = state.store.fetch_device(&id).await?;
+    let if_match = if_match_version(&headers).ok_or(RegistryError::Conflict)?;
+    if if_match != existing.version {
+        return Err(RegistryError::Conflict);
+    }
+
+    if let Some(public_key) = &existing.public_key {
+        verify_state_signature(public_key, &payload.state, payload.signature.as_deref())?;
+    }
+
+    let expected_version = existing.version;
+    let device = Device {
+        state: payload.state,
+        version: expected_version + 1,
+        ..existing
+    };
+    state
+        .store
+        .update_device(device.clone(), expected_version)
+        .await?;
+
+    let event = DeviceEvent {
+        kind: EventKind::Updated,
+        device_id: device.id.clone(),
+        payload: serde_json::to_value(&device).unwrap_or_default(),
+    };
+    enqueue_and_publish(&state, event).await?;
+    Ok(Json(device))
+}
+
+async fn delete_device(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, RegistryError> {
+    state.store.delete_device(&id).await?;
+    let event = DeviceEvent {
+        kind: EventKind::Deleted,
+        device_id: id,
+        payload: serde_json::json!({}),
+    };
+    enqueue_and_publish(&state, event).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_capabilities(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Capability>>, RegistryError> {
+    Ok(Json(state.store.list_capabilities(&id).await?))
+}
+
+async fn add_capability(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<CapabilityPayload>,
+) -> Result<Json<Capability>, RegistryError> {
+    let capability = state
+        .store
+        .add_capability(&id, payload.capability, payload.properties)
+        .await?;
+    let event = DeviceEvent {
+        kind: EventKind::Updated,
+        device_id: id,
+        payload: serde_json::json!({ "capability": capability.capability }),
+    };
+    enqueue_and_publish(&state, event).await?;
+    Ok(Json(capability))
+}
+
+async fn list_subscriptions(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Subscription>>, RegistryError> {
+    let rows = sqlx::query("SELECT id, url, kind, device_id FROM subscriptions")
+        .fetch_all(&state.pool)
+        .await?;
+    let subscriptions = rows
+        .into_iter()
+        .map(|row| Subscription {
+            id: row.get("id"),
+            url: row.get("url"),
+            kind: row
+                .get::<Option<String>, _>("kind")
+                .as_deref()
+                .and_then(EventKind::parse),
+            device_id: row.get("device_id"),
+        })
+        .collect();
+    Ok(Json(subscriptions))
+}
+
+async fn create_subscription(
+    State(state): State<AppState>,
+    Json(payload): Json<NewSubscription>,
+) -> Result<Json<Subscription>, RegistryError> {
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO subscriptions (id, url, kind, device_id) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&payload.url)
+        .bind(payload.kind.map(EventKind::as_str))
+        .bind(&payload.device_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(Subscription {
+        id,
+        url: payload.url,
+        kind: payload.kind,
+        device_id: payload.device_id,
+    }))
+}
+
+async fn delete_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, RegistryError> {
+    let result = sqlx::query("DELETE FROM subscriptions WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(RegistryError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Parses the caller's `If-Match: <version>` header. Callers treat a
+/// missing header the same as a stale version, since an update without one
+/// can't prove it observed the current state.
+fn if_match_version(headers: &HeaderMap) -> Option<i64> {
+    headers
+        .get(header::IF_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Verifies a detached Ed25519 signature over the canonical (sorted-key,
+/// via `serde_json::Value`'s `BTreeMap`-backed object representation) JSON
+/// encoding of `state`. `public_key_b64`/`signature_b64` are base64 as sent
+/// over the wire; any decode, length, or verification failure is reported
+/// uniformly as `RegistryError::InvalidSignature` so callers can't
+/// distinguish "malformed" from "forged".
+fn verify_state_signature(
+    public_key_b64: &str,
+    state: &serde_json::Value,
+    signature_b64: Option<&str>,
+) -> Result<(), RegistryError> {
+    let signature_b64 = signature_b64.ok_or(RegistryError::InvalidSignature)?;
+
+    let key_bytes: [u8; 32] = BASE64
+        .decode(public_key_b64)
+        .map_err(|_| RegistryError::InvalidSignature)?
+        .try_into()
+        .map_err(|_| RegistryError::InvalidSignature)?;
+    let verifying_key =
+        VerifyingKey::from_bytes(&key_bytes).map_err(|_| RegistryError::InvalidSignature)?;
+
+    let signature_bytes: [u8; 64] = BASE64
+        .decode(signature_b64)
+        .map_err(|_| RegistryError::InvalidSignature)?
+        .try_into()
+        .map_err(|_| RegistryError::InvalidSignature)?;
+    let signature = Signature::from_bytes(&signature_bytes);
+
+    let canonical = serde_json::to_vec(state).map_err(|_| RegistryError::InvalidSignature)?;
+    verifying_key
+        .verify(&canonical, &signature)
+        .map_err(|_| RegistryError::InvalidSignature)
+}
+
+/// Parses the standard `Last-Event-ID` header EventSource sends on
+/// reconnect, so `events_sse` can replay everything the client missed.
+fn last_event_id(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsWsQuery {
+    since: Option<u64>,
+}
+
+async fn events_sse(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, anyhow::Error>>> {
+    let stream = state
+        .event_log
+        .stream_since(last_event_id(&headers))
+        .map(|(seq, event)| match serde_json::to_string(&event) {
+            Ok(payload) => Ok(Event::default().id(seq.to_string()).data(payload)),
+            Err(err) => Err(anyhow::anyhow!(err)),
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::new())
+}
+
+async fn events_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(query): Query<EventsWsQuery>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        let mut receiver = Box::pin(state.event_log.stream_since(query.since).fuse());
+        let (mut tx, mut rx) = socket.split();
+        tokio::spawn(async move {
+            while let Some((seq, event)) = receiver.next().await {
+                let payload = serde_json::json!({ "seq": seq, "event": event });
+                if tx.send(Message::Text(payload.to_string())).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // drain incoming messages to keep connection alive
+        while let Some(Ok(msg)) = rx.next().await {
+            if matches!(msg, Message::Close(_)) {
+                break;
+            }
+        }
+    })
+}
+
+fn default_status() -> String {
+    "unknown".to_string()
+}
+
+struct OutboxRow {
+    id: i64,
+    event_json: String,
+    attempts: i64,
+    url: String,
+}
+
+/// Background worker that retries webhook delivery for outbox rows other
+/// handlers enqueue alongside their device mutation. Runs for the lifetime
+/// of the service; errors querying a tick are logged and retried next tick
+/// rather than killing the worker.
+async fn run_outbox_worker(pool: DbPool, http_client: Client) {
+    let mut ticker = tokio::time::interval(OUTBOX_TICK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(err) = dispatch_pending_outbox(&pool, &http_client).await {
+            tracing::warn!(%err, "failed to query pending outbox rows");
+        }
+    }
+}
+
+async fn dispatch_pending_outbox(pool: &DbPool, http_client: &Client) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT o.id as id, o.event_json as event_json, o.attempts as attempts, s.url as url \
+         FROM outbox o JOIN subscriptions s ON o.subscription_id = s.id \
+         WHERE o.status = 'pending' AND o.next_attempt_at <= ? \
+         ORDER BY o.next_attempt_at LIMIT ?",
+    )
+    .bind(now_millis())
+    .bind(OUTBOX_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let semaphore = Arc::new(Semaphore::new(OUTBOX_MAX_CONCURRENT_DELIVERIES));
+    let mut deliveries = FuturesUnordered::new();
+    for row in rows {
+        let outbox_row = OutboxRow {
+            id: row.get("id"),
+            event_json: row.get("event_json"),
+            attempts: row.get("attempts"),
+            url: row.get("url"),
+        };
+        let pool = pool.clone();
+        let http_client = http_client.clone();
+        let semaphore = semaphore.clone();
+        deliveries.push(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("outbox semaphore should not be closed");
+            deliver_outbox_row(&pool, &http_client, outbox_row).await
+        });
+    }
+
+    while let Some(result) = deliveries.next().await {
+        if let Err(err) = result {
+            tracing::warn!(%err, "failed to update outbox row after delivery attempt");
+        }
+    }
+    Ok(())
+}
+
+async fn deliver_outbox_row(
+    pool: &DbPool,
+    http_client: &Client,
+    row: OutboxRow,
+) -> Result<(), sqlx::Error> {
+    let delivered = http_client
+        .post(&row.url)
+        .timeout(OUTBOX_DELIVERY_TIMEOUT)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(row.event_json)
+        .send()
+        .await
+        .map(|response| response.status().is_success())
+        .unwrap_or(false);
+
+    if delivered {
+        sqlx::query("UPDATE outbox SET status = 'delivered' WHERE id = ?")
+            .bind(row.id)
+            .execute(pool)
+            .await?;
+        return Ok(());
+    }
+
+    let attempts = row.attempts + 1;
+    if attempts > OUTBOX_MAX_ATTEMPTS {
+        sqlx::query("UPDATE outbox SET status = 'dead', attempts = ? WHERE id = ?")
+            .bind(attempts)
+            .bind(row.id)
+            .execute(pool)
+            .await?;
+        return Ok(());
+    }
+
+    let next_attempt_at = now_millis() + full_jitter_backoff_ms(attempts);
+    sqlx::query("UPDATE outbox SET attempts = ?, next_attempt_at = ? WHERE id = ?")
+        .bind(attempts)
+        .bind(next_attempt_at)
+        .bind(row.id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Full-jitter exponential backoff (as opposed to the health checker's
+/// trim-a-fraction-off-the-top jitter): a delay drawn uniformly from
+/// `[0, base * 2^attempts]`, capped at [`OUTBOX_MAX_BACKOFF_MS`], so retries
+/// from many failing rows spread out instead of bunching at the cap.
+fn full_jitter_backoff_ms(attempts: i64) -> i64 {
+    let exponent = attempts.clamp(0, 20) as u32;
+    let capped = OUTBOX_BASE_BACKOFF_MS
+        .saturating_mul(1i64 << exponent)
+        .min(OUTBOX_MAX_BACKOFF_MS);
+    rand::thread_rng().gen_range(0..=capped.max(1))
+}
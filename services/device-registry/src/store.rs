@@ -0,0 +1,471 @@
+use async_trait::async_trait;
+use sqlx::Row;
+
+use crate::{Capability, DbPool, Device, Room};
+
+/// Error type returned by a [`RegistryStore`] implementation, independent of
+/// which backend (sqlx-backed SQL, sled) is actually in use.
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryStoreError {
+    #[error("record not found")]
+    NotFound,
+    #[error("device was modified by another writer; refetch and retry with the current version")]
+    Conflict,
+    #[error("storage backend error: {0}")]
+    Backend(String),
+}
+
+/// Storage abstraction for the device/room/capability tables, so the HTTP
+/// handlers in `lib.rs` don't depend on a specific backend. [`SqlxStore`]
+/// wraps the `sqlite`/`postgres` `DbPool` already used elsewhere in this
+/// service; [`SledStore`] (behind the `sled` feature) is a dependency-light
+/// embedded alternative for offline-first deployments. The outbox and
+/// subscription tables aren't part of this abstraction yet — they keep
+/// using `AppState::pool` directly regardless of which `RegistryStore` is
+/// selected.
+#[async_trait]
+pub trait RegistryStore: Send + Sync {
+    async fn list_rooms(&self) -> Result<Vec<Room>, RegistryStoreError>;
+    async fn create_room(&self, room: Room) -> Result<(), RegistryStoreError>;
+
+    async fn list_devices(&self) -> Result<Vec<Device>, RegistryStoreError>;
+    async fn fetch_device(&self, id: &str) -> Result<Device, RegistryStoreError>;
+    async fn create_device(&self, device: Device) -> Result<(), RegistryStoreError>;
+    /// Replaces the stored device with `device` (which already carries its
+    /// bumped `version`) iff the record's current version equals
+    /// `expected_version`. Mirrors the HTTP API's `If-Match`/CAS semantics.
+    async fn update_device(
+        &self,
+        device: Device,
+        expected_version: i64,
+    ) -> Result<(), RegistryStoreError>;
+    async fn delete_device(&self, id: &str) -> Result<(), RegistryStoreError>;
+
+    async fn list_capabilities(
+        &self,
+        device_id: &str,
+    ) -> Result<Vec<Capability>, RegistryStoreError>;
+    async fn add_capability(
+        &self,
+        device_id: &str,
+        capability: String,
+        properties: serde_json::Value,
+    ) -> Result<Capability, RegistryStoreError>;
+}
+
+fn parse_state(raw: String) -> serde_json::Value {
+    serde_json::from_str(&raw).unwrap_or_else(|_| serde_json::json!({}))
+}
+
+/// [`RegistryStore`] backed by the `sqlite`/`postgres` `DbPool` already used
+/// for the outbox and subscription tables.
+pub struct SqlxStore {
+    pool: DbPool,
+}
+
+impl SqlxStore {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RegistryStore for SqlxStore {
+    async fn list_rooms(&self) -> Result<Vec<Room>, RegistryStoreError> {
+        let rows = sqlx::query("SELECT id, name FROM rooms ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Room {
+                id: row.get("id"),
+                name: row.get("name"),
+            })
+            .collect())
+    }
+
+    async fn create_room(&self, room: Room) -> Result<(), RegistryStoreError> {
+        sqlx::query("INSERT INTO rooms (id, name) VALUES (?, ?)")
+            .bind(&room.id)
+            .bind(&room.name)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn list_devices(&self) -> Result<Vec<Device>, RegistryStoreError> {
+        let rows = sqlx::query(
+            "SELECT id, room_id, name, kind, status, state, version, public_key FROM devices ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Device {
+                id: row.get("id"),
+                room_id: row.get("room_id"),
+                name: row.get("name"),
+                kind: row.get("kind"),
+                status: row.get("status"),
+                state: parse_state(row.get("state")),
+                version: row.get("version"),
+                public_key: row.get("public_key"),
+            })
+            .collect())
+    }
+
+    async fn fetch_device(&self, id: &str) -> Result<Device, RegistryStoreError> {
+        let record = sqlx::query(
+            "SELECT id, room_id, name, kind, status, state, version, public_key FROM devices WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        let record = record.ok_or(RegistryStoreError::NotFound)?;
+        Ok(Device {
+            id: record.get("id"),
+            room_id: record.get("room_id"),
+            name: record.get("name"),
+            kind: record.get("kind"),
+            status: record.get("status"),
+            state: parse_state(record.get("state")),
+            version: record.get("version"),
+            public_key: record.get("public_key"),
+        })
+    }
+
+    async fn create_device(&self, device: Device) -> Result<(), RegistryStoreError> {
+        sqlx::query(
+            "INSERT INTO devices (id, room_id, name, kind, status, state, version, public_key) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&device.id)
+        .bind(&device.room_id)
+        .bind(&device.name)
+        .bind(&device.kind)
+        .bind(&device.status)
+        .bind(serde_json::to_string(&device.state).unwrap_or_else(|_| "{}".to_string()))
+        .bind(device.version)
+        .bind(&device.public_key)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn update_device(
+        &self,
+        device: Device,
+        expected_version: i64,
+    ) -> Result<(), RegistryStoreError> {
+        let result = sqlx::query(
+            "UPDATE devices SET room_id = ?, name = ?, kind = ?, status = ?, state = ?, \
+             public_key = ?, version = ? WHERE id = ? AND version = ?",
+        )
+        .bind(&device.room_id)
+        .bind(&device.name)
+        .bind(&device.kind)
+        .bind(&device.status)
+        .bind(serde_json::to_string(&device.state).unwrap_or_else(|_| "{}".to_string()))
+        .bind(&device.public_key)
+        .bind(device.version)
+        .bind(&device.id)
+        .bind(expected_version)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Err(RegistryStoreError::Conflict);
+        }
+        Ok(())
+    }
+
+    async fn delete_device(&self, id: &str) -> Result<(), RegistryStoreError> {
+        let result = sqlx::query("DELETE FROM devices WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Err(RegistryStoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn list_capabilities(
+        &self,
+        device_id: &str,
+    ) -> Result<Vec<Capability>, RegistryStoreError> {
+        let rows = sqlx::query(
+            "SELECT id, device_id, capability, properties FROM capabilities WHERE device_id = ?",
+        )
+        .bind(device_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Capability {
+                id: row.get("id"),
+                device_id: row.get("device_id"),
+                capability: row.get("capability"),
+                properties: parse_state(row.get("properties")),
+            })
+            .collect())
+    }
+
+    async fn add_capability(
+        &self,
+        device_id: &str,
+        capability: String,
+        properties: serde_json::Value,
+    ) -> Result<Capability, RegistryStoreError> {
+        let properties_json =
+            serde_json::to_string(&properties).unwrap_or_else(|_| "{}".to_string());
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        sqlx::query(
+            "INSERT INTO capabilities (device_id, capability, properties) VALUES (?, ?, ?)",
+        )
+        .bind(device_id)
+        .bind(&capability)
+        .bind(&properties_json)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        let record = sqlx::query(
+            "SELECT id FROM capabilities WHERE device_id = ? AND capability = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(device_id)
+        .bind(&capability)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        let cap_id: i64 = record.get("id");
+        tx.commit()
+            .await
+            .map_err(|err| RegistryStoreError::Backend(err.to_string()))?;
+        Ok(Capability {
+            id: cap_id,
+            device_id: device_id.to_string(),
+            capability,
+            properties,
+        })
+    }
+}
+
+#[cfg(feature = "sled")]
+pub use sled_store::SledStore;
+
+#[cfg(feature = "sled")]
+mod sled_store {
+    use async_trait::async_trait;
+    use sled::transaction::Transactional;
+
+    use super::{RegistryStore, RegistryStoreError};
+    use crate::{Capability, Device, Room};
+
+    /// Dependency-light, single-file embedded [`RegistryStore`] for
+    /// offline-first deployments that don't want to run a SQL engine.
+    ///
+    /// Keyspace:
+    /// - `devices`: `device/<id>` -> JSON-encoded [`Device`]
+    /// - `rooms`: `<id>` -> JSON-encoded [`Room`]
+    /// - `room_devices`: `<room_id>/<device_id>` -> empty value, a secondary
+    ///   index kept in lock-step with `devices` so a device's room
+    ///   membership can be looked up without scanning every device.
+    /// - `capabilities`: `<device_id>/<id>` -> JSON-encoded [`Capability`],
+    ///   where `<id>` comes from [`sled::Db::generate_id`].
+    pub struct SledStore {
+        db: sled::Db,
+        devices: sled::Tree,
+        rooms: sled::Tree,
+        room_devices: sled::Tree,
+        capabilities: sled::Tree,
+    }
+
+    impl SledStore {
+        pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, sled::Error> {
+            let db = sled::open(path)?;
+            Ok(Self {
+                devices: db.open_tree("devices")?,
+                rooms: db.open_tree("rooms")?,
+                room_devices: db.open_tree("room_devices")?,
+                capabilities: db.open_tree("capabilities")?,
+                db,
+            })
+        }
+    }
+
+    fn device_key(id: &str) -> Vec<u8> {
+        format!("device/{id}").into_bytes()
+    }
+
+    fn room_device_key(room_id: &str, device_id: &str) -> Vec<u8> {
+        format!("{room_id}/{device_id}").into_bytes()
+    }
+
+    fn to_backend_err(err: impl std::fmt::Display) -> RegistryStoreError {
+        RegistryStoreError::Backend(err.to_string())
+    }
+
+    fn decode_device(bytes: &[u8]) -> Result<Device, RegistryStoreError> {
+        serde_json::from_slice(bytes).map_err(to_backend_err)
+    }
+
+    #[async_trait]
+    impl RegistryStore for SledStore {
+        async fn list_rooms(&self) -> Result<Vec<Room>, RegistryStoreError> {
+            let mut rooms = Vec::new();
+            for entry in self.rooms.iter() {
+                let (_, value) = entry.map_err(to_backend_err)?;
+                rooms.push(serde_json::from_slice(&value).map_err(to_backend_err)?);
+            }
+            rooms.sort_by(|a: &Room, b: &Room| a.name.cmp(&b.name));
+            Ok(rooms)
+        }
+
+        async fn create_room(&self, room: Room) -> Result<(), RegistryStoreError> {
+            let bytes = serde_json::to_vec(&room).map_err(to_backend_err)?;
+            self.rooms
+                .insert(room.id.as_bytes(), bytes)
+                .map_err(to_backend_err)?;
+            Ok(())
+        }
+
+        async fn list_devices(&self) -> Result<Vec<Device>, RegistryStoreError> {
+            let mut devices = Vec::new();
+            for entry in self.devices.iter() {
+                let (_, value) = entry.map_err(to_backend_err)?;
+                devices.push(decode_device(&value)?);
+            }
+            devices.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(devices)
+        }
+
+        async fn fetch_device(&self, id: &str) -> Result<Device, RegistryStoreError> {
+            let bytes = self
+                .devices
+                .get(device_key(id))
+                .map_err(to_backend_err)?
+                .ok_or(RegistryStoreError::NotFound)?;
+            decode_device(&bytes)
+        }
+
+        async fn create_device(&self, device: Device) -> Result<(), RegistryStoreError> {
+            let bytes = serde_json::to_vec(&device).map_err(to_backend_err)?;
+            let room_id = device.room_id.clone();
+            let device_id = device.id.clone();
+            (&self.devices, &self.room_devices)
+                .transaction(move |(devices, room_devices)| {
+                    devices.insert(device_key(&device_id), bytes.clone())?;
+                    if let Some(room_id) = &room_id {
+                        room_devices.insert(room_device_key(room_id, &device_id), &[] as &[u8])?;
+                    }
+                    Ok(())
+                })
+                .map_err(to_backend_err)?;
+            Ok(())
+        }
+
+        async fn update_device(
+            &self,
+            device: Device,
+            expected_version: i64,
+        ) -> Result<(), RegistryStoreError> {
+            let key = device_key(&device.id);
+            let old_bytes = self
+                .devices
+                .get(&key)
+                .map_err(to_backend_err)?
+                .ok_or(RegistryStoreError::NotFound)?;
+            let existing = decode_device(&old_bytes)?;
+            if existing.version != expected_version {
+                return Err(RegistryStoreError::Conflict);
+            }
+            let new_bytes = serde_json::to_vec(&device).map_err(to_backend_err)?;
+            let cas = self
+                .devices
+                .compare_and_swap(&key, Some(old_bytes), Some(new_bytes))
+                .map_err(to_backend_err)?;
+            if cas.is_err() {
+                return Err(RegistryStoreError::Conflict);
+            }
+
+            if existing.room_id != device.room_id {
+                if let Some(old_room) = &existing.room_id {
+                    self.room_devices
+                        .remove(room_device_key(old_room, &device.id))
+                        .map_err(to_backend_err)?;
+                }
+                if let Some(new_room) = &device.room_id {
+                    self.room_devices
+                        .insert(room_device_key(new_room, &device.id), &[] as &[u8])
+                        .map_err(to_backend_err)?;
+                }
+            }
+            Ok(())
+        }
+
+        async fn delete_device(&self, id: &str) -> Result<(), RegistryStoreError> {
+            let bytes = self
+                .devices
+                .get(device_key(id))
+                .map_err(to_backend_err)?
+                .ok_or(RegistryStoreError::NotFound)?;
+            let existing = decode_device(&bytes)?;
+            let device_id = id.to_string();
+            (&self.devices, &self.room_devices)
+                .transaction(move |(devices, room_devices)| {
+                    devices.remove(device_key(&device_id))?;
+                    if let Some(room_id) = &existing.room_id {
+                        room_devices.remove(room_device_key(room_id, &device_id))?;
+                    }
+                    Ok(())
+                })
+                .map_err(to_backend_err)?;
+            Ok(())
+        }
+
+        async fn list_capabilities(
+            &self,
+            device_id: &str,
+        ) -> Result<Vec<Capability>, RegistryStoreError> {
+            let prefix = format!("{device_id}/");
+            let mut capabilities = Vec::new();
+            for entry in self.capabilities.scan_prefix(prefix.as_bytes()) {
+                let (_, value) = entry.map_err(to_backend_err)?;
+                capabilities.push(serde_json::from_slice(&value).map_err(to_backend_err)?);
+            }
+            Ok(capabilities)
+        }
+
+        async fn add_capability(
+            &self,
+            device_id: &str,
+            capability: String,
+            properties: serde_json::Value,
+        ) -> Result<Capability, RegistryStoreError> {
+            let id = self.db.generate_id().map_err(to_backend_err)? as i64;
+            let record = Capability {
+                id,
+                device_id: device_id.to_string(),
+                capability,
+                properties,
+            };
+            let bytes = serde_json::to_vec(&record).map_err(to_backend_err)?;
+            let key = format!("{device_id}/{id}");
+            self.capabilities
+                .insert(key.as_bytes(), bytes)
+                .map_err(to_backend_err)?;
+            Ok(record)
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+mod pricing;
+mod store;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -9,22 +12,84 @@ use axum::middleware::{from_fn, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use chrono::{DateTime, Local, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use pricing::{
+    cheapest_window, current_price, median_price, FilePricingProvider, HttpPricingProvider,
+    PricePoint, PricingProvider,
+};
 use serde::{Deserialize, Serialize};
+use store::{EnergyStateStore, FileEnergyStateStore, StateStoreError};
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
+use tokio::time::Duration as TokioDuration;
 
 use common_config::service_port;
 use common_obs::{
-    encode_prometheus_metrics, health_router, http_request_observe, ObsInit,
-    PROMETHEUS_CONTENT_TYPE,
+    encode_prometheus_metrics, health_router, http_request_observe, CounterVec, GaugeVec,
+    HistogramVec, ObsInit, PROMETHEUS_CONTENT_TYPE,
 };
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// How often the budget-watch worker re-checks its run queue when nothing
+/// is currently scheduled (e.g. no budgets configured yet).
+const BUDGET_WATCH_IDLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Buckets for the `consumption_kwh` histogram, sized for a household-scale
+/// reading rather than `common_obs`'s latency-oriented default buckets.
+const CONSUMPTION_KWH_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0];
+
+static BUDGET_ALERTS_TRIGGERED_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "energy_svc_budget_alerts_triggered_total",
+        "Budgets found over their configured limit during scheduled evaluation",
+        &["budget_id"],
+    )
+});
+
+static BUDGET_LIMIT_KWH: Lazy<GaugeVec> = Lazy::new(|| {
+    common_obs::register_gauge(
+        "energy_svc_budget_limit_kwh",
+        "Configured budget limit in kWh, labeled by budget id and period",
+        &["budget_id", "period"],
+    )
+});
+
+static BUDGET_EXCEEDED_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "energy_svc_budget_exceeded_total",
+        "Advice requests that found a budget over its configured limit",
+        &["budget_id"],
+    )
+});
+
+static ACTIVE_TOU_RATE_MULTIPLIER: Lazy<GaugeVec> = Lazy::new(|| {
+    common_obs::register_gauge(
+        "energy_svc_active_tou_rate_multiplier",
+        "Rate multiplier of the currently active time-of-use window, or 1.0 if none is active",
+        &[],
+    )
+});
+
+static ADVICE_CONSUMPTION_KWH: Lazy<HistogramVec> = Lazy::new(|| {
+    common_obs::register_histogram(
+        "energy_svc_advice_consumption_kwh",
+        "Consumption values reported by callers of GET /v1/advice",
+        &[],
+        CONSUMPTION_KWH_BUCKETS,
+    )
+});
 
 const SERVICE_NAME: &str = "energy-svc";
 const PORT_ENV: &str = "ENERGY_SVC_PORT";
 const DEFAULT_PORT: u16 = 8005;
+const PRICING_FEED_URL_ENV: &str = "ENERGY_SVC_PRICING_FEED_URL";
+const PRICING_FILE_ENV: &str = "ENERGY_SVC_PRICING_FILE";
+const PRICING_POLL_SECS_ENV: &str = "ENERGY_SVC_PRICING_POLL_SECS";
+const DEFAULT_PRICING_POLL_SECS: u64 = 3600;
+const STATE_FILE_ENV: &str = "ENERGY_SVC_STATE_FILE";
+const DEFAULT_STATE_PATH: &str = "data/energy-svc/state.json";
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -39,12 +104,25 @@ fn build_time() -> &'static str {
 #[derive(Clone)]
 struct AppState {
     state: Arc<RwLock<EnergyState>>,
+    store: Arc<dyn EnergyStateStore>,
+    alerts: Arc<RwLock<HashMap<String, BudgetAlert>>>,
 }
 
-#[derive(Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct EnergyState {
     budgets: HashMap<String, EnergyBudget>,
     tou_windows: Vec<TimeOfUseWindow>,
+    price_curve: Vec<PricePoint>,
+    /// Accumulated consumption for each budget's current period, keyed by
+    /// budget id. Reset automatically when `period_start` falls behind the
+    /// budget's current period boundary.
+    consumption: HashMap<String, PeriodConsumption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PeriodConsumption {
+    period_start: DateTime<Utc>,
+    accumulated_kwh: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,7 +133,7 @@ struct EnergyBudget {
     period: BudgetPeriod,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 enum BudgetPeriod {
     Daily,
@@ -69,6 +147,46 @@ impl Default for BudgetPeriod {
     }
 }
 
+impl BudgetPeriod {
+    /// How often the watch worker re-evaluates a budget on this period.
+    /// Weekly/monthly are fixed offsets rather than calendar-aware (a month
+    /// is approximated as 30 days). Besides the re-evaluation cadence, this
+    /// is the period length `get_advice` projects consumption over; the
+    /// budget's actual reset boundary comes from [`period_boundary`].
+    fn evaluation_interval(&self) -> Duration {
+        match self {
+            BudgetPeriod::Daily => Duration::from_secs(24 * 60 * 60),
+            BudgetPeriod::Weekly => Duration::from_secs(7 * 24 * 60 * 60),
+            BudgetPeriod::Monthly => Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// The start of `period`'s current occurrence containing `now`: local
+/// midnight for `Daily`, the most recent Monday midnight for `Weekly`, the
+/// 1st of the month at midnight for `Monthly`. Returned in UTC so it's
+/// directly comparable with a previously stored [`PeriodConsumption`]
+/// regardless of the server's local offset shifting (e.g. DST).
+fn period_boundary(period: BudgetPeriod, now: DateTime<Local>) -> DateTime<Utc> {
+    let today = now.date_naive();
+    let start_date = match period {
+        BudgetPeriod::Daily => today,
+        BudgetPeriod::Weekly => {
+            today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+        BudgetPeriod::Monthly => today.with_day(1).expect("first of month is always valid"),
+    };
+    let start_naive = start_date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is always a valid time");
+
+    Local
+        .from_local_datetime(&start_naive)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&start_naive))
+        .with_timezone(&Utc)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TimeOfUseWindow {
     name: String,
@@ -91,6 +209,36 @@ struct AdviceResponse {
     recommendations: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ConsumptionReading {
+    budget_id: String,
+    kwh: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleQuery {
+    load_kwh: f32,
+    duration_hours: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct ScheduleResponse {
+    start: DateTime<Utc>,
+    duration_hours: u32,
+    estimated_cost_cents: f32,
+}
+
+/// A budget the watch worker found over its limit at the last scheduled
+/// evaluation. Stays active until a later evaluation finds consumption back
+/// within the limit.
+#[derive(Debug, Clone, Serialize)]
+struct BudgetAlert {
+    budget_id: String,
+    limit_kwh: f32,
+    consumption_kwh: f32,
+    triggered_at: DateTime<Utc>,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ObsInit::init(SERVICE_NAME).map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
@@ -98,10 +246,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let port = service_port(PORT_ENV, DEFAULT_PORT);
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
 
+    let store: Arc<dyn EnergyStateStore> = Arc::new(FileEnergyStateStore::new(state_path()));
+    let initial_state = store
+        .load()
+        .await
+        .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+
     let state = AppState {
-        state: Arc::new(RwLock::new(EnergyState::default())),
+        state: Arc::new(RwLock::new(initial_state)),
+        store,
+        alerts: Arc::new(RwLock::new(HashMap::new())),
     };
 
+    if let Some(provider) = pricing_provider_from_env() {
+        spawn_price_poller(state.clone(), provider, pricing_poll_interval());
+    }
+
+    spawn_budget_watcher(state.clone());
+
     tracing::info!(
         event = "service_start",
         service = SERVICE_NAME,
@@ -115,7 +277,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = Router::new()
         .route("/v1/budgets", post(set_budgets))
         .route("/v1/tou", post(set_tou_windows))
+        .route("/v1/pricing", post(set_price_curve))
+        .route("/v1/consumption", post(record_consumption))
         .route("/v1/advice", get(get_advice))
+        .route("/v1/schedule", get(get_schedule))
+        .route("/v1/alerts", get(get_alerts))
         .route("/metrics", get(metrics))
         .with_state(state)
         .merge(health_router(SERVICE_NAME))
@@ -130,22 +296,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 async fn set_budgets(
     State(state): State<AppState>,
     Json(budgets): Json<Vec<EnergyBudget>>,
-) -> Json<StatusReply> {
-    let mut guard = state.state.write().await;
-    guard.budgets = budgets
-        .into_iter()
-        .map(|budget| (budget.id.clone(), budget))
-        .collect();
-    StatusReply::ok("budgets updated")
+) -> Result<Json<StatusReply>, EnergyError> {
+    let snapshot = {
+        let mut guard = state.state.write().await;
+        guard.budgets = budgets
+            .into_iter()
+            .map(|budget| (budget.id.clone(), budget))
+            .collect();
+        guard.clone()
+    };
+    for budget in snapshot.budgets.values() {
+        let period = match budget.period {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Weekly => "weekly",
+            BudgetPeriod::Monthly => "monthly",
+        };
+        BUDGET_LIMIT_KWH.set(&[budget.id.as_str(), period], budget.limit_kwh as f64);
+    }
+    state.store.save(&snapshot).await?;
+    Ok(StatusReply::ok("budgets updated"))
 }
 
 async fn set_tou_windows(
     State(state): State<AppState>,
     Json(windows): Json<Vec<TimeOfUseWindow>>,
+) -> Result<Json<StatusReply>, EnergyError> {
+    let snapshot = {
+        let mut guard = state.state.write().await;
+        guard.tou_windows = windows;
+        guard.clone()
+    };
+    state.store.save(&snapshot).await?;
+    Ok(StatusReply::ok("time-of-use windows updated"))
+}
+
+async fn record_consumption(
+    State(state): State<AppState>,
+    Json(reading): Json<ConsumptionReading>,
+) -> Result<Json<StatusReply>, EnergyError> {
+    let mut guard = state.state.write().await;
+    let budget = guard
+        .budgets
+        .get(&reading.budget_id)
+        .cloned()
+        .ok_or_else(|| {
+            EnergyError::InvalidRequest(format!("unknown budget id: {}", reading.budget_id))
+        })?;
+
+    let boundary = period_boundary(budget.period, Local::now());
+    let entry = guard
+        .consumption
+        .entry(reading.budget_id.clone())
+        .or_insert_with(|| PeriodConsumption {
+            period_start: boundary,
+            accumulated_kwh: 0.0,
+        });
+    if entry.period_start < boundary {
+        entry.period_start = boundary;
+        entry.accumulated_kwh = 0.0;
+    }
+    entry.accumulated_kwh += reading.kwh;
+
+    let snapshot = guard.clone();
+    drop(guard);
+    state.store.save(&snapshot).await?;
+    Ok(StatusReply::ok("consumption recorded"))
+}
+
+async fn set_price_curve(
+    State(state): State<AppState>,
+    Json(curve): Json<Vec<PricePoint>>,
 ) -> Json<StatusReply> {
     let mut guard = state.state.write().await;
-    guard.tou_windows = windows;
-    StatusReply::ok("time-of-use windows updated")
+    guard.price_curve = curve;
+    StatusReply::ok("price curve updated")
 }
 
 async fn get_advice(
@@ -156,28 +380,74 @@ async fn get_advice(
     let now_local: DateTime<Local> = Local::now();
     let current_time = now_local.time();
 
+    ADVICE_CONSUMPTION_KWH.observe(&[], query.consumption_kwh as f64);
+
+    let mut active_rate_multiplier = 1.0;
     let mut recommendations = Vec::new();
     for window in &snapshot.tou_windows {
         if let Some((start, end)) = parse_window(window) {
-            if in_window(current_time, start, end) && window.rate_multiplier > 1.0 {
-                recommendations.push(format!(
-                    "High rate period ({}) active. Consider delaying discretionary loads.",
-                    window.name
-                ));
+            if in_window(current_time, start, end) {
+                active_rate_multiplier = window.rate_multiplier;
+                if window.rate_multiplier > 1.0 {
+                    recommendations.push(format!(
+                        "High rate period ({}) active. Consider delaying discretionary loads.",
+                        window.name
+                    ));
+                }
             }
         }
     }
+    ACTIVE_TOU_RATE_MULTIPLIER.set(&[], active_rate_multiplier as f64);
 
     for budget in snapshot.budgets.values() {
-        if query.consumption_kwh > budget.limit_kwh {
+        let period_name = match budget.period {
+            BudgetPeriod::Daily => "Daily",
+            BudgetPeriod::Weekly => "Weekly",
+            BudgetPeriod::Monthly => "Monthly",
+        };
+        let accumulated_kwh = snapshot
+            .consumption
+            .get(&budget.id)
+            .map(|entry| entry.accumulated_kwh)
+            .unwrap_or(0.0);
+
+        if accumulated_kwh > budget.limit_kwh {
+            BUDGET_EXCEEDED_TOTAL.inc(&[budget.id.as_str()], 1);
+            recommendations.push(format!(
+                "{period_name} budget exceeded by {:.2} kWh. Reduce usage or reschedule appliances.",
+                accumulated_kwh - budget.limit_kwh
+            ));
+            continue;
+        }
+
+        if let Some(entry) = snapshot.consumption.get(&budget.id) {
+            let elapsed_secs = Utc::now()
+                .signed_duration_since(entry.period_start)
+                .num_seconds()
+                .max(0) as f64;
+            let period_secs = budget.period.evaluation_interval().as_secs_f64();
+            let elapsed_fraction = elapsed_secs / period_secs;
+
+            if elapsed_fraction > 0.0 {
+                let projected_kwh = accumulated_kwh as f64 / elapsed_fraction;
+                if projected_kwh > budget.limit_kwh as f64 {
+                    recommendations.push(format!(
+                        "{period_name} budget on pace to reach {:.2} kWh by period end, over its {:.2} kWh limit.",
+                        projected_kwh, budget.limit_kwh
+                    ));
+                }
+            }
+        }
+    }
+
+    if let (Some(spot_price), Some(median)) = (
+        current_price(&snapshot.price_curve, Utc::now()),
+        median_price(&snapshot.price_curve),
+    ) {
+        if spot_price > median {
             recommendations.push(format!(
-                "{} budget exceeded by {:.2} kWh. Reduce usage or reschedule appliances.",
-                match budget.period {
-                    BudgetPeriod::Daily => "Daily",
-                    BudgetPeriod::Weekly => "Weekly",
-                    BudgetPeriod::Monthly => "Monthly",
-                },
-                query.consumption_kwh - budget.limit_kwh
+                "Live spot price ({:.2}¢/kWh) is above today's median ({:.2}¢/kWh). Consider delaying discretionary loads.",
+                spot_price, median
             ));
         }
     }
@@ -193,6 +463,204 @@ async fn get_advice(
     })
 }
 
+#[derive(Debug, thiserror::Error)]
+enum EnergyError {
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("no price curve window is available for the requested duration")]
+    NoWindowAvailable,
+    #[error("failed to persist energy state: {0}")]
+    Persist(#[from] StateStoreError),
+}
+
+impl IntoResponse for EnergyError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            EnergyError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            EnergyError::NoWindowAvailable => StatusCode::UNPROCESSABLE_ENTITY,
+            EnergyError::Persist(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (
+            status,
+            Json(serde_json::json!({ "error": self.to_string() })),
+        )
+            .into_response()
+    }
+}
+
+async fn get_schedule(
+    State(state): State<AppState>,
+    Query(query): Query<ScheduleQuery>,
+) -> Result<Json<ScheduleResponse>, EnergyError> {
+    if query.duration_hours == 0 {
+        return Err(EnergyError::InvalidRequest(
+            "duration_hours must be greater than zero".to_string(),
+        ));
+    }
+
+    let snapshot = state.state.read().await.clone();
+    let window = cheapest_window(&snapshot.price_curve, query.duration_hours as usize)
+        .ok_or(EnergyError::NoWindowAvailable)?;
+
+    let average_price_cents = window.total_cost_cents / query.duration_hours as f32;
+
+    Ok(Json(ScheduleResponse {
+        start: window.start,
+        duration_hours: query.duration_hours,
+        estimated_cost_cents: average_price_cents * query.load_kwh,
+    }))
+}
+
+async fn get_alerts(State(state): State<AppState>) -> Json<Vec<BudgetAlert>> {
+    let alerts = state.alerts.read().await;
+    Json(alerts.values().cloned().collect())
+}
+
+/// Spawns the background worker that re-evaluates every configured budget
+/// against its limit on its own schedule, instead of only on-demand via
+/// `GET /v1/advice`. Modeled as a time-ordered run queue: `schedule` maps
+/// each pending budget's next-due `Instant` to its id, the earliest entry is
+/// popped and evaluated, then reinserted one period later. `tracked` records
+/// every budget id the worker has ever scheduled, so a budget added after
+/// startup (via `POST /v1/budgets`) is picked up and scheduled for
+/// evaluation on the next loop iteration instead of being ignored forever.
+fn spawn_budget_watcher(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut schedule: BTreeMap<Instant, String> = BTreeMap::new();
+        let mut tracked: HashSet<String> = HashSet::new();
+
+        loop {
+            {
+                let snapshot = state.state.read().await;
+                for id in snapshot.budgets.keys() {
+                    if tracked.insert(id.clone()) {
+                        schedule.insert(Instant::now(), id.clone());
+                    }
+                }
+            }
+
+            let Some((due_at, budget_id)) = schedule.pop_first() else {
+                tokio::time::sleep(BUDGET_WATCH_IDLE_INTERVAL).await;
+                continue;
+            };
+
+            let now = Instant::now();
+            if due_at > now {
+                tokio::time::sleep(due_at - now).await;
+            }
+
+            match evaluate_budget(&state, &budget_id).await {
+                Some(interval) => {
+                    schedule.insert(Instant::now() + interval, budget_id.clone());
+                }
+                None => {
+                    tracked.remove(&budget_id);
+                }
+            }
+        }
+    })
+}
+
+/// Evaluates one budget's current consumption against its limit, updating
+/// `state.alerts` and emitting a tracing event/counter bump when it's over.
+/// Returns the budget's re-evaluation interval, or `None` if the budget was
+/// removed (via a later `POST /v1/budgets`) since it was last scheduled.
+async fn evaluate_budget(state: &AppState, budget_id: &str) -> Option<Duration> {
+    let (budget, accumulated_kwh) = {
+        let snapshot = state.state.read().await;
+        let Some(budget) = snapshot.budgets.get(budget_id).cloned() else {
+            drop(snapshot);
+            state.alerts.write().await.remove(budget_id);
+            return None;
+        };
+        let accumulated_kwh = snapshot
+            .consumption
+            .get(budget_id)
+            .map(|entry| entry.accumulated_kwh)
+            .unwrap_or(0.0);
+        (budget, accumulated_kwh)
+    };
+
+    let mut alerts = state.alerts.write().await;
+    if accumulated_kwh > budget.limit_kwh {
+        tracing::warn!(
+            event = "budget_alert_triggered",
+            budget_id,
+            limit_kwh = budget.limit_kwh,
+            consumption_kwh = accumulated_kwh,
+            "budget exceeded during scheduled evaluation"
+        );
+        BUDGET_ALERTS_TRIGGERED_TOTAL.inc(&[budget_id], 1);
+        alerts.insert(
+            budget_id.to_string(),
+            BudgetAlert {
+                budget_id: budget_id.to_string(),
+                limit_kwh: budget.limit_kwh,
+                consumption_kwh: accumulated_kwh,
+                triggered_at: Utc::now(),
+            },
+        );
+    } else {
+        alerts.remove(budget_id);
+    }
+
+    Some(budget.period.evaluation_interval())
+}
+
+/// Builds a [`PricingProvider`] from environment configuration: an HTTP
+/// feed takes priority over a cached file, mirroring `service_port`'s
+/// "override, then default" precedence. Returns `None` when neither is
+/// configured, leaving the price curve to be set via `POST /v1/pricing`.
+fn pricing_provider_from_env() -> Option<Arc<dyn PricingProvider>> {
+    if let Ok(url) = std::env::var(PRICING_FEED_URL_ENV) {
+        return Some(Arc::new(HttpPricingProvider::new(url)));
+    }
+    if let Ok(path) = std::env::var(PRICING_FILE_ENV) {
+        return Some(Arc::new(FilePricingProvider::new(path)));
+    }
+    None
+}
+
+/// The energy state file path: an operator override via `STATE_FILE_ENV`,
+/// else `DEFAULT_STATE_PATH`, mirroring `service_port`'s override-then-default
+/// convention.
+fn state_path() -> String {
+    std::env::var(STATE_FILE_ENV).unwrap_or_else(|_| DEFAULT_STATE_PATH.to_string())
+}
+
+fn pricing_poll_interval() -> TokioDuration {
+    let secs = std::env::var(PRICING_POLL_SECS_ENV)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_PRICING_POLL_SECS);
+    TokioDuration::from_secs(secs.max(1))
+}
+
+/// Refreshes `state`'s price curve from `provider` on `interval`, for the
+/// lifetime of the service. A failed poll is logged and left for the next
+/// tick rather than torn down, since a transient feed outage shouldn't
+/// stop the service from serving the last-known curve.
+fn spawn_price_poller(
+    state: AppState,
+    provider: Arc<dyn PricingProvider>,
+    interval: TokioDuration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match provider.current_curve().await {
+                Ok(curve) => {
+                    state.state.write().await.price_curve = curve;
+                }
+                Err(error) => {
+                    tracing::warn!(%error, "failed to refresh spot price curve");
+                }
+            }
+        }
+    })
+}
+
 fn parse_window(window: &TimeOfUseWindow) -> Option<(NaiveTime, NaiveTime)> {
     let start = NaiveTime::parse_from_str(&window.start, "%H:%M").ok()?;
     let end = NaiveTime::parse_from_str(&window.end, "%H:%M").ok()?;
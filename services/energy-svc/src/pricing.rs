@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+/// One hour of a published spot-price curve.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub start: DateTime<Utc>,
+    pub price_cents: f32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PricingError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse price curve: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("request to price feed failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Supplies the current spot-price curve. Implementations may read a
+/// locally cached curve (useful offline or in tests) or poll a utility's
+/// HTTP feed; `get_advice`/`get_schedule` don't care which.
+#[async_trait]
+pub trait PricingProvider: Send + Sync {
+    async fn current_curve(&self) -> Result<Vec<PricePoint>, PricingError>;
+}
+
+/// Reads a `Vec<PricePoint>` cached as JSON on disk, following the same
+/// load shape as `services/updater`'s `FileStateStore`.
+#[derive(Debug, Clone)]
+pub struct FilePricingProvider {
+    path: PathBuf,
+}
+
+impl FilePricingProvider {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[async_trait]
+impl PricingProvider for FilePricingProvider {
+    async fn current_curve(&self) -> Result<Vec<PricePoint>, PricingError> {
+        match fs::read(&self.path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(PricingError::Io(err)),
+        }
+    }
+}
+
+/// Polls an hourly spot-price feed over HTTP, expecting a JSON body of
+/// `Vec<PricePoint>`.
+#[derive(Debug, Clone)]
+pub struct HttpPricingProvider {
+    client: Client,
+    endpoint: String,
+}
+
+impl HttpPricingProvider {
+    pub fn new(endpoint: String) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint,
+        }
+    }
+}
+
+#[async_trait]
+impl PricingProvider for HttpPricingProvider {
+    async fn current_curve(&self) -> Result<Vec<PricePoint>, PricingError> {
+        let points = self
+            .client
+            .get(&self.endpoint)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<PricePoint>>()
+            .await?;
+        Ok(points)
+    }
+}
+
+/// The cheapest contiguous window found by [`cheapest_window`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CheapestWindow {
+    pub start: DateTime<Utc>,
+    pub total_cost_cents: f32,
+}
+
+/// Finds the cheapest contiguous `duration_hours`-wide window starting at
+/// one of `curve`'s hourly points, ties broken by earliest start. Returns
+/// `None` if the curve has fewer points than the window needs.
+pub fn cheapest_window(curve: &[PricePoint], duration_hours: usize) -> Option<CheapestWindow> {
+    if duration_hours == 0 || curve.len() < duration_hours {
+        return None;
+    }
+
+    let mut sorted = curve.to_vec();
+    sorted.sort_by_key(|point| point.start);
+
+    let mut best: Option<CheapestWindow> = None;
+    for window in sorted.windows(duration_hours) {
+        let total_cost_cents: f32 = window.iter().map(|point| point.price_cents).sum();
+        let is_better = match &best {
+            Some(current) => total_cost_cents < current.total_cost_cents,
+            None => true,
+        };
+        if is_better {
+            best = Some(CheapestWindow {
+                start: window[0].start,
+                total_cost_cents,
+            });
+        }
+    }
+    best
+}
+
+/// The curve point covering `now`, if any.
+pub fn current_price(curve: &[PricePoint], now: DateTime<Utc>) -> Option<f32> {
+    curve
+        .iter()
+        .find(|point| {
+            let elapsed = now.signed_duration_since(point.start);
+            elapsed >= chrono::Duration::zero() && elapsed < chrono::Duration::hours(1)
+        })
+        .map(|point| point.price_cents)
+}
+
+/// The curve's median price, used as the "expensive right now" baseline.
+pub fn median_price(curve: &[PricePoint]) -> Option<f32> {
+    if curve.is_empty() {
+        return None;
+    }
+    let mut prices: Vec<f32> = curve.iter().map(|point| point.price_cents).collect();
+    // `total_cmp` gives NaN a well-defined (if meaningless) position instead
+    // of panicking, since `price_cents` can come from an untrusted price
+    // feed's or cached file's JSON (`HttpPricingProvider`,
+    // `FilePricingProvider`) that isn't guaranteed finite.
+    prices.sort_by(f32::total_cmp);
+    let mid = prices.len() / 2;
+    Some(if prices.len() % 2 == 0 {
+        (prices[mid - 1] + prices[mid]) / 2.0
+    } else {
+        prices[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hour_start(hour: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(hour * 3600, 0).expect("valid timestamp")
+    }
+
+    fn point(hour: i64, price_cents: f32) -> PricePoint {
+        PricePoint {
+            start: hour_start(hour),
+            price_cents,
+        }
+    }
+
+    #[test]
+    fn finds_cheapest_contiguous_window() {
+        let curve = vec![
+            point(0, 10.0),
+            point(1, 2.0),
+            point(2, 2.0),
+            point(3, 9.0),
+            point(4, 1.0),
+        ];
+
+        let window = cheapest_window(&curve, 2).expect("window");
+        assert_eq!(window.start, hour_start(1));
+        assert_eq!(window.total_cost_cents, 4.0);
+    }
+
+    #[test]
+    fn breaks_ties_by_earliest_start() {
+        let curve = vec![point(0, 5.0), point(1, 5.0), point(2, 5.0)];
+
+        let window = cheapest_window(&curve, 1).expect("window");
+        assert_eq!(window.start, hour_start(0));
+    }
+
+    #[test]
+    fn none_when_curve_shorter_than_window() {
+        let curve = vec![point(0, 5.0)];
+        assert!(cheapest_window(&curve, 2).is_none());
+    }
+
+    #[test]
+    fn median_of_even_length_curve_averages_middle_two() {
+        let curve = vec![point(0, 1.0), point(1, 2.0), point(2, 3.0), point(3, 4.0)];
+        assert_eq!(median_price(&curve), Some(2.5));
+    }
+}
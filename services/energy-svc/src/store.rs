@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::EnergyState;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateStoreError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse energy state: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Persists the budgets and time-of-use windows an operator has configured,
+/// so they survive a restart instead of resetting to empty every time
+/// `EnergyState` is recreated in memory.
+#[async_trait]
+pub trait EnergyStateStore: Send + Sync {
+    /// Returns the last-saved state, or `EnergyState::default()` if nothing
+    /// has been saved yet.
+    async fn load(&self) -> Result<EnergyState, StateStoreError>;
+    async fn save(&self, state: &EnergyState) -> Result<(), StateStoreError>;
+}
+
+/// Stores state as a JSON file, written atomically the same way
+/// `services/updater`'s `FileStateStore` does: the new bytes land in a
+/// sibling `.tmp` file, which is fsynced before an atomic rename replaces
+/// the real path, and the parent directory is fsynced afterward so the
+/// rename itself survives a crash.
+#[derive(Debug, Clone)]
+pub struct FileEnergyStateStore {
+    path: PathBuf,
+}
+
+impl FileEnergyStateStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    async fn ensure_parent_dir(&self) -> Result<(), std::io::Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Derives the atomic-write staging path for `path`, e.g.
+/// `data/energy-svc/state.json` -> `data/energy-svc/state.json.tmp`.
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+#[async_trait]
+impl EnergyStateStore for FileEnergyStateStore {
+    async fn load(&self) -> Result<EnergyState, StateStoreError> {
+        let primary = match fs::read(&self.path).await {
+            Ok(bytes) => Some(bytes),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(StateStoreError::Io(err)),
+        };
+
+        if let Some(bytes) = primary {
+            if let Ok(state) = serde_json::from_slice(&bytes) {
+                return Ok(state);
+            }
+        }
+
+        // The primary file is missing or failed to parse, which a crash
+        // mid-rename can leave behind; fall back to the staged write, which
+        // was fsynced in full before the rename was ever attempted.
+        match fs::read(tmp_path_for(&self.path)).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(EnergyState::default()),
+            Err(err) => Err(StateStoreError::Io(err)),
+        }
+    }
+
+    async fn save(&self, state: &EnergyState) -> Result<(), StateStoreError> {
+        self.ensure_parent_dir().await?;
+        let json = serde_json::to_vec_pretty(state)?;
+
+        let tmp_path = tmp_path_for(&self.path);
+        let mut tmp_file = fs::File::create(&tmp_path).await?;
+        tmp_file.write_all(&json).await?;
+        tmp_file.flush().await?;
+        tmp_file.sync_all().await?;
+        drop(tmp_file);
+
+        fs::rename(&tmp_path, &self.path).await?;
+
+        if let Some(parent) = self.path.parent() {
+            let dir = fs::File::open(parent).await?;
+            dir.sync_all().await?;
+        }
+        Ok(())
+    }
+}
+
+/// In-memory store for tests, matching `services/updater`'s
+/// `MemoryStateStore`.
+#[derive(Debug, Default)]
+pub struct MemoryEnergyStateStore {
+    state: tokio::sync::Mutex<EnergyState>,
+}
+
+#[async_trait]
+impl EnergyStateStore for MemoryEnergyStateStore {
+    async fn load(&self) -> Result<EnergyState, StateStoreError> {
+        Ok(self.state.lock().await.clone())
+    }
+
+    async fn save(&self, state: &EnergyState) -> Result<(), StateStoreError> {
+        *self.state.lock().await = state.clone();
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use axum::body::Body;
-use axum::extract::{MatchedPath, State};
+use axum::extract::{MatchedPath, Query, State};
 use axum::http::{header, HeaderValue, Request, StatusCode};
-use axum::middleware::{from_fn, Next};
+use axum::middleware::{from_fn, from_fn_with_state, Next};
 use axum::response::sse::{Event, KeepAlive};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
@@ -22,11 +23,21 @@ use common_obs::{
     ObsInit, PROMETHEUS_CONTENT_TYPE,
 };
 
+mod store;
+mod webhook_auth;
+
+use store::{InMemoryPresenceStore, PresenceStore, SharedPresenceStore};
+use webhook_auth::{verify_webhook_signature, WebhookAuthConfig};
+
 use std::time::Instant;
 
 const SERVICE_NAME: &str = "presence-svc";
 const PORT_ENV: &str = "PRESENCE_SVC_PORT";
 const DEFAULT_PORT: u16 = 8004;
+const WEBHOOK_SECRET_ENV: &str = "PRESENCE_WEBHOOK_SECRET";
+const WEBHOOK_REPLAY_WINDOW_ENV: &str = "PRESENCE_WEBHOOK_REPLAY_WINDOW_SECS";
+const RETENTION_ENV: &str = "PRESENCE_HISTORY_RETENTION_SECS";
+const DEFAULT_RETENTION_SECS: i64 = 7 * 24 * 60 * 60;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -41,6 +52,8 @@ fn build_time() -> &'static str {
 #[derive(Clone)]
 struct AppState {
     events: broadcast::Sender<PresenceEvent>,
+    store: SharedPresenceStore,
+    retention: chrono::Duration,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -90,7 +103,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
 
     let (tx, _) = broadcast::channel(128);
-    let state = AppState { events: tx };
+    let state = AppState {
+        events: tx,
+        store: Arc::new(InMemoryPresenceStore::new()),
+        retention: retention_from_env(),
+    };
+    let webhook_auth =
+        WebhookAuthConfig::from_env(WEBHOOK_SECRET_ENV, WEBHOOK_REPLAY_WINDOW_ENV);
 
     tracing::info!(
         event = "service_start",
@@ -102,12 +121,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "starting service"
     );
 
-    let app = Router::new()
+    let webhook_route = Router::new()
         .route("/v1/presence/webhook", post(intake_webhook))
+        .layer(from_fn_with_state(
+            webhook_auth,
+            verify_webhook_signature,
+        ))
+        .with_state(state.clone());
+
+    let app = Router::new()
         .route("/v1/presence/ble", post(intake_ble))
         .route("/v1/presence/events", get(stream_events))
+        .route("/v1/presence/history", get(history))
+        .route("/v1/presence/current", get(current))
         .route("/metrics", get(metrics))
         .with_state(state)
+        .merge(webhook_route)
         .merge(health_router(SERVICE_NAME))
         .layer(from_fn(track_http_metrics));
 
@@ -128,7 +157,7 @@ async fn intake_webhook(
         confidence: payload.confidence,
         observed_at: payload.observed_at,
     };
-    dispatch_event(&state.events, event);
+    dispatch_event(&state, event).await;
     StatusCode::ACCEPTED
 }
 
@@ -143,10 +172,41 @@ async fn intake_ble(State(state): State<AppState>, Json(payload): Json<BlePayloa
         confidence,
         observed_at: payload.observed_at,
     };
-    dispatch_event(&state.events, event);
+    dispatch_event(&state, event).await;
     StatusCode::ACCEPTED
 }
 
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    person_id: String,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+async fn history(State(state): State<AppState>, Query(query): Query<HistoryQuery>) -> Response {
+    match state
+        .store
+        .history(&query.person_id, query.since, query.until)
+        .await
+    {
+        Ok(events) => Json(events).into_response(),
+        Err(err) => {
+            tracing::error!(error = %err, "presence history query failed");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn current(State(state): State<AppState>) -> Response {
+    match state.store.current().await {
+        Ok(events) => Json(events).into_response(),
+        Err(err) => {
+            tracing::error!(error = %err, "presence current query failed");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 async fn stream_events(
     State(state): State<AppState>,
 ) -> axum::response::Sse<impl Stream<Item = Result<Event, anyhow::Error>>> {
@@ -192,11 +252,27 @@ async fn track_http_metrics(req: Request<Body>, next: Next) -> Response {
     response
 }
 
-fn dispatch_event(sender: &broadcast::Sender<PresenceEvent>, event: PresenceEvent) {
+async fn dispatch_event(state: &AppState, event: PresenceEvent) {
     tracing::info!(person = %event.person_id, location = %event.location, source = ?event.source, "presence event");
-    let _ = sender.send(event);
+    let _ = state.events.send(event.clone());
+
+    if let Err(err) = state.store.append(event).await {
+        tracing::error!(error = %err, "failed to append presence event to store");
+        return;
+    }
+    if let Err(err) = state.store.prune(state.retention).await {
+        tracing::error!(error = %err, "failed to prune presence history");
+    }
 }
 
 fn now_ts() -> DateTime<Utc> {
     Utc::now()
 }
+
+fn retention_from_env() -> chrono::Duration {
+    let secs = std::env::var(RETENTION_ENV)
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_RETENTION_SECS);
+    chrono::Duration::seconds(secs)
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+use crate::PresenceEvent;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PresenceStoreError {
+    #[error("lock poisoned")]
+    Poisoned,
+}
+
+/// Durable(-ish) presence history keyed by `(person_id, observed_at)` so
+/// entries are range-scannable per person, plus a cheap lookup for each
+/// person's most recent event.
+#[async_trait]
+pub trait PresenceStore: Send + Sync {
+    async fn append(&self, event: PresenceEvent) -> Result<(), PresenceStoreError>;
+
+    async fn history(
+        &self,
+        person_id: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PresenceEvent>, PresenceStoreError>;
+
+    async fn current(&self) -> Result<Vec<PresenceEvent>, PresenceStoreError>;
+
+    /// Drops entries older than `retention` relative to now. Called after
+    /// every append so the store never grows unbounded.
+    async fn prune(&self, retention: chrono::Duration) -> Result<(), PresenceStoreError>;
+}
+
+/// Default in-memory backend: a `BTreeMap` ordered by `(person_id,
+/// observed_at)` gives cheap range scans for history queries, alongside a
+/// per-person "latest seen" index for the current-location query.
+#[derive(Debug, Default)]
+pub struct InMemoryPresenceStore {
+    by_person: RwLock<BTreeMap<(String, DateTime<Utc>), PresenceEvent>>,
+    latest: RwLock<BTreeMap<String, PresenceEvent>>,
+}
+
+impl InMemoryPresenceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PresenceStore for InMemoryPresenceStore {
+    async fn append(&self, event: PresenceEvent) -> Result<(), PresenceStoreError> {
+        let key = (event.person_id.clone(), event.observed_at);
+        self.by_person.write().await.insert(key, event.clone());
+
+        let mut latest = self.latest.write().await;
+        match latest.get(&event.person_id) {
+            Some(current) if current.observed_at >= event.observed_at => {}
+            _ => {
+                latest.insert(event.person_id.clone(), event);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn history(
+        &self,
+        person_id: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PresenceEvent>, PresenceStoreError> {
+        let by_person = self.by_person.read().await;
+        let events = by_person
+            .range((person_id.to_string(), DateTime::<Utc>::MIN_UTC)..)
+            .take_while(|((id, _), _)| id == person_id)
+            .map(|(_, event)| event)
+            .filter(|event| since.map(|since| event.observed_at >= since).unwrap_or(true))
+            .filter(|event| until.map(|until| event.observed_at <= until).unwrap_or(true))
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    async fn current(&self) -> Result<Vec<PresenceEvent>, PresenceStoreError> {
+        Ok(self.latest.read().await.values().cloned().collect())
+    }
+
+    async fn prune(&self, retention: chrono::Duration) -> Result<(), PresenceStoreError> {
+        let cutoff = Utc::now() - retention;
+        self.by_person
+            .write()
+            .await
+            .retain(|(_, observed_at), _| *observed_at >= cutoff);
+        Ok(())
+    }
+}
+
+pub type SharedPresenceStore = Arc<dyn PresenceStore>;
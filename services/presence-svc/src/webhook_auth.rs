@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const SIGNATURE_HEADER: &str = "x-lokan-signature";
+const TIMESTAMP_HEADER: &str = "x-lokan-timestamp";
+const SIGNATURE_PREFIX: &str = "sha256=";
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Webhook intake authentication: HMAC-signed when a shared secret is
+/// configured, otherwise a no-op for backward compatibility.
+#[derive(Clone, Default)]
+pub struct WebhookAuthConfig {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    secret: Option<Vec<u8>>,
+    replay_window: Option<Duration>,
+}
+
+impl WebhookAuthConfig {
+    pub fn from_env(secret_env: &str, replay_window_env: &str) -> Self {
+        let secret = std::env::var(secret_env)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| value.into_bytes());
+        let replay_window = std::env::var(replay_window_env)
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        Self {
+            inner: Arc::new(Inner {
+                secret,
+                replay_window,
+            }),
+        }
+    }
+}
+
+pub async fn verify_webhook_signature(
+    axum::extract::State(config): axum::extract::State<WebhookAuthConfig>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(secret) = config.inner.secret.as_ref() else {
+        return next.run(req).await;
+    };
+
+    let (parts, body) = req.into_parts();
+    let body_bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let signature_header = parts
+        .headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match signature_header.and_then(|header| verify_signature(secret, &body_bytes, header)) {
+        Some(true) => {}
+        _ => return StatusCode::UNAUTHORIZED.into_response(),
+    }
+
+    if let Some(window) = config.inner.replay_window {
+        let timestamp = parts
+            .headers
+            .get(TIMESTAMP_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc));
+
+        match timestamp {
+            Some(observed_at) if within_window(observed_at, window) => {}
+            _ => return StatusCode::UNAUTHORIZED.into_response(),
+        }
+    }
+
+    let req = Request::from_parts(parts, Body::from(body_bytes));
+    next.run(req).await
+}
+
+fn verify_signature(secret: &[u8], body: &[u8], header_value: &str) -> Option<bool> {
+    let hex_signature = header_value.strip_prefix(SIGNATURE_PREFIX)?;
+    let expected = hex_decode(hex_signature)?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).ok()?;
+    mac.update(body);
+    Some(mac.verify_slice(&expected).is_ok())
+}
+
+fn within_window(observed_at: DateTime<Utc>, window: Duration) -> bool {
+    let age = Utc::now().signed_duration_since(observed_at);
+    age >= chrono::Duration::zero() && age.to_std().map(|age| age <= window).unwrap_or(false)
+}
+
+fn hex_decode(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&value[idx..idx + 2], 16).ok())
+        .collect()
+}
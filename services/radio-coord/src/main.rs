@@ -3,29 +3,42 @@ use std::sync::Arc;
 
 use axum::body::Body;
 use axum::extract::{MatchedPath, State};
-use axum::http::{header, HeaderValue, Request, StatusCode};
-use axum::middleware::{from_fn, Next};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::middleware::{from_fn, from_fn_with_state, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Utc};
+use common_auth::{validate_token, SigningKey, TokenClaims};
 use common_config::{load, MsgBusConfig, ServiceConfig};
 use common_mdns::announce;
-use common_msgbus::{MessageBus, NatsBus, NatsConfig};
+use common_msgbus::{MessageBus, NatsBus, NatsConfig, DEFAULT_MAX_PAYLOAD};
 use common_obs::{
     encode_prometheus_metrics, http_request_observe, msgbus_publish_total, ObsInit,
     PROMETHEUS_CONTENT_TYPE,
 };
 use parking_lot::RwLock;
+use pbkdf2::pbkdf2_hmac;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha1::Sha1;
 use tokio::net::TcpListener;
 
 use std::time::Instant;
 
+mod persistence;
+mod regulatory;
+mod thread_tlv;
+
 const SERVICE_NAME: &str = "radio-coord";
 type SharedState = Arc<AppState>;
 
+/// WPA-Personal derives its 256-bit PSK with 4096 PBKDF2-HMAC-SHA1 rounds
+/// (IEEE 802.11-2020, 12.7.1.5.3); WPA3-Personal's transition mode derives
+/// the same PSK for its WPA2-PSK fallback.
+const WPA_PSK_PBKDF2_ITERATIONS: u32 = 4096;
+const WPA_PSK_LEN: usize = 32;
+
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn build_sha() -> &'static str {
@@ -55,6 +68,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let bus_config = NatsConfig {
         url: config.bus.url.clone(),
         request_timeout: config.bus.request_timeout(),
+        max_payload: DEFAULT_MAX_PAYLOAD,
     };
     let bus: Arc<dyn MessageBus> = Arc::new(NatsBus::connect(bus_config).await?);
 
@@ -65,17 +79,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
-    let state = Arc::new(AppState::new(bus));
+    let persist_path = config
+        .persistence_enabled
+        .then(|| config.persistence_path.clone());
+
+    let restored = persist_path.as_deref().and_then(|path| {
+        match persistence::load::<RadioMapSnapshot>(path) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                tracing::error!(
+                    %err,
+                    path,
+                    "failed to load persisted radio map snapshot; starting empty"
+                );
+                None
+            }
+        }
+    });
+
+    let auth = AuthState::from_config(&config);
+    let state = Arc::new(AppState::new(
+        bus,
+        config.country.clone(),
+        auth,
+        persist_path,
+    ));
+
+    if let Some(snapshot) = restored {
+        state.restore(snapshot.clone());
+        if config.persistence_republish {
+            republish_restored_state(&state, &snapshot).await;
+        }
+    }
 
     let app = Router::new()
         .route("/v1/health", get(health))
         .route("/v1/thread/dataset", post(apply_thread_dataset))
+        .route("/v1/thread/dataset/tlv", post(apply_thread_dataset_tlv))
         .route("/v1/thread/channel", post(update_thread_channel))
         .route("/v1/wifi/config", post(apply_wifi_config))
         .route("/v1/wifi/channel", post(update_wifi_channel))
+        .route("/v1/wifi/scan", post(scan_wifi))
         .route("/v1/diag/radio-map", get(radio_map))
         .route("/metrics", get(metrics))
         .with_state(state.clone())
+        .layer(from_fn_with_state(state.clone(), bearer_auth_guard))
         .layer(from_fn(track_http_metrics));
 
     let listener = TcpListener::bind(addr).await?;
@@ -117,22 +165,216 @@ async fn track_http_metrics(req: Request<Body>, next: Next) -> Response {
     response
 }
 
+/// Enforces bearer-token auth on every route except `/v1/health` and
+/// `/metrics`, per `state.auth.mode`: `Off` skips the check entirely,
+/// `Observe` logs what would have been denied but still lets the request
+/// through, `Enforce` rejects it with a 401 in the same JSON envelope
+/// `ApiError` uses elsewhere.
+async fn bearer_auth_guard(
+    State(state): State<SharedState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    if state.auth.mode == AuthMode::Off {
+        return next.run(req).await;
+    }
+
+    let path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    if !route_requires_auth(&path) {
+        return next.run(req).await;
+    }
+
+    match authenticate(&state, req.headers(), required_scope(&path)) {
+        Ok(claims) => {
+            req.extensions_mut().insert(claims);
+            next.run(req).await
+        }
+        Err(reason) => {
+            if state.auth.mode == AuthMode::Observe {
+                tracing::warn!(
+                    event = "auth_observe_denied",
+                    path = path.as_str(),
+                    reason = reason.as_str(),
+                    "bearer auth would have denied this request"
+                );
+                return next.run(req).await;
+            }
+            ApiError::Unauthorized(reason).into_response()
+        }
+    }
+}
+
+/// Whether `path` needs a bearer token at all: every route except the
+/// health check and metrics scrape, which monitoring infrastructure must
+/// be able to reach unauthenticated.
+fn route_requires_auth(path: &str) -> bool {
+    !matches!(path, "/v1/health" | "/metrics")
+}
+
+/// The scope an authenticated request to `path` must carry: `radio.read`
+/// for the read-only diagnostics route, `radio.write` for every gated
+/// Thread/Wi-Fi mutation.
+fn required_scope(path: &str) -> &'static str {
+    if path == "/v1/diag/radio-map" {
+        "radio.read"
+    } else {
+        "radio.write"
+    }
+}
+
+fn authenticate(state: &AppState, headers: &HeaderMap, scope: &str) -> Result<TokenClaims, String> {
+    let key = state
+        .auth
+        .key
+        .as_deref()
+        .ok_or_else(|| "no signing key configured".to_string())?;
+    let token = bearer_token(headers)
+        .ok_or_else(|| "missing or malformed Authorization header".to_string())?;
+    let claims = validate_token(token, key).map_err(|err| err.to_string())?;
+    if !claims.has_scope(scope) {
+        return Err(format!("token is missing required scope '{scope}'"));
+    }
+    Ok(claims)
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// How strictly [`bearer_auth_guard`] enforces the bearer-token check.
+/// Defaults to `Off` so existing deployments keep working without a
+/// signing key configured.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum AuthMode {
+    Off,
+    Observe,
+    Enforce,
+}
+
+impl Default for AuthMode {
+    fn default() -> Self {
+        AuthMode::Off
+    }
+}
+
+/// Resolved bearer-auth configuration [`bearer_auth_guard`] consults per
+/// request.
+#[derive(Clone)]
+struct AuthState {
+    mode: AuthMode,
+    key: Option<Arc<SigningKey>>,
+}
+
+impl AuthState {
+    /// Builds the auth state from config, reading the RS256 public key
+    /// file if one was configured. Falls back to `AuthMode::Off` (with a
+    /// warning) when enforcement was requested but no usable key ended up
+    /// configured, so a misconfiguration can't lock every route behind a
+    /// 401 with no way to recover short of a redeploy.
+    fn from_config(config: &RadioCoordConfig) -> Self {
+        if config.auth_mode == AuthMode::Off {
+            return Self {
+                mode: AuthMode::Off,
+                key: None,
+            };
+        }
+
+        let key = if let Some(secret) = &config.auth_jwt_secret {
+            Some(SigningKey::Hmac(secret.as_bytes().to_vec()))
+        } else if let Some(path) = &config.auth_jwt_public_key_path {
+            match std::fs::read(path) {
+                Ok(pem) => Some(SigningKey::Rsa(pem)),
+                Err(err) => {
+                    tracing::error!(
+                        %err,
+                        path = path.as_str(),
+                        "failed to read RS256 public key; leaving bearer auth disabled"
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        match key {
+            Some(key) => Self {
+                mode: config.auth_mode,
+                key: Some(Arc::new(key)),
+            },
+            None => {
+                tracing::warn!(
+                    "auth_mode is set but no signing key is configured; leaving bearer auth disabled"
+                );
+                Self {
+                    mode: AuthMode::Off,
+                    key: None,
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 struct AppState {
     bus: Arc<dyn MessageBus>,
     radio_map: Arc<RwLock<RadioMapSnapshot>>,
+    country: String,
+    auth: AuthState,
+    persist_path: Option<String>,
 }
 
 impl AppState {
-    fn new(bus: Arc<dyn MessageBus>) -> Self {
+    fn new(
+        bus: Arc<dyn MessageBus>,
+        country: String,
+        auth: AuthState,
+        persist_path: Option<String>,
+    ) -> Self {
         Self {
             bus,
             radio_map: Arc::new(RwLock::new(RadioMapSnapshot::default())),
+            country,
+            auth,
+            persist_path,
         }
     }
 
     fn snapshot(&self) -> RadioMapSnapshot {
-        self.radio_map.read().clone()
+        let mut map = self.radio_map.read().clone();
+        map.country = self.country.clone();
+        map
+    }
+
+    /// Loads a snapshot restored from disk at startup into the live map,
+    /// leaving `country` to keep tracking the current config rather than
+    /// whatever country was active when the snapshot was written.
+    fn restore(&self, snapshot: RadioMapSnapshot) {
+        let mut map = self.radio_map.write();
+        map.thread = snapshot.thread;
+        map.wifi = snapshot.wifi;
+    }
+
+    /// Atomically persists the current snapshot to `persist_path`, if
+    /// persistence is enabled. Logs rather than propagating failures so a
+    /// disk hiccup doesn't turn a radio mutation that already succeeded
+    /// into a 503 for the caller.
+    fn persist(&self) {
+        let Some(path) = self.persist_path.as_deref() else {
+            return;
+        };
+        if let Err(err) = persistence::save(path, &self.snapshot()) {
+            tracing::error!(%err, path, "failed to persist radio map snapshot");
+        }
     }
 
     fn update_thread_dataset(&self, request: &ThreadDatasetRequest) {
@@ -167,17 +409,23 @@ impl AppState {
         }
     }
 
-    fn update_wifi_config(&self, request: &WifiConfigRequest) {
+    fn update_wifi_config(
+        &self,
+        request: &WifiConfigRequest,
+        psk: Option<String>,
+        channel: Option<u8>,
+    ) {
         let now = Utc::now();
         let mut map = self.radio_map.write();
         map.wifi.config = Some(WifiConfigSnapshot {
             ssid: request.ssid.clone(),
             security: request.security.as_str().to_string(),
             band: request.band.clone(),
-            channel: request.channel,
+            channel,
+            psk,
             updated_at: now,
         });
-        if let Some(channel) = request.channel {
+        if let Some(channel) = channel {
             map.wifi.channel = Some(WifiChannelSnapshot {
                 channel,
                 band: request.band.clone(),
@@ -186,32 +434,48 @@ impl AppState {
         }
     }
 
-    fn update_wifi_channel(&self, request: &WifiChannelRequest) {
+    fn update_wifi_channel(&self, request: &WifiChannelRequest, channel: u8) {
         let now = Utc::now();
         let mut map = self.radio_map.write();
         map.wifi.channel = Some(WifiChannelSnapshot {
-            channel: request.channel,
+            channel,
             band: request.band.clone(),
             updated_at: now,
         });
     }
+
+    fn update_wifi_survey(&self, neighbors: Vec<WifiNeighbor>) {
+        let now = Utc::now();
+        let mut map = self.radio_map.write();
+        map.wifi.survey = WifiSurveySnapshot {
+            neighbors,
+            updated_at: Some(now),
+        };
+    }
+
+    fn wifi_survey_neighbors(&self) -> Vec<WifiNeighbor> {
+        self.radio_map.read().wifi.survey.neighbors.clone()
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RadioMapSnapshot {
+    /// The regulatory domain (ISO 3166-1 alpha-2) `validate_wifi_config`/
+    /// `validate_wifi_channel` enforce channels and bands against.
+    country: String,
     thread: ThreadSnapshot,
     wifi: WifiSnapshot,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ThreadSnapshot {
     dataset: Option<ThreadDatasetSnapshot>,
     channel: Option<ThreadChannelSnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ThreadDatasetSnapshot {
     dataset_id: String,
@@ -223,7 +487,7 @@ struct ThreadDatasetSnapshot {
     updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ThreadChannelSnapshot {
     channel: u8,
@@ -232,14 +496,15 @@ struct ThreadChannelSnapshot {
     updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct WifiSnapshot {
     config: Option<WifiConfigSnapshot>,
     channel: Option<WifiChannelSnapshot>,
+    survey: WifiSurveySnapshot,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct WifiConfigSnapshot {
     ssid: String,
@@ -248,10 +513,14 @@ struct WifiConfigSnapshot {
     band: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     channel: Option<u8>,
+    /// The derived 256-bit pre-shared key, as 64 hex chars. `None` for Open
+    /// networks, or when the request opted into legacy passphrase pass-through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    psk: Option<String>,
     updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct WifiChannelSnapshot {
     channel: u8,
@@ -260,6 +529,37 @@ struct WifiChannelSnapshot {
     updated_at: DateTime<Utc>,
 }
 
+/// The most recent `POST /v1/wifi/scan` result, used as the congestion
+/// model behind `channel: "auto"` selection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WifiSurveySnapshot {
+    neighbors: Vec<WifiNeighbor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// One BSS observed during a scan, mirroring the BSS-description +
+/// protection model most WLAN scan tooling reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WifiNeighbor {
+    ssid: String,
+    bssid: String,
+    channel: u8,
+    band: String,
+    rssi: i16,
+    protection: WifiProtection,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum WifiProtection {
+    Open,
+    Wpa2,
+    Wpa3,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ThreadDatasetRequest {
@@ -273,6 +573,20 @@ struct ThreadDatasetRequest {
     master_key: Option<String>,
     #[serde(default)]
     pskc: Option<String>,
+    /// When true, the response also carries the equivalent Active
+    /// Operational Dataset TLV stream, hex-encoded, alongside the JSON.
+    #[serde(default)]
+    emit_tlv: bool,
+}
+
+/// `POST /v1/thread/dataset/tlv`'s body: a dataset id and the Active
+/// Operational Dataset as a hex-encoded TLV stream, decoded into the same
+/// fields [`ThreadDatasetRequest`] carries.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadDatasetTlvRequest {
+    dataset_id: String,
+    tlv: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -293,18 +607,56 @@ struct WifiConfigRequest {
     security: WifiSecurity,
     #[serde(default)]
     band: Option<String>,
+    /// Omitted means "no channel directive"; `"auto"` auto-selects the
+    /// least-congested channel for `band` from the latest Wi-Fi survey.
     #[serde(default)]
-    channel: Option<u8>,
+    channel: Option<ChannelSelection>,
+    /// Which form of the WPA-Personal credential to publish on the bus.
+    /// Ignored for `Open` networks, which have no credential to derive.
+    #[serde(default)]
+    credential_output: WifiCredentialOutput,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct WifiChannelRequest {
-    channel: u8,
+    /// `"auto"` auto-selects the least-congested channel for `band` from
+    /// the latest Wi-Fi survey.
+    channel: ChannelSelection,
     #[serde(default)]
     band: Option<String>,
 }
 
+/// A requested Wi-Fi channel: an explicit number, or `"auto"` to have
+/// `select_auto_channel` pick one from the latest survey.
+#[derive(Debug, Clone, Copy)]
+enum ChannelSelection {
+    Manual(u8),
+    Auto,
+}
+
+impl<'de> Deserialize<'de> for ChannelSelection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u8),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(channel) => Ok(ChannelSelection::Manual(channel)),
+            Raw::Text(text) if text.eq_ignore_ascii_case("auto") => Ok(ChannelSelection::Auto),
+            Raw::Text(other) => Err(serde::de::Error::custom(format!(
+                "invalid channel '{other}'; expected a channel number or \"auto\""
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct Acknowledgement {
@@ -312,6 +664,17 @@ struct Acknowledgement {
     message: String,
 }
 
+/// `apply_thread_dataset`'s response: the usual acknowledgement, plus the
+/// hex-encoded TLV stream when the request set `emitTlv`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadDatasetAck {
+    #[serde(flatten)]
+    ack: Acknowledgement,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tlv: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 enum WifiSecurity {
@@ -336,10 +699,28 @@ impl WifiSecurity {
     }
 }
 
+/// Which credential form `apply_wifi_config` publishes for a secured network:
+/// the derived PSK (the default, so the cleartext passphrase never reaches
+/// the bus), or the raw passphrase for legacy downstream agents that have
+/// not switched over to consuming `psk` yet.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum WifiCredentialOutput {
+    Psk,
+    Passphrase,
+}
+
+impl Default for WifiCredentialOutput {
+    fn default() -> Self {
+        WifiCredentialOutput::Psk
+    }
+}
+
 #[derive(Debug)]
 enum ApiError {
     Validation(String),
     Bus(String),
+    Unauthorized(String),
 }
 
 impl axum::response::IntoResponse for ApiError {
@@ -347,6 +728,9 @@ impl axum::response::IntoResponse for ApiError {
         let (status, code, message) = match self {
             ApiError::Validation(message) => (StatusCode::BAD_REQUEST, "validation_error", message),
             ApiError::Bus(message) => (StatusCode::SERVICE_UNAVAILABLE, "bus_error", message),
+            ApiError::Unauthorized(message) => {
+                (StatusCode::UNAUTHORIZED, "unauthorized_error", message)
+            }
         };
 
         let body = Json(json!({
@@ -366,6 +750,29 @@ struct RadioCoordConfig {
     pub port: u16,
     pub announce_mdns: bool,
     pub mdns_service: String,
+    /// ISO 3166-1 alpha-2 regulatory domain, looked up in `regulatory`'s
+    /// country table to enforce Wi-Fi channel/band requests.
+    pub country: String,
+    /// How strictly `bearer_auth_guard` enforces bearer-token auth.
+    /// Defaults to `Off` so existing deployments keep working unchanged.
+    pub auth_mode: AuthMode,
+    /// HS256 shared secret for verifying bearer tokens. Takes precedence
+    /// over `auth_jwt_public_key_path` if both are set.
+    pub auth_jwt_secret: Option<String>,
+    /// Path to a PEM-encoded RSA public key for verifying RS256 bearer
+    /// tokens, used when `auth_jwt_secret` isn't set.
+    pub auth_jwt_public_key_path: Option<String>,
+    /// Whether the radio map snapshot is written to `persistence_path` on
+    /// each mutation and reloaded on startup. Defaults to off so existing
+    /// deployments don't suddenly start writing to an unprovisioned path.
+    pub persistence_enabled: bool,
+    /// Where the persisted radio map snapshot is written/read, atomically
+    /// (the path with its extension replaced by `tmp`, then a rename).
+    pub persistence_path: String,
+    /// Whether a snapshot restored from disk at startup is re-published to
+    /// the bus so downstream radio agents (Thread border router, Wi-Fi AP
+    /// driver, ...) reconverge without waiting for a fresh user apply.
+    pub persistence_republish: bool,
     #[serde(flatten)]
     pub bus: MsgBusConfig,
 }
@@ -377,6 +784,13 @@ impl Default for RadioCoordConfig {
             port: 8009,
             announce_mdns: true,
             mdns_service: "_lokan._tcp".to_string(),
+            country: "US".to_string(),
+            auth_mode: AuthMode::default(),
+            auth_jwt_secret: None,
+            auth_jwt_public_key_path: None,
+            persistence_enabled: false,
+            persistence_path: "/var/lib/lokan/radio-coord/radio-map.json".to_string(),
+            persistence_republish: false,
             bus: MsgBusConfig::default(),
         }
     }
@@ -399,7 +813,7 @@ impl RadioCoordConfig {
 async fn apply_thread_dataset(
     State(state): State<SharedState>,
     Json(request): Json<ThreadDatasetRequest>,
-) -> Result<(StatusCode, Json<Acknowledgement>), ApiError> {
+) -> Result<(StatusCode, Json<ThreadDatasetAck>), ApiError> {
     validate_thread_dataset(&request)?;
 
     let event = json!({
@@ -413,6 +827,71 @@ async fn apply_thread_dataset(
 
     publish_event(&state, "radio.thread.dataset.set", &event).await?;
     state.update_thread_dataset(&request);
+    state.persist();
+
+    let tlv = if request.emit_tlv {
+        let bytes = thread_tlv::encode(
+            request.channel,
+            &request.pan_id,
+            request.xpan_id.as_deref(),
+            &request.network_name,
+            request.pskc.as_deref(),
+            request.master_key.as_deref(),
+        )
+        .map_err(ApiError::Validation)?;
+        Some(hex_encode(&bytes))
+    } else {
+        None
+    };
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ThreadDatasetAck {
+            ack: Acknowledgement {
+                accepted: true,
+                message: "thread dataset accepted".to_string(),
+            },
+            tlv,
+        }),
+    ))
+}
+
+/// Decodes the Active Operational Dataset TLV stream in the request body
+/// and applies it the same way [`apply_thread_dataset`] applies discrete
+/// fields: validated, published to `radio.thread.dataset.set`, and stored
+/// as a `ThreadDatasetSnapshot`.
+async fn apply_thread_dataset_tlv(
+    State(state): State<SharedState>,
+    Json(request): Json<ThreadDatasetTlvRequest>,
+) -> Result<(StatusCode, Json<Acknowledgement>), ApiError> {
+    let bytes = thread_tlv::decode_hex(&request.tlv, "tlv").map_err(ApiError::Validation)?;
+    let decoded = thread_tlv::decode(&bytes).map_err(ApiError::Validation)?;
+
+    let dataset = ThreadDatasetRequest {
+        dataset_id: request.dataset_id,
+        network_name: decoded.network_name,
+        channel: decoded.channel,
+        pan_id: decoded.pan_id,
+        xpan_id: decoded.extended_pan_id,
+        master_key: decoded.network_key,
+        pskc: decoded.pskc,
+        emit_tlv: false,
+    };
+
+    validate_thread_dataset(&dataset)?;
+
+    let event = json!({
+        "action": "thread.dataset.apply",
+        "datasetId": dataset.dataset_id,
+        "networkName": dataset.network_name,
+        "channel": dataset.channel,
+        "panId": dataset.pan_id,
+        "xpanId": dataset.xpan_id,
+    });
+
+    publish_event(&state, "radio.thread.dataset.set", &event).await?;
+    state.update_thread_dataset(&dataset);
+    state.persist();
 
     Ok((
         StatusCode::ACCEPTED,
@@ -437,6 +916,7 @@ async fn update_thread_channel(
 
     publish_event(&state, "radio.thread.channel.set", &event).await?;
     state.update_thread_channel(&request);
+    state.persist();
 
     Ok((
         StatusCode::ACCEPTED,
@@ -451,18 +931,23 @@ async fn apply_wifi_config(
     State(state): State<SharedState>,
     Json(request): Json<WifiConfigRequest>,
 ) -> Result<(StatusCode, Json<Acknowledgement>), ApiError> {
-    validate_wifi_config(&request)?;
+    validate_wifi_config(&request, &state.country)?;
+    let channel = resolve_optional_channel(&state, request.channel, request.band.as_deref())?;
+    let (psk, passphrase) = derive_wifi_credential(&request);
 
     let event = json!({
         "action": "wifi.config.apply",
         "ssid": request.ssid,
         "security": request.security.as_str(),
         "band": request.band,
-        "channel": request.channel,
+        "channel": channel,
+        "psk": psk.clone(),
+        "passphrase": passphrase,
     });
 
     publish_event(&state, "radio.wifi.config.set", &event).await?;
-    state.update_wifi_config(&request);
+    state.update_wifi_config(&request, psk, channel);
+    state.persist();
 
     Ok((
         StatusCode::ACCEPTED,
@@ -477,16 +962,18 @@ async fn update_wifi_channel(
     State(state): State<SharedState>,
     Json(request): Json<WifiChannelRequest>,
 ) -> Result<(StatusCode, Json<Acknowledgement>), ApiError> {
-    validate_wifi_channel(&request)?;
+    validate_wifi_channel(&request, &state.country)?;
+    let channel = resolve_channel(&state, request.channel, request.band.as_deref())?;
 
     let event = json!({
         "action": "wifi.channel.update",
-        "channel": request.channel,
+        "channel": channel,
         "band": request.band,
     });
 
     publish_event(&state, "radio.wifi.channel.set", &event).await?;
-    state.update_wifi_channel(&request);
+    state.update_wifi_channel(&request, channel);
+    state.persist();
 
     Ok((
         StatusCode::ACCEPTED,
@@ -497,6 +984,28 @@ async fn update_wifi_channel(
     ))
 }
 
+/// Issues a scan request over the bus and awaits the BSS results on the
+/// reply subject NATS allocates for `MessageBus::request`, then stores them
+/// as `RadioMapSnapshot`'s `wifi.survey` for later `channel: "auto"` calls.
+async fn scan_wifi(State(state): State<SharedState>) -> Result<Json<Vec<WifiNeighbor>>, ApiError> {
+    let request = json!({ "action": "wifi.scan.request" });
+    let payload = serde_json::to_vec(&request).map_err(|err| ApiError::Bus(err.to_string()))?;
+
+    let response = state
+        .bus
+        .request("radio.wifi.scan.request", &payload)
+        .await
+        .map_err(|err| ApiError::Bus(err.to_string()))?;
+
+    let neighbors: Vec<WifiNeighbor> = serde_json::from_slice(&response.payload)
+        .map_err(|err| ApiError::Bus(format!("invalid scan response: {err}")))?;
+
+    state.update_wifi_survey(neighbors.clone());
+    state.persist();
+
+    Ok(Json(neighbors))
+}
+
 fn validate_thread_dataset(request: &ThreadDatasetRequest) -> Result<(), ApiError> {
     ensure_hex(&request.dataset_id, 32, "datasetId")?;
     ensure_name(&request.network_name, 1, 16, "networkName")?;
@@ -522,7 +1031,7 @@ fn validate_thread_channel(request: &ThreadChannelRequest) -> Result<(), ApiErro
     Ok(())
 }
 
-fn validate_wifi_config(request: &WifiConfigRequest) -> Result<(), ApiError> {
+fn validate_wifi_config(request: &WifiConfigRequest, country: &str) -> Result<(), ApiError> {
     ensure_name(&request.ssid, 1, 32, "ssid")?;
     if matches!(request.security, WifiSecurity::Wpa2 | WifiSecurity::Wpa3) {
         let passphrase = request.passphrase.as_ref().ok_or_else(|| {
@@ -533,27 +1042,185 @@ fn validate_wifi_config(request: &WifiConfigRequest) -> Result<(), ApiError> {
                 "passphrase must be between 8 and 63 characters".to_string(),
             ));
         }
+        if !passphrase.is_ascii() {
+            return Err(ApiError::Validation(
+                "passphrase must contain only ASCII characters".to_string(),
+            ));
+        }
     }
 
     if let Some(band) = &request.band {
         ensure_band(band)?;
     }
 
-    if let Some(channel) = request.channel {
+    if let Some(ChannelSelection::Manual(channel)) = request.channel {
         ensure_wifi_channel(channel)?;
+        if let Some(band) = &request.band {
+            regulatory::ensure_channel_allowed(country, band, channel)
+                .map_err(ApiError::Validation)?;
+        }
     }
 
     Ok(())
 }
 
-fn validate_wifi_channel(request: &WifiChannelRequest) -> Result<(), ApiError> {
-    ensure_wifi_channel(request.channel)?;
+/// Builds the `(psk, passphrase)` pair published in the `wifi.config.apply`
+/// event: exactly one is `Some` for a secured network, chosen by
+/// `request.credential_output`, and both are `None` for an Open network,
+/// which has no credential to derive.
+fn derive_wifi_credential(request: &WifiConfigRequest) -> (Option<String>, Option<String>) {
+    if request.security == WifiSecurity::Open {
+        return (None, None);
+    }
+    let Some(passphrase) = request.passphrase.as_deref() else {
+        return (None, None);
+    };
+
+    match request.credential_output {
+        WifiCredentialOutput::Psk => {
+            let psk = derive_wpa_psk(passphrase, &request.ssid);
+            (Some(hex_encode(&psk)), None)
+        }
+        WifiCredentialOutput::Passphrase => (None, Some(passphrase.to_string())),
+    }
+}
+
+/// Derives the WPA-Personal pre-shared key: PBKDF2(HMAC-SHA1, passphrase,
+/// salt = SSID's raw UTF-8 bytes, 4096 iterations, dkLen = 32 bytes). WPA3's
+/// SAE/WPA2-PSK transition mode derives the same PSK for its WPA2 fallback,
+/// so this is used unchanged for both `Wpa2` and `Wpa3`.
+fn derive_wpa_psk(passphrase: &str, ssid: &str) -> [u8; WPA_PSK_LEN] {
+    let mut psk = [0u8; WPA_PSK_LEN];
+    pbkdf2_hmac::<Sha1>(
+        passphrase.as_bytes(),
+        ssid.as_bytes(),
+        WPA_PSK_PBKDF2_ITERATIONS,
+        &mut psk,
+    );
+    psk
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn validate_wifi_channel(request: &WifiChannelRequest, country: &str) -> Result<(), ApiError> {
     if let Some(band) = &request.band {
         ensure_band(band)?;
     }
+    if let ChannelSelection::Manual(channel) = request.channel {
+        ensure_wifi_channel(channel)?;
+        if let Some(band) = &request.band {
+            regulatory::ensure_channel_allowed(country, band, channel)
+                .map_err(ApiError::Validation)?;
+        }
+    }
     Ok(())
 }
 
+/// Resolves an optional [`ChannelSelection`]: `None` stays `None` (no
+/// channel directive), `Some(_)` resolves the same way [`resolve_channel`]
+/// does.
+fn resolve_optional_channel(
+    state: &SharedState,
+    selection: Option<ChannelSelection>,
+    band: Option<&str>,
+) -> Result<Option<u8>, ApiError> {
+    selection
+        .map(|selection| resolve_channel(state, selection, band))
+        .transpose()
+}
+
+/// Resolves a [`ChannelSelection`] to a concrete channel: `Manual` passes
+/// its channel through unchanged, `Auto` picks the least-congested
+/// candidate for `band` from the latest `POST /v1/wifi/scan` survey.
+fn resolve_channel(
+    state: &SharedState,
+    selection: ChannelSelection,
+    band: Option<&str>,
+) -> Result<u8, ApiError> {
+    match selection {
+        ChannelSelection::Manual(channel) => Ok(channel),
+        ChannelSelection::Auto => {
+            let band = band.ok_or_else(|| {
+                ApiError::Validation("band is required to auto-select a channel".to_string())
+            })?;
+            select_auto_channel(&state.wifi_survey_neighbors(), band).ok_or_else(|| {
+                ApiError::Validation(format!("no candidate channels are known for band '{band}'"))
+            })
+        }
+    }
+}
+
+/// The candidate channels considered for auto-selection on `band`, or
+/// `None` if `band` isn't a single recognized band (e.g. `"dual"`, which
+/// doesn't name one channel plan to choose from).
+fn candidate_channels(band: &str) -> Option<Vec<u8>> {
+    match band.to_ascii_lowercase().as_str() {
+        "2.4ghz" => Some((1..=11).collect()),
+        "5ghz" => Some(vec![
+            36, 40, 44, 48, 52, 56, 60, 64, 100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140,
+            144, 149, 153, 157, 161, 165,
+        ]),
+        "6ghz" => Some((1..=233).step_by(4).collect()),
+        _ => None,
+    }
+}
+
+/// Picks the candidate channel on `band` with the lowest congestion score,
+/// ties broken by the lowest channel number. Returns `None` when `band`
+/// isn't recognized by [`candidate_channels`].
+fn select_auto_channel(neighbors: &[WifiNeighbor], band: &str) -> Option<u8> {
+    candidate_channels(band)?
+        .into_iter()
+        .map(|channel| (channel, congestion_score(neighbors, band, channel)))
+        .min_by(|(channel_a, score_a), (channel_b, score_b)| {
+            score_a
+                .partial_cmp(score_b)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(channel_a.cmp(channel_b))
+        })
+        .map(|(channel, _)| channel)
+}
+
+/// Sums, over every surveyed neighbor on `band`, the neighbor's linearized
+/// RSSI weighted by how much it overlaps `candidate`. Lower is less
+/// congested.
+fn congestion_score(neighbors: &[WifiNeighbor], band: &str, candidate: u8) -> f64 {
+    neighbors
+        .iter()
+        .filter(|neighbor| neighbor.band.eq_ignore_ascii_case(band))
+        .map(|neighbor| {
+            rssi_weight(neighbor.rssi) * channel_overlap_weight(band, candidate, neighbor.channel)
+        })
+        .sum()
+}
+
+/// Converts a dBm reading to a linear power weight, so e.g. a -40dBm
+/// neighbor contributes far more congestion than a -80dBm one.
+fn rssi_weight(rssi_dbm: i16) -> f64 {
+    10f64.powf(rssi_dbm as f64 / 10.0)
+}
+
+/// How much a neighbor observed on `observed` congests `candidate` on
+/// `band`: full weight for an exact match; for 2.4GHz, whose channels are
+/// only 5MHz apart, a linear partial weight for neighbors within ±4
+/// channels (their 20MHz-wide signal still overlaps `candidate`); zero
+/// otherwise, since 5GHz/6GHz channel plans are non-overlapping.
+fn channel_overlap_weight(band: &str, candidate: u8, observed: u8) -> f64 {
+    if !band.eq_ignore_ascii_case("2.4ghz") {
+        return if candidate == observed { 1.0 } else { 0.0 };
+    }
+    let distance = (candidate as i16 - observed as i16).unsigned_abs() as f64;
+    if distance == 0.0 {
+        1.0
+    } else if distance <= 4.0 {
+        1.0 - distance / 5.0
+    } else {
+        0.0
+    }
+}
+
 fn ensure_thread_channel(channel: u8) -> Result<(), ApiError> {
     if (11..=26).contains(&channel) {
         Ok(())
@@ -638,3 +1305,60 @@ async fn publish_event(
 async fn radio_map(State(state): State<SharedState>) -> Json<RadioMapSnapshot> {
     Json(state.snapshot())
 }
+
+/// Re-publishes each restored section of `snapshot` to the bus so
+/// downstream radio agents, which only react to bus events and don't share
+/// this process's memory, reconverge after a restart instead of waiting for
+/// a fresh user-initiated apply. Each event fires under a `.restore` action
+/// name so consumers can tell a replay apart from a live change.
+async fn republish_restored_state(state: &AppState, snapshot: &RadioMapSnapshot) {
+    if let Some(dataset) = &snapshot.thread.dataset {
+        let event = json!({
+            "action": "thread.dataset.restore",
+            "datasetId": dataset.dataset_id,
+            "networkName": dataset.network_name,
+            "channel": dataset.channel,
+            "panId": dataset.pan_id,
+            "xpanId": dataset.xpan_id,
+        });
+        if let Err(err) = publish_event(state, "radio.thread.dataset.set", &event).await {
+            tracing::error!(?err, "failed to republish restored thread dataset");
+        }
+    }
+
+    if let Some(channel) = &snapshot.thread.channel {
+        let event = json!({
+            "action": "thread.channel.restore",
+            "channel": channel.channel,
+            "datasetId": channel.dataset_id,
+        });
+        if let Err(err) = publish_event(state, "radio.thread.channel.set", &event).await {
+            tracing::error!(?err, "failed to republish restored thread channel");
+        }
+    }
+
+    if let Some(config) = &snapshot.wifi.config {
+        let event = json!({
+            "action": "wifi.config.restore",
+            "ssid": config.ssid,
+            "security": config.security,
+            "band": config.band,
+            "channel": config.channel,
+            "psk": config.psk,
+        });
+        if let Err(err) = publish_event(state, "radio.wifi.config.set", &event).await {
+            tracing::error!(?err, "failed to republish restored wifi config");
+        }
+    }
+
+    if let Some(channel) = &snapshot.wifi.channel {
+        let event = json!({
+            "action": "wifi.channel.restore",
+            "channel": channel.channel,
+            "band": channel.band,
+        });
+        if let Err(err) = publish_event(state, "radio.wifi.channel.set", &event).await {
+            tracing::error!(?err, "failed to republish restored wifi channel");
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Durable on-disk storage for the radio map snapshot: without this, a
+//! restart of radio-coord silently forgets the last-applied Thread dataset
+//! and Wi-Fi config until something re-pushes them. Writes are atomic
+//! (`path` with its extension replaced by `tmp`, then a rename) so a crash mid-write can never leave a half-written
+//! file for the next [`load`] to choke on, and every file carries a schema
+//! version tag so a future `*Snapshot` shape change can be migrated instead
+//! of silently discarded.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Bumped whenever a persisted `*Snapshot` struct's shape changes in a way
+/// `serde`'s defaulting can't absorb; [`load`] refuses to decode a file
+/// written under a different version rather than guess.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Atomically writes `snapshot` to `path` as JSON, tagged with
+/// [`SCHEMA_VERSION`].
+pub fn save<T: Serialize>(path: &str, snapshot: &T) -> Result<(), String> {
+    let body = serde_json::json!({
+        "schemaVersion": SCHEMA_VERSION,
+        "snapshot": snapshot,
+    });
+    let bytes = serde_json::to_vec_pretty(&body)
+        .map_err(|err| format!("failed to encode snapshot: {err}"))?;
+
+    let target = Path::new(path);
+    let tmp_path = target.with_extension("tmp");
+    std::fs::write(&tmp_path, &bytes)
+        .map_err(|err| format!("failed to write {}: {err}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, target).map_err(|err| {
+        format!(
+            "failed to rename {} to {}: {err}",
+            tmp_path.display(),
+            target.display()
+        )
+    })?;
+    Ok(())
+}
+
+/// Loads a snapshot previously written by [`save`]. Returns `Ok(None)` if
+/// `path` doesn't exist yet (the common case on a fresh deployment), and
+/// `Err` if the file exists but is unreadable, malformed, or tagged with a
+/// schema version this build doesn't know how to migrate.
+pub fn load<T: DeserializeOwned>(path: &str) -> Result<Option<T>, String> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("failed to read {path}: {err}")),
+    };
+
+    let value: serde_json::Value =
+        serde_json::from_slice(&bytes).map_err(|err| format!("failed to parse {path}: {err}"))?;
+
+    let schema_version = value.get("schemaVersion").and_then(|v| v.as_u64());
+    if schema_version != Some(SCHEMA_VERSION as u64) {
+        return Err(format!(
+            "{path} has schema version {:?}, this build only knows how to read {SCHEMA_VERSION}; no migration defined",
+            schema_version
+        ));
+    }
+
+    let snapshot = value
+        .get("snapshot")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    serde_json::from_value(snapshot)
+        .map_err(|err| format!("failed to decode snapshot in {path}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Sample {
+        value: u32,
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "radio_coord_persistence_test_{}_{name}",
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn round_trips_a_saved_snapshot() {
+        let path = temp_path("roundtrip");
+        let sample = Sample { value: 42 };
+
+        save(&path, &sample).expect("save");
+        let loaded: Option<Sample> = load(&path).expect("load");
+
+        assert_eq!(loaded, Some(sample));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn missing_file_loads_as_none() {
+        let loaded: Option<Sample> = load(&temp_path("missing")).expect("load");
+        assert_eq!(loaded, None);
+    }
+
+    #[test]
+    fn mismatched_schema_version_is_rejected() {
+        let path = temp_path("bad-version");
+        std::fs::write(&path, r#"{"schemaVersion":999,"snapshot":{"value":1}}"#).expect("write");
+
+        let result: Result<Option<Sample>, String> = load(&path);
+
+        assert!(result.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-country Wi-Fi regulatory domain table: which bands and channels a
+//! country's regulator allows, modeled the way Wi-Fi driver firmware keys a
+//! CLM (Country/Locale Matrix) table by ISO 3166-1 alpha-2 code.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+/// One band's allowed channel set and, if the regulator sets one, its
+/// maximum conducted transmit power in dBm.
+#[derive(Debug, Clone)]
+struct BandRule {
+    channels: Vec<u8>,
+    #[allow(dead_code)] // not yet surfaced anywhere; kept for the next consumer
+    max_power_dbm: Option<u8>,
+}
+
+fn rule(channels: Vec<u8>, max_power_dbm: u8) -> BandRule {
+    BandRule {
+        channels,
+        max_power_dbm: Some(max_power_dbm),
+    }
+}
+
+fn unii_5ghz(extra: &[u8]) -> Vec<u8> {
+    let mut channels = vec![
+        36, 40, 44, 48, 52, 56, 60, 64, 100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140,
+    ];
+    channels.extend_from_slice(extra);
+    channels
+}
+
+fn psc_6ghz() -> Vec<u8> {
+    (1..=233).step_by(4).collect()
+}
+
+/// Keyed the same way requests name bands: `"2.4ghz"`, `"5ghz"`, `"6ghz"`.
+static REGULATORY_TABLE: Lazy<HashMap<&'static str, HashMap<&'static str, BandRule>>> =
+    Lazy::new(|| {
+        let mut table = HashMap::new();
+
+        // United States (FCC Part 15).
+        table.insert(
+            "US",
+            HashMap::from([
+                ("2.4ghz", rule((1..=11).collect(), 30)),
+                ("5ghz", rule(unii_5ghz(&[149, 153, 157, 161, 165]), 30)),
+                ("6ghz", rule(psc_6ghz(), 30)),
+            ]),
+        );
+
+        // Japan (ARIB STD-T66) - 2.4GHz extends to channel 14, which is
+        // DSSS-only at 1Mbps; UNII-3 (149-165) isn't allocated.
+        table.insert(
+            "JP",
+            HashMap::from([
+                ("2.4ghz", rule((1..=14).collect(), 20)),
+                ("5ghz", rule(unii_5ghz(&[]), 23)),
+                ("6ghz", rule(psc_6ghz(), 23)),
+            ]),
+        );
+
+        // European Union (ETSI EN 301 893) - channel 13 is allowed on 2.4GHz;
+        // UNII-3 isn't harmonized, so 5GHz tops out at channel 140.
+        table.insert(
+            "EU",
+            HashMap::from([
+                ("2.4ghz", rule((1..=13).collect(), 20)),
+                ("5ghz", rule(unii_5ghz(&[]), 23)),
+                ("6ghz", rule(psc_6ghz(), 23)),
+            ]),
+        );
+
+        table
+    });
+
+/// Checks whether `country` (ISO 3166-1 alpha-2, case-insensitive) permits
+/// `channel` on `band`. Returns `Err` naming the offending channel, band, or
+/// country when it doesn't — including when `country` has no table entry at
+/// all, since an unrecognized country can't be assumed permissive.
+pub fn ensure_channel_allowed(country: &str, band: &str, channel: u8) -> Result<(), String> {
+    let country_code = country.to_ascii_uppercase();
+    let rules = REGULATORY_TABLE
+        .get(country_code.as_str())
+        .ok_or_else(|| format!("no regulatory domain table for country '{country_code}'"))?;
+
+    let band_key = band.to_ascii_lowercase();
+    let band_rule = rules
+        .get(band_key.as_str())
+        .ok_or_else(|| format!("band '{band}' is not permitted in country '{country_code}'"))?;
+
+    if band_rule.channels.contains(&channel) {
+        Ok(())
+    } else {
+        Err(format!(
+            "channel {channel} on band '{band}' is not permitted in country '{country_code}'"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn us_allows_2_4ghz_channel_6() {
+        assert!(ensure_channel_allowed("US", "2.4ghz", 6).is_ok());
+    }
+
+    #[test]
+    fn us_rejects_2_4ghz_channel_14() {
+        assert!(ensure_channel_allowed("US", "2.4ghz", 14).is_err());
+    }
+
+    #[test]
+    fn japan_allows_2_4ghz_channel_14() {
+        assert!(ensure_channel_allowed("jp", "2.4ghz", 14).is_ok());
+    }
+
+    #[test]
+    fn eu_rejects_unii_3_channel_149() {
+        assert!(ensure_channel_allowed("EU", "5ghz", 149).is_err());
+    }
+
+    #[test]
+    fn unknown_country_is_rejected() {
+        assert!(ensure_channel_allowed("ZZ", "2.4ghz", 6).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rand::Rng;
+
+use lokan_event::{Event as BusEvent, EventTransport};
+
+use crate::{Action, ActionStatus};
+
+/// Env var pointing at the device service's base URL for `SetDeviceState`
+/// dispatch, mirroring `scene-svc`'s `DEVICE_REGISTRY_URL`.
+pub(crate) const DEVICE_REGISTRY_URL_ENV: &str = "DEVICE_REGISTRY_URL";
+pub(crate) const DEFAULT_DEVICE_REGISTRY_URL: &str = "http://127.0.0.1:8001";
+
+/// Upper bound on dispatch attempts (the first try plus retries) before an
+/// action is given up on and recorded as `ActionStatus::Failed`.
+const MAX_DISPATCH_ATTEMPTS: u32 = 4;
+/// Base delay for the full-jitter backoff between retries; doubles per
+/// attempt the same way `device-registry`'s outbox worker backs off.
+const RETRY_BASE_BACKOFF_MS: u64 = 100;
+const RETRY_MAX_BACKOFF_MS: u64 = 5_000;
+
+/// Outcome of a single dispatch attempt, before retry bookkeeping turns it
+/// into an [`ActionStatus`].
+pub(crate) enum DispatchOutcome {
+    Success,
+    Failed(String),
+}
+
+/// Identifies an action firing deterministically, so a retried
+/// tick (the scheduler re-evaluating after a crash, or `run_event_listener`
+/// redelivering an event) can't double-actuate a device. Derived from
+/// `(rule_id, tick, action_index)` — the same triple is reused verbatim
+/// across retries of the *same* attempt. Two event-triggered firings of one
+/// rule within the same scheduler tick get the same key.
+fn idempotency_key(rule_id: &str, tick: u64, action_index: usize) -> String {
+    format!("{rule_id}:{tick}:{action_index}")
+}
+
+/// Dispatches a single [`Action`] to its real sink. Implementations are
+/// expected to be idempotent given the same `idempotency_key`, so
+/// [`dispatch_with_retry`] can safely retry a failed attempt.
+#[async_trait]
+pub(crate) trait ActionDispatcher: Send + Sync {
+    async fn dispatch(&self, action: &Action, idempotency_key: &str) -> DispatchOutcome;
+}
+
+/// Live [`ActionDispatcher`]: `EmitEvent` publishes to the shared event bus,
+/// `SetDeviceState` issues an HTTP PUT to the device service, matching
+/// `scene-svc`'s `HttpDeviceRegistry::apply_state`.
+pub(crate) struct LiveDispatcher {
+    event_transport: Arc<dyn EventTransport>,
+    http_client: reqwest::Client,
+    device_registry_url: String,
+}
+
+impl LiveDispatcher {
+    pub(crate) fn new(event_transport: Arc<dyn EventTransport>) -> Self {
+        let device_registry_url = std::env::var(DEVICE_REGISTRY_URL_ENV)
+            .unwrap_or_else(|_| DEFAULT_DEVICE_REGISTRY_URL.to_string());
+        Self {
+            event_transport,
+            http_client: reqwest::Client::new(),
+            device_registry_url,
+        }
+    }
+
+    /// Fetches `device_id`'s current `version`, so a `SetDeviceState` PUT
+    /// can carry it as `If-Match` and satisfy device-registry's
+    /// optimistic-concurrency check.
+    async fn fetch_device_version(&self, device_id: &str) -> Result<i64, String> {
+        let url = format!("{}/v1/devices/{}", self.device_registry_url, device_id);
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("device service returned {}", response.status()));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|err| err.to_string())?;
+        body.get("version")
+            .and_then(|value| value.as_i64())
+            .ok_or_else(|| "device service response missing version".to_string())
+    }
+}
+
+#[async_trait]
+impl ActionDispatcher for LiveDispatcher {
+    async fn dispatch(&self, action: &Action, idempotency_key: &str) -> DispatchOutcome {
+        match action {
+            Action::EmitEvent { subject, payload } => {
+                let mut payload = payload.clone();
+                if let serde_json::Value::Object(map) = &mut payload {
+                    map.insert(
+                        "idempotency_key".to_string(),
+                        serde_json::Value::String(idempotency_key.to_string()),
+                    );
+                }
+                match self
+                    .event_transport
+                    .publish(BusEvent::new(subject.clone(), payload))
+                    .await
+                {
+                    Ok(()) => DispatchOutcome::Success,
+                    Err(err) => DispatchOutcome::Failed(err.to_string()),
+                }
+            }
+            Action::SetDeviceState { device_id, state } => {
+                // `device-registry`'s `/v1/devices/:id/state` requires an
+                // `If-Match: <version>` naming the version this write
+                // observed, and rejects a stale one with 412. Fetching the
+                // current version right before each PUT means a 412 here
+                // (another writer raced us) is resolved by simply letting
+                // `dispatch_with_retry` try again, since the next attempt
+                // re-fetches a fresh version rather than replaying this one.
+                let version = match self.fetch_device_version(device_id).await {
+                    Ok(version) => version,
+                    Err(err) => return DispatchOutcome::Failed(err),
+                };
+                let url = format!("{}/v1/devices/{}/state", self.device_registry_url, device_id);
+                let response = self
+                    .http_client
+                    .put(url)
+                    .header("Idempotency-Key", idempotency_key)
+                    .header("If-Match", version.to_string())
+                    .json(&serde_json::json!({ "state": state }))
+                    .send()
+                    .await;
+                match response {
+                    Ok(response) if response.status().is_success() => DispatchOutcome::Success,
+                    Ok(response) => {
+                        DispatchOutcome::Failed(format!("device service returned {}", response.status()))
+                    }
+                    Err(err) => DispatchOutcome::Failed(err.to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// No-op [`ActionDispatcher`] for `/v1/rules:test`'s dry-run path: reports
+/// every action as dispatched without calling anything, so users can preview
+/// what a rule *would* do without actuating real devices or publishing real
+/// events.
+pub(crate) struct NoopDispatcher;
+
+#[async_trait]
+impl ActionDispatcher for NoopDispatcher {
+    async fn dispatch(&self, _action: &Action, _idempotency_key: &str) -> DispatchOutcome {
+        DispatchOutcome::Success
+    }
+}
+
+/// Full-jitter exponential backoff, same shape as `device-registry`'s outbox
+/// worker: a delay drawn uniformly from `[0, base * 2^attempt]`, capped at
+/// [`RETRY_MAX_BACKOFF_MS`].
+fn retry_backoff_ms(attempt: u32) -> u64 {
+    let exponent = attempt.min(20);
+    let capped = RETRY_BASE_BACKOFF_MS
+        .saturating_mul(1u64 << exponent)
+        .min(RETRY_MAX_BACKOFF_MS);
+    rand::thread_rng().gen_range(0..=capped.max(1))
+}
+
+/// Dispatches `action` through `dispatcher`, making up to
+/// [`MAX_DISPATCH_ATTEMPTS`] attempts in all with [`retry_backoff_ms`] between tries,
+/// and folds the result into an [`ActionStatus`] for the rule trace.
+pub(crate) async fn dispatch_with_retry(
+    dispatcher: &dyn ActionDispatcher,
+    action: &Action,
+    rule_id: &str,
+    tick: u64,
+    action_index: usize,
+) -> ActionStatus {
+    let key = idempotency_key(rule_id, tick, action_index);
+    let mut last_error = String::new();
+    for attempt in 0..MAX_DISPATCH_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(retry_backoff_ms(attempt))).await;
+        }
+        match dispatcher.dispatch(action, &key).await {
+            DispatchOutcome::Success => return ActionStatus::Executed { at: Utc::now() },
+            DispatchOutcome::Failed(error) => last_error = error,
+        }
+    }
+    ActionStatus::Failed { error: last_error }
+}
@@ -1,19 +1,28 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use axum::body::Body;
+use axum::body::{Body, Bytes};
 use axum::extract::{MatchedPath, Path, Query, State};
-use axum::http::{header, HeaderValue, Request, StatusCode};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
 use axum::middleware::{from_fn, Next};
-use axum::response::{IntoResponse, Response};
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
 use axum::routing::{delete, get, post};
 use axum::{Json, Router};
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use json_patch::Patch;
+use lokan_event::{Event as BusEvent, EventBus, EventTransport};
 use parking_lot::RwLock;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
+use tokio::sync::{broadcast, Semaphore};
+use tokio_stream::wrappers::BroadcastStream;
 use uuid::Uuid;
 
 use common_config::service_port;
@@ -24,10 +33,30 @@ use common_obs::{
 
 use std::time::Instant;
 
+mod dispatch;
+mod repo;
+
+use dispatch::{dispatch_with_retry, ActionDispatcher, LiveDispatcher, NoopDispatcher};
+use repo::{MemoryRepo, PersistedRule, RuleRepo, SqlxRepo};
+
 const SERVICE_NAME: &str = "rule-engine";
 const PORT_ENV: &str = "RULE_ENGINE_PORT";
 const DEFAULT_PORT: u16 = 8002;
 const TICK_INTERVAL_MS: u64 = 500;
+/// Capacity of the in-process event bus `run_event_listener` subscribes to.
+const EVENT_BUS_CAPACITY: usize = 1024;
+/// Upper bound on how many `Trigger::Event` rules a single incoming event
+/// evaluates concurrently, so a burst on a busy subject can't starve the
+/// interval scheduler of CPU time.
+const MAX_EVENT_FANOUT: usize = 16;
+
+/// Content type for an RFC 6902 JSON Patch request body.
+const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+/// Content type for an RFC 7386 JSON Merge Patch request body.
+const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+/// Backlog of the live trace broadcast channel; a slow SSE subscriber drops
+/// the oldest entries rather than blocking `record_trace`.
+const TRACE_STREAM_CAPACITY: usize = 256;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -41,27 +70,133 @@ fn build_time() -> &'static str {
 
 const MAX_TRACE_ENTRIES: usize = 100;
 
+/// Env var naming `RULE_ENGINE_DATABASE_URL` pointing at a durable store for
+/// [`RuleRepo`]; unset falls back to [`MemoryRepo`], matching the old
+/// restart-loses-everything behavior.
+const DATABASE_URL_ENV: &str = "RULE_ENGINE_DATABASE_URL";
+
 #[derive(Clone)]
 struct AppState {
     rules: Arc<RwLock<HashMap<String, RuleInstance>>>,
     traces: Arc<RwLock<HashMap<String, VecDeque<RuleTraceEntry>>>>,
+    /// Reverse index from a `Trigger::Event` rule's subject pattern (e.g.
+    /// `sensors.*`) to the IDs of every rule registered with that pattern,
+    /// so `run_event_listener` doesn't have to scan every rule per event.
+    subject_index: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    /// Last tick observed by `run_scheduler`, exposed to event-triggered
+    /// evaluations so their context's `tick` field matches the interval
+    /// path's.
+    tick: Arc<AtomicU64>,
+    /// Publishes every [`RuleTraceEntry`] as it's recorded, so
+    /// `rule_trace_stream` can subscribe instead of polling `traces`.
+    trace_events: broadcast::Sender<RuleTraceEvent>,
+    /// Durable backing store for rules and traces, so a restart rehydrates
+    /// `rules`/`traces` instead of starting empty. Creates and patches go
+    /// through here before the in-memory maps above are updated; a delete
+    /// removes the in-memory rule first, and traces are written alongside.
+    repo: Arc<dyn RuleRepo>,
+    /// Where a fired rule's actions actually go. `/v1/rules:test` swaps this
+    /// for a [`NoopDispatcher`] per-request instead of overriding it here.
+    dispatcher: Arc<dyn ActionDispatcher>,
 }
 
 impl AppState {
-    fn new() -> Self {
+    fn new(repo: Arc<dyn RuleRepo>, dispatcher: Arc<dyn ActionDispatcher>) -> Self {
+        let (trace_events, _) = broadcast::channel(TRACE_STREAM_CAPACITY);
         Self {
             rules: Arc::new(RwLock::new(HashMap::new())),
             traces: Arc::new(RwLock::new(HashMap::new())),
+            subject_index: Arc::new(RwLock::new(HashMap::new())),
+            tick: Arc::new(AtomicU64::new(0)),
+            trace_events,
+            repo,
+            dispatcher,
+        }
+    }
+
+    /// Loads every persisted rule from `repo` and rebuilds the in-memory
+    /// `rules`/`subject_index`/`traces` maps, recomputing each rule's
+    /// `ScheduleState` relative to the current tick (0 at startup) rather
+    /// than trusting a stale `next_tick` from before the restart.
+    async fn rehydrate(&self) -> Result<(), repo::RuleRepoError> {
+        let persisted = self.repo.load_all().await?;
+        let current_tick = self.tick.load(Ordering::Relaxed);
+        for rule in persisted {
+            let schedule = ScheduleState::new(&rule.definition.trigger, current_tick);
+            self.index_event_trigger(&rule.definition.id, &rule.definition.trigger);
+            let traces = self.repo.traces_for(&rule.definition.id).await?;
+            self.traces
+                .write()
+                .insert(rule.definition.id.clone(), traces.into_iter().collect());
+            self.rules.write().insert(
+                rule.definition.id.clone(),
+                RuleInstance {
+                    definition: rule.definition,
+                    schedule,
+                    version: rule.version,
+                },
+            );
         }
+        Ok(())
     }
 
+    fn index_event_trigger(&self, rule_id: &str, trigger: &Trigger) {
+        if let Trigger::Event { subject } = trigger {
+            self.subject_index
+                .write()
+                .entry(subject.clone())
+                .or_default()
+                .insert(rule_id.to_string());
+        }
+    }
+
+    fn unindex_event_trigger(&self, rule_id: &str, trigger: &Trigger) {
+        if let Trigger::Event { subject } = trigger {
+            let mut index = self.subject_index.write();
+            if let Some(rule_ids) = index.get_mut(subject) {
+                rule_ids.remove(rule_id);
+                if rule_ids.is_empty() {
+                    index.remove(subject);
+                }
+            }
+        }
+    }
+
+    /// Every rule ID whose registered subject pattern matches `subject`,
+    /// via [`subject_matches`] (so `sensors.*` patterns are included).
+    fn rules_for_subject(&self, subject: &str) -> HashSet<String> {
+        self.subject_index
+            .read()
+            .iter()
+            .filter(|(pattern, _)| subject_matches(pattern, subject))
+            .flat_map(|(_, rule_ids)| rule_ids.iter().cloned())
+            .collect()
+    }
+
+    /// Records `entry` in the in-memory `traces` map and broadcasts it, same
+    /// as before this module persisted anything, then write-throughs it to
+    /// `repo` on a spawned task: a slow or down backend shouldn't add
+    /// latency to rule evaluation, and the in-memory copy is already the
+    /// source of truth for `/v1/diag/trace` either way.
     fn record_trace(&self, rule_id: &str, entry: RuleTraceEntry) {
         let mut guard = self.traces.write();
         let deque = guard.entry(rule_id.to_string()).or_default();
         if deque.len() == MAX_TRACE_ENTRIES {
             deque.pop_front();
         }
-        deque.push_back(entry);
+        deque.push_back(entry.clone());
+        drop(guard);
+        let _ = self.trace_events.send(RuleTraceEvent {
+            rule_id: rule_id.to_string(),
+            entry: entry.clone(),
+        });
+        let repo = self.repo.clone();
+        let rule_id = rule_id.to_string();
+        tokio::spawn(async move {
+            if let Err(err) = repo.record_trace(&rule_id, &entry).await {
+                tracing::warn!(rule = %rule_id, error = %err, "failed to persist rule trace");
+            }
+        });
     }
 
     fn traces_for(&self, rule_id: &str) -> Option<Vec<RuleTraceEntry>> {
@@ -84,6 +219,10 @@ impl AppState {
 struct RuleInstance {
     definition: RuleDefinition,
     schedule: ScheduleState,
+    /// Opaque optimistic-concurrency counter, surfaced to callers as an
+    /// `ETag` and required back as `If-Match` on `PATCH`/`DELETE` so
+    /// concurrent writers can't silently clobber one another.
+    version: u64,
 }
 
 #[derive(Clone)]
@@ -110,12 +249,55 @@ enum Trigger {
     Event { subject: String },
 }
 
+/// A condition is a recursive expression tree: the three original
+/// comparators remain leaves, `And`/`Or`/`Not` combine sub-conditions, and
+/// `Between`/`In`/`Matches` add the other leaf shapes real automations need.
+/// Old flat rules (a single leaf variant) still deserialize unchanged since
+/// every new variant is additive under the existing `type` tag.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum Condition {
     Equals { left: ValueRef, right: ValueRef },
     GreaterThan { left: ValueRef, right: ValueRef },
     LessThan { left: ValueRef, right: ValueRef },
+    Between { value: ValueRef, low: ValueRef, high: ValueRef },
+    In { value: ValueRef, set: Vec<ValueRef> },
+    Matches { value: ValueRef, regex: String },
+    And { conditions: Vec<Condition> },
+    Or { conditions: Vec<Condition> },
+    Not { condition: Box<Condition> },
+}
+
+/// Arithmetic operator for [`ValueRef::Expr`], resolved over two operands
+/// coerced to `f64` before comparison in a `Condition`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl ArithOp {
+    fn apply(&self, left: f64, right: f64) -> Option<f64> {
+        match self {
+            ArithOp::Add => Some(left + right),
+            ArithOp::Sub => Some(left - right),
+            ArithOp::Mul => Some(left * right),
+            ArithOp::Div if right != 0.0 => Some(left / right),
+            ArithOp::Div => None,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,6 +305,14 @@ enum Condition {
 enum ValueRef {
     Literal { value: serde_json::Value },
     Context { path: String },
+    /// Arithmetic over two nested `ValueRef`s, resolved recursively to an
+    /// `f64` so e.g. `temperature - offset > 0` can be expressed without a
+    /// dedicated condition variant per operator.
+    Expr {
+        op: ArithOp,
+        left: Box<ValueRef>,
+        right: Box<ValueRef>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -143,6 +333,16 @@ struct RuleTestRequest {
     rule: RuleDefinition,
     #[serde(default)]
     context: serde_json::Map<String, serde_json::Value>,
+    /// Routes actions to a [`NoopDispatcher`] instead of `AppState::dispatcher`
+    /// so callers can preview a rule's full evaluate-then-dispatch pipeline
+    /// without actuating real devices or publishing real events. Defaults to
+    /// `true`; set `false` to exercise the live sinks.
+    #[serde(default = "default_dry_run")]
+    dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize)]
@@ -158,10 +358,14 @@ struct ActionExecution {
     status: ActionStatus,
 }
 
+/// Real outcome of dispatching an [`Action`], as opposed to the old
+/// stamp-only `Executed`/`Skipped`. `Failed` is only reached after
+/// [`dispatch_with_retry`] exhausts its retries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[serde(tag = "status", rename_all = "snake_case")]
 enum ActionStatus {
-    Executed,
+    Executed { at: DateTime<Utc> },
+    Failed { error: String },
     Skipped,
 }
 
@@ -171,6 +375,12 @@ enum RuleEngineError {
     NotFound,
     #[error("invalid request: {0}")]
     InvalidRequest(String),
+    #[error("rule was modified by another writer; refetch and retry with the current version")]
+    Conflict,
+    #[error("unsupported content type: {0}")]
+    UnsupportedMediaType(String),
+    #[error("storage backend error: {0}")]
+    Backend(String),
 }
 
 impl axum::response::IntoResponse for RuleEngineError {
@@ -178,6 +388,9 @@ impl axum::response::IntoResponse for RuleEngineError {
         let status = match self {
             RuleEngineError::NotFound => StatusCode::NOT_FOUND,
             RuleEngineError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            RuleEngineError::Conflict => StatusCode::PRECONDITION_FAILED,
+            RuleEngineError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            RuleEngineError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
         (
             status,
@@ -194,12 +407,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let port = service_port(PORT_ENV, DEFAULT_PORT);
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
 
-    let state = AppState::new();
+    let repo: Arc<dyn RuleRepo> = match std::env::var(DATABASE_URL_ENV) {
+        Ok(url) => Arc::new(SqlxRepo::connect(&url).await.map_err(
+            |err| -> Box<dyn std::error::Error> { Box::new(err) },
+        )?),
+        Err(_) => Arc::new(MemoryRepo::new()),
+    };
+
+    let event_transport: Arc<dyn EventTransport> = Arc::new(EventBus::new(EVENT_BUS_CAPACITY));
+    let dispatcher: Arc<dyn ActionDispatcher> =
+        Arc::new(LiveDispatcher::new(event_transport.clone()));
+
+    let state = AppState::new(repo, dispatcher);
+    state
+        .rehydrate()
+        .await
+        .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
     let scheduler_state = state.clone();
     tokio::spawn(async move {
         run_scheduler(scheduler_state).await;
     });
 
+    let listener_state = state.clone();
+    let listener_transport = event_transport.clone();
+    tokio::spawn(async move {
+        run_event_listener(listener_state, listener_transport).await;
+    });
+
     tracing::info!(
         event = "service_start",
         service = SERVICE_NAME,
@@ -212,9 +446,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let app = Router::new()
         .route("/v1/rules", get(list_rules).post(create_rule))
-        .route("/v1/rules/:id", delete(delete_rule))
+        .route(
+            "/v1/rules/:id",
+            get(get_rule).patch(patch_rule).delete(delete_rule),
+        )
         .route("/v1/rules:test", post(test_rule))
         .route("/v1/diag/trace", get(rule_trace))
+        .route("/v1/diag/trace/stream", get(rule_trace_stream))
         .route("/metrics", get(metrics))
         .with_state(state)
         .merge(health_router(SERVICE_NAME))
@@ -239,11 +477,20 @@ async fn list_rules(State(state): State<AppState>) -> Json<Vec<RuleDefinition>>
 async fn create_rule(
     State(state): State<AppState>,
     Json(mut payload): Json<RuleDefinition>,
-) -> Json<RuleDefinition> {
+) -> Result<impl IntoResponse, RuleEngineError> {
     if payload.id.is_empty() {
         payload.id = Uuid::new_v4().to_string();
     }
+    state
+        .repo
+        .upsert(&PersistedRule {
+            definition: payload.clone(),
+            version: 0,
+        })
+        .await
+        .map_err(|err| RuleEngineError::Backend(err.to_string()))?;
     state.init_trace_slot(&payload.id);
+    state.index_event_trigger(&payload.id, &payload.trigger);
     let mut guard = state.rules.write();
     let ticks = guard
         .values()
@@ -255,27 +502,174 @@ async fn create_rule(
         RuleInstance {
             schedule: ScheduleState::new(&payload.trigger, ticks),
             definition: payload.clone(),
+            version: 0,
         },
     );
-    Json(payload)
+    drop(guard);
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, version_etag(0))],
+        Json(payload),
+    ))
+}
+
+async fn get_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, RuleEngineError> {
+    let guard = state.rules.read();
+    let instance = guard.get(&id).ok_or(RuleEngineError::NotFound)?;
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, version_etag(instance.version))],
+        Json(instance.definition.clone()),
+    ))
+}
+
+async fn patch_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, RuleEngineError> {
+    let if_match = if_match_version(&headers).ok_or(RuleEngineError::Conflict)?;
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let guard = state.rules.read();
+    let existing = guard.get(&id).ok_or(RuleEngineError::NotFound)?;
+    if existing.version != if_match {
+        return Err(RuleEngineError::Conflict);
+    }
+    let existing_trigger = existing.definition.trigger.clone();
+    let existing_schedule = existing.schedule.clone();
+    let existing_version = existing.version;
+
+    let mut document = serde_json::to_value(&existing.definition)
+        .map_err(|err| RuleEngineError::InvalidRequest(err.to_string()))?;
+    drop(guard);
+
+    match content_type.as_str() {
+        JSON_PATCH_CONTENT_TYPE => {
+            let patch: Patch = serde_json::from_slice(&body).map_err(|err| {
+                RuleEngineError::InvalidRequest(format!("invalid json patch: {err}"))
+            })?;
+            json_patch::patch(&mut document, &patch).map_err(|err| {
+                RuleEngineError::InvalidRequest(format!("patch application failed: {err}"))
+            })?;
+        }
+        MERGE_PATCH_CONTENT_TYPE => {
+            let merge: serde_json::Value = serde_json::from_slice(&body).map_err(|err| {
+                RuleEngineError::InvalidRequest(format!("invalid merge patch: {err}"))
+            })?;
+            json_patch::merge(&mut document, &merge);
+        }
+        other => return Err(RuleEngineError::UnsupportedMediaType(other.to_string())),
+    }
+
+    let mut patched: RuleDefinition = serde_json::from_value(document)
+        .map_err(|err| RuleEngineError::InvalidRequest(err.to_string()))?;
+    patched.id = id.clone();
+
+    let trigger_changed =
+        serde_json::to_value(&existing_trigger).ok() != serde_json::to_value(&patched.trigger).ok();
+    let schedule = if trigger_changed {
+        ScheduleState::new(&patched.trigger, state.tick.load(Ordering::Relaxed))
+    } else {
+        existing_schedule
+    };
+    let new_version = existing_version + 1;
+
+    state
+        .repo
+        .upsert(&PersistedRule {
+            definition: patched.clone(),
+            version: new_version,
+        })
+        .await
+        .map_err(|err| RuleEngineError::Backend(err.to_string()))?;
+
+    let mut guard = state.rules.write();
+    match guard.get(&id) {
+        Some(current) if current.version == existing_version => {
+            guard.insert(
+                id.clone(),
+                RuleInstance {
+                    definition: patched.clone(),
+                    schedule,
+                    version: new_version,
+                },
+            );
+        }
+        _ => return Err(RuleEngineError::Conflict),
+    }
+    drop(guard);
+
+    if trigger_changed {
+        state.unindex_event_trigger(&id, &existing_trigger);
+        state.index_event_trigger(&id, &patched.trigger);
+    }
+
+    Ok((
+        StatusCode::OK,
+        [(header::ETAG, version_etag(new_version))],
+        Json(patched),
+    ))
 }
 
 async fn delete_rule(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, RuleEngineError> {
+    let if_match = if_match_version(&headers).ok_or(RuleEngineError::Conflict)?;
     let mut guard = state.rules.write();
-    if guard.remove(&id).is_some() {
-        state.drop_trace_slot(&id);
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Err(RuleEngineError::NotFound)
+    let instance = guard.get(&id).ok_or(RuleEngineError::NotFound)?;
+    if instance.version != if_match {
+        return Err(RuleEngineError::Conflict);
     }
+    let instance = guard.remove(&id).expect("presence checked above");
+    drop(guard);
+    state
+        .repo
+        .remove(&id)
+        .await
+        .map_err(|err| RuleEngineError::Backend(err.to_string()))?;
+    state.drop_trace_slot(&id);
+    state.unindex_event_trigger(&id, &instance.definition.trigger);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Renders an `ETag` header value from a rule's opaque version counter.
+fn version_etag(version: u64) -> HeaderValue {
+    HeaderValue::from_str(&version.to_string()).unwrap_or_else(|_| HeaderValue::from_static("0"))
+}
+
+/// Parses the caller's `If-Match: <version>` header. Callers treat a
+/// missing or unparsable header the same as a stale version, since an
+/// update without one can't prove it observed the current state.
+fn if_match_version(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::IF_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
 }
 
-async fn test_rule(Json(request): Json<RuleTestRequest>) -> Json<RuleTestResponse> {
+async fn test_rule(
+    State(state): State<AppState>,
+    Json(request): Json<RuleTestRequest>,
+) -> Json<RuleTestResponse> {
     let now = Utc::now();
-    let evaluation = evaluate_rule(&request.rule, &request.context, now);
+    let noop = Arc::new(NoopDispatcher);
+    let dispatcher: &dyn ActionDispatcher = if request.dry_run {
+        noop.as_ref()
+    } else {
+        state.dispatcher.as_ref()
+    };
+    let evaluation = evaluate_rule(&request.rule, &request.context, now, dispatcher, 0).await;
     Json(RuleTestResponse {
         fired: evaluation.fired,
         trace: evaluation.trace,
@@ -289,7 +683,7 @@ struct EvaluationResult {
     actions: Vec<ActionExecution>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct RuleTraceEntry {
     timestamp: DateTime<Utc>,
     fired: bool,
@@ -298,10 +692,20 @@ struct RuleTraceEntry {
     actions: Vec<ActionExecution>,
 }
 
-fn evaluate_rule(
+/// A [`RuleTraceEntry`] tagged with the rule it belongs to, broadcast on
+/// [`AppState::trace_events`] so `rule_trace_stream` can filter by `rule_id`.
+#[derive(Debug, Clone)]
+struct RuleTraceEvent {
+    rule_id: String,
+    entry: RuleTraceEntry,
+}
+
+async fn evaluate_rule(
     rule: &RuleDefinition,
     context: &serde_json::Map<String, serde_json::Value>,
     now: DateTime<Utc>,
+    dispatcher: &dyn ActionDispatcher,
+    tick: u64,
 ) -> EvaluationResult {
     let mut trace = Vec::new();
     trace.push(format!("evaluating rule {}", rule.id));
@@ -320,10 +724,11 @@ fn evaluate_rule(
 
     let mut actions = Vec::new();
     if conditions_met {
-        for action in &rule.actions {
+        for (index, action) in rule.actions.iter().enumerate() {
+            let status = dispatch_with_retry(dispatcher, action, &rule.id, tick, index).await;
             actions.push(ActionExecution {
                 action: action.clone(),
-                status: ActionStatus::Executed,
+                status,
             });
         }
         trace.push("conditions satisfied".to_string());
@@ -370,10 +775,134 @@ impl Condition {
             Condition::LessThan { left, right } => {
                 compare_numeric("less_than", left, right, context, now, |l, r| l < r)
             }
+            Condition::Between { value, low, high } => {
+                let v = value.resolve(context, now);
+                let lo = low.resolve(context, now);
+                let hi = high.resolve(context, now);
+                match (v.as_f64(), lo.as_f64(), hi.as_f64()) {
+                    (Some(v), Some(lo), Some(hi)) if v >= lo && v <= hi => {
+                        ConditionState::Matched(format!("between matched: {v} in [{lo}, {hi}]"))
+                    }
+                    (Some(v), Some(lo), Some(hi)) => {
+                        ConditionState::Failed(format!("between failed: {v} not in [{lo}, {hi}]"))
+                    }
+                    _ => ConditionState::Failed(format!(
+                        "between failed: unable to coerce {v:?}, {lo:?} or {hi:?} to numbers"
+                    )),
+                }
+            }
+            Condition::In { value, set } => {
+                let resolved = value.resolve(context, now);
+                let matched = set.iter().any(|item| item.resolve(context, now) == resolved);
+                if matched {
+                    ConditionState::Matched(format!("in matched: {resolved:?}"))
+                } else {
+                    ConditionState::Failed(format!("in failed: {resolved:?} not in set"))
+                }
+            }
+            Condition::Matches { value, regex } => {
+                let resolved = value.resolve(context, now);
+                let text = resolved.as_str().map(str::to_string).unwrap_or_else(|| resolved.to_string());
+                match Regex::new(regex) {
+                    Ok(re) if re.is_match(&text) => {
+                        ConditionState::Matched(format!("matches matched: {text:?} ~= {regex:?}"))
+                    }
+                    Ok(_) => {
+                        ConditionState::Failed(format!("matches failed: {text:?} !~ {regex:?}"))
+                    }
+                    Err(err) => ConditionState::Failed(format!(
+                        "matches failed: invalid regex {regex:?}: {err}"
+                    )),
+                }
+            }
+            Condition::Not { condition } => match condition.evaluate(context, now) {
+                ConditionState::Matched(inner) => {
+                    ConditionState::Failed(format!("not failed: {inner}"))
+                }
+                ConditionState::Failed(inner) => {
+                    ConditionState::Matched(format!("not matched: {inner}"))
+                }
+            },
+            Condition::And { conditions } => {
+                evaluate_combinator("and", conditions, context, now, |results| {
+                    results.iter().all(|matched| *matched)
+                })
+            }
+            Condition::Or { conditions } => {
+                evaluate_combinator("or", conditions, context, now, |results| {
+                    results.iter().any(|matched| *matched)
+                })
+            }
+        }
+    }
+
+    /// Short symbolic label for this condition, used to render each child's
+    /// outcome inside an `And`/`Or` trace entry (e.g. `occupancy==true`)
+    /// instead of the verbose leaf message `evaluate` produces on its own.
+    fn describe(&self) -> String {
+        match self {
+            Condition::Equals { left, right } => format!("{}=={}", left.describe(), right.describe()),
+            Condition::GreaterThan { left, right } => {
+                format!("{}>{}", left.describe(), right.describe())
+            }
+            Condition::LessThan { left, right } => {
+                format!("{}<{}", left.describe(), right.describe())
+            }
+            Condition::Between { value, low, high } => {
+                format!("{} between [{}, {}]", value.describe(), low.describe(), high.describe())
+            }
+            Condition::In { value, set } => format!(
+                "{} in [{}]",
+                value.describe(),
+                set.iter().map(ValueRef::describe).collect::<Vec<_>>().join(", ")
+            ),
+            Condition::Matches { value, regex } => format!("{} ~= {regex:?}", value.describe()),
+            Condition::Not { condition } => format!("not({})", condition.describe()),
+            Condition::And { conditions } => format!(
+                "and({})",
+                conditions.iter().map(Condition::describe).collect::<Vec<_>>().join(", ")
+            ),
+            Condition::Or { conditions } => format!(
+                "or({})",
+                conditions.iter().map(Condition::describe).collect::<Vec<_>>().join(", ")
+            ),
         }
     }
 }
 
+/// Shared implementation of `Condition::And`/`Condition::Or`: evaluates every
+/// child (no short-circuit, so the rendered trace covers the full sub-tree)
+/// and folds their outcomes with `combine`.
+fn evaluate_combinator(
+    label: &str,
+    conditions: &[Condition],
+    context: &serde_json::Map<String, serde_json::Value>,
+    now: DateTime<Utc>,
+    combine: impl Fn(&[bool]) -> bool,
+) -> ConditionState {
+    let outcomes: Vec<bool> = conditions
+        .iter()
+        .map(|condition| matches!(condition.evaluate(context, now), ConditionState::Matched(_)))
+        .collect();
+    let rendered = conditions
+        .iter()
+        .zip(&outcomes)
+        .map(|(condition, matched)| {
+            format!(
+                "{} ({})",
+                condition.describe(),
+                if *matched { "matched" } else { "failed" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if combine(&outcomes) {
+        ConditionState::Matched(format!("{label} matched: [{rendered}]"))
+    } else {
+        ConditionState::Failed(format!("{label} failed: [{rendered}]"))
+    }
+}
+
 fn compare_numeric<F: Fn(f64, f64) -> bool>(
     label: &str,
     left: &ValueRef,
@@ -409,6 +938,30 @@ impl ValueRef {
                     _ => serde_json::Value::Null,
                 })
             }
+            ValueRef::Expr { op, left, right } => {
+                let left = left.resolve(context, now).as_f64();
+                let right = right.resolve(context, now).as_f64();
+                match (left, right) {
+                    (Some(left), Some(right)) => op
+                        .apply(left, right)
+                        .and_then(serde_json::Number::from_f64)
+                        .map(serde_json::Value::Number)
+                        .unwrap_or(serde_json::Value::Null),
+                    _ => serde_json::Value::Null,
+                }
+            }
+        }
+    }
+
+    /// Short symbolic label used by [`Condition::describe`], e.g. a context
+    /// path renders as its dotted path and a literal as its JSON form.
+    fn describe(&self) -> String {
+        match self {
+            ValueRef::Literal { value } => value.to_string(),
+            ValueRef::Context { path } => path.clone(),
+            ValueRef::Expr { op, left, right } => {
+                format!("({} {} {})", left.describe(), op.symbol(), right.describe())
+            }
         }
     }
 }
@@ -444,38 +997,170 @@ async fn run_scheduler(state: AppState) {
     loop {
         interval.tick().await;
         tick = tick.wrapping_add(1);
+        state.tick.store(tick, Ordering::Relaxed);
         let now = Utc::now();
-        let mut guard = state.rules.write();
-        for instance in guard.values_mut() {
-            if instance.schedule.should_fire(tick) {
-                let mut context = serde_json::Map::new();
-                context.insert(
-                    "now".to_string(),
-                    serde_json::Value::String(now.to_rfc3339()),
-                );
-                context.insert("tick".to_string(), serde_json::Value::Number(tick.into()));
-                let started = Instant::now();
-                let result = evaluate_rule(&instance.definition, &context, now);
-                let duration = started.elapsed().as_secs_f64() * 1_000.0;
-                let trace_entry = RuleTraceEntry {
-                    timestamp: now,
-                    fired: result.fired,
-                    duration_ms: duration,
-                    trace: result.trace.clone(),
-                    actions: result.actions.clone(),
-                };
-                if result.fired {
-                    tracing::info!(rule = %instance.definition.id, trace = ?result.trace, "rule fired");
-                } else {
-                    tracing::debug!(rule = %instance.definition.id, trace = ?result.trace, "rule skipped");
-                }
-                state.record_trace(&instance.definition.id, trace_entry);
+
+        // Snapshot the rules due to fire this tick and drop the lock before
+        // evaluating them, since evaluation now dispatches actions over the
+        // network and a `parking_lot::RwLock` guard must never span an
+        // `.await` point.
+        let due: Vec<RuleDefinition> = {
+            let guard = state.rules.read();
+            guard
+                .values()
+                .filter(|instance| instance.schedule.should_fire(tick))
+                .map(|instance| instance.definition.clone())
+                .collect()
+        };
+
+        for definition in due {
+            let mut context = serde_json::Map::new();
+            context.insert(
+                "now".to_string(),
+                serde_json::Value::String(now.to_rfc3339()),
+            );
+            context.insert("tick".to_string(), serde_json::Value::Number(tick.into()));
+            let started = Instant::now();
+            let result =
+                evaluate_rule(&definition, &context, now, state.dispatcher.as_ref(), tick).await;
+            let duration = started.elapsed().as_secs_f64() * 1_000.0;
+            let trace_entry = RuleTraceEntry {
+                timestamp: now,
+                fired: result.fired,
+                duration_ms: duration,
+                trace: result.trace.clone(),
+                actions: result.actions.clone(),
+            };
+            if result.fired {
+                tracing::info!(rule = %definition.id, trace = ?result.trace, "rule fired");
+            } else {
+                tracing::debug!(rule = %definition.id, trace = ?result.trace, "rule skipped");
+            }
+            state.record_trace(&definition.id, trace_entry);
+            if let Some(instance) = state.rules.write().get_mut(&definition.id) {
                 instance.schedule.advance();
             }
         }
     }
 }
 
+/// NATS-style single-level wildcard match between a rule's registered
+/// subject `pattern` (e.g. `sensors.*`) and an incoming event's `subject`:
+/// a `*` token matches exactly one `.`-separated token, every other token
+/// must match literally, and both must have the same number of tokens.
+fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut pattern_tokens = pattern.split('.');
+    let mut subject_tokens = subject.split('.');
+    loop {
+        match (pattern_tokens.next(), subject_tokens.next()) {
+            (Some(p), Some(s)) => {
+                if p != "*" && p != s {
+                    return false;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Turns an event's JSON payload into an evaluation context: an object
+/// payload is used as-is, so `Context { path: "temperature" }` resolves
+/// fields directly; any other JSON shape is wrapped under a `payload` key.
+fn event_context(payload: &serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    match payload {
+        serde_json::Value::Object(map) => map.clone(),
+        other => {
+            let mut map = serde_json::Map::new();
+            map.insert("payload".to_string(), other.clone());
+            map
+        }
+    }
+}
+
+/// Event-driven complement to [`run_scheduler`]: subscribes to `transport`
+/// and, for each event, evaluates every `Trigger::Event` rule whose subject
+/// pattern matches (via [`subject_matches`] and [`AppState::rules_for_subject`]),
+/// same as the interval path but keyed off an incoming event instead of a
+/// tick. Matching rules for one event are evaluated concurrently, bounded
+/// by [`MAX_EVENT_FANOUT`] so a burst on a busy subject can't starve the
+/// scheduler loop of CPU time.
+async fn run_event_listener(state: AppState, transport: Arc<dyn EventTransport>) {
+    let semaphore = Arc::new(Semaphore::new(MAX_EVENT_FANOUT));
+    let mut events = transport.subscribe().await;
+    while let Some(event) = events.next().await {
+        let rule_ids = state.rules_for_subject(&event.topic);
+        if rule_ids.is_empty() {
+            continue;
+        }
+
+        let mut tasks = Vec::with_capacity(rule_ids.len());
+        for rule_id in rule_ids {
+            let state = state.clone();
+            let event = event.clone();
+            let semaphore = semaphore.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .expect("event fan-out semaphore should not be closed");
+                evaluate_event_rule(&state, &rule_id, &event).await;
+            }));
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Evaluates a single `Trigger::Event` rule against `event` and records a
+/// [`RuleTraceEntry`], exactly as [`run_scheduler`] does for interval rules.
+async fn evaluate_event_rule(state: &AppState, rule_id: &str, event: &BusEvent) {
+    let definition = match state.rules.read().get(rule_id) {
+        Some(instance) => instance.definition.clone(),
+        None => return,
+    };
+    let Trigger::Event { subject } = &definition.trigger else {
+        return;
+    };
+    if !subject_matches(subject, &event.topic) {
+        return;
+    }
+
+    let now = Utc::now();
+    let mut context = event_context(&event.payload);
+    context.insert(
+        "now".to_string(),
+        serde_json::Value::String(now.to_rfc3339()),
+    );
+    context.insert(
+        "tick".to_string(),
+        serde_json::Value::Number(state.tick.load(Ordering::Relaxed).into()),
+    );
+    context.insert(
+        "subject".to_string(),
+        serde_json::Value::String(event.topic.clone()),
+    );
+
+    let started = Instant::now();
+    let tick = state.tick.load(Ordering::Relaxed);
+    let result = evaluate_rule(&definition, &context, now, state.dispatcher.as_ref(), tick).await;
+    let duration = started.elapsed().as_secs_f64() * 1_000.0;
+    let trace_entry = RuleTraceEntry {
+        timestamp: now,
+        fired: result.fired,
+        duration_ms: duration,
+        trace: result.trace.clone(),
+        actions: result.actions.clone(),
+    };
+    if result.fired {
+        tracing::info!(rule = %definition.id, subject = %event.topic, trace = ?result.trace, "rule fired");
+    } else {
+        tracing::debug!(rule = %definition.id, subject = %event.topic, trace = ?result.trace, "rule skipped");
+    }
+    state.record_trace(&definition.id, trace_entry);
+}
+
 impl ScheduleState {
     fn new(trigger: &Trigger, current_tick: u64) -> Self {
         match trigger {
@@ -574,12 +1259,65 @@ async fn rule_trace(
     }))
 }
 
+/// Streams [`RuleTraceEntry`] values for a single rule as they're produced,
+/// so a UI doesn't have to poll [`rule_trace`]. Replays the existing
+/// `traces` history first (oldest to newest) so a late subscriber sees
+/// recent context, then forwards everything published on
+/// [`AppState::trace_events`] afterward.
+async fn rule_trace_stream(
+    State(state): State<AppState>,
+    Query(params): Query<TraceQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, RuleEngineError> {
+    if params.rule_id.trim().is_empty() {
+        return Err(RuleEngineError::InvalidRequest(
+            "rule_id query parameter is required".to_string(),
+        ));
+    }
+    if !state.rules.read().contains_key(params.rule_id.as_str()) {
+        return Err(RuleEngineError::NotFound);
+    }
+
+    let rule_id = params.rule_id;
+    let history: Vec<RuleTraceEntry> = state
+        .traces_for(&rule_id)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .collect();
+    let replay = futures::stream::iter(
+        history
+            .into_iter()
+            .filter_map(|entry| trace_sse_event(&entry)),
+    );
+
+    let live_rule_id = rule_id.clone();
+    let live = BroadcastStream::new(state.trace_events.subscribe()).filter_map(move |event| {
+        let live_rule_id = live_rule_id.clone();
+        async move {
+            match event {
+                Ok(event) if event.rule_id == live_rule_id => trace_sse_event(&event.entry),
+                _ => None,
+            }
+        }
+    });
+
+    Ok(Sse::new(replay.chain(live)).keep_alive(KeepAlive::new()))
+}
+
+/// Serializes a trace entry into an SSE `data:` frame, dropping it (rather
+/// than failing the whole stream) if it somehow can't be encoded as JSON.
+fn trace_sse_event(entry: &RuleTraceEntry) -> Option<Result<Event, Infallible>> {
+    serde_json::to_string(entry)
+        .ok()
+        .map(|payload| Ok(Event::default().data(payload)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn evaluate_rule_executes_when_conditions_match() {
+    #[tokio::test]
+    async fn evaluate_rule_executes_when_conditions_match() {
         let rule = RuleDefinition {
             id: "rule-1".to_string(),
             name: None,
@@ -600,12 +1338,15 @@ mod tests {
 
         let mut context = serde_json::Map::new();
         context.insert("temperature".to_string(), serde_json::json!(72));
-        let result = evaluate_rule(&rule, &context, Utc::now());
+        let result = evaluate_rule(&rule, &context, Utc::now(), &NoopDispatcher, 0).await;
         assert!(result.fired);
-        assert!(matches!(result.actions[0].status, ActionStatus::Executed));
+        assert!(matches!(
+            result.actions[0].status,
+            ActionStatus::Executed { .. }
+        ));
 
         context.insert("temperature".to_string(), serde_json::json!(68));
-        let result = evaluate_rule(&rule, &context, Utc::now());
+        let result = evaluate_rule(&rule, &context, Utc::now(), &NoopDispatcher, 0).await;
         assert!(!result.fired);
         assert!(matches!(result.actions[0].status, ActionStatus::Skipped));
     }
@@ -620,4 +1361,104 @@ mod tests {
         schedule.advance();
         assert!(schedule.next_tick > next_before);
     }
+
+    #[test]
+    fn subject_matches_single_level_wildcard() {
+        assert!(subject_matches("sensors.*", "sensors.temp"));
+        assert!(!subject_matches("sensors.*", "sensors.temp.extra"));
+        assert!(!subject_matches("sensors.*", "actuators.temp"));
+        assert!(subject_matches("sensors.temp", "sensors.temp"));
+        assert!(!subject_matches("sensors.temp", "sensors.humidity"));
+    }
+
+    #[test]
+    fn subject_index_tracks_event_rules_across_create_and_delete() {
+        let state = AppState::new(Arc::new(MemoryRepo::new()), Arc::new(NoopDispatcher));
+        let trigger = Trigger::Event {
+            subject: "sensors.*".to_string(),
+        };
+        state.index_event_trigger("rule-1", &trigger);
+        assert_eq!(state.rules_for_subject("sensors.temp").len(), 1);
+
+        state.unindex_event_trigger("rule-1", &trigger);
+        assert!(state.rules_for_subject("sensors.temp").is_empty());
+    }
+
+    #[tokio::test]
+    async fn record_trace_publishes_to_the_trace_event_channel() {
+        let state = AppState::new(Arc::new(MemoryRepo::new()), Arc::new(NoopDispatcher));
+        let mut rx = state.trace_events.subscribe();
+        let entry = RuleTraceEntry {
+            timestamp: Utc::now(),
+            fired: true,
+            duration_ms: 1.0,
+            trace: vec!["ok".to_string()],
+            actions: vec![],
+        };
+        state.record_trace("rule-1", entry.clone());
+
+        let received = rx.try_recv().expect("trace event should be published");
+        assert_eq!(received.rule_id, "rule-1");
+        assert_eq!(received.entry.fired, entry.fired);
+    }
+
+    fn sample_rule(id: &str) -> RuleDefinition {
+        RuleDefinition {
+            id: id.to_string(),
+            name: Some("test rule".to_string()),
+            trigger: Trigger::Interval { seconds: 5 },
+            conditions: vec![],
+            actions: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_patch_updates_a_field_and_bumps_the_version() {
+        let state = AppState::new(Arc::new(MemoryRepo::new()), Arc::new(NoopDispatcher));
+        let created = create_rule(State(state.clone()), Json(sample_rule(""))).await;
+        let created = created.into_response();
+        let id = state.rules.read().keys().next().unwrap().clone();
+        assert_eq!(created.status(), StatusCode::OK);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_static("0"));
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(MERGE_PATCH_CONTENT_TYPE),
+        );
+        let body = Bytes::from_static(br#"{"name":"renamed"}"#);
+        let response = patch_rule(State(state.clone()), Path(id.clone()), headers, body)
+            .await
+            .expect("patch should apply")
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::ETAG).unwrap(),
+            HeaderValue::from_static("1")
+        );
+
+        let guard = state.rules.read();
+        let instance = guard.get(&id).unwrap();
+        assert_eq!(instance.definition.name.as_deref(), Some("renamed"));
+        assert_eq!(instance.version, 1);
+    }
+
+    #[tokio::test]
+    async fn patch_with_stale_if_match_is_rejected_with_precondition_failed() {
+        let state = AppState::new(Arc::new(MemoryRepo::new()), Arc::new(NoopDispatcher));
+        create_rule(State(state.clone()), Json(sample_rule(""))).await;
+        let id = state.rules.read().keys().next().unwrap().clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_static("99"));
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(MERGE_PATCH_CONTENT_TYPE),
+        );
+        let body = Bytes::from_static(br#"{"name":"renamed"}"#);
+        let err = patch_rule(State(state), Path(id), headers, body)
+            .await
+            .expect_err("stale version should be rejected");
+        assert!(matches!(err, RuleEngineError::Conflict));
+    }
 }
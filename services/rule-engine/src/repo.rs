@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use sqlx::{AnyPool, Row};
+
+use crate::{RuleDefinition, RuleTraceEntry};
+
+const MAX_TRACE_ENTRIES: i64 = 100;
+
+/// Error type returned by a [`RuleRepo`] implementation, independent of which
+/// backend (in-memory, sqlx-backed SQL) is actually in use.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RuleRepoError {
+    #[error("rule not found")]
+    NotFound,
+    #[error("storage backend error: {0}")]
+    Backend(String),
+}
+
+/// A rule as rehydrated from storage: its definition plus the opaque
+/// optimistic-concurrency counter surfaced as an `ETag` elsewhere in this
+/// service.
+#[derive(Debug, Clone)]
+pub(crate) struct PersistedRule {
+    pub(crate) definition: RuleDefinition,
+    pub(crate) version: u64,
+}
+
+/// Storage abstraction for rule definitions and their trace history, so
+/// `AppState` survives a process restart instead of losing every rule. The
+/// in-memory maps `AppState` keeps for scheduling and the subject index stay
+/// as-is; they're rebuilt from [`RuleRepo::load_all`] on startup.
+#[async_trait]
+pub(crate) trait RuleRepo: Send + Sync {
+    async fn load_all(&self) -> Result<Vec<PersistedRule>, RuleRepoError>;
+    async fn upsert(&self, rule: &PersistedRule) -> Result<(), RuleRepoError>;
+    async fn remove(&self, id: &str) -> Result<(), RuleRepoError>;
+    /// Appends a trace entry for `rule_id`, pruning down to the same
+    /// [`crate::MAX_TRACE_ENTRIES`] cap `AppState::record_trace` enforces
+    /// in-memory.
+    async fn record_trace(
+        &self,
+        rule_id: &str,
+        entry: &RuleTraceEntry,
+    ) -> Result<(), RuleRepoError>;
+    async fn traces_for(&self, rule_id: &str) -> Result<Vec<RuleTraceEntry>, RuleRepoError>;
+}
+
+/// Default [`RuleRepo`]: rules and traces live only for the life of the
+/// process, exactly as `AppState` behaved before this module existed. Used
+/// whenever `RULE_ENGINE_DATABASE_URL` isn't set.
+pub(crate) struct MemoryRepo {
+    rules: parking_lot::RwLock<std::collections::HashMap<String, PersistedRule>>,
+    traces: parking_lot::RwLock<
+        std::collections::HashMap<String, std::collections::VecDeque<RuleTraceEntry>>,
+    >,
+}
+
+impl MemoryRepo {
+    pub(crate) fn new() -> Self {
+        Self {
+            rules: parking_lot::RwLock::new(std::collections::HashMap::new()),
+            traces: parking_lot::RwLock::new(std::collections::HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl RuleRepo for MemoryRepo {
+    async fn load_all(&self) -> Result<Vec<PersistedRule>, RuleRepoError> {
+        Ok(self.rules.read().values().cloned().collect())
+    }
+
+    async fn upsert(&self, rule: &PersistedRule) -> Result<(), RuleRepoError> {
+        self.rules
+            .write()
+            .insert(rule.definition.id.clone(), rule.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), RuleRepoError> {
+        self.rules.write().remove(id);
+        self.traces.write().remove(id);
+        Ok(())
+    }
+
+    async fn record_trace(
+        &self,
+        rule_id: &str,
+        entry: &RuleTraceEntry,
+    ) -> Result<(), RuleRepoError> {
+        let mut guard = self.traces.write();
+        let deque = guard.entry(rule_id.to_string()).or_default();
+        if deque.len() as i64 == MAX_TRACE_ENTRIES {
+            deque.pop_front();
+        }
+        deque.push_back(entry.clone());
+        Ok(())
+    }
+
+    async fn traces_for(&self, rule_id: &str) -> Result<Vec<RuleTraceEntry>, RuleRepoError> {
+        Ok(self
+            .traces
+            .read()
+            .get(rule_id)
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+/// [`RuleRepo`] backed by `sqlx::AnyPool`, so `RULE_ENGINE_DATABASE_URL`
+/// selects sqlite or postgres purely via its connection string scheme, the
+/// same way `device-registry`'s `DbPool` does.
+pub(crate) struct SqlxRepo {
+    pool: AnyPool,
+}
+
+impl SqlxRepo {
+    pub(crate) async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
+        let pool = AnyPool::connect(database_url).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS rules (\
+                id TEXT PRIMARY KEY, \
+                definition TEXT NOT NULL, \
+                version INTEGER NOT NULL\
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        // Only sqlite auto-assigns an `INTEGER PRIMARY KEY`; postgres needs
+        // a SERIAL column for the inserts in `record_trace` to get a `seq`.
+        let seq_column = if database_url.starts_with("postgres") {
+            "seq SERIAL PRIMARY KEY"
+        } else {
+            "seq INTEGER PRIMARY KEY"
+        };
+        let create_traces = format!(
+            "CREATE TABLE IF NOT EXISTS rule_traces (\
+                {seq_column}, \
+                rule_id TEXT NOT NULL, \
+                entry TEXT NOT NULL\
+            )"
+        );
+        sqlx::query(&create_traces)
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl RuleRepo for SqlxRepo {
+    async fn load_all(&self) -> Result<Vec<PersistedRule>, RuleRepoError> {
+        let rows = sqlx::query("SELECT definition, version FROM rules")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        rows.into_iter()
+            .map(|row| {
+                let raw: String = row.get("definition");
+                let version: i64 = row.get("version");
+                serde_json::from_str(&raw)
+                    .map(|definition| PersistedRule {
+                        definition,
+                        version: version as u64,
+                    })
+                    .map_err(|err| RuleRepoError::Backend(err.to_string()))
+            })
+            .collect()
+    }
+
+    async fn upsert(&self, rule: &PersistedRule) -> Result<(), RuleRepoError> {
+        let raw = serde_json::to_string(&rule.definition)
+            .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        sqlx::query(
+            "INSERT INTO rules (id, definition, version) VALUES (?, ?, ?) \
+             ON CONFLICT(id) DO UPDATE SET definition = excluded.definition, version = excluded.version",
+        )
+        .bind(&rule.definition.id)
+        .bind(raw)
+        .bind(rule.version as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), RuleRepoError> {
+        sqlx::query("DELETE FROM rules WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        sqlx::query("DELETE FROM rule_traces WHERE rule_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn record_trace(
+        &self,
+        rule_id: &str,
+        entry: &RuleTraceEntry,
+    ) -> Result<(), RuleRepoError> {
+        let raw =
+            serde_json::to_string(entry).map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        sqlx::query("INSERT INTO rule_traces (rule_id, entry) VALUES (?, ?)")
+            .bind(rule_id)
+            .bind(raw)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        sqlx::query(
+            "DELETE FROM rule_traces WHERE rule_id = ? AND seq NOT IN \
+             (SELECT seq FROM rule_traces WHERE rule_id = ? ORDER BY seq DESC LIMIT ?)",
+        )
+        .bind(rule_id)
+        .bind(rule_id)
+        .bind(MAX_TRACE_ENTRIES)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        Ok(())
+    }
+
+    async fn traces_for(&self, rule_id: &str) -> Result<Vec<RuleTraceEntry>, RuleRepoError> {
+        let rows = sqlx::query(
+            "SELECT entry FROM rule_traces WHERE rule_id = ? ORDER BY seq ASC",
+        )
+        .bind(rule_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| RuleRepoError::Backend(err.to_string()))?;
+        rows.into_iter()
+            .map(|row| {
+                let raw: String = row.get("entry");
+                serde_json::from_str(&raw).map_err(|err| RuleRepoError::Backend(err.to_string()))
+            })
+            .collect()
+    }
+}
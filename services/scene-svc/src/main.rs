@@ -1,27 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
-use axum::extract::State;
-use axum::routing::post;
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{Path, State, WebSocketUpgrade};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
 use axum::{Json, Router};
+use futures_core::Stream;
+use futures_util::stream::FuturesUnordered;
+use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex, Semaphore};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing_subscriber::EnvFilter;
 
 use common_config::service_port;
 use common_obs::health_router;
 
+mod scene_store;
+
+use scene_store::{SceneStore, SceneStoreError, SharedSceneStore, SledSceneStore, StoredScene};
+
 const SERVICE_NAME: &str = "scene-svc";
 const PORT_ENV: &str = "SCENE_SVC_PORT";
 const DEFAULT_PORT: u16 = 8003;
 const DEFAULT_REGISTRY_URL: &str = "http://127.0.0.1:8001";
+const SLED_PATH_ENV: &str = "SCENE_SVC_SLED_PATH";
+const DEFAULT_SLED_PATH: &str = "scene-svc.sled";
+const STALENESS_WINDOW_ENV: &str = "SCENE_SVC_STALENESS_WINDOW_SECS";
+const DEFAULT_STALENESS_WINDOW_SECS: i64 = 300;
+const MAX_CONCURRENCY_ENV: &str = "SCENE_SVC_MAX_CONCURRENCY";
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+/// How many finished request IDs a [`ws_scenes`] connection remembers (to
+/// reject a reused ID) before clearing the set, so a long-lived connection
+/// that issues many requests doesn't grow this without bound.
+const WS_FINISHED_GC_THRESHOLD: usize = 256;
 
 #[derive(Clone)]
 struct AppState<C: DeviceRegistryClient + Send + Sync + 'static> {
     executor: Arc<SceneExecutor<C>>,
+    store: SharedSceneStore,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -29,12 +54,64 @@ struct SceneRequest {
     #[allow(dead_code)]
     pub scene_id: Option<String>,
     pub operations: Vec<DeviceOperation>,
+    #[serde(default)]
+    pub strategy: SceneStrategy,
+}
+
+/// How [`SceneExecutor::run_scene`] applies a scene's operations.
+/// `Sequential` is the original, ordered behavior: fail-fast, independent
+/// devices applied one at a time. `Parallel` runs up to
+/// [`SceneExecutor::max_concurrency`] operations at once and is faster for
+/// scenes whose devices don't depend on each other, at the cost of
+/// out-of-order progress events.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum SceneStrategy {
+    #[default]
+    Sequential,
+    Parallel,
 }
 
+/// Body of `PUT /v1/scenes/:id`: the operations a saved scene replays.
 #[derive(Debug, Clone, Deserialize)]
+struct SceneDefinition {
+    operations: Vec<DeviceOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct DeviceOperation {
     pub device_id: String,
     pub state: serde_json::Value,
+    /// Milliseconds since the Unix epoch when this operation was produced.
+    /// When present, the executor rejects it as stale rather than applying
+    /// it if it's not strictly newer than the last timestamp accepted for
+    /// this device, or if it's older than the configured staleness window.
+    /// `None` skips the check entirely, for callers that don't track
+    /// timestamps.
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+}
+
+fn current_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn staleness_window_from_env() -> Duration {
+    let secs = std::env::var(STALENESS_WINDOW_ENV)
+        .ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_STALENESS_WINDOW_SECS);
+    Duration::from_secs(secs.max(0) as u64)
+}
+
+fn max_concurrency_from_env() -> usize {
+    std::env::var(MAX_CONCURRENCY_ENV)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY)
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -68,6 +145,16 @@ enum DeviceStatus {
     Skipped,
 }
 
+/// One unit of progress pushed onto [`SceneExecutor::run_scene`]'s channel:
+/// either a single device's result as the scene runs, or the terminal
+/// [`SceneStatus`] once every device (and any rollback) has completed.
+/// `apply_scene` and `apply_scene_stream` both consume this, so the
+/// buffered and SSE handlers can never disagree about what happened.
+enum SceneEvent {
+    Device(DeviceApplyResult),
+    Done(SceneStatus),
+}
+
 #[derive(Debug, thiserror::Error)]
 enum SceneError {
     #[error("registry unreachable: {0}")]
@@ -78,6 +165,28 @@ enum SceneError {
     Unexpected,
 }
 
+/// Error type for the scene-library routes (`/v1/scenes*`), separate from
+/// [`SceneError`] since it wraps storage failures rather than device
+/// registry ones.
+#[derive(Debug, thiserror::Error)]
+enum SceneApiError {
+    #[error("storage error: {0}")]
+    Store(#[from] SceneStoreError),
+}
+
+impl IntoResponse for SceneApiError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match &self {
+            SceneApiError::Store(err) => match err {
+                SceneStoreError::NotFound => StatusCode::NOT_FOUND,
+                SceneStoreError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+        };
+        let msg = self.to_string();
+        (status, Json(serde_json::json!({ "error": msg }))).into_response()
+    }
+}
+
 #[async_trait]
 trait DeviceRegistryClient: Clone + Send + Sync {
     async fn fetch_state(&self, device_id: &str) -> Result<serde_json::Value, SceneError>;
@@ -145,84 +254,327 @@ impl DeviceRegistryClient for HttpDeviceRegistry {
 
 struct SceneExecutor<C: DeviceRegistryClient> {
     client: C,
+    /// Last accepted operation timestamp per device, used to reject
+    /// out-of-order or replayed writes. Only devices whose operations
+    /// carry a `timestamp` are tracked.
+    last_applied: Mutex<HashMap<String, i64>>,
+    staleness_window: Duration,
+    /// Upper bound on simultaneous `fetch_state`+`apply_state` pairs under
+    /// [`SceneStrategy::Parallel`]. Ignored by `Sequential`.
+    max_concurrency: usize,
 }
 
 impl<C: DeviceRegistryClient> SceneExecutor<C> {
-    async fn apply_scene(&self, request: SceneRequest) -> SceneResponse {
-        let mut results = Vec::with_capacity(request.operations.len());
+    fn new(client: C, staleness_window: Duration, max_concurrency: usize) -> Self {
+        Self {
+            client,
+            last_applied: Mutex::new(HashMap::new()),
+            staleness_window,
+            max_concurrency: max_concurrency.max(1),
+        }
+    }
+
+    /// Checks `op`'s optional timestamp for staleness and, if it passes,
+    /// atomically reserves it as the new `last_applied` value for this
+    /// device under the same lock acquisition as the check. This closes the
+    /// race where two concurrent operations against the same device both
+    /// read the same stale `last_applied` value before either one records
+    /// — without reserving here, the slower of the two to finish its
+    /// `fetch_state`/`apply_state` round trip could clobber the other's
+    /// write. `None` timestamp always passes and reserves nothing.
+    ///
+    /// Returns `Ok(previous)` with whatever was reserved for this device
+    /// before this call (to restore via [`Self::rollback_reservation`] if
+    /// the apply that follows fails), or `Err(reason)` if the operation
+    /// must be skipped.
+    async fn reject_stale(&self, op: &DeviceOperation) -> Result<Option<i64>, String> {
+        let Some(timestamp) = op.timestamp else {
+            return Ok(None);
+        };
+
+        let now = current_millis();
+        let age_ms = now.saturating_sub(timestamp);
+        if age_ms > self.staleness_window.as_millis() as i64 {
+            return Err(format!(
+                "stale timestamp: {timestamp} is older than the {}s staleness window",
+                self.staleness_window.as_secs()
+            ));
+        }
+
+        let mut last_applied = self.last_applied.lock().await;
+        let previous = last_applied.get(&op.device_id).copied();
+        if let Some(previous) = previous {
+            if timestamp <= previous {
+                return Err(format!(
+                    "stale timestamp: {timestamp} is not newer than the last applied {previous}"
+                ));
+            }
+        }
+        last_applied.insert(op.device_id.clone(), timestamp);
+        Ok(previous)
+    }
+
+    /// Rolls back a reservation [`Self::reject_stale`] made for `op` when
+    /// the apply that followed it failed, restoring `previous` (or
+    /// clearing the entry if there was none) so a timestamp that was never
+    /// actually applied doesn't permanently block a later retry. A no-op
+    /// for an `op` with no timestamp, since nothing was reserved for it.
+    async fn rollback_reservation(&self, op: &DeviceOperation, previous: Option<i64>) {
+        let Some(timestamp) = op.timestamp else {
+            return;
+        };
+        let mut last_applied = self.last_applied.lock().await;
+        // Only roll back if nothing newer has reserved/applied since --
+        // another concurrent operation for the same device may have
+        // already reserved (or applied) a later timestamp, which this
+        // failed operation's rollback must not clobber.
+        if last_applied.get(&op.device_id) == Some(&timestamp) {
+            match previous {
+                Some(previous) => {
+                    last_applied.insert(op.device_id.clone(), previous);
+                }
+                None => {
+                    last_applied.remove(&op.device_id);
+                }
+            }
+        }
+    }
+
+    /// Runs the scene, pushing a [`SceneEvent::Device`] onto `events` as
+    /// each device's `fetch_state`/`apply_state` (or rollback) completes,
+    /// and returns the final [`SceneStatus`] once done. Does not itself
+    /// send [`SceneEvent::Done`] — callers already hold the status, so
+    /// buffered and streaming callers can each decide when to emit it.
+    /// Dispatches on [`SceneRequest::strategy`]; see [`Self::run_sequential`]
+    /// and [`Self::run_parallel`] for the two execution modes.
+    async fn run_scene(
+        &self,
+        request: SceneRequest,
+        events: mpsc::UnboundedSender<SceneEvent>,
+    ) -> SceneStatus {
+        match request.strategy {
+            SceneStrategy::Sequential => self.run_sequential(&request.operations, &events).await,
+            SceneStrategy::Parallel => self.run_parallel(&request.operations, &events).await,
+        }
+    }
+
+    /// Applies `operations` one at a time, in order, stopping at the first
+    /// failure and rolling back everything already applied. The original
+    /// (and still default) execution mode.
+    async fn run_sequential(
+        &self,
+        operations: &[DeviceOperation],
+        events: &mpsc::UnboundedSender<SceneEvent>,
+    ) -> SceneStatus {
         let mut previous_states: Vec<(String, serde_json::Value)> = Vec::new();
         let mut failure_encountered = false;
 
-        for op in &request.operations {
-            if failure_encountered {
-                results.push(DeviceApplyResult {
+        for op in operations {
+            let result = if failure_encountered {
+                DeviceApplyResult {
                     device_id: op.device_id.clone(),
                     status: DeviceStatus::Skipped,
                     detail: Some("skipped due to prior failure".to_string()),
-                });
-                continue;
-            }
+                }
+            } else {
+                match self.reject_stale(op).await {
+                    Err(reason) => DeviceApplyResult {
+                        device_id: op.device_id.clone(),
+                        status: DeviceStatus::Skipped,
+                        detail: Some(reason),
+                    },
+                    Ok(reserved) => match self.client.fetch_state(&op.device_id).await {
+                        Ok(prev) => match self.client.apply_state(&op.device_id, &op.state).await {
+                            Ok(_) => {
+                                previous_states.push((op.device_id.clone(), prev));
+                                DeviceApplyResult {
+                                    device_id: op.device_id.clone(),
+                                    status: DeviceStatus::Applied,
+                                    detail: None,
+                                }
+                            }
+                            Err(err) => {
+                                failure_encountered = true;
+                                self.rollback_reservation(op, reserved).await;
+                                DeviceApplyResult {
+                                    device_id: op.device_id.clone(),
+                                    status: DeviceStatus::Failed,
+                                    detail: Some(err.to_string()),
+                                }
+                            }
+                        },
+                        Err(err) => {
+                            failure_encountered = true;
+                            self.rollback_reservation(op, reserved).await;
+                            DeviceApplyResult {
+                                device_id: op.device_id.clone(),
+                                status: DeviceStatus::Failed,
+                                detail: Some(err.to_string()),
+                            }
+                        }
+                    },
+                }
+            };
+            let _ = events.send(SceneEvent::Device(result));
+        }
+
+        if !failure_encountered {
+            return SceneStatus::Applied;
+        }
+        if self
+            .rollback(previous_states.into_iter().rev(), events)
+            .await
+        {
+            SceneStatus::PartialFailure
+        } else {
+            SceneStatus::Failed
+        }
+    }
+
+    /// Applies up to [`Self::max_concurrency`] operations at once via a
+    /// [`Semaphore`]-bounded [`FuturesUnordered`], recording every
+    /// successful apply into a shared compensation log of `(device_id,
+    /// previous_state)`. As soon as any operation fails, in-flight work is
+    /// still awaited to completion but no not-yet-started operation begins
+    /// its `fetch_state`/`apply_state` — it's marked `Skipped` instead.
+    /// Once every task has finished, the compensation log is replayed in
+    /// reverse to roll back everything that succeeded, same as
+    /// [`Self::run_sequential`].
+    async fn run_parallel(
+        &self,
+        operations: &[DeviceOperation],
+        events: &mpsc::UnboundedSender<SceneEvent>,
+    ) -> SceneStatus {
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrency));
+        let compensation_log: Mutex<Vec<(String, serde_json::Value)>> = Mutex::new(Vec::new());
+        let failed = AtomicBool::new(false);
+
+        let mut tasks: FuturesUnordered<_> = operations
+            .iter()
+            .map(|op| {
+                let semaphore = semaphore.clone();
+                let compensation_log = &compensation_log;
+                let failed = &failed;
+                async move {
+                    let _permit = semaphore
+                        .acquire_owned()
+                        .await
+                        .expect("scene semaphore should not be closed");
 
-            match self.client.fetch_state(&op.device_id).await {
-                Ok(prev) => match self.client.apply_state(&op.device_id, &op.state).await {
-                    Ok(_) => {
-                        previous_states.push((op.device_id.clone(), prev));
-                        results.push(DeviceApplyResult {
+                    if failed.load(Ordering::SeqCst) {
+                        return DeviceApplyResult {
                             device_id: op.device_id.clone(),
-                            status: DeviceStatus::Applied,
-                            detail: None,
-                        });
+                            status: DeviceStatus::Skipped,
+                            detail: Some("skipped due to prior failure".to_string()),
+                        };
                     }
-                    Err(err) => {
-                        failure_encountered = true;
-                        results.push(DeviceApplyResult {
-                            device_id: op.device_id.clone(),
-                            status: DeviceStatus::Failed,
-                            detail: Some(err.to_string()),
-                        });
+                    let reserved = match self.reject_stale(op).await {
+                        Err(reason) => {
+                            return DeviceApplyResult {
+                                device_id: op.device_id.clone(),
+                                status: DeviceStatus::Skipped,
+                                detail: Some(reason),
+                            };
+                        }
+                        Ok(reserved) => reserved,
+                    };
+
+                    match self.client.fetch_state(&op.device_id).await {
+                        Ok(prev) => match self.client.apply_state(&op.device_id, &op.state).await {
+                            Ok(_) => {
+                                compensation_log
+                                    .lock()
+                                    .await
+                                    .push((op.device_id.clone(), prev));
+                                DeviceApplyResult {
+                                    device_id: op.device_id.clone(),
+                                    status: DeviceStatus::Applied,
+                                    detail: None,
+                                }
+                            }
+                            Err(err) => {
+                                failed.store(true, Ordering::SeqCst);
+                                self.rollback_reservation(op, reserved).await;
+                                DeviceApplyResult {
+                                    device_id: op.device_id.clone(),
+                                    status: DeviceStatus::Failed,
+                                    detail: Some(err.to_string()),
+                                }
+                            }
+                        },
+                        Err(err) => {
+                            failed.store(true, Ordering::SeqCst);
+                            self.rollback_reservation(op, reserved).await;
+                            DeviceApplyResult {
+                                device_id: op.device_id.clone(),
+                                status: DeviceStatus::Failed,
+                                detail: Some(err.to_string()),
+                            }
+                        }
                     }
-                },
-                Err(err) => {
-                    failure_encountered = true;
-                    results.push(DeviceApplyResult {
-                        device_id: op.device_id.clone(),
-                        status: DeviceStatus::Failed,
-                        detail: Some(err.to_string()),
-                    });
                 }
-            }
+            })
+            .collect();
+
+        while let Some(result) = tasks.next().await {
+            let _ = events.send(SceneEvent::Device(result));
         }
 
-        if failure_encountered {
-            for (device_id, prev_state) in previous_states.into_iter().rev() {
-                if let Err(err) = self.client.apply_state(&device_id, &prev_state).await {
-                    results.push(DeviceApplyResult {
-                        device_id,
-                        status: DeviceStatus::Failed,
-                        detail: Some(format!("rollback failed: {err}")),
-                    });
-                } else {
-                    results.push(DeviceApplyResult {
-                        device_id,
-                        status: DeviceStatus::RolledBack,
-                        detail: Some("rolled back".to_string()),
-                    });
-                }
-            }
+        if !failed.load(Ordering::SeqCst) {
+            return SceneStatus::Applied;
+        }
+        let to_rollback = compensation_log.into_inner().into_iter().rev();
+        if self.rollback(to_rollback, events).await {
+            SceneStatus::PartialFailure
+        } else {
+            SceneStatus::Failed
         }
+    }
 
-        let status = if failure_encountered {
-            if results
-                .iter()
-                .any(|r| matches!(r.status, DeviceStatus::RolledBack))
-            {
-                SceneStatus::PartialFailure
+    /// Replays a compensation log (already in rollback order) by restoring
+    /// each device's previous state, sending a [`SceneEvent::Device`] per
+    /// attempt. Returns whether at least one device was rolled back
+    /// successfully, which callers use to distinguish `PartialFailure` from
+    /// a total `Failed`.
+    async fn rollback(
+        &self,
+        to_rollback: impl Iterator<Item = (String, serde_json::Value)>,
+        events: &mpsc::UnboundedSender<SceneEvent>,
+    ) -> bool {
+        let mut rolled_back_any = false;
+        for (device_id, prev_state) in to_rollback {
+            let result = if let Err(err) = self.client.apply_state(&device_id, &prev_state).await {
+                DeviceApplyResult {
+                    device_id,
+                    status: DeviceStatus::Failed,
+                    detail: Some(format!("rollback failed: {err}")),
+                }
             } else {
-                SceneStatus::Failed
+                rolled_back_any = true;
+                DeviceApplyResult {
+                    device_id,
+                    status: DeviceStatus::RolledBack,
+                    detail: Some("rolled back".to_string()),
+                }
+            };
+            let _ = events.send(SceneEvent::Device(result));
+        }
+        rolled_back_any
+    }
+
+    /// Runs the scene to completion and returns every device's result at
+    /// once. Built on [`Self::run_scene`] — see [`apply_scene_stream`] for
+    /// the streaming equivalent.
+    async fn apply_scene(&self, request: SceneRequest) -> SceneResponse {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let status = self.run_scene(request, tx).await;
+
+        let mut results = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let SceneEvent::Device(result) = event {
+                results.push(result);
             }
-        } else {
-            SceneStatus::Applied
-        };
+        }
 
         SceneResponse { status, results }
     }
@@ -242,14 +594,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         base: registry_url,
     };
 
+    let sled_path = std::env::var(SLED_PATH_ENV).unwrap_or_else(|_| DEFAULT_SLED_PATH.to_string());
+    let store: SharedSceneStore = Arc::new(SledSceneStore::open(&sled_path)?);
+    let staleness_window = staleness_window_from_env();
+    let max_concurrency = max_concurrency_from_env();
+
     let state = AppState {
-        executor: Arc::new(SceneExecutor { client }),
+        executor: Arc::new(SceneExecutor::new(
+            client,
+            staleness_window,
+            max_concurrency,
+        )),
+        store,
     };
 
     tracing::info!(%addr, service = SERVICE_NAME, "starting service");
 
     let app = Router::new()
         .route("/v1/scenes:apply", post(apply_scene))
+        .route("/v1/scenes:apply/stream", post(apply_scene_stream))
+        .route("/v1/scenes/ws", get(ws_scenes))
+        .route("/v1/scenes", get(list_scenes))
+        .route(
+            "/v1/scenes/:id",
+            get(get_scene).put(put_scene).delete(delete_scene),
+        )
+        .route("/v1/scenes/:id/apply", post(apply_stored_scene))
         .with_state(state)
         .merge(health_router(SERVICE_NAME));
 
@@ -272,6 +642,236 @@ async fn apply_scene<C: DeviceRegistryClient + Send + Sync + 'static>(
     Json(response)
 }
 
+/// Streaming sibling of [`apply_scene`]: emits one SSE event per device as
+/// each `fetch_state`/`apply_state` (or rollback) completes, then a
+/// terminal `done` event carrying the final [`SceneStatus`], instead of
+/// making the client wait for the whole scene to finish.
+async fn apply_scene_stream<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+    Json(payload): Json<SceneRequest>,
+) -> axum::response::Sse<impl Stream<Item = Result<Event, anyhow::Error>>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let status = state.executor.run_scene(payload, tx.clone()).await;
+        let _ = tx.send(SceneEvent::Done(status));
+    });
+
+    let stream = UnboundedReceiverStream::new(rx).map(|event| scene_event_to_sse(&event));
+    axum::response::Sse::new(stream).keep_alive(KeepAlive::new())
+}
+
+/// Renders a [`SceneEvent`] as an SSE event with an explicit `event:` name
+/// (`applied`, `failed`, `rolled_back`, `skipped`, or `done`) so browsers
+/// and CLIs can react to a rollback as it happens instead of only at the
+/// end.
+fn scene_event_to_sse(event: &SceneEvent) -> Result<Event, anyhow::Error> {
+    match event {
+        SceneEvent::Device(result) => {
+            let name = match &result.status {
+                DeviceStatus::Applied => "applied",
+                DeviceStatus::RolledBack => "rolled_back",
+                DeviceStatus::Failed => "failed",
+                DeviceStatus::Skipped => "skipped",
+            };
+            let payload = serde_json::to_string(result)?;
+            Ok(Event::default().event(name).data(payload))
+        }
+        SceneEvent::Done(status) => {
+            let payload = serde_json::to_string(status)?;
+            Ok(Event::default().event("done").data(payload))
+        }
+    }
+}
+
+/// Inbound frame for the `/v1/scenes/ws` RPC channel: `method` is currently
+/// always `"apply_scene"`, with `params` holding the [`SceneRequest`] body.
+#[derive(Debug, Deserialize)]
+struct WsRequestFrame {
+    id: u64,
+    method: String,
+    params: serde_json::Value,
+}
+
+fn ws_progress_frame(id: u64, result: &DeviceApplyResult) -> String {
+    serde_json::json!({ "id": id, "progress": result }).to_string()
+}
+
+fn ws_result_frame(id: u64, response: &SceneResponse) -> String {
+    serde_json::json!({ "id": id, "result": response }).to_string()
+}
+
+fn ws_error_frame(id: u64, error: impl std::fmt::Display) -> String {
+    serde_json::json!({ "id": id, "error": error.to_string() }).to_string()
+}
+
+/// WebSocket sibling of [`apply_scene`]: a persistent, multiplexed RPC
+/// channel instead of one request per connection. See
+/// [`handle_scene_socket`] for the framing and concurrency rules.
+async fn ws_scenes<C: DeviceRegistryClient + Send + Sync + 'static>(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState<C>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_scene_socket(socket, state))
+}
+
+/// Runs a single `/v1/scenes/ws` connection. Each inbound `{"id", "method",
+/// "params"}` frame spawns its own task against the shared [`SceneExecutor`],
+/// so multiple `apply_scene` calls can be in flight on one connection at
+/// once; each sends a `{"id", "progress"}` frame per device as it completes,
+/// followed by a single terminal `{"id", "result"}` or `{"id", "error"}`
+/// frame. `in_flight` rejects a request that reuses an ID already running;
+/// `finished` remembers completed IDs for the same check and is cleared
+/// once it passes [`WS_FINISHED_GC_THRESHOLD`] so a long-lived connection
+/// doesn't accumulate one entry per request forever.
+async fn handle_scene_socket<C: DeviceRegistryClient + Send + Sync + 'static>(
+    socket: WebSocket,
+    state: AppState<C>,
+) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+
+    tokio::spawn(async move {
+        while let Some(frame) = out_rx.recv().await {
+            if ws_tx.send(Message::Text(frame)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let in_flight: Arc<Mutex<HashSet<u64>>> = Arc::new(Mutex::new(HashSet::new()));
+    let finished: Arc<Mutex<HashSet<u64>>> = Arc::new(Mutex::new(HashSet::new()));
+
+    while let Some(Ok(msg)) = ws_rx.next().await {
+        let text = match msg {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let request: WsRequestFrame = match serde_json::from_str(&text) {
+            Ok(request) => request,
+            Err(err) => {
+                let _ = out_tx.send(ws_error_frame(0, format!("invalid request: {err}")));
+                continue;
+            }
+        };
+
+        if request.method != "apply_scene" {
+            let _ = out_tx.send(ws_error_frame(
+                request.id,
+                format!("unknown method: {}", request.method),
+            ));
+            continue;
+        }
+
+        {
+            let mut in_flight_ids = in_flight.lock().await;
+            let already_seen = finished.lock().await.contains(&request.id);
+            if !in_flight_ids.insert(request.id) || already_seen {
+                let _ = out_tx.send(ws_error_frame(request.id, "request id already in use"));
+                continue;
+            }
+        }
+
+        let scene_request: SceneRequest = match serde_json::from_value(request.params) {
+            Ok(scene_request) => scene_request,
+            Err(err) => {
+                in_flight.lock().await.remove(&request.id);
+                let _ = out_tx.send(ws_error_frame(request.id, format!("invalid params: {err}")));
+                continue;
+            }
+        };
+
+        let id = request.id;
+        let executor = state.executor.clone();
+        let out_tx = out_tx.clone();
+        let in_flight = in_flight.clone();
+        let finished = finished.clone();
+
+        tokio::spawn(async move {
+            let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+            let progress_tx = out_tx.clone();
+            let collector = tokio::spawn(async move {
+                let mut results = Vec::new();
+                while let Some(event) = events_rx.recv().await {
+                    if let SceneEvent::Device(result) = event {
+                        let _ = progress_tx.send(ws_progress_frame(id, &result));
+                        results.push(result);
+                    }
+                }
+                results
+            });
+
+            let status = executor.run_scene(scene_request, events_tx).await;
+            let results = collector.await.unwrap_or_default();
+            let _ = out_tx.send(ws_result_frame(id, &SceneResponse { status, results }));
+
+            in_flight.lock().await.remove(&id);
+            let mut finished_ids = finished.lock().await;
+            finished_ids.insert(id);
+            if finished_ids.len() > WS_FINISHED_GC_THRESHOLD {
+                finished_ids.clear();
+            }
+        });
+    }
+}
+
+async fn list_scenes<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+) -> Result<Json<Vec<StoredScene>>, SceneApiError> {
+    Ok(Json(state.store.list().await?))
+}
+
+async fn put_scene<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+    Path(id): Path<String>,
+    Json(payload): Json<SceneDefinition>,
+) -> Result<StatusCode, SceneApiError> {
+    state
+        .store
+        .put(StoredScene {
+            id,
+            operations: payload.operations,
+        })
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_scene<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+    Path(id): Path<String>,
+) -> Result<Json<StoredScene>, SceneApiError> {
+    Ok(Json(state.store.get(&id).await?))
+}
+
+async fn delete_scene<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, SceneApiError> {
+    state.store.delete(&id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Loads a saved scene's operations and runs them through the same
+/// [`SceneExecutor::apply_scene`] the stateless `/v1/scenes:apply` endpoint
+/// uses, so a saved scene gets the same rollback-on-failure guarantees.
+async fn apply_stored_scene<C: DeviceRegistryClient + Send + Sync + 'static>(
+    State(state): State<AppState<C>>,
+    Path(id): Path<String>,
+) -> Result<Json<SceneResponse>, SceneApiError> {
+    let scene = state.store.get(&id).await?;
+    let response = state
+        .executor
+        .apply_scene(SceneRequest {
+            scene_id: Some(scene.id),
+            operations: scene.operations,
+            strategy: SceneStrategy::default(),
+        })
+        .await;
+    Ok(Json(response))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,9 +918,11 @@ mod tests {
         }
         *registry.fail_on.lock().await = Some("two".to_string());
 
-        let executor = SceneExecutor {
-            client: registry.clone(),
-        };
+        let executor = SceneExecutor::new(
+            registry.clone(),
+            Duration::from_secs(300),
+            DEFAULT_MAX_CONCURRENCY,
+        );
         let response = executor
             .apply_scene(SceneRequest {
                 scene_id: None,
@@ -328,12 +930,15 @@ mod tests {
                     DeviceOperation {
                         device_id: "one".to_string(),
                         state: serde_json::json!({"power": "on"}),
+                        timestamp: None,
                     },
                     DeviceOperation {
                         device_id: "two".to_string(),
                         state: serde_json::json!({"power": "on"}),
+                        timestamp: None,
                     },
                 ],
+                strategy: SceneStrategy::Sequential,
             })
             .await;
 
@@ -344,4 +949,266 @@ mod tests {
             .find(|r| r.device_id == "one" && matches!(r.status, DeviceStatus::RolledBack));
         assert!(applied.is_some(), "device one should have been rolled back");
     }
+
+    #[tokio::test]
+    async fn parallel_scene_rolls_back_all_committed_devices_on_failure() {
+        let registry = MockRegistry::default();
+        {
+            let mut devices = registry.devices.lock().await;
+            devices.insert("one".to_string(), serde_json::json!({"power": "off"}));
+            devices.insert("two".to_string(), serde_json::json!({"power": "off"}));
+            devices.insert("three".to_string(), serde_json::json!({"power": "off"}));
+        }
+        *registry.fail_on.lock().await = Some("three".to_string());
+
+        let executor = SceneExecutor::new(
+            registry.clone(),
+            Duration::from_secs(300),
+            DEFAULT_MAX_CONCURRENCY,
+        );
+        let response = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![
+                    DeviceOperation {
+                        device_id: "one".to_string(),
+                        state: serde_json::json!({"power": "on"}),
+                        timestamp: None,
+                    },
+                    DeviceOperation {
+                        device_id: "two".to_string(),
+                        state: serde_json::json!({"power": "on"}),
+                        timestamp: None,
+                    },
+                    DeviceOperation {
+                        device_id: "three".to_string(),
+                        state: serde_json::json!({"power": "on"}),
+                        timestamp: None,
+                    },
+                ],
+                strategy: SceneStrategy::Parallel,
+            })
+            .await;
+
+        assert!(matches!(
+            response.status,
+            SceneStatus::PartialFailure | SceneStatus::Failed
+        ));
+        for device_id in ["one", "two"] {
+            let result = response
+                .results
+                .iter()
+                .rev()
+                .find(|r| r.device_id == device_id)
+                .unwrap_or_else(|| panic!("missing result for {device_id}"));
+            assert!(
+                matches!(
+                    result.status,
+                    DeviceStatus::RolledBack | DeviceStatus::Skipped
+                ),
+                "device {device_id} should have been rolled back or never applied, got {:?}",
+                result.status
+            );
+        }
+        let committed_then_rolled_back = response
+            .results
+            .iter()
+            .any(|r| matches!(r.status, DeviceStatus::RolledBack));
+        assert!(
+            committed_then_rolled_back,
+            "at least one device should have been committed then rolled back"
+        );
+        let devices = registry.devices.lock().await;
+        assert_eq!(
+            devices.get("one"),
+            Some(&serde_json::json!({"power": "off"}))
+        );
+        assert_eq!(
+            devices.get("two"),
+            Some(&serde_json::json!({"power": "off"}))
+        );
+    }
+
+    #[tokio::test]
+    async fn timestamp_ordering_rejects_equal_and_older_but_accepts_newer() {
+        let registry = MockRegistry::default();
+        registry
+            .devices
+            .lock()
+            .await
+            .insert("one".to_string(), serde_json::json!({"power": "off"}));
+
+        let executor = SceneExecutor::new(
+            registry.clone(),
+            Duration::from_secs(300),
+            DEFAULT_MAX_CONCURRENCY,
+        );
+        let base = current_millis();
+        let op_at = |timestamp: i64| DeviceOperation {
+            device_id: "one".to_string(),
+            state: serde_json::json!({"power": "on"}),
+            timestamp: Some(base + timestamp),
+        };
+
+        let first = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![op_at(1_000)],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+        assert!(matches!(first.results[0].status, DeviceStatus::Applied));
+
+        let equal = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![op_at(1_000)],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+        assert!(matches!(equal.results[0].status, DeviceStatus::Skipped));
+        assert!(equal.results[0]
+            .detail
+            .as_deref()
+            .unwrap_or_default()
+            .contains("stale timestamp"));
+
+        let older = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![op_at(500)],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+        assert!(matches!(older.results[0].status, DeviceStatus::Skipped));
+
+        let newer = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![op_at(2_000)],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+        assert!(matches!(newer.results[0].status, DeviceStatus::Applied));
+    }
+
+    #[tokio::test]
+    async fn future_timestamp_within_staleness_window_is_applied() {
+        let registry = MockRegistry::default();
+        registry
+            .devices
+            .lock()
+            .await
+            .insert("one".to_string(), serde_json::json!({"power": "off"}));
+
+        let executor = SceneExecutor::new(
+            registry.clone(),
+            Duration::from_secs(300),
+            DEFAULT_MAX_CONCURRENCY,
+        );
+        let future = current_millis() + 10_000;
+        let response = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![DeviceOperation {
+                    device_id: "one".to_string(),
+                    state: serde_json::json!({"power": "on"}),
+                    timestamp: Some(future),
+                }],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+
+        assert!(matches!(response.results[0].status, DeviceStatus::Applied));
+    }
+
+    #[tokio::test]
+    async fn timestamp_older_than_staleness_window_is_skipped() {
+        let registry = MockRegistry::default();
+        registry
+            .devices
+            .lock()
+            .await
+            .insert("one".to_string(), serde_json::json!({"power": "off"}));
+
+        let executor = SceneExecutor::new(
+            registry.clone(),
+            Duration::from_secs(60),
+            DEFAULT_MAX_CONCURRENCY,
+        );
+        let stale = current_millis() - Duration::from_secs(120).as_millis() as i64;
+        let response = executor
+            .apply_scene(SceneRequest {
+                scene_id: None,
+                operations: vec![DeviceOperation {
+                    device_id: "one".to_string(),
+                    state: serde_json::json!({"power": "on"}),
+                    timestamp: Some(stale),
+                }],
+                strategy: SceneStrategy::Sequential,
+            })
+            .await;
+
+        assert!(matches!(response.results[0].status, DeviceStatus::Skipped));
+    }
+
+    #[derive(Default)]
+    struct InMemorySceneStore {
+        scenes: Mutex<HashMap<String, StoredScene>>,
+    }
+
+    #[async_trait]
+    impl SceneStore for InMemorySceneStore {
+        async fn put(&self, scene: StoredScene) -> Result<(), SceneStoreError> {
+            self.scenes.lock().await.insert(scene.id.clone(), scene);
+            Ok(())
+        }
+
+        async fn get(&self, id: &str) -> Result<StoredScene, SceneStoreError> {
+            self.scenes
+                .lock()
+                .await
+                .get(id)
+                .cloned()
+                .ok_or(SceneStoreError::NotFound)
+        }
+
+        async fn delete(&self, id: &str) -> Result<(), SceneStoreError> {
+            self.scenes
+                .lock()
+                .await
+                .remove(id)
+                .map(|_| ())
+                .ok_or(SceneStoreError::NotFound)
+        }
+
+        async fn list(&self) -> Result<Vec<StoredScene>, SceneStoreError> {
+            Ok(self.scenes.lock().await.values().cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_scene_roundtrips_through_the_store() {
+        let store = InMemorySceneStore::default();
+        store
+            .put(StoredScene {
+                id: "movie_night".to_string(),
+                operations: vec![DeviceOperation {
+                    device_id: "one".to_string(),
+                    state: serde_json::json!({"power": "on"}),
+                    timestamp: None,
+                }],
+            })
+            .await
+            .unwrap();
+
+        let fetched = store.get("movie_night").await.unwrap();
+        assert_eq!(fetched.operations.len(), 1);
+
+        store.delete("movie_night").await.unwrap();
+        assert!(matches!(
+            store.get("movie_night").await,
+            Err(SceneStoreError::NotFound)
+        ));
+    }
 }
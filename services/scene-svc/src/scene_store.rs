@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::DeviceOperation;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SceneStoreError {
+    #[error("scene not found")]
+    NotFound,
+    #[error("storage backend error: {0}")]
+    Backend(String),
+}
+
+/// A saved scene: the operations `POST /v1/scenes/:id/apply` replays
+/// through the same [`SceneExecutor`](crate::SceneExecutor) used by the
+/// stateless `/v1/scenes:apply` endpoint.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StoredScene {
+    pub id: String,
+    pub operations: Vec<DeviceOperation>,
+}
+
+/// Storage abstraction for named scenes, mirroring how
+/// [`DeviceRegistryClient`](crate::DeviceRegistryClient) abstracts the
+/// device registry: HTTP handlers depend only on this trait, not on a
+/// specific backend. [`SledSceneStore`] is the real, embedded-database
+/// implementation; tests use an in-memory mock instead.
+#[async_trait]
+pub trait SceneStore: Send + Sync {
+    async fn put(&self, scene: StoredScene) -> Result<(), SceneStoreError>;
+    async fn get(&self, id: &str) -> Result<StoredScene, SceneStoreError>;
+    async fn delete(&self, id: &str) -> Result<(), SceneStoreError>;
+    async fn list(&self) -> Result<Vec<StoredScene>, SceneStoreError>;
+}
+
+fn to_backend_err(err: impl std::fmt::Display) -> SceneStoreError {
+    SceneStoreError::Backend(err.to_string())
+}
+
+/// [`SceneStore`] backed by a single-file embedded `sled` database, keyed
+/// directly by scene ID so saved scenes survive a restart without pulling
+/// in a full SQL engine.
+pub struct SledSceneStore {
+    scenes: sled::Tree,
+}
+
+impl SledSceneStore {
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, sled::Error> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            scenes: db.open_tree("scenes")?,
+        })
+    }
+}
+
+#[async_trait]
+impl SceneStore for SledSceneStore {
+    async fn put(&self, scene: StoredScene) -> Result<(), SceneStoreError> {
+        let bytes = serde_json::to_vec(&scene).map_err(to_backend_err)?;
+        self.scenes
+            .insert(scene.id.as_bytes(), bytes)
+            .map_err(to_backend_err)?;
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<StoredScene, SceneStoreError> {
+        let bytes = self
+            .scenes
+            .get(id.as_bytes())
+            .map_err(to_backend_err)?
+            .ok_or(SceneStoreError::NotFound)?;
+        serde_json::from_slice(&bytes).map_err(to_backend_err)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), SceneStoreError> {
+        let removed = self.scenes.remove(id.as_bytes()).map_err(to_backend_err)?;
+        removed.ok_or(SceneStoreError::NotFound)?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<StoredScene>, SceneStoreError> {
+        let mut scenes = Vec::new();
+        for entry in self.scenes.iter() {
+            let (_, value) = entry.map_err(to_backend_err)?;
+            scenes.push(serde_json::from_slice(&value).map_err(to_backend_err)?);
+        }
+        scenes.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(scenes)
+    }
+}
+
+pub type SharedSceneStore = std::sync::Arc<dyn SceneStore>;
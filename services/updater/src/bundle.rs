@@ -1,9 +1,11 @@
-use std::collections::BTreeMap;
-use std::path::{Component, Path};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use ed25519_dalek::pkcs8::DecodePublicKey;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use once_cell::sync::Lazy;
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use tokio::fs;
@@ -11,6 +13,30 @@ use tokio::io::AsyncReadExt;
 
 use crate::state::Slot;
 
+static COMPONENTS_REUSED_TOTAL: Lazy<common_obs::CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "updater_bundle_components_reused_total",
+        "Bundle components resolved from a component store instead of the bundle",
+        &[],
+    )
+});
+
+static COMPONENTS_FETCHED_TOTAL: Lazy<common_obs::CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "updater_bundle_components_fetched_total",
+        "Bundle components read from the staged bundle directory",
+        &[],
+    )
+});
+
+static COMPONENTS_PATCHED_TOTAL: Lazy<common_obs::CounterVec> = Lazy::new(|| {
+    common_obs::register_counter(
+        "updater_bundle_components_patched_total",
+        "Bundle components reconstructed by applying a delta patch to an installed base",
+        &[],
+    )
+});
+
 const SIGNATURE_PEM_LABEL: &str = "ED25519 SIGNATURE";
 const DEFAULT_PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
 
@@ -19,6 +45,11 @@ pub struct ManifestComponent {
     pub name: String,
     pub path: String,
     pub sha256: String,
+    /// When set, `path` names a binary patch to apply to the previously
+    /// installed component with this digest, instead of the full component
+    /// bytes. `sha256` still describes the *reconstructed* component.
+    #[serde(default)]
+    pub patch_base_sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -27,9 +58,78 @@ pub struct Manifest {
     pub build_sha: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub target_slot: Slot,
+    #[serde(default)]
+    pub base_version: Option<String>,
+    #[serde(default)]
+    pub base_build_sha: Option<String>,
+    /// Monotonic anti-rollback counter, independent of `version`'s free-form
+    /// display string. Checked against [`crate::state::UpdaterState`]'s
+    /// floor in `UpdaterCore::stage` so a signed-but-older bundle can't
+    /// downgrade the device to a previously patched vulnerability.
+    #[serde(default)]
+    pub security_version: u64,
     pub components: Vec<ManifestComponent>,
 }
 
+/// Resolves a previously-installed component by content digest so a delta
+/// bundle doesn't need to ship bytes the device already has.
+#[async_trait]
+pub trait ComponentStore: Send + Sync {
+    async fn resolve(&self, sha256: &str) -> Option<PathBuf>;
+
+    /// Caches a freshly-verified component so a later delta bundle can reuse it.
+    async fn adopt(&self, sha256: &str, source: &Path) -> Result<(), BundleError>;
+
+    /// Caches component bytes reconstructed in memory (e.g. by applying a
+    /// delta patch), the same as `adopt` does for a file already on disk.
+    async fn store_bytes(&self, sha256: &str, bytes: &[u8]) -> Result<(), BundleError>;
+}
+
+/// Content-addressed cache of component files, keyed by their sha256 digest.
+pub struct FilesystemComponentStore {
+    root: PathBuf,
+}
+
+impl FilesystemComponentStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, sha256: &str) -> PathBuf {
+        self.root.join(sha256.to_lowercase())
+    }
+}
+
+#[async_trait]
+impl ComponentStore for FilesystemComponentStore {
+    async fn resolve(&self, sha256: &str) -> Option<PathBuf> {
+        let path = self.path_for(sha256);
+        fs::metadata(&path).await.ok().map(|_| path)
+    }
+
+    async fn adopt(&self, sha256: &str, source: &Path) -> Result<(), BundleError> {
+        fs::create_dir_all(&self.root).await?;
+        let dest = self.path_for(sha256);
+        if fs::metadata(&dest).await.is_ok() {
+            return Ok(());
+        }
+        if fs::hard_link(source, &dest).await.is_err() {
+            fs::copy(source, &dest).await?;
+        }
+        Ok(())
+    }
+
+    async fn store_bytes(&self, sha256: &str, bytes: &[u8]) -> Result<(), BundleError> {
+        fs::create_dir_all(&self.root).await?;
+        let dest = self.path_for(sha256);
+        if fs::metadata(&dest).await.is_ok() {
+            return Ok(());
+        }
+        fs::write(&dest, bytes).await?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StageBundleMetadata {
     manifest: Manifest,
@@ -91,34 +191,188 @@ pub enum BundleError {
     InvalidPublicKey(String),
     #[error("public key PEM must have label '{DEFAULT_PUBLIC_KEY_LABEL}'")]
     UnexpectedPublicKeyLabel,
+    #[error(
+        "signature threshold not met: required {required}, found {found} distinct trusted signers"
+    )]
+    ThresholdNotMet { required: usize, found: usize },
+    #[error("delta bundle expects base version {expected:?}, active slot reports {found:?}")]
+    DeltaBaseMismatch {
+        expected: String,
+        found: Option<String>,
+    },
+    #[error("delta patch base component missing from component store: {0}")]
+    MissingBaseComponent(String),
+    #[error("component store required to stage delta component: {0}")]
+    ComponentStoreRequired(String),
+    #[error("invalid delta patch: {0}")]
+    InvalidPatch(String),
+}
+
+/// A trusted signing key, identified so multiple keys can be trusted at once
+/// during key rotation.
+#[derive(Clone)]
+pub struct TrustedKey {
+    pub key_id: String,
+    pub verifying_key: VerifyingKey,
+}
+
+impl TrustedKey {
+    pub fn from_public_key_pem(
+        key_id: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, BundleError> {
+        Ok(Self {
+            key_id: key_id.into(),
+            verifying_key: parse_verifying_key_pem(&std::fs::read(path)?)?,
+        })
+    }
+}
+
+fn parse_verifying_key_pem(contents: &[u8]) -> Result<VerifyingKey, BundleError> {
+    let pem = parse_pem(contents).map_err(|err| BundleError::InvalidPublicKey(err.to_string()))?;
+    if pem.tag() != DEFAULT_PUBLIC_KEY_LABEL {
+        return Err(BundleError::UnexpectedPublicKeyLabel);
+    }
+    VerifyingKey::from_public_key_der(pem.contents())
+        .map_err(|err| BundleError::InvalidPublicKey(err.to_string()))
 }
 
 #[async_trait]
 pub trait BundleVerifier: Send + Sync {
-    async fn verify(&self, bundle_path: &str) -> Result<StageBundleMetadata, BundleError>;
+    /// `installed_version` is the manifest version currently installed in
+    /// the active slot, if known. It is only consulted for delta bundles,
+    /// which must be rebuilt against that exact base.
+    async fn verify(
+        &self,
+        bundle_path: &str,
+        installed_version: Option<&str>,
+    ) -> Result<StageBundleMetadata, BundleError>;
+}
+
+/// A component cache write deferred until `FilesystemBundleVerifier::verify`
+/// has confirmed the bundle's signature threshold, so a bundle that fails
+/// verification never leaves anything behind in the shared
+/// [`ComponentStore`].
+enum PendingAdoption {
+    /// A component resolved from the staged bundle directory, identified by
+    /// path so `ComponentStore::adopt` can read it directly off disk.
+    Path { sha256: String, path: PathBuf },
+    /// A component reconstructed in memory by applying a delta patch.
+    Bytes { sha256: String, bytes: Vec<u8> },
 }
 
 pub struct FilesystemBundleVerifier {
-    verifying_key: VerifyingKey,
+    keys: Vec<TrustedKey>,
+    threshold: usize,
+    component_store: Option<Arc<dyn ComponentStore>>,
 }
 
 impl FilesystemBundleVerifier {
+    /// Builds a single-key verifier requiring that one key's signature (the
+    /// `M = 1` case).
     pub fn from_public_key_pem(path: impl AsRef<Path>) -> Result<Self, BundleError> {
-        let contents = std::fs::read(path)?;
-        let pem =
-            parse_pem(&contents).map_err(|err| BundleError::InvalidPublicKey(err.to_string()))?;
-        if pem.tag() != DEFAULT_PUBLIC_KEY_LABEL {
-            return Err(BundleError::UnexpectedPublicKeyLabel);
+        let verifying_key = parse_verifying_key_pem(&std::fs::read(path)?)?;
+        Self::from_keyset(
+            vec![TrustedKey {
+                key_id: "default".to_string(),
+                verifying_key,
+            }],
+            1,
+        )
+    }
+
+    /// Builds a quorum verifier that accepts a bundle once at least
+    /// `threshold` distinct keys in `keys` have produced valid signatures
+    /// over the checksum file.
+    pub fn from_keyset(keys: Vec<TrustedKey>, threshold: usize) -> Result<Self, BundleError> {
+        if threshold == 0 || threshold > keys.len() {
+            return Err(BundleError::ThresholdNotMet {
+                required: threshold,
+                found: keys.len(),
+            });
         }
-        let verifying_key = VerifyingKey::from_public_key_der(pem.contents())
-            .map_err(|err| BundleError::InvalidPublicKey(err.to_string()))?;
-        Ok(Self { verifying_key })
+        Ok(Self {
+            keys,
+            threshold,
+            component_store: None,
+        })
+    }
+
+    /// Lets unchanged components be resolved from a content-addressed cache
+    /// instead of requiring them in every bundle.
+    pub fn with_component_store(mut self, store: Arc<dyn ComponentStore>) -> Self {
+        self.component_store = Some(store);
+        self
     }
+
+    async fn resolve_from_store(&self, sha256: &str) -> Option<PathBuf> {
+        match &self.component_store {
+            Some(store) => store.resolve(sha256).await,
+            None => None,
+        }
+    }
+
+    /// Verifies `sig/signature.pem` and any `sig/signature-<key_id>.pem`
+    /// files against the keyset, returning the set of distinct key ids whose
+    /// signature over `checksum_bytes` validated.
+    async fn verify_signatures(
+        &self,
+        sig_dir: &Path,
+        checksum_bytes: &[u8],
+    ) -> Result<BTreeSet<String>, BundleError> {
+        let mut verified = BTreeSet::new();
+        let mut entries = fs::read_dir(sig_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+
+            if file_name == "signature.pem" {
+                let signature = read_signature_pem(&entry.path()).await?;
+                if let Some(key) = self
+                    .keys
+                    .iter()
+                    .find(|key| key.verifying_key.verify(checksum_bytes, &signature).is_ok())
+                {
+                    verified.insert(key.key_id.clone());
+                }
+            } else if let Some(key_id) = file_name
+                .strip_prefix("signature-")
+                .and_then(|rest| rest.strip_suffix(".pem"))
+            {
+                let signature = read_signature_pem(&entry.path()).await?;
+                if let Some(key) = self.keys.iter().find(|key| key.key_id == key_id) {
+                    if key.verifying_key.verify(checksum_bytes, &signature).is_ok() {
+                        verified.insert(key.key_id.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(verified)
+    }
+}
+
+async fn read_signature_pem(path: &Path) -> Result<Signature, BundleError> {
+    let bytes = fs::read(path).await?;
+    let pem = parse_pem(&bytes).map_err(|err| BundleError::InvalidSignature(err.to_string()))?;
+    if pem.tag() != SIGNATURE_PEM_LABEL {
+        return Err(BundleError::UnexpectedSignatureLabel);
+    }
+    let signature_array: [u8; 64] = pem
+        .contents()
+        .try_into()
+        .map_err(|_| BundleError::InvalidSignatureLength)?;
+    Ok(Signature::from_bytes(&signature_array))
 }
 
 #[async_trait]
 impl BundleVerifier for FilesystemBundleVerifier {
-    async fn verify(&self, bundle_path: &str) -> Result<StageBundleMetadata, BundleError> {
+    async fn verify(
+        &self,
+        bundle_path: &str,
+        installed_version: Option<&str>,
+    ) -> Result<StageBundleMetadata, BundleError> {
         let root = Path::new(bundle_path);
         let metadata = fs::metadata(root).await;
         let metadata = match metadata {
@@ -141,27 +395,34 @@ impl BundleVerifier for FilesystemBundleVerifier {
             return Err(BundleError::EmptyComponents);
         }
 
+        if let Some(base_version) = &manifest.base_version {
+            if installed_version != Some(base_version.as_str()) {
+                return Err(BundleError::DeltaBaseMismatch {
+                    expected: base_version.clone(),
+                    found: installed_version.map(str::to_string),
+                });
+            }
+        }
+
         let checksum_path = root.join("sig/sha256sum");
         let checksum_bytes = fs::read(&checksum_path).await?;
         let checksum_str = std::str::from_utf8(&checksum_bytes)
             .map_err(|err| BundleError::InvalidChecksumEncoding(err.to_string()))?;
         let mut checksum_entries = parse_sha256sum(checksum_str)?;
 
+        // Components destined for the content-addressed cache are staged
+        // here rather than adopted immediately: adopting as each component
+        // is checksummed would let a bundle that ultimately fails the
+        // signature threshold below still leave its components in the
+        // shared cache. They're only handed to `component_store` once the
+        // whole bundle (checksums and signatures) has verified.
+        let mut pending_adoptions: Vec<PendingAdoption> = Vec::new();
+
         for component in &manifest.components {
             validate_relative_path(&component.path)?;
+            let expected_checksum = component.sha256.to_lowercase();
             let component_path = root.join(&component.path);
-            let component_metadata = fs::metadata(&component_path).await.map_err(|err| {
-                if err.kind() == std::io::ErrorKind::NotFound {
-                    BundleError::MissingComponentFile(component.path.clone())
-                } else {
-                    BundleError::Io(err)
-                }
-            })?;
-            if !component_metadata.is_file() {
-                return Err(BundleError::MissingComponentFile(component.path.clone()));
-            }
 
-            let expected_checksum = component.sha256.to_lowercase();
             let checksum_entry = checksum_entries
                 .remove(&component.path)
                 .ok_or_else(|| BundleError::MissingChecksumEntry(component.path.clone()))?;
@@ -173,7 +434,47 @@ impl BundleVerifier for FilesystemBundleVerifier {
                 });
             }
 
-            let actual_checksum = compute_sha256(&component_path).await?;
+            if let Some(base_sha256) = &component.patch_base_sha256 {
+                let store = self
+                    .component_store
+                    .as_ref()
+                    .ok_or_else(|| BundleError::ComponentStoreRequired(component.path.clone()))?;
+                let base_path = store
+                    .resolve(base_sha256)
+                    .await
+                    .ok_or_else(|| BundleError::MissingBaseComponent(base_sha256.clone()))?;
+                let patch_bytes = fs::read(&component_path)
+                    .await
+                    .map_err(|_| BundleError::MissingComponentFile(component.path.clone()))?;
+                let base_bytes = fs::read(&base_path).await?;
+                let reconstructed = apply_delta_patch(&base_bytes, &patch_bytes)?;
+
+                let actual_checksum = format!("{:x}", Sha256::digest(&reconstructed));
+                if actual_checksum != expected_checksum {
+                    return Err(BundleError::ChecksumMismatch {
+                        path: component.path.clone(),
+                        expected: expected_checksum,
+                        actual: actual_checksum,
+                    });
+                }
+
+                pending_adoptions.push(PendingAdoption::Bytes {
+                    sha256: expected_checksum,
+                    bytes: reconstructed,
+                });
+                COMPONENTS_PATCHED_TOTAL.inc(&[], 1);
+                continue;
+            }
+
+            let (resolved_path, reused) = match fs::metadata(&component_path).await {
+                Ok(metadata) if metadata.is_file() => (component_path, false),
+                Ok(_) | Err(_) => match self.resolve_from_store(&expected_checksum).await {
+                    Some(cached_path) => (cached_path, true),
+                    None => return Err(BundleError::MissingComponentFile(component.path.clone())),
+                },
+            };
+
+            let actual_checksum = compute_sha256(&resolved_path).await?;
             if actual_checksum != expected_checksum {
                 return Err(BundleError::ChecksumMismatch {
                     path: component.path.clone(),
@@ -181,34 +482,108 @@ impl BundleVerifier for FilesystemBundleVerifier {
                     actual: actual_checksum,
                 });
             }
+
+            if reused {
+                COMPONENTS_REUSED_TOTAL.inc(&[], 1);
+            } else {
+                COMPONENTS_FETCHED_TOTAL.inc(&[], 1);
+                if self.component_store.is_some() {
+                    pending_adoptions.push(PendingAdoption::Path {
+                        sha256: expected_checksum,
+                        path: resolved_path,
+                    });
+                }
+            }
         }
 
         if let Some((unexpected_path, _)) = checksum_entries.into_iter().next() {
             return Err(BundleError::UnexpectedChecksumEntry(unexpected_path));
         }
 
-        let signature_path = root.join("sig/signature.pem");
-        let signature_bytes = fs::read(&signature_path).await?;
-        let signature_pem = parse_pem(&signature_bytes)
-            .map_err(|err| BundleError::InvalidSignature(err.to_string()))?;
-        if signature_pem.tag() != SIGNATURE_PEM_LABEL {
-            return Err(BundleError::UnexpectedSignatureLabel);
+        let sig_dir = root.join("sig");
+        let verified_key_ids = self.verify_signatures(&sig_dir, &checksum_bytes).await?;
+        if verified_key_ids.len() < self.threshold {
+            return Err(BundleError::ThresholdNotMet {
+                required: self.threshold,
+                found: verified_key_ids.len(),
+            });
         }
 
-        let signature_array: [u8; 64] = signature_pem
-            .contents()
-            .try_into()
-            .map_err(|_| BundleError::InvalidSignatureLength)?;
-        let signature = Signature::from_bytes(&signature_array);
-
-        self.verifying_key
-            .verify(&checksum_bytes, &signature)
-            .map_err(|_| BundleError::SignatureMismatch)?;
+        if let Some(store) = &self.component_store {
+            for adoption in pending_adoptions {
+                match adoption {
+                    PendingAdoption::Bytes { sha256, bytes } => {
+                        store.store_bytes(&sha256, &bytes).await?
+                    }
+                    PendingAdoption::Path { sha256, path } => store.adopt(&sha256, &path).await?,
+                }
+            }
+        }
 
         Ok(StageBundleMetadata::new(manifest))
     }
 }
 
+/// Applies a minimal bsdiff-style binary patch, reconstructing the target
+/// bytes from `base` plus the ops encoded in `patch`.
+///
+/// Layout (little-endian): a 4-byte magic `b"LKPD"`, followed by a sequence
+/// of `(copy_len: u32, insert_len: u32, insert_bytes)` ops. Applying an op
+/// copies `copy_len` bytes from the current cursor in `base`, then appends
+/// `insert_bytes` verbatim; this mirrors bsdiff's copy/insert control
+/// stream without requiring the base and patch sizes to match.
+fn apply_delta_patch(base: &[u8], patch: &[u8]) -> Result<Vec<u8>, BundleError> {
+    const MAGIC: &[u8; 4] = b"LKPD";
+
+    if patch.len() < MAGIC.len() || &patch[..MAGIC.len()] != MAGIC {
+        return Err(BundleError::InvalidPatch(
+            "missing or invalid patch magic".to_string(),
+        ));
+    }
+
+    let mut cursor = MAGIC.len();
+    let mut base_pos = 0usize;
+    let mut output = Vec::new();
+
+    while cursor < patch.len() {
+        let copy_len = read_u32(patch, &mut cursor)? as usize;
+        let insert_len = read_u32(patch, &mut cursor)? as usize;
+
+        let copy_end = base_pos
+            .checked_add(copy_len)
+            .ok_or_else(|| BundleError::InvalidPatch("copy range overflows".to_string()))?;
+        let copy_slice = base.get(base_pos..copy_end).ok_or_else(|| {
+            BundleError::InvalidPatch("copy range exceeds base component".to_string())
+        })?;
+        output.extend_from_slice(copy_slice);
+        base_pos = copy_end;
+
+        let insert_end = cursor
+            .checked_add(insert_len)
+            .ok_or_else(|| BundleError::InvalidPatch("insert range overflows".to_string()))?;
+        let insert_slice = patch.get(cursor..insert_end).ok_or_else(|| {
+            BundleError::InvalidPatch("insert range exceeds patch bytes".to_string())
+        })?;
+        output.extend_from_slice(insert_slice);
+        cursor = insert_end;
+    }
+
+    Ok(output)
+}
+
+fn read_u32(bytes: &[u8], cursor: &mut usize) -> Result<u32, BundleError> {
+    let end = cursor
+        .checked_add(4)
+        .ok_or_else(|| BundleError::InvalidPatch("truncated patch op".to_string()))?;
+    let slice = bytes
+        .get(*cursor..end)
+        .ok_or_else(|| BundleError::InvalidPatch("truncated patch op".to_string()))?;
+    *cursor = end;
+    Ok(u32::from_le_bytes(
+        slice.try_into().expect("slice is 4 bytes"),
+    ))
+}
+
 fn parse_pem(bytes: &[u8]) -> Result<pem::Pem, pem::PemError> {
     pem::parse(bytes)
 }
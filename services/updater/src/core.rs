@@ -1,12 +1,28 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::Mutex;
+use async_trait::async_trait;
+use chrono::Utc;
+use common_msgbus::MessageBus;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
 
-use crate::bundle::BundleVerifier;
+use crate::bundle::{BundleError, BundleVerifier};
+use crate::events::UpdateEvent;
 use crate::health::{HealthCheckError, HealthClient};
-use crate::state::{CommitError, RollbackError, Slot, StageError, UpdaterState};
-use crate::store::{StateStore, StateStoreError};
+use crate::report::{HealthProbeResult, ReportOperation, ResultCode, UpdateReport};
+use crate::state::{CommitError, PendingWatchdog, RollbackError, Slot, StageError, UpdaterState};
+use crate::store::{StateStore, StateStoreError, StateTransition};
+
+/// Subject reports are published to; mirrors `lokan.audit.rbac` in
+/// `lib.rs` for the same best-effort "log always, publish when connected"
+/// treatment of the message bus.
+const UPDATE_REPORT_SUBJECT: &str = "lokan.updater.reports";
+
+/// Backlog kept per [`UpdaterCore::subscribe_events`] receiver before the
+/// oldest event is dropped; generous relative to a single stage/commit cycle
+/// so a briefly lagging client doesn't miss progress.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
 
 #[derive(Debug, thiserror::Error)]
 pub enum UpdaterError {
@@ -20,8 +36,37 @@ pub enum UpdaterError {
     Store(#[from] StateStoreError),
     #[error(transparent)]
     Health(#[from] HealthCheckError),
-    #[error("health check quorum not satisfied before deadline")]
-    HealthQuorumFailed,
+}
+
+/// Result of [`UpdaterCore::commit_on_health`]: either the staged slot
+/// reached health quorum and was promoted, or the deadline expired and the
+/// updater automatically reverted it, leaving the previous slot active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitOutcome {
+    Committed(Slot),
+    RollbackPerformed {
+        failed_slot: Slot,
+        active_slot: Slot,
+    },
+}
+
+/// Abstracts the passage of time between [`UpdaterCore::watch_after_commit`]
+/// polling ticks, so a test can drive the watch through every tick of a
+/// window without actually waiting it out.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    async fn sleep(&self, duration: Duration);
+}
+
+/// [`Clock`] that sleeps for real; what `UpdaterCore` uses outside tests.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
 }
 
 #[derive(Clone)]
@@ -33,6 +78,9 @@ pub struct UpdaterCore {
     health_deadline: Duration,
     health_quorum: usize,
     bundle_verifier: Arc<dyn BundleVerifier>,
+    events: broadcast::Sender<UpdateEvent>,
+    bus: Option<Arc<dyn MessageBus>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl UpdaterCore {
@@ -43,13 +91,28 @@ impl UpdaterCore {
         health_deadline: Duration,
         health_quorum: usize,
         bundle_verifier: Arc<dyn BundleVerifier>,
+        bus: Option<Arc<dyn MessageBus>>,
     ) -> Result<Self, UpdaterError> {
-        let state = match store.load().await? {
+        let mut state = match store.load().await? {
             Some(state) => state,
             None => UpdaterState::default(),
         };
+        if state.reconcile_after_restart() {
+            tracing::warn!(
+                "found a slot still Booting on restart; treating its commit as failed"
+            );
+            store.save(&state).await?;
+        }
+        if state.watchdog_expired(Utc::now()) {
+            tracing::warn!(
+                "found a slot still Confirming past its deadline on restart; rolling back"
+            );
+            store.save(&state).await?;
+        }
+        let pending_watchdog = state.pending_watchdog.clone();
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
-        Ok(Self {
+        let core = Self {
             state: Arc::new(Mutex::new(state)),
             store,
             health_client,
@@ -57,33 +120,237 @@ impl UpdaterCore {
             health_deadline,
             health_quorum,
             bundle_verifier,
-        })
+            events,
+            bus,
+            clock: Arc::new(SystemClock),
+        };
+
+        if let Some(pending) = pending_watchdog {
+            core.resume_watchdog(pending);
+        }
+
+        Ok(core)
+    }
+
+    /// Resumes a [`PendingWatchdog`] loaded from [`StateStore::load`],
+    /// e.g. after a crash or restart mid-window. Runs for whatever window
+    /// remains (at least one `interval`, so an already-elapsed window still
+    /// gets a final health check before being confirmed) rather than the
+    /// original full `window`.
+    fn resume_watchdog(&self, pending: PendingWatchdog) {
+        let elapsed = Utc::now()
+            .signed_duration_since(pending.started_at)
+            .to_std()
+            .unwrap_or(Duration::ZERO);
+        let interval = Duration::from_secs(pending.interval_secs.max(1));
+        let remaining = Duration::from_secs(pending.window_secs)
+            .saturating_sub(elapsed)
+            .max(interval);
+
+        tracing::info!(
+            slot = ?pending.slot,
+            remaining_secs = remaining.as_secs(),
+            "resuming post-commit watchdog after restart",
+        );
+        // Dropping the handle immediately leaves the watch running in the
+        // background for the rest of its window; nothing here needs to
+        // cancel it early or await its verdict.
+        let _ = self.watch_after_commit(remaining, interval, pending.quorum);
+    }
+
+    /// Overrides the [`Clock`] driving [`Self::watch_after_commit`]'s
+    /// polling interval. Only ever needed by tests; production callers get
+    /// [`SystemClock`] from [`Self::new`].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 
     pub async fn state(&self) -> UpdaterState {
         self.state.lock().await.clone()
     }
 
+    /// Subscribes to live progress events as this core advances through
+    /// stage/commit/rollback. Events published before this call are not
+    /// replayed; a lagging receiver skips ahead rather than blocking
+    /// publishers, per [`broadcast::Receiver`]'s usual semantics.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<UpdateEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish_event(&self, event: UpdateEvent) {
+        // No subscribers is the common case outside of a live SSE client;
+        // a send error there is expected, not worth logging.
+        let _ = self.events.send(event);
+    }
+
+    /// Persists a report to the [`StateStore`] and publishes it to the
+    /// message bus (best-effort, mirroring [`crate::RbacGuardState`]'s
+    /// "log always, publish when connected" treatment) so operators can
+    /// audit why an operation was rejected without scraping logs.
+    async fn record_report(&self, report: UpdateReport) {
+        if let Err(err) = self.store.append_report(&report).await {
+            tracing::warn!(%err, "failed to persist update report");
+        }
+
+        let Some(bus) = &self.bus else {
+            return;
+        };
+        match serde_json::to_vec(&report) {
+            Ok(bytes) => {
+                if let Err(err) = bus.publish(UPDATE_REPORT_SUBJECT, &bytes).await {
+                    tracing::warn!(%err, subject = UPDATE_REPORT_SUBJECT, "failed to publish update report");
+                }
+            }
+            Err(err) => tracing::warn!(%err, "failed to serialize update report"),
+        }
+    }
+
+    /// Returns the most recently recorded reports, newest first, so an
+    /// operator can see why a stage/commit/rollback was rejected.
+    pub async fn list_reports(&self, limit: usize) -> Result<Vec<UpdateReport>, UpdaterError> {
+        Ok(self.store.list_reports(limit).await?)
+    }
+
     pub async fn stage(&self, artifact: String) -> Result<Slot, UpdaterError> {
-        let metadata = self
+        let started_at = Utc::now();
+        self.publish_event(UpdateEvent::Staging);
+
+        let installed_version = self.state.lock().await.active_version();
+
+        let metadata = match self
             .bundle_verifier
-            .verify(&artifact)
+            .verify(&artifact, installed_version.as_deref())
             .await
-            .map_err(|err| StageError::InvalidBundle(err.to_string()))?;
+        {
+            Ok(metadata) => metadata,
+            Err(BundleError::DeltaBaseMismatch { expected, found }) => {
+                let reason = format!(
+                    "delta bundle expects base version {expected:?}, active slot reports {found:?}"
+                );
+                self.publish_event(UpdateEvent::Failed {
+                    reason: reason.clone(),
+                });
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Stage,
+                    result: ResultCode::DeltaBaseMismatch,
+                    artifact: Some(artifact),
+                    source_slot: None,
+                    target_slot: None,
+                    message: reason,
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                return Err(StageError::DeltaBaseMismatch { expected, found }.into());
+            }
+            Err(err) => {
+                let reason = err.to_string();
+                self.publish_event(UpdateEvent::Failed {
+                    reason: reason.clone(),
+                });
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Stage,
+                    result: bundle_error_result_code(&err),
+                    artifact: Some(artifact),
+                    source_slot: None,
+                    target_slot: None,
+                    message: reason.clone(),
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                return Err(StageError::InvalidBundle(reason).into());
+            }
+        };
+        self.publish_event(UpdateEvent::BundleVerified);
 
         let mut state = self.state.lock().await;
-        let slot = state.stage(artifact, Some(metadata.target_slot()))?;
-        self.store.save(&state).await?;
-        Ok(slot)
+        let source_slot = state.active;
+        let target_slot = Some(metadata.target_slot());
+        match state.stage(
+            artifact.clone(),
+            Some(metadata.manifest().version.clone()),
+            metadata.manifest().security_version,
+            target_slot,
+        ) {
+            Ok(slot) => {
+                self.store
+                    .append_intent(&StateTransition::Stage {
+                        slot,
+                        artifact: artifact.clone(),
+                    })
+                    .await?;
+                self.store.save(&state).await?;
+                drop(state);
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Stage,
+                    result: ResultCode::Success,
+                    artifact: Some(artifact),
+                    source_slot,
+                    target_slot: Some(slot),
+                    message: "bundle verified and staged".to_string(),
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                Ok(slot)
+            }
+            Err(err) => {
+                drop(state);
+                let result = match &err {
+                    StageError::RollbackProtection { .. } => ResultCode::RollbackProtection,
+                    _ => ResultCode::InvalidState,
+                };
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Stage,
+                    result,
+                    artifact: Some(artifact),
+                    source_slot,
+                    target_slot,
+                    message: err.to_string(),
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                Err(err.into())
+            }
+        }
     }
 
-    pub async fn commit_on_health(&self) -> Result<Slot, UpdaterError> {
+    pub async fn commit_on_health(&self) -> Result<CommitOutcome, UpdaterError> {
+        let started_at = Utc::now();
         let slot = {
             let mut state = self.state.lock().await;
             let slot = state.begin_commit()?;
+            self.store
+                .append_intent(&StateTransition::BeginCommit { slot })
+                .await?;
             self.store.save(&state).await?;
             slot
         };
+        self.publish_event(UpdateEvent::Committing);
+
+        let health_probes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let probes_for_observer = health_probes.clone();
+        let events = self.events.clone();
+        let on_probe = move |endpoint: &str, ok: bool| {
+            let _ = events.send(UpdateEvent::HealthProbe {
+                endpoint: endpoint.to_string(),
+                ok,
+            });
+            probes_for_observer
+                .lock()
+                .expect("health probe log lock poisoned")
+                .push(HealthProbeResult {
+                    endpoint: endpoint.to_string(),
+                    healthy: ok,
+                });
+        };
 
         let healthy = self
             .health_client
@@ -91,18 +358,194 @@ impl UpdaterCore {
                 self.health_endpoints.as_ref(),
                 self.health_deadline,
                 self.health_quorum,
+                Some(&on_probe),
             )
             .await?;
 
+        let probes = health_probes
+            .lock()
+            .expect("health probe log lock poisoned")
+            .clone();
+
         let mut state = self.state.lock().await;
+        let artifact = state
+            .slots
+            .get(&slot)
+            .and_then(|info| info.artifact.clone());
         if healthy {
+            self.publish_event(UpdateEvent::QuorumReached);
+            let source_slot = state.active;
             state.finalize_commit(slot);
+            self.store
+                .append_intent(&StateTransition::FinalizeCommit { slot })
+                .await?;
             self.store.save(&state).await?;
-            Ok(slot)
+            drop(state);
+            self.publish_event(UpdateEvent::Committed);
+            self.record_report(UpdateReport {
+                operation: ReportOperation::Commit,
+                result: ResultCode::Success,
+                artifact,
+                source_slot,
+                target_slot: Some(slot),
+                message: "commit succeeded after health quorum reached".to_string(),
+                health_probes: probes,
+                started_at,
+                finished_at: Utc::now(),
+            })
+            .await;
+            Ok(CommitOutcome::Committed(slot))
         } else {
             state.fail_commit(slot);
+            let active_slot = state.auto_rollback(slot);
+            self.store
+                .append_intent(&StateTransition::FailCommit { slot })
+                .await?;
+            self.store.save(&state).await?;
+            drop(state);
+            self.publish_event(UpdateEvent::Failed {
+                reason: "health quorum not reached before deadline".to_string(),
+            });
+            self.publish_event(UpdateEvent::RolledBack);
+            self.record_report(UpdateReport {
+                operation: ReportOperation::Commit,
+                result: ResultCode::HealthQuorumFailed,
+                artifact,
+                source_slot: Some(slot),
+                target_slot: Some(active_slot),
+                message: "health quorum not reached before deadline; rolled back".to_string(),
+                health_probes: probes,
+                started_at,
+                finished_at: Utc::now(),
+            })
+            .await;
+            Ok(CommitOutcome::RollbackPerformed {
+                failed_slot: slot,
+                active_slot,
+            })
+        }
+    }
+
+    /// Combines [`Self::commit_on_health`] with [`Self::watch_after_commit`]:
+    /// once the commit itself reaches quorum, the new slot is
+    /// committed-provisional until `window` elapses without a quorum drop,
+    /// at which point it becomes committed-confirmed. Unlike calling the two
+    /// separately, the observation window is persisted via
+    /// [`PendingWatchdog`] before the watch is spawned, so a crash mid-window
+    /// resumes it on the next [`Self::new`] instead of leaving the slot
+    /// stuck (or silently treated as confirmed).
+    ///
+    /// Returns the commit's own outcome alongside a [`WatchHandle`] for the
+    /// spawned watch, or `None` if the commit itself didn't reach quorum
+    /// (there is nothing to watch).
+    pub async fn commit_with_watchdog(
+        &self,
+        window: Duration,
+        interval: Duration,
+    ) -> Result<(CommitOutcome, Option<WatchHandle>), UpdaterError> {
+        let outcome = self.commit_on_health().await?;
+        let CommitOutcome::Committed(slot) = outcome else {
+            return Ok((outcome, None));
+        };
+
+        let started_at = Utc::now();
+        {
+            let mut state = self.state.lock().await;
+            state.begin_watchdog(PendingWatchdog {
+                slot,
+                window_secs: window.as_secs(),
+                interval_secs: interval.as_secs(),
+                quorum: self.health_quorum,
+                started_at,
+            });
             self.store.save(&state).await?;
-            Err(UpdaterError::HealthQuorumFailed)
+        }
+
+        let handle = self.watch_after_commit(window, interval, self.health_quorum);
+        Ok((CommitOutcome::Committed(slot), Some(handle)))
+    }
+
+    /// Keeps polling [`HealthClient::wait_for_quorum`] on `interval` for the
+    /// duration of `window` after a successful commit. If any tick drops
+    /// below `quorum`, the watch auto-rolls back the same way an operator
+    /// would: [`Self::mark_bad`] followed by [`Self::rollback`]. Callers
+    /// that gain confidence before `window` elapses can end the watch early
+    /// via [`WatchHandle::cancel`].
+    pub fn watch_after_commit(
+        &self,
+        window: Duration,
+        interval: Duration,
+        quorum: usize,
+    ) -> WatchHandle {
+        let (cancel, mut cancel_rx) = broadcast::channel(1);
+        let core = self.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut elapsed = Duration::ZERO;
+            while elapsed < window {
+                tokio::select! {
+                    _ = core.clock.sleep(interval) => {}
+                    _ = cancel_rx.recv() => {
+                        core.confirm_watchdog().await;
+                        return;
+                    }
+                }
+                elapsed += interval;
+
+                let healthy = match core
+                    .health_client
+                    .wait_for_quorum(
+                        core.health_endpoints.as_ref(),
+                        core.health_deadline,
+                        quorum,
+                        None,
+                    )
+                    .await
+                {
+                    Ok(healthy) => healthy,
+                    Err(err) => {
+                        tracing::warn!(%err, "post-commit health watch probe failed, retrying next tick");
+                        continue;
+                    }
+                };
+
+                if healthy {
+                    continue;
+                }
+
+                core.publish_event(UpdateEvent::Failed {
+                    reason: "post-commit health watch detected a quorum drop".to_string(),
+                });
+                if let Err(err) = core.mark_bad().await {
+                    tracing::warn!(%err, "post-commit health watch failed to mark the active slot bad");
+                    return;
+                }
+                if let Err(err) = core.rollback().await {
+                    tracing::warn!(%err, "post-commit health watch failed to roll back");
+                }
+                return;
+            }
+
+            core.confirm_watchdog().await;
+            core.publish_event(UpdateEvent::WatchdogConfirmed);
+        });
+
+        WatchHandle { cancel, handle }
+    }
+
+    /// Promotes the active slot out of `Confirming` and clears whatever
+    /// [`PendingWatchdog`] is set, marking it committed-confirmed.
+    /// Best-effort: a store error is logged rather than propagated, since
+    /// this only ever runs from inside a spawned watch task with no
+    /// caller left to hand an error to.
+    async fn confirm_watchdog(&self) {
+        let mut state = self.state.lock().await;
+        if let Some(active) = state.active {
+            state.confirm_boot(active);
+        }
+        state.confirm_watchdog();
+        if let Err(err) = self.store.save(&state).await {
+            tracing::warn!(%err, "failed to persist watchdog confirmation");
         }
     }
 
@@ -114,9 +557,84 @@ impl UpdaterCore {
     }
 
     pub async fn rollback(&self) -> Result<Slot, UpdaterError> {
+        let started_at = Utc::now();
         let mut state = self.state.lock().await;
-        let slot = state.rollback()?;
-        self.store.save(&state).await?;
-        Ok(slot)
+        let source_slot = state.active;
+        match state.rollback() {
+            Ok(slot) => {
+                self.store.append_intent(&StateTransition::Rollback).await?;
+                self.store.save(&state).await?;
+                drop(state);
+                self.publish_event(UpdateEvent::RolledBack);
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Rollback,
+                    result: ResultCode::Success,
+                    artifact: None,
+                    source_slot,
+                    target_slot: Some(slot),
+                    message: "rollback to previous active slot succeeded".to_string(),
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                Ok(slot)
+            }
+            Err(err) => {
+                drop(state);
+                self.record_report(UpdateReport {
+                    operation: ReportOperation::Rollback,
+                    result: ResultCode::InvalidState,
+                    artifact: None,
+                    source_slot,
+                    target_slot: None,
+                    message: err.to_string(),
+                    health_probes: Vec::new(),
+                    started_at,
+                    finished_at: Utc::now(),
+                })
+                .await;
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Handle to a post-commit health watch spawned by
+/// [`UpdaterCore::watch_after_commit`]. Dropping it leaves the watch running
+/// in the background for the rest of its window.
+pub struct WatchHandle {
+    cancel: broadcast::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl WatchHandle {
+    /// Ends the watch window early, e.g. once an operator has otherwise
+    /// confirmed the commit is healthy.
+    pub async fn cancel(self) {
+        let _ = self.cancel.send(());
+        let _ = self.handle.await;
+    }
+}
+
+/// Maps a bundle verification failure onto the coarser [`ResultCode`] used
+/// in reports, grouping variants the same way an operator would when
+/// triaging a rejected update.
+fn bundle_error_result_code(err: &BundleError) -> ResultCode {
+    match err {
+        BundleError::SignatureMismatch
+        | BundleError::InvalidSignature(_)
+        | BundleError::InvalidSignatureLength
+        | BundleError::UnexpectedSignatureLabel
+        | BundleError::InvalidPublicKey(_)
+        | BundleError::UnexpectedPublicKeyLabel
+        | BundleError::ThresholdNotMet { .. } => ResultCode::SignatureInvalid,
+        BundleError::ChecksumMismatch { .. }
+        | BundleError::MissingChecksumEntry(_)
+        | BundleError::UnexpectedChecksumEntry(_)
+        | BundleError::InvalidChecksumFormat { .. }
+        | BundleError::InvalidChecksumEncoding(_) => ResultCode::ChecksumMismatch,
+        BundleError::DeltaBaseMismatch { .. } => ResultCode::DeltaBaseMismatch,
+        _ => ResultCode::Other,
     }
 }
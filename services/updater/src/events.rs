@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+/// Progress event published by [`crate::UpdaterCore`] as it advances through
+/// a stage/commit/rollback cycle, so a UI or CLI can follow along over
+/// `GET /v1/update/events` instead of polling `GET /v1/update/status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UpdateEvent {
+    Staging,
+    BundleVerified,
+    Committing,
+    HealthProbe { endpoint: String, ok: bool },
+    QuorumReached,
+    Committed,
+    WatchdogConfirmed,
+    RolledBack,
+    Failed { reason: String },
+}
+
+impl UpdateEvent {
+    /// SSE `event:` name for this variant, so clients can filter without
+    /// parsing the JSON `kind` field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpdateEvent::Staging => "staging",
+            UpdateEvent::BundleVerified => "bundle_verified",
+            UpdateEvent::Committing => "committing",
+            UpdateEvent::HealthProbe { .. } => "health_probe",
+            UpdateEvent::QuorumReached => "quorum_reached",
+            UpdateEvent::Committed => "committed",
+            UpdateEvent::WatchdogConfirmed => "watchdog_confirmed",
+            UpdateEvent::RolledBack => "rolled_back",
+            UpdateEvent::Failed { .. } => "failed",
+        }
+    }
+}
@@ -1,16 +1,210 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use rand::Rng;
 use reqwest::Client;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use tokio::time::sleep;
 
+/// Upper bound on in-flight health probes per polling round.
+const MAX_CONCURRENT_PROBES: usize = 8;
+/// Per-request timeout so a single hung endpoint can't stall the deadline.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Ceiling on the exponential backoff between polling rounds.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, thiserror::Error)]
 pub enum HealthCheckError {
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
+    #[error("failed to build pinned TLS client: {0}")]
+    TlsConfig(String),
+    #[error("certificate pin mismatch for {endpoint}")]
+    PinMismatch { endpoint: String },
+}
+
+/// A SHA-256 fingerprint of a certificate's DER-encoded SubjectPublicKeyInfo.
+pub type Spki256 = [u8; 32];
+
+fn spki_fingerprint(
+    cert: &rustls::pki_types::CertificateDer<'_>,
+) -> Result<Spki256, HealthCheckError> {
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref())
+        .map_err(|err| HealthCheckError::TlsConfig(err.to_string()))?;
+    let spki_der = parsed.tbs_certificate.subject_pki.raw;
+    let mut hasher = Sha256::new();
+    hasher.update(spki_der);
+    Ok(hasher.finalize().into())
+}
+
+/// Rejects any certificate whose SubjectPublicKeyInfo fingerprint isn't in a
+/// statically configured pin set.
+#[derive(Debug)]
+struct StaticPinVerifier {
+    pins: Vec<Spki256>,
+}
+
+impl rustls::client::danger::ServerCertVerifier for StaticPinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        let fingerprint =
+            spki_fingerprint(end_entity).map_err(|err| rustls::Error::General(err.to_string()))?;
+        if self.pins.contains(&fingerprint) {
+            Ok(rustls::client::danger::ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "certificate pin mismatch".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        rustls::crypto::ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Trust-on-first-use: records the SPKI fingerprint seen for each server
+/// name and rejects any later handshake that presents a different one.
+#[derive(Debug, Default)]
+struct TofuPinVerifier {
+    seen: RwLock<HashMap<String, Spki256>>,
 }
 
+impl rustls::client::danger::ServerCertVerifier for TofuPinVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        let fingerprint =
+            spki_fingerprint(end_entity).map_err(|err| rustls::Error::General(err.to_string()))?;
+        let name = server_name_key(server_name);
+
+        let mut seen = self.seen.write().expect("lock poisoned");
+        match seen.get(&name) {
+            Some(pinned) if *pinned == fingerprint => {
+                Ok(rustls::client::danger::ServerCertVerified::assertion())
+            }
+            Some(_) => Err(rustls::Error::General(format!(
+                "TOFU pin mismatch for {name}"
+            ))),
+            None => {
+                seen.insert(name, fingerprint);
+                Ok(rustls::client::danger::ServerCertVerified::assertion())
+            }
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        rustls::crypto::ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn server_name_key(server_name: &rustls::pki_types::ServerName<'_>) -> String {
+    format!("{server_name:?}")
+}
+
+fn client_with_tls_config(config: rustls::ClientConfig) -> Result<Client, HealthCheckError> {
+    Client::builder()
+        .use_preconfigured_tls(config)
+        .build()
+        .map_err(|err| HealthCheckError::TlsConfig(err.to_string()))
+}
+
+/// Recovers [`HealthCheckError::PinMismatch`] from the opaque
+/// `reqwest::Error` raised when a pinned/TOFU `ServerCertVerifier` rejects a
+/// handshake.
+fn classify_transport_error(err: reqwest::Error, endpoint: &str) -> HealthCheckError {
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        if cause.to_string().contains("pin mismatch") {
+            return HealthCheckError::PinMismatch {
+                endpoint: endpoint.to_string(),
+            };
+        }
+        source = cause.source();
+    }
+    HealthCheckError::Http(err)
+}
+
+/// Callback invoked with each endpoint's probe result as it resolves, so a
+/// caller can surface progress (e.g. as [`crate::events::UpdateEvent::HealthProbe`])
+/// without waiting for the whole quorum round to finish.
+pub type ProbeObserver<'a> = &'a (dyn Fn(&str, bool) + Send + Sync);
+
 #[async_trait]
 pub trait HealthClient: Send + Sync {
     async fn wait_for_quorum(
@@ -18,6 +212,7 @@ pub trait HealthClient: Send + Sync {
         endpoints: &[String],
         deadline: Duration,
         quorum: usize,
+        on_probe: Option<ProbeObserver<'_>>,
     ) -> Result<bool, HealthCheckError>;
 }
 
@@ -25,6 +220,7 @@ pub trait HealthClient: Send + Sync {
 pub struct HttpHealthClient {
     client: Client,
     poll_interval: Duration,
+    request_timeout: Duration,
 }
 
 impl Default for HttpHealthClient {
@@ -38,7 +234,72 @@ impl HttpHealthClient {
         Self {
             client: Client::builder().build().expect("reqwest client"),
             poll_interval,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Builds a client that only trusts servers presenting one of the given
+    /// SPKI SHA-256 fingerprints, bypassing normal CA-chain validation.
+    pub fn with_pins(
+        poll_interval: Duration,
+        pins: Vec<Spki256>,
+    ) -> Result<Self, HealthCheckError> {
+        let config = rustls::ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(StaticPinVerifier { pins }))
+            .with_no_client_auth();
+        Ok(Self {
+            client: client_with_tls_config(config)?,
+            poll_interval,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        })
+    }
+
+    /// Builds a trust-on-first-use client: the first certificate seen for a
+    /// server name is pinned, and later handshakes presenting a different
+    /// fingerprint are rejected with [`HealthCheckError::PinMismatch`].
+    pub fn with_tofu(poll_interval: Duration) -> Result<Self, HealthCheckError> {
+        let config = rustls::ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(TofuPinVerifier::default()))
+            .with_no_client_auth();
+        Ok(Self {
+            client: client_with_tls_config(config)?,
+            poll_interval,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        })
+    }
+
+    /// Probes a single endpoint. A certificate pin mismatch is propagated as
+    /// a hard error since it signals a potentially hostile endpoint; any
+    /// other transport failure, non-success status, or unparsable/non-"ok"
+    /// body is treated as "not healthy this round" rather than aborting the
+    /// whole poll.
+    async fn probe(&self, endpoint: &str) -> Result<bool, HealthCheckError> {
+        let response = match self
+            .client
+            .get(endpoint)
+            .timeout(self.request_timeout)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                return match classify_transport_error(err, endpoint) {
+                    err @ HealthCheckError::PinMismatch { .. } => Err(err),
+                    _ => Ok(false),
+                }
+            }
+        };
+
+        if !response.status().is_success() {
+            return Ok(false);
         }
+
+        Ok(matches!(
+            response.json::<HealthResponse>().await,
+            Ok(body) if body.status.eq_ignore_ascii_case("ok")
+        ))
     }
 }
 
@@ -54,6 +315,7 @@ impl HealthClient for HttpHealthClient {
         endpoints: &[String],
         deadline: Duration,
         quorum: usize,
+        on_probe: Option<ProbeObserver<'_>>,
     ) -> Result<bool, HealthCheckError> {
         if quorum == 0 || endpoints.is_empty() {
             return Ok(true);
@@ -61,21 +323,126 @@ impl HealthClient for HttpHealthClient {
 
         let quorum = quorum.min(endpoints.len());
         let deadline_at = Instant::now() + deadline;
+        let mut confirmed_healthy: HashSet<&str> = HashSet::new();
+        let mut round = 0u32;
 
         loop {
-            let mut healthy = 0;
-            for endpoint in endpoints {
-                let response = self.client.get(endpoint).send().await?.error_for_status()?;
-
-                if response.status().is_success() {
-                    match response.json::<HealthResponse>().await {
-                        Ok(body) if body.status.eq_ignore_ascii_case("ok") => healthy += 1,
-                        _ => {}
-                    }
+            let remaining: Vec<&str> = endpoints
+                .iter()
+                .map(String::as_str)
+                .filter(|endpoint| !confirmed_healthy.contains(endpoint))
+                .collect();
+
+            let results = stream::iter(remaining)
+                .map(|endpoint| async move { (endpoint, self.probe(endpoint).await) })
+                .buffer_unordered(MAX_CONCURRENT_PROBES)
+                .collect::<Vec<_>>()
+                .await;
+
+            for (endpoint, result) in results {
+                let result = result?;
+                if let Some(on_probe) = on_probe {
+                    on_probe(endpoint, result);
+                }
+                if result {
+                    confirmed_healthy.insert(endpoint);
+                }
+            }
+
+            if confirmed_healthy.len() >= quorum {
+                return Ok(true);
+            }
+
+            if Instant::now() >= deadline_at {
+                return Ok(false);
+            }
+
+            let backoff = self.next_backoff(round).min(deadline_at - Instant::now());
+            sleep(backoff).await;
+            round += 1;
+        }
+    }
+}
+
+impl HttpHealthClient {
+    /// Exponential backoff seeded from `poll_interval`, jittered by up to
+    /// 20% and capped at [`MAX_POLL_INTERVAL`].
+    fn next_backoff(&self, round: u32) -> Duration {
+        let exponent = round.min(6);
+        let base = self
+            .poll_interval
+            .saturating_mul(1u32 << exponent)
+            .min(MAX_POLL_INTERVAL);
+
+        let jitter_frac = rand::thread_rng().gen_range(0.0..0.2);
+        let jittered = base.as_secs_f64() * (1.0 - jitter_frac);
+        Duration::from_secs_f64(jittered.max(0.0))
+    }
+}
+
+/// Minimal interface [`WatchHealthClient`] needs to check whether a named
+/// service is serving right now. Lets this live in `updater` without a
+/// hard dependency on whatever crate owns the actual service registry
+/// (e.g. `lokan-core`'s `ServiceManager`/`HealthRegistry`).
+pub trait ServiceHealthWatch: Send + Sync {
+    /// `Some(true)` if `service`'s last known status was serving,
+    /// `Some(false)` if it's known but not serving, `None` if `service`
+    /// isn't tracked at all.
+    fn is_serving(&self, service: &str) -> Option<bool>;
+}
+
+/// [`HealthClient`] that checks in-process `watch`-channel-backed service
+/// status (via [`ServiceHealthWatch`]) instead of making HTTP probes like
+/// [`HttpHealthClient`]. Lets `commit_on_health` reflect whether the host's
+/// own services came back up after a staged bundle, instead of requiring
+/// an external health checker reachable over HTTP.
+pub struct WatchHealthClient<W> {
+    watch_source: W,
+    poll_interval: Duration,
+}
+
+impl<W: ServiceHealthWatch> WatchHealthClient<W> {
+    pub fn new(watch_source: W) -> Self {
+        Self {
+            watch_source,
+            poll_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+#[async_trait]
+impl<W: ServiceHealthWatch> HealthClient for WatchHealthClient<W> {
+    async fn wait_for_quorum(
+        &self,
+        endpoints: &[String],
+        deadline: Duration,
+        quorum: usize,
+        on_probe: Option<ProbeObserver<'_>>,
+    ) -> Result<bool, HealthCheckError> {
+        if quorum == 0 || endpoints.is_empty() {
+            return Ok(true);
+        }
+
+        let quorum = quorum.min(endpoints.len());
+        let deadline_at = Instant::now() + deadline;
+        let mut confirmed_healthy: HashSet<&str> = HashSet::new();
+
+        loop {
+            for service in endpoints.iter().map(String::as_str) {
+                if confirmed_healthy.contains(service) {
+                    continue;
+                }
+
+                let serving = self.watch_source.is_serving(service).unwrap_or(false);
+                if let Some(on_probe) = on_probe {
+                    on_probe(service, serving);
+                }
+                if serving {
+                    confirmed_healthy.insert(service);
                 }
             }
 
-            if healthy >= quorum {
+            if confirmed_healthy.len() >= quorum {
                 return Ok(true);
             }
 
@@ -83,7 +450,8 @@ impl HealthClient for HttpHealthClient {
                 return Ok(false);
             }
 
-            sleep(self.poll_interval).await;
+            let remaining = deadline_at - Instant::now();
+            sleep(self.poll_interval.min(remaining)).await;
         }
     }
 }
@@ -100,6 +468,7 @@ impl HealthClient for StubHealthClient {
         _endpoints: &[String],
         _deadline: Duration,
         _quorum: usize,
+        _on_probe: Option<ProbeObserver<'_>>,
     ) -> Result<bool, HealthCheckError> {
         Ok(self.result)
     }
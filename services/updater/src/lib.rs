@@ -1,31 +1,47 @@
 use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use axum::body::Body;
-use axum::extract::{MatchedPath, State};
-use axum::http::{header, HeaderValue, Request, StatusCode};
-use axum::middleware::{from_fn, Next};
-use axum::response::{IntoResponse, Response};
+use axum::extract::{MatchedPath, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use axum::middleware::{from_fn, from_fn_with_state, Next};
+use axum::response::sse::{Event, KeepAlive};
+use axum::response::{IntoResponse, Response, Sse};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use common_auth::rbac::{RbacPolicy, Role};
 use common_config::service_port;
+use common_msgbus::{MessageBus, NatsBus, NatsConfig, DEFAULT_MAX_PAYLOAD};
 use common_obs::{
     encode_prometheus_metrics, http_request_observe, ObsInit, ObsInitError, PROMETHEUS_CONTENT_TYPE,
 };
+use futures_core::Stream;
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
+use tokio_stream::wrappers::BroadcastStream;
 
 pub mod bundle;
 mod core;
+mod events;
 mod health;
+mod report;
 mod state;
 mod store;
 
-pub use crate::core::{UpdaterCore, UpdaterError};
-pub use crate::health::{HealthCheckError, HealthClient, StubHealthClient};
-pub use crate::state::{Slot, SlotState, UpdaterState};
-pub use crate::store::{FileStateStore, MemoryStateStore, StateStore};
+pub use crate::core::{Clock, CommitOutcome, SystemClock, UpdaterCore, UpdaterError, WatchHandle};
+pub use crate::events::UpdateEvent;
+pub use crate::health::{
+    HealthCheckError, HealthClient, ProbeObserver, ServiceHealthWatch, StubHealthClient,
+    WatchHealthClient,
+};
+pub use crate::report::{HealthProbeResult, ReportOperation, ResultCode, UpdateReport};
+pub use crate::state::{PendingWatchdog, Slot, SlotState, UpdaterState};
+pub use crate::store::{
+    FileStateStore, K2vStateStore, MemoryStateStore, StateStore, StateTransition,
+};
 
 use crate::bundle::FilesystemBundleVerifier;
 use crate::health::HttpHealthClient;
@@ -49,11 +65,26 @@ const HEALTH_DEADLINE_ENV: &str = "UPDATER_HEALTH_DEADLINE_SECS";
 const HEALTH_ENDPOINTS_ENV: &str = "UPDATER_HEALTH_ENDPOINTS";
 const HEALTH_QUORUM_ENV: &str = "UPDATER_HEALTH_QUORUM";
 const DEFAULT_HEALTH_DEADLINE: Duration = Duration::from_secs(30);
+const WATCHDOG_WINDOW_ENV: &str = "UPDATER_WATCHDOG_WINDOW_SECS";
+const WATCHDOG_INTERVAL_ENV: &str = "UPDATER_WATCHDOG_INTERVAL_SECS";
+/// How long a commit stays committed-provisional, watched for a
+/// post-commit quorum drop, before [`commit`] treats it as
+/// committed-confirmed.
+const DEFAULT_WATCHDOG_WINDOW: Duration = Duration::from_secs(300);
+const DEFAULT_WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
 const OTA_PUBLIC_KEY_ENV: &str = "UPDATER_OTA_PUBLIC_KEY";
 const DEFAULT_OTA_PUBLIC_KEY_PATH: &str = concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/../../security/pki/dev/ota/ota_signing_public.pem"
 );
+const COMPONENT_STORE_PATH_ENV: &str = "UPDATER_COMPONENT_STORE_PATH";
+const DEFAULT_COMPONENT_STORE_PATH: &str = "data/updater/components";
+const RBAC_POLICY_ENV: &str = "UPDATER_RBAC_POLICY";
+const ROLE_HEADER: &str = "x-lokan-role";
+const BUS_URL_ENV: &str = "UPDATER_BUS_URL";
+const DEFAULT_BUS_URL: &str = "nats://127.0.0.1:4222";
+const RBAC_AUDIT_SUBJECT: &str = "lokan.audit.rbac";
+const DEFAULT_REPORT_LIMIT: usize = 20;
 
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     ObsInit::init(SERVICE_NAME).map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
@@ -84,19 +115,26 @@ pub async fn serve(addr: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
 
 pub async fn build_router() -> Result<Router, UpdaterError> {
     let core = default_core().await?;
-    Ok(router_with_core(core))
+    let rbac = load_rbac_guard().await;
+    Ok(router_with_core(core, rbac))
 }
 
-fn router_with_core(core: UpdaterCore) -> Router {
+fn router_with_core(core: UpdaterCore, rbac: Option<RbacGuardState>) -> Router {
     let app_state = AppState { core };
 
-    let api = Router::new()
+    let mut api = Router::new()
         .route("/v1/update/stage", post(stage))
         .route("/v1/update/commit", post(commit))
         .route("/v1/update/rollback", post(rollback))
         .route("/v1/update/status", get(status))
+        .route("/v1/update/events", get(update_events))
+        .route("/v1/update/reports", get(reports))
         .with_state(app_state);
 
+    if let Some(rbac) = rbac {
+        api = api.layer(from_fn_with_state(rbac, rbac_guard));
+    }
+
     Router::new()
         .route("/metrics", get(metrics))
         .merge(api)
@@ -133,6 +171,147 @@ async fn track_http_metrics(req: Request<Body>, next: Next) -> Response {
     response
 }
 
+/// State consulted by [`rbac_guard`]. Cloneable and `with_state`-friendly so
+/// the same policy/bus pair can be mounted on another service's router
+/// (e.g. commissioning) without re-deriving it from env vars.
+#[derive(Clone)]
+struct RbacGuardState {
+    policy: Arc<RbacPolicy>,
+    bus: Option<Arc<dyn MessageBus>>,
+}
+
+impl RbacGuardState {
+    async fn record_audit(
+        &self,
+        action: &str,
+        role: Role,
+        method: &axum::http::Method,
+        path: &str,
+        allowed: bool,
+    ) {
+        let outcome = if allowed { "allow" } else { "deny" };
+        tracing::info!(
+            event = "rbac_audit",
+            action,
+            role = role.as_str(),
+            method = %method,
+            path,
+            outcome,
+            "rbac decision"
+        );
+
+        let Some(bus) = &self.bus else {
+            return;
+        };
+
+        let payload = serde_json::json!({
+            "action": action,
+            "role": role.as_str(),
+            "method": method.as_str(),
+            "path": path,
+            "outcome": outcome,
+        });
+        let Ok(bytes) = serde_json::to_vec(&payload) else {
+            return;
+        };
+        if let Err(err) = bus.publish(RBAC_AUDIT_SUBJECT, &bytes).await {
+            tracing::warn!(%err, subject = RBAC_AUDIT_SUBJECT, "failed to publish rbac audit event");
+        }
+    }
+}
+
+/// Loads the [`RbacPolicy`] named by `UPDATER_RBAC_POLICY`, if set, and pairs
+/// it with a best-effort message bus connection for audit publication.
+/// Routes stay open when the env var is unset so existing deployments don't
+/// need a policy file to keep working.
+async fn load_rbac_guard() -> Option<RbacGuardState> {
+    let policy_path = std::env::var(RBAC_POLICY_ENV).ok()?;
+    let policy = match RbacPolicy::from_path(Path::new(&policy_path)) {
+        Ok(policy) => Arc::new(policy),
+        Err(err) => {
+            tracing::error!(
+                %err,
+                path = policy_path.as_str(),
+                "failed to load RBAC policy; leaving updater routes unguarded"
+            );
+            return None;
+        }
+    };
+
+    Some(RbacGuardState {
+        policy,
+        bus: connect_bus_best_effort().await,
+    })
+}
+
+/// Connects to the message bus named by `UPDATER_BUS_URL` (or the default
+/// local NATS address), logging and returning `None` on failure rather than
+/// failing startup — callers treat the bus as an optional publication
+/// sink, not a hard dependency.
+async fn connect_bus_best_effort() -> Option<Arc<dyn MessageBus>> {
+    let bus_url = std::env::var(BUS_URL_ENV).unwrap_or_else(|_| DEFAULT_BUS_URL.to_string());
+    let bus_config = NatsConfig {
+        url: bus_url.clone(),
+        request_timeout: Duration::from_secs(5),
+        max_payload: DEFAULT_MAX_PAYLOAD,
+    };
+    match NatsBus::connect(bus_config).await {
+        Ok(bus) => Some(Arc::new(bus)),
+        Err(err) => {
+            tracing::warn!(
+                %err,
+                url = bus_url.as_str(),
+                "failed to connect to message bus"
+            );
+            None
+        }
+    }
+}
+
+async fn rbac_guard(
+    State(state): State<RbacGuardState>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let role = extract_role(req.headers());
+    let method = req.method().clone();
+    let path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let decision = state.policy.authorize(role, &method, &path);
+
+    if let Some(action) = &decision.audit_action {
+        state
+            .record_audit(action, role, &method, &path, decision.allowed)
+            .await;
+    }
+
+    if !decision.allowed {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: format!("role {} is not permitted to access {}", role.as_str(), path),
+            }),
+        )
+            .into_response();
+    }
+
+    req.extensions_mut().insert(role);
+
+    next.run(req).await
+}
+
+fn extract_role(headers: &HeaderMap) -> Role {
+    headers
+        .get(ROLE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(Role::Guest)
+}
+
 pub fn init_for_tests() -> Result<(), ObsInitError> {
     ObsInit::init(SERVICE_NAME)
 }
@@ -152,6 +331,33 @@ struct SlotResponse {
     slot: Slot,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+enum CommitResponse {
+    Committed {
+        slot: Slot,
+    },
+    RollbackPerformed {
+        failed_slot: Slot,
+        active_slot: Slot,
+    },
+}
+
+impl From<CommitOutcome> for CommitResponse {
+    fn from(outcome: CommitOutcome) -> Self {
+        match outcome {
+            CommitOutcome::Committed(slot) => CommitResponse::Committed { slot },
+            CommitOutcome::RollbackPerformed {
+                failed_slot,
+                active_slot,
+            } => CommitResponse::RollbackPerformed {
+                failed_slot,
+                active_slot,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct ErrorResponse {
     error: String,
@@ -169,9 +375,23 @@ async fn stage(State(state): State<AppState>, Json(payload): Json<StageRequest>)
     }
 }
 
+/// Commits the staged slot and, if it reaches quorum, leaves it
+/// committed-provisional under a [`UpdaterCore::commit_with_watchdog`]
+/// observation window (`UPDATER_WATCHDOG_WINDOW_SECS` /
+/// `UPDATER_WATCHDOG_INTERVAL_SECS`) instead of treating it as
+/// committed-confirmed right away. The response itself still reflects only
+/// the commit's own immediate outcome; a later quorum drop during the
+/// window rolls back in the background and is visible via `/v1/update/events`
+/// or the next `/v1/update/status`.
 async fn commit(State(state): State<AppState>) -> Response {
-    match state.core.commit_on_health().await {
-        Ok(slot) => (StatusCode::OK, Json(SlotResponse { slot })).into_response(),
+    match state
+        .core
+        .commit_with_watchdog(watchdog_window_from_env(), watchdog_interval_from_env())
+        .await
+    {
+        Ok((outcome, _handle)) => {
+            (StatusCode::OK, Json(CommitResponse::from(outcome))).into_response()
+        }
         Err(err) => error_response(err),
     }
 }
@@ -188,6 +408,37 @@ async fn status(State(state): State<AppState>) -> Response {
     Json(snapshot).into_response()
 }
 
+#[derive(Debug, Deserialize)]
+struct ReportsQuery {
+    limit: Option<usize>,
+}
+
+/// Returns the most recent stage/commit/rollback reports, newest first, so
+/// an operator can see why a commit was rejected without scraping logs.
+async fn reports(State(state): State<AppState>, Query(query): Query<ReportsQuery>) -> Response {
+    let limit = query.limit.unwrap_or(DEFAULT_REPORT_LIMIT);
+    match state.core.list_reports(limit).await {
+        Ok(reports) => Json(reports).into_response(),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Streams [`UpdateEvent`]s as the core advances through stage/commit/
+/// rollback, so a client can follow a multi-phase update live instead of
+/// polling [`status`].
+async fn update_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let stream =
+        BroadcastStream::new(state.core.subscribe_events()).filter_map(|event| async move {
+            let event = event.ok()?;
+            let payload = serde_json::to_string(&event).ok()?;
+            Some(Ok(Event::default().event(event.name()).data(payload)))
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::new())
+}
+
 async fn health_contract() -> Response {
     Json(HealthResponseBody { status: "ok" }).into_response()
 }
@@ -217,11 +468,11 @@ async fn info_contract() -> Response {
 fn error_response(err: UpdaterError) -> Response {
     let status = match &err {
         UpdaterError::Stage(StageError::SlotBooting) => StatusCode::CONFLICT,
+        UpdaterError::Stage(StageError::RollbackProtection { .. }) => StatusCode::CONFLICT,
         UpdaterError::Stage(_) => StatusCode::BAD_REQUEST,
         UpdaterError::Commit(CommitError::NothingStaged) => StatusCode::BAD_REQUEST,
         UpdaterError::Commit(CommitError::InvalidStageState) => StatusCode::CONFLICT,
         UpdaterError::Rollback(_) => StatusCode::CONFLICT,
-        UpdaterError::HealthQuorumFailed => StatusCode::SERVICE_UNAVAILABLE,
         UpdaterError::Store(_) | UpdaterError::Health(_) => StatusCode::INTERNAL_SERVER_ERROR,
     };
 
@@ -243,11 +494,18 @@ async fn default_core() -> Result<UpdaterCore, UpdaterError> {
 
     let public_key_path = std::env::var(OTA_PUBLIC_KEY_ENV)
         .unwrap_or_else(|_| DEFAULT_OTA_PUBLIC_KEY_PATH.to_string());
+    let component_store_path = std::env::var(COMPONENT_STORE_PATH_ENV)
+        .unwrap_or_else(|_| DEFAULT_COMPONENT_STORE_PATH.to_string());
     let bundle_verifier = Arc::new(
         FilesystemBundleVerifier::from_public_key_pem(&public_key_path)
-            .map_err(|err| StageError::InvalidBundle(err.to_string()))?,
+            .map_err(|err| StageError::InvalidBundle(err.to_string()))?
+            .with_component_store(Arc::new(bundle::FilesystemComponentStore::new(
+                component_store_path,
+            ))),
     );
 
+    let bus = connect_bus_best_effort().await;
+
     UpdaterCore::new(
         store,
         health_client,
@@ -255,6 +513,7 @@ async fn default_core() -> Result<UpdaterCore, UpdaterError> {
         deadline,
         quorum,
         bundle_verifier,
+        bus,
     )
     .await
 }
@@ -293,7 +552,23 @@ fn health_quorum_from_env(default: usize) -> usize {
         .unwrap_or(default)
 }
 
+fn watchdog_window_from_env() -> Duration {
+    std::env::var(WATCHDOG_WINDOW_ENV)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_WATCHDOG_WINDOW)
+}
+
+fn watchdog_interval_from_env() -> Duration {
+    std::env::var(WATCHDOG_INTERVAL_ENV)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_WATCHDOG_INTERVAL)
+}
+
 #[cfg(test)]
 pub fn router_for_tests(core: UpdaterCore) -> Router {
-    router_with_core(core)
+    router_with_core(core, None)
 }
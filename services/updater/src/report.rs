@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::state::Slot;
+
+/// Operation an [`UpdateReport`] records the outcome of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportOperation {
+    Stage,
+    Commit,
+    Rollback,
+}
+
+/// Granular outcome for an [`UpdateReport`], fine enough that an operator
+/// can tell why an operation was rejected without scraping logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultCode {
+    Success,
+    SignatureInvalid,
+    ChecksumMismatch,
+    DeltaBaseMismatch,
+    RollbackProtection,
+    HealthQuorumFailed,
+    InvalidState,
+    StoreError,
+    Other,
+}
+
+/// One endpoint's probe outcome during a commit's health gate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbeResult {
+    pub endpoint: String,
+    pub healthy: bool,
+}
+
+/// Durable record of a single stage/commit/rollback operation, appended to
+/// the [`crate::StateStore`] and published to the message bus so operators
+/// can audit an outcome without scraping logs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateReport {
+    pub operation: ReportOperation,
+    pub result: ResultCode,
+    pub artifact: Option<String>,
+    pub source_slot: Option<Slot>,
+    pub target_slot: Option<Slot>,
+    pub message: String,
+    #[serde(default)]
+    pub health_probes: Vec<HealthProbeResult>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Ord, PartialOrd, Hash)]
@@ -26,6 +27,11 @@ pub enum SlotState {
     Inactive,
     Staged,
     Booting,
+    /// Committed and running, but not yet confirmed healthy after boot —
+    /// [`UpdaterState::confirm_boot`] promotes it to [`Self::Active`], while
+    /// [`UpdaterState::watchdog_expired`] demotes it to [`Self::Bad`] and
+    /// auto-rolls back if the confirm deadline passes first.
+    Confirming,
     Active,
     Bad,
 }
@@ -35,6 +41,24 @@ pub struct SlotInfo {
     pub state: SlotState,
     pub artifact: Option<String>,
     pub generation: u64,
+    /// Manifest `version` staged (and, once active, installed) into this
+    /// slot. Consulted against a delta bundle's `base_version` before a
+    /// patch is applied against whatever is currently running.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Manifest `security_version` staged into this slot. Folded into
+    /// [`UpdaterState::min_security_version`] once this slot is
+    /// successfully committed, raising the anti-rollback floor.
+    #[serde(default)]
+    pub security_version: u64,
+    /// Set alongside `generation` when this slot enters
+    /// [`SlotState::Confirming`]: the point past which
+    /// [`UpdaterState::watchdog_expired`] treats it as failed. Persisted
+    /// so a reboot loop can tell "booted but never confirmed" apart from
+    /// "never booted at all" from the state file alone, with no running
+    /// [`crate::UpdaterCore`] or health check required.
+    #[serde(default)]
+    pub confirm_deadline: Option<DateTime<Utc>>,
 }
 
 impl SlotInfo {
@@ -43,10 +67,28 @@ impl SlotInfo {
             state,
             artifact: None,
             generation: 0,
+            version: None,
+            security_version: 0,
+            confirm_deadline: None,
         }
     }
 }
 
+/// Tracks an in-progress [`crate::UpdaterCore::commit_with_watchdog`]
+/// observation window. While a slot has a `PendingWatchdog`, it is
+/// committed-provisional rather than committed-confirmed: persisting this
+/// (instead of only holding it in memory) lets a crash mid-window resume
+/// the watch from roughly where it left off instead of silently treating
+/// the slot as confirmed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingWatchdog {
+    pub slot: Slot,
+    pub window_secs: u64,
+    pub interval_secs: u64,
+    pub quorum: usize,
+    pub started_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UpdaterState {
     pub generation: u64,
@@ -55,6 +97,29 @@ pub struct UpdaterState {
     pub staging: Option<Slot>,
     pub last_failed: Option<Slot>,
     pub slots: BTreeMap<Slot, SlotInfo>,
+    /// Anti-rollback floor: the highest `security_version` ever committed
+    /// and health-gated. A bundle with a lower `security_version` is
+    /// refused at `stage` time. Only raised on a successful commit, never
+    /// on stage alone, so a candidate that fails its health gate can't
+    /// raise the floor and brick a legitimate future downgrade-repair.
+    #[serde(default)]
+    pub min_security_version: u64,
+    /// Set while the active slot is committed-provisional, i.e. still
+    /// inside a [`crate::UpdaterCore::commit_with_watchdog`] observation
+    /// window. `None` means the active slot is committed-confirmed (or no
+    /// watchdog was ever used for it).
+    #[serde(default)]
+    pub pending_watchdog: Option<PendingWatchdog>,
+    /// How long a freshly committed slot stays [`SlotState::Confirming`]
+    /// before [`Self::watchdog_expired`] treats it as failed. Defaults to
+    /// five minutes; a caller wanting a different boot-confirmation window
+    /// can set this directly before the next [`Self::finalize_commit`].
+    #[serde(default = "default_confirm_window_secs")]
+    pub confirm_window_secs: u64,
+}
+
+fn default_confirm_window_secs() -> u64 {
+    300
 }
 
 impl Default for UpdaterState {
@@ -69,6 +134,9 @@ impl Default for UpdaterState {
             staging: None,
             last_failed: None,
             slots,
+            min_security_version: 0,
+            pending_watchdog: None,
+            confirm_window_secs: default_confirm_window_secs(),
         }
     }
 }
@@ -85,6 +153,13 @@ pub enum StageError {
     TargetSlotMismatch { expected: Slot, requested: Slot },
     #[error("failed to validate bundle: {0}")]
     InvalidBundle(String),
+    #[error("delta bundle expects base version {expected:?}, active slot reports {found:?}")]
+    DeltaBaseMismatch {
+        expected: String,
+        found: Option<String>,
+    },
+    #[error("bundle security version {found} is below the anti-rollback minimum {minimum}")]
+    RollbackProtection { minimum: u64, found: u64 },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -104,7 +179,20 @@ pub enum RollbackError {
 }
 
 impl UpdaterState {
-    pub fn stage(&mut self, artifact: String, target: Option<Slot>) -> Result<Slot, StageError> {
+    pub fn stage(
+        &mut self,
+        artifact: String,
+        version: Option<String>,
+        security_version: u64,
+        target: Option<Slot>,
+    ) -> Result<Slot, StageError> {
+        if security_version < self.min_security_version {
+            return Err(StageError::RollbackProtection {
+                minimum: self.min_security_version,
+                found: security_version,
+            });
+        }
+
         if let Some(slot) = self.staging {
             if let Some(requested) = target {
                 if requested != slot {
@@ -128,6 +216,8 @@ impl UpdaterState {
 
             info.state = SlotState::Staged;
             info.artifact = Some(artifact);
+            info.version = version;
+            info.security_version = security_version;
             self.generation += 1;
             info.generation = self.generation;
             return Ok(slot);
@@ -152,6 +242,8 @@ impl UpdaterState {
             .expect("candidate slot must exist in state");
         info.state = SlotState::Staged;
         info.artifact = Some(artifact);
+        info.version = version;
+        info.security_version = security_version;
         self.generation += 1;
         info.generation = self.generation;
         self.staging = Some(candidate);
@@ -159,8 +251,22 @@ impl UpdaterState {
         Ok(candidate)
     }
 
+    /// The manifest version installed in the currently-active slot, if any.
+    /// Compared against a delta bundle's `base_version` before its patch is
+    /// applied.
+    pub fn active_version(&self) -> Option<String> {
+        self.active
+            .and_then(|slot| self.slots.get(&slot))
+            .and_then(|info| info.version.clone())
+    }
+
     fn is_slot_available_for_stage(&self, slot: Slot) -> bool {
-        if self.active == Some(slot) && self.slots[&slot].state == SlotState::Active {
+        if self.active == Some(slot)
+            && matches!(
+                self.slots[&slot].state,
+                SlotState::Active | SlotState::Confirming
+            )
+        {
             return false;
         }
 
@@ -187,6 +293,11 @@ impl UpdaterState {
         }
     }
 
+    /// Commits `slot`, but doesn't yet trust it: the slot enters
+    /// [`SlotState::Confirming`] rather than [`SlotState::Active`], and
+    /// `previous_active` is retained as a rollback target until
+    /// [`Self::confirm_boot`] or [`Self::watchdog_expired`] resolves it one
+    /// way or the other.
     pub fn finalize_commit(&mut self, slot: Slot) {
         let previous_active = self.active;
         if let Some(prev) = previous_active {
@@ -198,7 +309,10 @@ impl UpdaterState {
         }
 
         if let Some(info) = self.slots.get_mut(&slot) {
-            info.state = SlotState::Active;
+            info.state = SlotState::Confirming;
+            info.confirm_deadline =
+                Some(Utc::now() + chrono::Duration::seconds(self.confirm_window_secs as i64));
+            self.min_security_version = self.min_security_version.max(info.security_version);
         }
 
         self.previous_active = previous_active.filter(|prev| *prev != slot);
@@ -207,6 +321,80 @@ impl UpdaterState {
         self.last_failed = None;
     }
 
+    /// Promotes the active slot from `Confirming` to `Active`, confirming
+    /// it booted successfully, and releases `previous_active` as a
+    /// rollback target now that it's no longer needed. A no-op (returns
+    /// `false`) if `slot` isn't the active slot or isn't `Confirming` —
+    /// e.g. a late caller racing [`Self::watchdog_expired`].
+    pub fn confirm_boot(&mut self, slot: Slot) -> bool {
+        if self.active != Some(slot) {
+            return false;
+        }
+        let Some(info) = self.slots.get_mut(&slot) else {
+            return false;
+        };
+        if info.state != SlotState::Confirming {
+            return false;
+        }
+
+        info.state = SlotState::Active;
+        info.confirm_deadline = None;
+        self.previous_active = None;
+        true
+    }
+
+    /// Checks whether the active slot is still `Confirming` past its
+    /// `confirm_deadline` as of `now`; if so, marks it `Bad` and
+    /// auto-rolls back to the retained `previous_active` slot, the same
+    /// as an operator-driven [`Self::mark_active_bad`] followed by
+    /// [`Self::rollback`]. Returns whether a rollback was performed.
+    ///
+    /// Depends on nothing but persisted state, so a reboot loop can call
+    /// this (passing the current time) right after loading a
+    /// [`crate::StateStore`] snapshot — before any health check or
+    /// [`crate::UpdaterCore`] is even running — to recover a slot that
+    /// booted but was never confirmed.
+    pub fn watchdog_expired(&mut self, now: DateTime<Utc>) -> bool {
+        let Some(active) = self.active else {
+            return false;
+        };
+        let Some(info) = self.slots.get(&active) else {
+            return false;
+        };
+        if info.state != SlotState::Confirming {
+            return false;
+        }
+        let Some(deadline) = info.confirm_deadline else {
+            return false;
+        };
+        if now < deadline {
+            return false;
+        }
+
+        if self.mark_active_bad().is_none() {
+            return false;
+        }
+        self.rollback().is_ok()
+    }
+
+    /// Reconciles a snapshot loaded after a restart: a slot still
+    /// `Booting` means the process crashed between [`Self::begin_commit`]
+    /// persisting that transition and the health gate that would have
+    /// called [`Self::finalize_commit`] or [`Self::fail_commit`]. Since
+    /// the commit never reached a verdict, it's treated the same as a
+    /// failed one rather than left stuck. Returns whether a slot needed
+    /// this.
+    pub fn reconcile_after_restart(&mut self) -> bool {
+        let Some(slot) = Slot::ALL
+            .into_iter()
+            .find(|slot| self.slots[slot].state == SlotState::Booting)
+        else {
+            return false;
+        };
+        self.fail_commit(slot);
+        true
+    }
+
     pub fn fail_commit(&mut self, slot: Slot) {
         if let Some(info) = self.slots.get_mut(&slot) {
             info.state = SlotState::Bad;
@@ -215,6 +403,19 @@ impl UpdaterState {
         self.staging = None;
     }
 
+    /// Reverts a slot that failed its post-commit health gate back to
+    /// `Inactive` so it is immediately eligible for re-staging, leaving the
+    /// previously active slot untouched.
+    pub fn auto_rollback(&mut self, failed_slot: Slot) -> Slot {
+        if let Some(info) = self.slots.get_mut(&failed_slot) {
+            info.state = SlotState::Inactive;
+        }
+        self.last_failed = None;
+        self.staging = None;
+
+        self.active.expect("active slot must be set during commit")
+    }
+
     pub fn mark_active_bad(&mut self) -> Option<Slot> {
         let active = self.active?;
         let info = self
@@ -222,13 +423,15 @@ impl UpdaterState {
             .get_mut(&active)
             .expect("active slot must exist in state");
 
-        if info.state != SlotState::Active {
+        if !matches!(info.state, SlotState::Active | SlotState::Confirming) {
             return None;
         }
 
         info.state = SlotState::Bad;
+        info.confirm_deadline = None;
         self.active = None;
         self.last_failed = Some(active);
+        self.pending_watchdog = None;
         Some(active)
     }
 
@@ -251,7 +454,20 @@ impl UpdaterState {
         self.previous_active = None;
         self.last_failed = None;
         self.staging = None;
+        self.pending_watchdog = None;
 
         Ok(previous_active)
     }
+
+    /// Starts a [`PendingWatchdog`] observation window for `slot`, marking
+    /// it committed-provisional.
+    pub fn begin_watchdog(&mut self, pending: PendingWatchdog) {
+        self.pending_watchdog = Some(pending);
+    }
+
+    /// Clears the current [`PendingWatchdog`], marking its slot
+    /// committed-confirmed. A no-op if no watchdog is pending.
+    pub fn confirm_watchdog(&mut self) {
+        self.pending_watchdog = None;
+    }
 }
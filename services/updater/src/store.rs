@@ -1,10 +1,15 @@
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-use crate::state::UpdaterState;
+use crate::report::UpdateReport;
+use crate::state::{Slot, SlotState, UpdaterState};
 
 #[derive(Debug, thiserror::Error)]
 pub enum StateStoreError {
@@ -12,23 +17,69 @@ pub enum StateStoreError {
     Io(#[from] std::io::Error),
     #[error("failed to parse updater state: {0}")]
     Parse(#[from] serde_json::Error),
+    #[error("k2v request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("k2v value was not valid base64: {0}")]
+    Encoding(#[from] base64::DecodeError),
+}
+
+/// One entry in a [`StateStore`]'s intent journal, appended ahead of the
+/// [`UpdaterState`] snapshot that results from it. `Stage` and
+/// `BeginCommit` are the two transitions a crash is most likely to land
+/// in the middle of (the former waits on bundle verification, the latter
+/// on a health quorum), so a journal that records intent before the
+/// matching snapshot write lands gives a postmortem - or a future
+/// reconciliation pass - something to go on even if the snapshot write
+/// itself never completed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StateTransition {
+    Stage { slot: Slot, artifact: String },
+    BeginCommit { slot: Slot },
+    FinalizeCommit { slot: Slot },
+    FailCommit { slot: Slot },
+    Rollback,
 }
 
 #[async_trait]
 pub trait StateStore: Send + Sync {
     async fn load(&self) -> Result<Option<UpdaterState>, StateStoreError>;
     async fn save(&self, state: &UpdaterState) -> Result<(), StateStoreError>;
+
+    /// Appends a completed operation's report. Reports accumulate
+    /// independently of the mutable [`UpdaterState`] snapshot so history
+    /// survives even as that snapshot is repeatedly overwritten.
+    async fn append_report(&self, report: &UpdateReport) -> Result<(), StateStoreError>;
+
+    /// Returns up to `limit` most recently appended reports, newest first.
+    async fn list_reports(&self, limit: usize) -> Result<Vec<UpdateReport>, StateStoreError>;
+
+    /// Records `transition` to the durable intent journal ahead of the
+    /// [`Self::save`] call that persists its result, so a crash between
+    /// the two still leaves a record of what was about to happen. The
+    /// default is a no-op: a store whose `save` is already the sole
+    /// source of truth (e.g. [`MemoryStateStore`], which can't outlive
+    /// the process) has nothing for a journal to add.
+    async fn append_intent(&self, _transition: &StateTransition) -> Result<(), StateStoreError> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FileStateStore {
     path: PathBuf,
+    reports_path: PathBuf,
+    journal_path: PathBuf,
 }
 
 impl FileStateStore {
     pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let reports_path = reports_path_for(&path);
+        let journal_path = journal_path_for(&path);
         Self {
-            path: path.as_ref().to_path_buf(),
+            path,
+            reports_path,
+            journal_path,
         }
     }
 
@@ -38,16 +89,73 @@ impl FileStateStore {
         }
         Ok(())
     }
+
+    /// Writes `json` to `self.path` without ever leaving a truncated file on
+    /// disk: the bytes land in a sibling `.tmp` file first, which is fsynced
+    /// before an atomic rename replaces the real path, and the parent
+    /// directory is fsynced afterward so the rename itself survives a crash.
+    async fn write_atomic(&self, json: &[u8]) -> Result<(), std::io::Error> {
+        let tmp_path = tmp_path_for(&self.path);
+        let mut tmp_file = fs::File::create(&tmp_path).await?;
+        tmp_file.write_all(json).await?;
+        tmp_file.flush().await?;
+        tmp_file.sync_all().await?;
+        drop(tmp_file);
+
+        fs::rename(&tmp_path, &self.path).await?;
+
+        if let Some(parent) = self.path.parent() {
+            let dir = fs::File::open(parent).await?;
+            dir.sync_all().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Derives the reports log path from the state file path, e.g.
+/// `data/updater/state.json` -> `data/updater/state.reports.jsonl`.
+fn reports_path_for(state_path: &Path) -> PathBuf {
+    let mut file_name = state_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(".reports.jsonl");
+    state_path.with_file_name(file_name)
+}
+
+/// Derives the atomic-write staging path for `path`, e.g.
+/// `data/updater/state.json` -> `data/updater/state.json.tmp`.
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// Derives the intent journal path from the state file path, e.g.
+/// `data/updater/state.json` -> `data/updater/state.journal.jsonl`.
+fn journal_path_for(state_path: &Path) -> PathBuf {
+    let mut file_name = state_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(".journal.jsonl");
+    state_path.with_file_name(file_name)
 }
 
 #[async_trait]
 impl StateStore for FileStateStore {
     async fn load(&self) -> Result<Option<UpdaterState>, StateStoreError> {
-        match fs::read(&self.path).await {
-            Ok(bytes) => {
-                let state = serde_json::from_slice(&bytes)?;
-                Ok(Some(state))
+        let primary = match fs::read(&self.path).await {
+            Ok(bytes) => Some(bytes),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(StateStoreError::Io(err)),
+        };
+
+        if let Some(bytes) = primary {
+            if let Ok(state) = serde_json::from_slice(&bytes) {
+                return Ok(Some(state));
             }
+        }
+
+        // The primary file is missing or failed to parse, which a crash
+        // mid-rename can leave behind; fall back to the staged write, which
+        // was fsynced in full before the rename was ever attempted.
+        match fs::read(tmp_path_for(&self.path)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(err) => Err(StateStoreError::Io(err)),
         }
@@ -56,16 +164,63 @@ impl StateStore for FileStateStore {
     async fn save(&self, state: &UpdaterState) -> Result<(), StateStoreError> {
         self.ensure_parent_dir().await?;
         let json = serde_json::to_vec_pretty(state)?;
-        let mut file = fs::File::create(&self.path).await?;
-        file.write_all(&json).await?;
+        self.write_atomic(&json).await?;
+        Ok(())
+    }
+
+    async fn append_report(&self, report: &UpdateReport) -> Result<(), StateStoreError> {
+        self.ensure_parent_dir().await?;
+        let mut line = serde_json::to_vec(report)?;
+        line.push(b'\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.reports_path)
+            .await?;
+        file.write_all(&line).await?;
         file.flush().await?;
         Ok(())
     }
+
+    async fn list_reports(&self, limit: usize) -> Result<Vec<UpdateReport>, StateStoreError> {
+        let bytes = match fs::read(&self.reports_path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(StateStoreError::Io(err)),
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        let mut reports = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<UpdateReport>, _>>()?;
+        reports.reverse();
+        reports.truncate(limit);
+        Ok(reports)
+    }
+
+    async fn append_intent(&self, transition: &StateTransition) -> Result<(), StateStoreError> {
+        self.ensure_parent_dir().await?;
+        let mut line = serde_json::to_vec(transition)?;
+        line.push(b'\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.journal_path)
+            .await?;
+        file.write_all(&line).await?;
+        // Unlike the reports log, the journal exists for crash-consistency,
+        // so the entry needs to survive a crash immediately after this call
+        // returns rather than whenever the OS next flushes its page cache.
+        file.sync_all().await?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct MemoryStateStore {
     pub state: tokio::sync::Mutex<Option<UpdaterState>>,
+    pub reports: tokio::sync::Mutex<Vec<UpdateReport>>,
 }
 
 #[async_trait]
@@ -78,4 +233,198 @@ impl StateStore for MemoryStateStore {
         *self.state.lock().await = Some(state.clone());
         Ok(())
     }
+
+    async fn append_report(&self, report: &UpdateReport) -> Result<(), StateStoreError> {
+        self.reports.lock().await.push(report.clone());
+        Ok(())
+    }
+
+    async fn list_reports(&self, limit: usize) -> Result<Vec<UpdateReport>, StateStoreError> {
+        let reports = self.reports.lock().await;
+        Ok(reports.iter().rev().take(limit).cloned().collect())
+    }
+}
+
+/// A single K2V item as returned by a GET: concurrent writers can each leave
+/// behind a value the server hasn't reconciled yet, so a read may surface
+/// more than one. `None` represents a tombstone (a deleted value that is
+/// still concurrent with another write).
+#[derive(Debug, Deserialize)]
+struct K2vGetResponse {
+    causality_token: Option<String>,
+    #[serde(default)]
+    values: Vec<Option<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct K2vPutRequest<'a> {
+    causality_token: Option<&'a str>,
+    value: &'a str,
+}
+
+/// Picks the value a concurrent write should win against another, per
+/// [`K2vStateStore`]'s merge rule: the higher update generation wins; on a
+/// tie, a recorded failure (`last_failed`, or any slot left `Bad`) wins over
+/// a clean `Active` state so a failure observed by one controller is never
+/// silently dropped by a concurrent write from another.
+fn merge_states(a: UpdaterState, b: UpdaterState) -> UpdaterState {
+    match a.generation.cmp(&b.generation) {
+        std::cmp::Ordering::Greater => a,
+        std::cmp::Ordering::Less => b,
+        std::cmp::Ordering::Equal if records_failure(&a) && !records_failure(&b) => a,
+        std::cmp::Ordering::Equal if records_failure(&b) && !records_failure(&a) => b,
+        std::cmp::Ordering::Equal => a,
+    }
+}
+
+fn records_failure(state: &UpdaterState) -> bool {
+    state.last_failed.is_some()
+        || state
+            .slots
+            .values()
+            .any(|info| info.state == SlotState::Bad)
+}
+
+/// [`StateStore`] backed by a K2V-style key-value service (e.g. Garage's K2V
+/// API): every slot/generation update is written under one partition/sort
+/// key pair, tagged with the causality token the service returned on the
+/// preceding read. Concurrent writers racing on that key leave behind
+/// sibling values instead of silently overwriting one another; a read
+/// reconciles every sibling with [`merge_states`] and writes the reconciled
+/// value straight back (tagged with the combined token from that read) so
+/// the reconciliation is itself durable rather than repeated by every
+/// reader.
+#[derive(Debug, Clone)]
+pub struct K2vStateStore {
+    client: Client,
+    base_url: String,
+    bucket: String,
+    state_key: String,
+    reports_key: String,
+}
+
+impl K2vStateStore {
+    /// `base_url` points at the K2V endpoint, e.g. `http://garage:3904`.
+    /// `bucket` and `partition_key` scope this updater instance's state
+    /// within the shared service, so a fleet of controllers coordinating
+    /// over the same bucket/partition see each other's writes.
+    pub fn new(
+        base_url: impl Into<String>,
+        bucket: impl Into<String>,
+        partition_key: impl Into<String>,
+    ) -> Self {
+        let partition_key = partition_key.into();
+        Self {
+            client: Client::new(),
+            base_url: base_url.into(),
+            bucket: bucket.into(),
+            state_key: format!("{partition_key}/state"),
+            reports_key: format!("{partition_key}/reports"),
+        }
+    }
+
+    fn item_url(&self, sort_key: &str) -> String {
+        format!("{}/{}/{}", self.base_url, self.bucket, sort_key)
+    }
+
+    /// Reads every concurrent sibling under `sort_key` plus the causality
+    /// token covering them, decoding each as JSON via `T`.
+    async fn get_item<T: for<'de> Deserialize<'de>>(
+        &self,
+        sort_key: &str,
+    ) -> Result<(Vec<T>, Option<String>), StateStoreError> {
+        let response = self.client.get(self.item_url(sort_key)).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok((Vec::new(), None));
+        }
+        let body: K2vGetResponse = response.error_for_status()?.json().await?;
+
+        let mut values = Vec::with_capacity(body.values.len());
+        for value in body.values.into_iter().flatten() {
+            let bytes = BASE64.decode(value)?;
+            values.push(serde_json::from_slice(&bytes)?);
+        }
+        Ok((values, body.causality_token))
+    }
+
+    /// Writes `value` back under `sort_key`, tagged with `causality_token`
+    /// so the service can tell this write supersedes whatever siblings it
+    /// was read alongside.
+    async fn put_item<T: Serialize>(
+        &self,
+        sort_key: &str,
+        value: &T,
+        causality_token: Option<&str>,
+    ) -> Result<(), StateStoreError> {
+        let encoded = BASE64.encode(serde_json::to_vec(value)?);
+        let request = K2vPutRequest {
+            causality_token,
+            value: &encoded,
+        };
+        self.client
+            .put(self.item_url(sort_key))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StateStore for K2vStateStore {
+    async fn load(&self) -> Result<Option<UpdaterState>, StateStoreError> {
+        let (siblings, causality_token) = self.get_item::<UpdaterState>(&self.state_key).await?;
+        let Some(reconciled) = siblings.into_iter().reduce(merge_states) else {
+            return Ok(None);
+        };
+
+        // Read-repair: collapse the siblings this read observed into a
+        // single reconciled value, tagged with the token that covers all of
+        // them, so later readers don't have to redo this merge.
+        self.put_item(&self.state_key, &reconciled, causality_token.as_deref())
+            .await?;
+        Ok(Some(reconciled))
+    }
+
+    async fn save(&self, state: &UpdaterState) -> Result<(), StateStoreError> {
+        let (siblings, causality_token) = self.get_item::<UpdaterState>(&self.state_key).await?;
+        let reconciled = siblings
+            .into_iter()
+            .fold(state.clone(), |acc, sibling| merge_states(acc, sibling));
+        self.put_item(&self.state_key, &reconciled, causality_token.as_deref())
+            .await
+    }
+
+    async fn append_report(&self, report: &UpdateReport) -> Result<(), StateStoreError> {
+        let sort_key = format!(
+            "{}/{}",
+            self.reports_key,
+            report.started_at.timestamp_millis()
+        );
+        self.put_item(&sort_key, report, None).await
+    }
+
+    async fn list_reports(&self, limit: usize) -> Result<Vec<UpdateReport>, StateStoreError> {
+        let prefix = format!("{}/", self.reports_key);
+        let response = self
+            .client
+            .get(format!("{}/{}", self.base_url, self.bucket))
+            .query(&[("prefix", prefix.as_str()), ("reverse", "true")])
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+
+        let sort_keys: Vec<String> = response.error_for_status()?.json().await?;
+        let mut reports = Vec::with_capacity(limit.min(sort_keys.len()));
+        for sort_key in sort_keys.into_iter().take(limit) {
+            let (mut siblings, _) = self.get_item::<UpdateReport>(&sort_key).await?;
+            if let Some(report) = siblings.pop() {
+                reports.push(report);
+            }
+        }
+        Ok(reports)
+    }
 }
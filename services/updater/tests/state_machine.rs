@@ -5,7 +5,11 @@ use std::time::Duration;
 use async_trait::async_trait;
 use tokio::sync::Mutex;
 
-use updater::{HealthCheckError, HealthClient, MemoryStateStore, Slot, SlotState, UpdaterCore};
+use updater::bundle::{BundleError, BundleVerifier, StageBundleMetadata};
+use updater::{
+    Clock, CommitOutcome, HealthCheckError, HealthClient, MemoryStateStore, PendingWatchdog,
+    ProbeObserver, Slot, SlotState, StateStore, UpdaterCore, UpdaterState,
+};
 
 #[derive(Debug, Clone)]
 struct TestStep {
@@ -15,7 +19,7 @@ struct TestStep {
 #[derive(Debug, Clone)]
 enum Action {
     Stage { artifact: &'static str },
-    Commit { expect_ok: bool },
+    Commit { expect_committed: bool },
     MarkBad { expect_some: bool },
     Rollback { expect_ok: bool },
 }
@@ -47,18 +51,21 @@ async fn state_machine_transitions() {
                 active: Some(Slot::B),
                 staging: None,
                 last_failed: None,
-                slots: vec![(Slot::A, SlotState::Inactive), (Slot::B, SlotState::Active)],
+                slots: vec![
+                    (Slot::A, SlotState::Inactive),
+                    (Slot::B, SlotState::Confirming),
+                ],
             },
         },
         TestCase {
-            name: "commit_failure_marks_bad",
+            name: "commit_failure_auto_rolls_back",
             steps: vec![stage_step("artifact:v2"), commit_step(false)],
             health_results: vec![false],
             expected: Expected {
                 active: Some(Slot::A),
                 staging: None,
-                last_failed: Some(Slot::B),
-                slots: vec![(Slot::A, SlotState::Active), (Slot::B, SlotState::Bad)],
+                last_failed: None,
+                slots: vec![(Slot::A, SlotState::Active), (Slot::B, SlotState::Inactive)],
             },
         },
         TestCase {
@@ -86,24 +93,251 @@ async fn state_machine_transitions() {
     for case in cases {
         let store = Arc::new(MemoryStateStore::default()) as Arc<dyn updater::StateStore>;
         let health_client = Arc::new(SequenceHealthClient::new(case.health_results.clone()));
-        let core = UpdaterCore::new(store, health_client, Vec::new(), Duration::from_secs(1), 0)
-            .await
-            .expect("core init");
+        let bundle_verifier = Arc::new(StubBundleVerifier) as Arc<dyn BundleVerifier>;
+        let core = UpdaterCore::new(
+            store,
+            health_client,
+            Vec::new(),
+            Duration::from_secs(1),
+            0,
+            bundle_verifier,
+            None,
+        )
+        .await
+        .expect("core init");
 
         run_steps(&case, &core).await;
         assert_state(&case, &core).await;
     }
 }
 
+#[tokio::test]
+async fn watch_after_commit_rolls_back_on_quorum_drop() {
+    // Consumed in order: the commit itself, then each watch tick.
+    let health_client = Arc::new(SequenceHealthClient::new(vec![true, true, true, false]));
+    let core = new_core(health_client).await;
+
+    core.stage("artifact:v1".to_string())
+        .await
+        .expect("stage succeeded");
+    let outcome = core.commit_on_health().await.expect("commit succeeded");
+    assert!(matches!(outcome, CommitOutcome::Committed(Slot::B)));
+
+    let interval = Duration::from_millis(1);
+    core.watch_after_commit(interval * 4, interval, 1)
+        .cancel()
+        .await;
+
+    let state = core.state().await;
+    assert_eq!(state.active, Some(Slot::A), "watch should have rolled back");
+    assert_eq!(state.staging, None);
+    assert_eq!(state.last_failed, None);
+    assert_eq!(state.slots[&Slot::A].state, SlotState::Active);
+    assert_eq!(state.slots[&Slot::B].state, SlotState::Inactive);
+}
+
+#[tokio::test]
+async fn watch_after_commit_leaves_healthy_slot_active() {
+    let health_client = Arc::new(SequenceHealthClient::new(vec![true, true, true, true]));
+    let core = new_core(health_client).await;
+
+    core.stage("artifact:v1".to_string())
+        .await
+        .expect("stage succeeded");
+    core.commit_on_health().await.expect("commit succeeded");
+
+    let interval = Duration::from_millis(1);
+    core.watch_after_commit(interval * 3, interval, 1)
+        .cancel()
+        .await;
+
+    let state = core.state().await;
+    assert_eq!(
+        state.active,
+        Some(Slot::B),
+        "healthy window should not roll back"
+    );
+    assert_eq!(state.last_failed, None);
+    assert_eq!(state.slots[&Slot::B].state, SlotState::Active);
+}
+
+#[tokio::test]
+async fn commit_with_watchdog_confirms_after_healthy_window() {
+    let health_client = Arc::new(SequenceHealthClient::new(vec![true, true, true, true]));
+    let core = new_core(health_client).await;
+
+    core.stage("artifact:v1".to_string())
+        .await
+        .expect("stage succeeded");
+
+    let interval = Duration::from_millis(1);
+    let (outcome, handle) = core
+        .commit_with_watchdog(interval * 3, interval)
+        .await
+        .expect("commit succeeded");
+    assert!(matches!(outcome, CommitOutcome::Committed(Slot::B)));
+    handle
+        .expect("committed outcome returns a handle")
+        .cancel()
+        .await;
+
+    let state = core.state().await;
+    assert_eq!(state.active, Some(Slot::B));
+    assert_eq!(
+        state.pending_watchdog, None,
+        "healthy window should confirm the commit"
+    );
+    assert_eq!(
+        state.slots[&Slot::B].state,
+        SlotState::Active,
+        "healthy window should promote the slot out of Confirming"
+    );
+}
+
+#[tokio::test]
+async fn commit_with_watchdog_rolls_back_on_quorum_drop() {
+    // Consumed in order: the commit itself, then each watch tick.
+    let health_client = Arc::new(SequenceHealthClient::new(vec![true, true, true, false]));
+    let core = new_core(health_client).await;
+
+    core.stage("artifact:v1".to_string())
+        .await
+        .expect("stage succeeded");
+
+    let interval = Duration::from_millis(1);
+    let (outcome, handle) = core
+        .commit_with_watchdog(interval * 4, interval)
+        .await
+        .expect("commit succeeded");
+    assert!(matches!(outcome, CommitOutcome::Committed(Slot::B)));
+    handle
+        .expect("committed outcome returns a handle")
+        .cancel()
+        .await;
+
+    let state = core.state().await;
+    assert_eq!(state.active, Some(Slot::A), "watch should have rolled back");
+    assert_eq!(state.staging, None);
+    assert_eq!(state.last_failed, None);
+    assert_eq!(
+        state.pending_watchdog, None,
+        "a rollback should also clear the pending watchdog"
+    );
+}
+
+#[tokio::test]
+async fn pending_watchdog_resumes_after_restart() {
+    let store = Arc::new(MemoryStateStore::default());
+
+    // Simulate a crash mid-window: slot B is active but still
+    // committed-provisional, with its observation window already elapsed.
+    let mut seed = UpdaterState::default();
+    seed.active = Some(Slot::B);
+    seed.previous_active = Some(Slot::A);
+    seed.slots.get_mut(&Slot::A).unwrap().state = SlotState::Inactive;
+    seed.slots.get_mut(&Slot::B).unwrap().state = SlotState::Active;
+    seed.pending_watchdog = Some(PendingWatchdog {
+        slot: Slot::B,
+        window_secs: 1,
+        interval_secs: 1,
+        quorum: 1,
+        started_at: chrono::Utc::now() - chrono::Duration::seconds(10),
+    });
+    store.save(&seed).await.expect("seed state saved");
+
+    // The resumed watch's only tick reports the provisional slot unhealthy.
+    let health_client = Arc::new(SequenceHealthClient::new(vec![false]));
+    let bundle_verifier = Arc::new(StubBundleVerifier) as Arc<dyn BundleVerifier>;
+    let core = UpdaterCore::new(
+        store.clone() as Arc<dyn StateStore>,
+        health_client,
+        Vec::new(),
+        Duration::from_secs(1),
+        1,
+        bundle_verifier,
+        None,
+    )
+    .await
+    .expect("core init resumes the pending watchdog");
+
+    // The resumed watch runs on a real clock with a floor of one second per
+    // tick; give it time to complete its single tick and roll back.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+
+    let state = core.state().await;
+    assert_eq!(
+        state.active,
+        Some(Slot::A),
+        "resumed watch should have rolled back the unhealthy provisional slot"
+    );
+    assert_eq!(state.pending_watchdog, None);
+}
+
+/// Builds a core with a no-op [`Clock`] so `watch_after_commit` burns through
+/// its ticks without actually waiting out the interval/window durations.
+async fn new_core(health_client: Arc<SequenceHealthClient>) -> UpdaterCore {
+    let store = Arc::new(MemoryStateStore::default()) as Arc<dyn updater::StateStore>;
+    let bundle_verifier = Arc::new(StubBundleVerifier) as Arc<dyn BundleVerifier>;
+    UpdaterCore::new(
+        store,
+        health_client,
+        Vec::new(),
+        Duration::from_secs(1),
+        0,
+        bundle_verifier,
+        None,
+    )
+    .await
+    .expect("core init")
+    .with_clock(Arc::new(NoopClock))
+}
+
+/// [`Clock`] that resolves immediately, so tests drive `watch_after_commit`
+/// through every tick without real delay.
+struct NoopClock;
+
+#[async_trait]
+impl Clock for NoopClock {
+    async fn sleep(&self, _duration: Duration) {}
+}
+
+/// Always targets slot B, matching the default state's only stageable slot.
+struct StubBundleVerifier;
+
+#[async_trait]
+impl BundleVerifier for StubBundleVerifier {
+    async fn verify(
+        &self,
+        bundle_path: &str,
+        _installed_version: Option<&str>,
+    ) -> Result<StageBundleMetadata, BundleError> {
+        Ok(StageBundleMetadata::new(updater::bundle::Manifest {
+            version: "test".to_string(),
+            build_sha: "test".to_string(),
+            created_at: chrono::Utc::now(),
+            target_slot: Slot::B,
+            base_version: None,
+            base_build_sha: None,
+            security_version: 0,
+            components: vec![updater::bundle::ManifestComponent {
+                name: "component".to_string(),
+                path: bundle_path.to_string(),
+                sha256: "0".repeat(64),
+                patch_base_sha256: None,
+            }],
+        }))
+    }
+}
+
 fn stage_step(artifact: &'static str) -> TestStep {
     TestStep {
         action: Action::Stage { artifact },
     }
 }
 
-fn commit_step(expect_ok: bool) -> TestStep {
+fn commit_step(expect_committed: bool) -> TestStep {
     TestStep {
-        action: Action::Commit { expect_ok },
+        action: Action::Commit { expect_committed },
     }
 }
 
@@ -117,11 +351,13 @@ async fn run_steps(case: &TestCase, core: &UpdaterCore) {
                         panic!("{name}: stage failed: {err}", name = case.name, err = err)
                     });
             }
-            Action::Commit { expect_ok } => {
-                let result = core.commit_on_health().await;
+            Action::Commit { expect_committed } => {
+                let outcome = core.commit_on_health().await.unwrap_or_else(|err| {
+                    panic!("{name}: commit failed: {err}", name = case.name, err = err)
+                });
                 assert_eq!(
-                    result.is_ok(),
-                    expect_ok,
+                    matches!(outcome, CommitOutcome::Committed(_)),
+                    expect_committed,
                     "{}: commit expectation",
                     case.name
                 );
@@ -205,6 +441,7 @@ impl HealthClient for SequenceHealthClient {
         _endpoints: &[String],
         _deadline: Duration,
         _quorum: usize,
+        _on_probe: Option<ProbeObserver<'_>>,
     ) -> Result<bool, HealthCheckError> {
         let mut guard = self.results.lock().await;
         Ok(guard.pop_front().unwrap_or(true))